@@ -4,7 +4,7 @@ use std::time;
 
 fn main() {
     env_logger::init();
-    let mut device: obd2::Obd2<obd2::device::Elm327> = obd2::Obd2::default();
+    let mut device = obd2::Obd2::new(obd2::device::Elm327::new("/dev/ttyUSB0").unwrap());
 
     println!("VIN: {:?}", device.get_vin());
     for s in device.get_service_1_pid_support_1().unwrap().iter() {
@@ -29,7 +29,7 @@ fn main() {
 
     let state = time::Instant::now();
     while state.elapsed() < time::Duration::from_secs(5) {
-        println!("");
+        println!();
         println!(
             "Coolant Temperature: {:?}",
             device.get_engine_coolant_temperature()
@@ -41,7 +41,7 @@ fn main() {
             "Intake air temp (ºC): {:?}",
             device.get_intake_air_temperature()
         );
-        println!("Air flow rate (g/s): {:?}", device.get_air_flow_rate());
+        println!("Air flow rate (g/s): {:?}", device.get_maf());
         println!(
             "Throttle position (%): {:?}",
             device.get_throttle_position()
@@ -0,0 +1,141 @@
+/* C API of the obd2 crate, built with the `cdylib` feature */
+
+#ifndef OBD2_H
+#define OBD2_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The result of a call
+typedef enum Obd2Status {
+  // The call succeeded
+  OBD2_STATUS_OK = 0,
+  // A pointer was null, a string was not valid UTF-8, or a PID is not known
+  OBD2_STATUS_INVALID_ARGUMENT = 1,
+  // No ECU responded, which usually means the vehicle does not support the request
+  OBD2_STATUS_NO_DATA = 2,
+  // The adapter did not respond in time
+  OBD2_STATUS_TIMEOUT = 3,
+  // The vehicle rejected the request or does not support it
+  OBD2_STATUS_NOT_SUPPORTED = 4,
+  // Communicating with the adapter failed
+  OBD2_STATUS_DEVICE = 5,
+  // Another error occurred
+  OBD2_STATUS_OTHER = 6,
+  // The library panicked, and the device it was using is lost
+  OBD2_STATUS_PANIC = 7,
+} Obd2Status;
+
+// An open device
+typedef struct Obd2Device Obd2Device;
+
+// A device being polled on a background thread
+typedef struct Obd2Poller Obd2Poller;
+
+// A diagnostic trouble code, like `P0301`, as a NUL-terminated string
+typedef struct Obd2Dtc {
+  // The code, followed by NUL bytes
+  char code[8];
+} Obd2Dtc;
+
+// Called with each value polled, on the poller's thread, with the `user_data` given to
+// `obd2_poll_start`
+typedef void (*Obd2SampleCallback)(uint8_t pid, float value, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Get a description of `status`, as a static NUL-terminated string
+const char *obd2_status_message(enum Obd2Status status);
+
+// Open an ELM327 adapter connected to the serial port at `path`, like `/dev/ttyUSB0` or `COM3`
+//
+// # Safety
+// `path` must be a NUL-terminated string, and `device` must be valid for writes.
+enum Obd2Status obd2_open_serial(const char *path, struct Obd2Device **device);
+
+// Open an ELM327 adapter connected over Wi-Fi at `address`, like `192.168.0.10:35000`
+//
+// # Safety
+// `address` must be a NUL-terminated string, and `device` must be valid for writes.
+enum Obd2Status obd2_open_tcp(const char *address, struct Obd2Device **device);
+
+// Open a simulated vehicle, for testing without an adapter
+//
+// # Safety
+// `device` must be valid for writes.
+enum Obd2Status obd2_open_simulator(struct Obd2Device **device);
+
+// Close `device`, which must not be used afterwards
+//
+// Passing a null pointer does nothing.
+//
+// # Safety
+// `device` must be null or a device that was opened and not yet closed or being polled.
+void obd2_close(struct Obd2Device *device);
+
+// Read the value of service 1 `pid` from the first ECU that responds, in the units of SAE J1979
+//
+// Only PIDs that hold a single number, like 0x0C for the engine speed, can be read.
+//
+// # Safety
+// `device` must be an open device, and `value` must be valid for writes.
+enum Obd2Status obd2_read_pid(struct Obd2Device *device, uint8_t pid, float *value);
+
+// Read the stored DTCs of every ECU into `dtcs`, which has room for `capacity` codes
+//
+// The number of DTCs is written to `count`, even if it is more than `capacity`, in which case
+// only the first `capacity` are written.
+//
+// # Safety
+// `device` must be an open device, `dtcs` must be valid for writing `capacity` codes (or null if
+// `capacity` is 0), and `count` must be valid for writes.
+enum Obd2Status obd2_read_dtcs(struct Obd2Device *device,
+                               struct Obd2Dtc *dtcs,
+                               size_t capacity,
+                               size_t *count);
+
+// Clear the DTCs and freeze frames of every ECU, and turn off the "check engine" light
+//
+// Vehicles usually only accept this with the ignition on and the engine off.
+//
+// # Safety
+// `device` must be an open device.
+enum Obd2Status obd2_clear_dtcs(struct Obd2Device *device);
+
+// Poll `pids` every `interval_ms` milliseconds on a new thread, calling `callback` with each
+// value
+//
+// On success, the poller is written to `poller`, and `device` belongs to it until
+// `obd2_poll_stop` gives it back. Requests that fail are skipped.
+//
+// # Safety
+// `device` must be an open device, `pids` must be valid for reading `count` PIDs, `poller` must
+// be valid for writes, and `user_data` must be safe to use from the poller's thread.
+enum Obd2Status obd2_poll_start(struct Obd2Device *device,
+                                const uint8_t *pids,
+                                size_t count,
+                                uint32_t interval_ms,
+                                Obd2SampleCallback callback,
+                                void *user_data,
+                                struct Obd2Poller **poller);
+
+// Stop `poller` and write its device back to `device`, once the current request and callback
+// have finished
+//
+// No more callbacks are made after this returns, and the poller is freed. If polling panicked,
+// the device is lost: null is written to `device` and the status is `OBD2_STATUS_PANIC`.
+//
+// # Safety
+// `poller` must be a poller that was started and not yet stopped, and `device` must be valid
+// for writes.
+enum Obd2Status obd2_poll_stop(struct Obd2Poller *poller, struct Obd2Device **device);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* OBD2_H */
@@ -0,0 +1,267 @@
+//! The `dashboard` command: gauges for the engine speed, vehicle speed and coolant temperature,
+//! with the DTCs and the state of the connection
+
+use std::{
+    collections::HashMap,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use obd2::{
+    commands::{pid::StandardPid, Dtc, Obd2DataRetrieval},
+    units::UnitSystem,
+    Error, Obd2Device, Protocol,
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Gauge, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use super::Device;
+
+/// How often the gauges are read
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the DTCs and the adapter voltage are read
+const DTC_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A gauge, with its range and the value above which it turns red, in metric units
+struct Dial {
+    pid: StandardPid,
+    min: f32,
+    max: f32,
+    warning: f32,
+}
+
+const DIALS: [Dial; 3] = [
+    Dial {
+        pid: StandardPid::EngineSpeed,
+        min: 0.,
+        max: 8000.,
+        warning: 6000.,
+    },
+    Dial {
+        pid: StandardPid::VehicleSpeed,
+        min: 0.,
+        max: 200.,
+        warning: f32::INFINITY,
+    },
+    Dial {
+        pid: StandardPid::EngineCoolantTemperature,
+        min: -40.,
+        max: 130.,
+        warning: 110.,
+    },
+];
+
+/// What the thread reading the vehicle sends to the display
+enum Update {
+    Info {
+        vin: Option<String>,
+        protocol: Option<Protocol>,
+    },
+    Values(HashMap<StandardPid, f32>),
+    Voltage(f32),
+    Dtcs(Vec<Dtc>),
+    Error(String),
+}
+
+/// Everything shown on the dashboard
+#[derive(Default)]
+struct State {
+    vin: Option<String>,
+    protocol: Option<Protocol>,
+    values: HashMap<StandardPid, f32>,
+    voltage: Option<f32>,
+    dtcs: Option<Vec<Dtc>>,
+    connected: bool,
+    error: Option<String>,
+}
+
+impl State {
+    fn update(&mut self, update: Update) {
+        match update {
+            Update::Info { vin, protocol } => {
+                self.vin = vin;
+                self.protocol = protocol;
+            }
+            Update::Values(values) => {
+                self.values = values;
+                self.connected = true;
+                self.error = None;
+            }
+            Update::Voltage(voltage) => self.voltage = Some(voltage),
+            Update::Dtcs(dtcs) => self.dtcs = Some(dtcs),
+            Update::Error(e) => self.error = Some(e),
+        }
+    }
+}
+
+/// Show the dashboard until `q`, Esc or Ctrl-C is pressed
+pub fn run(device: Device, units: UnitSystem) -> Result<(), Error> {
+    let (updates, received) = mpsc::channel();
+    // the thread stops when it cannot send an update, after the dashboard is closed
+    thread::spawn(move || read(device, updates));
+
+    let mut terminal = ratatui::init();
+    let result = show(&mut terminal, &received, units);
+    ratatui::restore();
+    result.map_err(|e| obd2::device::Error::from(e).into())
+}
+
+fn show(
+    terminal: &mut DefaultTerminal,
+    updates: &Receiver<Update>,
+    units: UnitSystem,
+) -> std::io::Result<()> {
+    let mut state = State::default();
+    loop {
+        for update in updates.try_iter() {
+            state.update(update);
+        }
+        terminal.draw(|frame| draw(frame, &state, units))?;
+
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c;
+                if key.kind == KeyEventKind::Press && quit {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Read the vehicle on this thread, sending what is read to the dashboard
+fn read(mut device: Device, updates: Sender<Update>) {
+    let info = Update::Info {
+        vin: device.get_vin().ok(),
+        protocol: device.protocol().ok(),
+    };
+    if updates.send(info).is_err() {
+        return;
+    }
+
+    let pids = DIALS.map(|dial| dial.pid);
+    let mut dtcs_read: Option<Instant> = None;
+    loop {
+        let update = match device.get_snapshot(&pids) {
+            Ok(snapshot) => Update::Values(
+                snapshot
+                    .into_iter()
+                    .filter_map(|(pid, values)| Some((pid, *values.first()?)))
+                    .collect(),
+            ),
+            Err(e) => Update::Error(e.to_string()),
+        };
+        if updates.send(update).is_err() {
+            return;
+        }
+
+        if dtcs_read.is_none_or(|time| time.elapsed() >= DTC_INTERVAL) {
+            dtcs_read = Some(Instant::now());
+            let update = match device.get_dtcs() {
+                Ok(dtcs) => Update::Dtcs(dtcs.into_iter().flatten().collect()),
+                Err(e) => Update::Error(e.to_string()),
+            };
+            if updates.send(update).is_err() {
+                return;
+            }
+            if let Ok(voltage) = device.get_adapter_voltage() {
+                let _ = updates.send(Update::Voltage(voltage));
+            }
+        }
+        thread::sleep(INTERVAL);
+    }
+}
+
+fn draw(frame: &mut Frame, state: &State, units: UnitSystem) {
+    let [status, banner, rpm, speed, coolant, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    frame.render_widget(
+        Paragraph::new(status_line(state)).block(Block::bordered().title(" obd2 ")),
+        status,
+    );
+
+    let (text, style) = match &state.dtcs {
+        None => ("Reading DTCs".to_owned(), Style::new().dark_gray()),
+        Some(dtcs) if dtcs.is_empty() => ("No DTCs".to_owned(), Style::new().green()),
+        Some(dtcs) => {
+            let codes: Vec<String> = dtcs.iter().map(|dtc| dtc.to_string()).collect();
+            let text = format!("{} DTCs: {}", dtcs.len(), codes.join(", "));
+            (text, Style::new().white().on_red().bold())
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(text)
+            .style(style)
+            .block(Block::bordered().title(" DTCs ")),
+        banner,
+    );
+
+    for (dial, area) in DIALS.iter().zip([rpm, speed, coolant]) {
+        frame.render_widget(
+            gauge(dial, state.values.get(&dial.pid).copied(), units),
+            area,
+        );
+    }
+
+    frame.render_widget(Line::from(" q: quit").dark_gray(), help);
+}
+
+fn status_line(state: &State) -> Line<'_> {
+    let mut spans = vec![match (&state.error, state.connected) {
+        (Some(e), _) => Span::raw(format!("No response: {}", e)).red(),
+        (None, false) => Span::raw("Connecting").yellow(),
+        (None, true) => Span::raw("Connected").green(),
+    }];
+    if let Some(protocol) = &state.protocol {
+        spans.push(Span::raw(format!("  ·  {}", protocol)));
+    }
+    if let Some(vin) = &state.vin {
+        spans.push(Span::raw(format!("  ·  VIN {}", vin)));
+    }
+    if let Some(voltage) = state.voltage {
+        spans.push(Span::raw(format!("  ·  {:.1} V", voltage)));
+    }
+    Line::from(spans)
+}
+
+fn gauge(dial: &Dial, value: Option<f32>, units: UnitSystem) -> Gauge<'static> {
+    let block = Block::bordered().title(format!(" {} ", dial.pid.name()));
+    let Some(value) = value else {
+        return Gauge::default().block(block).ratio(0.).label("-");
+    };
+
+    let ratio = ((value - dial.min) / (dial.max - dial.min)).clamp(0., 1.);
+    let color = if value >= dial.warning {
+        Color::Red
+    } else {
+        Color::Green
+    };
+    let unit = units.pid_unit(dial.pid).unwrap_or_default();
+    Gauge::default()
+        .block(block)
+        .gauge_style(Style::new().fg(color))
+        .ratio(f64::from(ratio))
+        .label(format!(
+            "{:.0} {}",
+            units.convert_pid(dial.pid, value),
+            unit
+        ))
+}
@@ -0,0 +1,423 @@
+//! `obd2`, a command-line tool for reading data from a vehicle
+//!
+//! This is built with the `cli` feature, like `cargo install obd2 --features cli`, and talks to an
+//! ELM327 adapter on a serial port by default. Run `obd2 --help` for its commands. With the `tui`
+//! feature, it also has a live dashboard.
+
+#[cfg(feature = "tui")]
+mod dashboard;
+
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    process::ExitCode,
+    sync::mpsc::RecvTimeoutError,
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use obd2::{
+    commands::{pid::StandardPid, Dtc, Obd2DataRetrieval},
+    device::{Elm327, Obd2Simulator, TcpElm327},
+    inspection::{check_inspection_readiness, InspectionResult, InspectionRules},
+    logger::{CsvFormat, CsvLogger},
+    poller::Obd2Poller,
+    report::generate_report,
+    units::UnitSystem,
+    Error, Obd2, Obd2Device, Obd2DeviceDyn,
+};
+
+type Device = Box<dyn Obd2DeviceDyn + Send>;
+
+/// Read data from a vehicle over OBD-II
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Serial port of the ELM327 adapter
+    #[arg(short, long, global = true, default_value = "/dev/ttyUSB0")]
+    port: String,
+
+    /// Address of a Wi-Fi ELM327 adapter, like 192.168.0.10:35000, to use instead of the serial
+    /// port
+    #[arg(long, global = true)]
+    tcp: Option<String>,
+
+    /// Use a simulated vehicle instead of an adapter
+    #[arg(long, global = true, conflicts_with = "tcp")]
+    simulator: bool,
+
+    /// Units to show values in
+    #[arg(short, long, global = true, value_enum, default_value_t = Units::Metric)]
+    units: Units,
+
+    /// Log what is sent to and received from the adapter, more with -vv, to include in bug
+    /// reports
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show the VIN, the protocol and the PIDs supported by each ECU
+    Scan,
+
+    /// Check whether the vehicle is ready to pass an emissions inspection
+    Inspect {
+        /// Use the rules of California's Smog Check, which fails permanent DTCs
+        #[arg(long)]
+        california: bool,
+    },
+
+    /// Read the VIN, readiness monitors, DTCs, freeze frames and monitor tests into a report, in
+    /// Markdown
+    Report {
+        /// Write the report as an HTML page instead
+        #[arg(long)]
+        html: bool,
+    },
+
+    /// Read or clear the diagnostic trouble codes
+    Dtc {
+        #[command(subcommand)]
+        action: Option<DtcAction>,
+    },
+
+    /// Show the values of PIDs in a table, updated live
+    ///
+    /// PIDs are given by number, like 0C, or by name, like engine-speed.
+    Watch {
+        /// PIDs to show
+        #[arg(required = true, value_parser = parse_pid)]
+        pids: Vec<StandardPid>,
+
+        /// Milliseconds between requests for each PID
+        #[arg(short, long, default_value_t = 500)]
+        interval: u64,
+    },
+
+    /// Show gauges for the engine speed, vehicle speed and coolant temperature, with the DTCs
+    #[cfg(feature = "tui")]
+    Dashboard,
+
+    /// Record the values of PIDs to a CSV file, until stopped with Ctrl-C
+    Log {
+        /// File to write
+        output: PathBuf,
+
+        /// PIDs to record, by number or name like for `watch`
+        #[arg(required = true, value_parser = parse_pid)]
+        pids: Vec<StandardPid>,
+
+        /// Milliseconds between requests for each PID
+        #[arg(short, long, default_value_t = 500)]
+        interval: u64,
+
+        /// Stop after this many seconds
+        #[arg(short, long)]
+        duration: Option<u64>,
+
+        /// Write the file in the layout of the Torque Pro app
+        #[arg(long)]
+        torque: bool,
+    },
+}
+
+#[derive(Subcommand, Clone, Copy)]
+enum DtcAction {
+    /// Read the stored DTCs, which turn on the check engine light (the default)
+    Read,
+
+    /// Read the pending DTCs, detected during the current or last drive cycle
+    Pending,
+
+    /// Read the permanent DTCs, which cannot be cleared by a scan tool
+    Permanent,
+
+    /// Clear the DTCs and turn off the check engine light
+    Clear {
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum Units {
+    Metric,
+    Us,
+    Imperial,
+}
+
+impl From<Units> for UnitSystem {
+    fn from(units: Units) -> Self {
+        match units {
+            Units::Metric => UnitSystem::Metric,
+            Units::Us => UnitSystem::UsCustomary,
+            Units::Imperial => UnitSystem::Imperial,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let level = match args.verbose {
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .init();
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    let mut device = open(&args)?;
+    let units = UnitSystem::from(args.units);
+    match args.command {
+        Command::Scan => scan(&mut device),
+        Command::Inspect { california } => {
+            let rules = if california {
+                InspectionRules::california()
+            } else {
+                InspectionRules::default()
+            };
+            let result = check_inspection_readiness(&mut device, &rules)?;
+            match result {
+                InspectionResult::Pass => println!("Pass"),
+                InspectionResult::NotReady(_) => println!("Not ready"),
+                InspectionResult::Fail(_) => println!("Fail"),
+            }
+            for issue in result.issues() {
+                println!("  - {}", issue);
+            }
+            Ok(())
+        }
+        Command::Report { html } => {
+            let report = generate_report(&mut device)?;
+            if html {
+                print!("{}", report.to_html());
+            } else {
+                print!("{}", report.to_markdown());
+            }
+            Ok(())
+        }
+        Command::Dtc { action } => dtc(&mut device, action.unwrap_or(DtcAction::Read)),
+        Command::Watch { pids, interval } => {
+            watch(device, &pids, Duration::from_millis(interval), units)
+        }
+        #[cfg(feature = "tui")]
+        Command::Dashboard => dashboard::run(device, units),
+        Command::Log {
+            output,
+            pids,
+            interval,
+            duration,
+            torque,
+        } => {
+            let format = if torque {
+                CsvFormat::TorquePro
+            } else {
+                CsvFormat::Plain
+            };
+            let logger = CsvLogger::new(output, pids.iter().copied())
+                .with_units(units)
+                .with_format(format);
+            let interval = Duration::from_millis(interval);
+            record(
+                device,
+                logger,
+                &pids,
+                interval,
+                duration.map(Duration::from_secs),
+            )
+        }
+    }
+}
+
+fn open(args: &Args) -> Result<Device, Error> {
+    Ok(if args.simulator {
+        Obd2Simulator::new().boxed()
+    } else if let Some(address) = &args.tcp {
+        Obd2::new(TcpElm327::connect(address.as_str())?).boxed()
+    } else {
+        Obd2::new(Elm327::new(&args.port)?).boxed()
+    })
+}
+
+fn scan(device: &mut Device) -> Result<(), Error> {
+    match device.get_vin() {
+        Ok(vin) => println!("VIN: {}", vin),
+        Err(e) => println!("VIN: unavailable ({})", e),
+    }
+    match device.protocol() {
+        Ok(protocol) => println!("Protocol: {}", protocol),
+        Err(e) => println!("Protocol: unavailable ({})", e),
+    }
+
+    for (i, support) in device.get_pid_support()?.iter().enumerate() {
+        println!();
+        println!("Supported PIDs of ECU {}:", i + 1);
+        for pid in support.pids() {
+            match StandardPid::from_pid(pid) {
+                Some(standard) => println!("  {:02X}  {}", pid, standard.name()),
+                None => println!("  {:02X}", pid),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn dtc(device: &mut Device, action: DtcAction) -> Result<(), Error> {
+    let dtcs = match action {
+        DtcAction::Read => device.get_dtcs()?,
+        DtcAction::Pending => device.get_pending_dtcs()?,
+        DtcAction::Permanent => device
+            .get_permanent_dtcs()?
+            .into_iter()
+            .map(|ecu| ecu.into_iter().map(|dtc| dtc.0).collect())
+            .collect(),
+        DtcAction::Clear { yes } => {
+            let question = "Clearing the DTCs also resets the readiness monitors. Continue?";
+            if !yes && !confirm(question)? {
+                return Ok(());
+            }
+            device.obd_mode_command(0x04)?;
+            println!("DTCs cleared");
+            return Ok(());
+        }
+    };
+    print_dtcs(&dtcs);
+    Ok(())
+}
+
+fn print_dtcs(dtcs: &[Vec<Dtc>]) {
+    if dtcs.iter().all(Vec::is_empty) {
+        println!("No DTCs");
+        return;
+    }
+    for (i, ecu) in dtcs.iter().enumerate() {
+        if ecu.is_empty() {
+            continue;
+        }
+        println!("ECU {}:", i + 1);
+        for dtc in ecu {
+            // with the alternate flag, codes in the database are followed by their description
+            println!("  {:#}", dtc);
+        }
+    }
+}
+
+fn watch(
+    device: Device,
+    pids: &[StandardPid],
+    interval: Duration,
+    units: UnitSystem,
+) -> Result<(), Error> {
+    let cache = poller(device, pids, interval).start_cache();
+    let width = pids.iter().map(|pid| pid.name().len()).max().unwrap_or(0);
+    loop {
+        thread::sleep(interval);
+
+        // clear the terminal and move to its top
+        let mut table = String::from("\x1b[2J\x1b[H");
+        for &pid in pids {
+            let value = match cache.latest(pid) {
+                Some(cached) => format!("{:>10.2}", units.convert_pid(pid, cached.value)),
+                None => format!("{:>10}", "-"),
+            };
+            let unit = units.pid_unit(pid).unwrap_or_default();
+            table.push_str(&format!("{:<width$}  {} {}\n", pid.name(), value, unit));
+        }
+        table.push_str(&format!(
+            "\n{} failed requests. Ctrl-C to stop\n",
+            cache.errors()
+        ));
+        print!("{}", table);
+        io::stdout().flush().map_err(obd2::device::Error::from)?;
+    }
+}
+
+fn record(
+    device: Device,
+    mut logger: CsvLogger,
+    pids: &[StandardPid],
+    interval: Duration,
+    duration: Option<Duration>,
+) -> Result<(), Error> {
+    let poller = poller(device, pids, interval).start();
+    eprintln!("Logging to {}, Ctrl-C to stop", logger.path().display());
+
+    let start = Instant::now();
+    let mut flushed = Instant::now();
+    while duration.is_none_or(|duration| start.elapsed() < duration) {
+        match poller.samples().recv_timeout(Duration::from_secs(1)) {
+            Ok(Ok(sample)) => logger.add_sample(&sample)?,
+            Ok(Err(_)) | Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        // little is lost when stopped with Ctrl-C
+        if flushed.elapsed() >= Duration::from_secs(1) {
+            logger.flush()?;
+            flushed = Instant::now();
+        }
+    }
+    logger.flush()
+}
+
+fn poller(device: Device, pids: &[StandardPid], interval: Duration) -> Obd2Poller<Device> {
+    pids.iter().fold(Obd2Poller::new(device), |poller, &pid| {
+        poller.poll(pid, interval)
+    })
+}
+
+/// Ask a yes or no question on the terminal, with no as the default
+fn confirm(question: &str) -> Result<bool, Error> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush().map_err(obd2::device::Error::from)?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(obd2::device::Error::from)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Parse a PID given by its number in hex, like `0C` or `0x0C`, or by its name, like
+/// `engine-speed`
+fn parse_pid(s: &str) -> Result<StandardPid, String> {
+    let number = s.trim_start_matches("0x");
+    let pid = match u8::from_str_radix(number, 16) {
+        Ok(number) => StandardPid::from_pid(number),
+        Err(_) => StandardPid::ALL
+            .iter()
+            .copied()
+            .find(|pid| slug(pid.name()) == slug(s)),
+    }
+    .ok_or_else(|| format!("unknown PID {:?}", s))?;
+    match pid.unit() {
+        Some(_) => Ok(pid),
+        None => Err(format!("{} does not hold a single number", pid.name())),
+    }
+}
+
+/// Lowercase `name` with only its letters and digits, so that names match regardless of spaces,
+/// dashes and case
+fn slug(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
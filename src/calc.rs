@@ -0,0 +1,207 @@
+//! Calculations from live values
+//!
+//! Vehicles rarely report fuel economy directly, so it is computed from the fuel rate and the
+//! speed. Newer vehicles report the engine's fuel rate (PID 0x5E), and for the rest it is
+//! estimated from the mass of air the engine takes in (PID 0x10), which gasoline engines mix with
+//! a fixed ratio of fuel.
+//!
+//! # Example
+//! ```
+//! use obd2::{
+//!     calc::{self, FuelProperties},
+//!     commands::FuelType,
+//! };
+//!
+//! // a gasoline engine taking in 10 g/s of air at 50 km/h
+//! let fuel = FuelProperties::for_fuel_type(FuelType::Gasoline).unwrap();
+//! let fuel_rate = calc::fuel_rate(fuel, Some(10.), None).unwrap();
+//! assert!((fuel_rate - 3.29).abs() < 0.01); // L/h
+//!
+//! let consumption = calc::liters_per_100km(fuel_rate, 50.).unwrap();
+//! assert!((consumption - 6.57).abs() < 0.01);
+//! assert!((calc::mpg(consumption) - 35.8).abs() < 0.1);
+//! ```
+
+use core::time::Duration;
+
+use crate::commands::{FuelType, LiveData};
+
+/// The properties of a liquid fuel needed to find how much of it an engine burns
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FuelProperties {
+    /// The mass of air needed to burn a unit mass of the fuel
+    pub stoichiometric_ratio: f32,
+
+    /// The density of the fuel in kg/L
+    pub density: f32,
+}
+
+impl FuelProperties {
+    /// Gasoline without ethanol
+    pub const GASOLINE: Self = Self::new(14.7, 0.745);
+
+    /// Diesel
+    pub const DIESEL: Self = Self::new(14.5, 0.832);
+
+    /// Ethanol
+    pub const ETHANOL: Self = Self::new(9.0, 0.789);
+
+    /// Methanol
+    pub const METHANOL: Self = Self::new(6.4, 0.792);
+
+    /// Liquefied petroleum gas, as a liquid
+    pub const LPG: Self = Self::new(15.5, 0.51);
+
+    /// Propane, as a liquid
+    pub const PROPANE: Self = Self::new(15.7, 0.493);
+
+    /// Create a [`FuelProperties`] for a fuel with the air-fuel ratio `stoichiometric_ratio`
+    /// and `density` in kg/L, like a blend of gasoline and ethanol
+    pub const fn new(stoichiometric_ratio: f32, density: f32) -> Self {
+        FuelProperties {
+            stoichiometric_ratio,
+            density,
+        }
+    }
+
+    /// Get the properties of the fuel the vehicle reports in PID 0x51
+    ///
+    /// This is `None` for electric vehicles, and for compressed natural gas, which is not measured
+    /// in liters.
+    pub fn for_fuel_type(fuel_type: FuelType) -> Option<Self> {
+        use FuelType as F;
+        match fuel_type {
+            F::Gasoline | F::BifuelGasoline | F::HybridGasoline => Some(Self::GASOLINE),
+            F::Diesel | F::BifuelDiesel | F::HybridDiesel => Some(Self::DIESEL),
+            F::Ethanol | F::BifuelEthanol | F::HybridEthanol => Some(Self::ETHANOL),
+            F::Methanol | F::BifuelMethanol => Some(Self::METHANOL),
+            F::Lpg | F::BifuelLpg => Some(Self::LPG),
+            F::Propane | F::BifuelPropane => Some(Self::PROPANE),
+            _ => None,
+        }
+    }
+
+    /// Estimate the fuel rate in L/h of an engine taking in `maf` g/s of air
+    ///
+    /// This assumes the engine burns the fuel with exactly the air it needs, as gasoline engines
+    /// do while cruising. Diesel engines run with extra air, so the estimate is too high for them.
+    pub fn fuel_rate_from_maf(&self, maf: f32) -> f32 {
+        maf / self.stoichiometric_ratio * 3600. / (self.density * 1000.)
+    }
+}
+
+/// Get the fuel rate in L/h, from the engine fuel rate (PID 0x5E) if the vehicle reports it, or
+/// else estimated from the mass air flow rate (PID 0x10) in g/s
+///
+/// This is `None` if neither is known.
+pub fn fuel_rate(
+    fuel: FuelProperties,
+    maf: Option<f32>,
+    engine_fuel_rate: Option<f32>,
+) -> Option<f32> {
+    engine_fuel_rate.or_else(|| maf.map(|maf| fuel.fuel_rate_from_maf(maf)))
+}
+
+/// Get the fuel consumption in L/100km from the fuel rate in L/h and the speed in km/h
+///
+/// This is `None` when the vehicle is stopped, since it is not moving any distance.
+pub fn liters_per_100km(fuel_rate: f32, speed: f32) -> Option<f32> {
+    (speed > 0.).then(|| fuel_rate / speed * 100.)
+}
+
+/// Convert a fuel consumption in L/100km to miles per US gallon
+///
+/// While coasting with the fuel cut off, this is infinite.
+pub fn mpg(liters_per_100km: f32) -> f32 {
+    235.214_58 / liters_per_100km
+}
+
+/// Convert a fuel consumption in L/100km to miles per imperial gallon
+pub fn mpg_imperial(liters_per_100km: f32) -> f32 {
+    282.480_94 / liters_per_100km
+}
+
+/// The fuel used and distance driven over a trip, for its average fuel consumption
+///
+/// Add each sample of the fuel rate and speed with the time since the previous sample, and the
+/// fuel and distance are added up as if they were constant in between.
+///
+/// # Example
+/// ```
+/// use obd2::calc::{FuelProperties, TripFuelEconomy};
+/// use std::time::Duration;
+///
+/// let mut trip = TripFuelEconomy::new(FuelProperties::GASOLINE);
+/// // an hour at 60 km/h using 4 L/h, then ten minutes stopped using 0.6 L/h
+/// trip.add(4., 60., Duration::from_secs(3600));
+/// trip.add(0.6, 0., Duration::from_secs(600));
+///
+/// assert!((trip.fuel_used() - 4.1).abs() < 0.001);
+/// assert_eq!(trip.distance(), 60.);
+/// assert!((trip.liters_per_100km().unwrap() - 6.83).abs() < 0.01);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TripFuelEconomy {
+    fuel: FuelProperties,
+    fuel_used: f32,
+    distance: f32,
+}
+
+impl TripFuelEconomy {
+    /// Start a trip of a vehicle using `fuel`
+    pub fn new(fuel: FuelProperties) -> Self {
+        TripFuelEconomy {
+            fuel,
+            fuel_used: 0.,
+            distance: 0.,
+        }
+    }
+
+    /// Add `elapsed` time at `fuel_rate` L/h and `speed` km/h, and get the consumption at that
+    /// moment in L/100km
+    pub fn add(&mut self, fuel_rate: f32, speed: f32, elapsed: Duration) -> Option<f32> {
+        let hours = elapsed.as_secs_f32() / 3600.;
+        self.fuel_used += fuel_rate * hours;
+        self.distance += speed * hours;
+        liters_per_100km(fuel_rate, speed)
+    }
+
+    /// Add `elapsed` time at the values in `data`, and get the consumption at that moment in
+    /// L/100km
+    ///
+    /// The fuel rate is `engine_fuel_rate` in L/h (PID 0x5E) if it is given, or else estimated from
+    /// the mass air flow rate. Nothing is added if the fuel rate or speed is not known.
+    pub fn add_live_data(
+        &mut self,
+        data: &LiveData,
+        engine_fuel_rate: Option<f32>,
+        elapsed: Duration,
+    ) -> Option<f32> {
+        let fuel_rate = fuel_rate(self.fuel, data.maf, engine_fuel_rate)?;
+        self.add(fuel_rate, data.speed?, elapsed)
+    }
+
+    /// The fuel used in L
+    pub fn fuel_used(&self) -> f32 {
+        self.fuel_used
+    }
+
+    /// The distance driven in km
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// The average fuel consumption in L/100km, or `None` before the vehicle has moved
+    pub fn liters_per_100km(&self) -> Option<f32> {
+        (self.distance > 0.).then(|| self.fuel_used / self.distance * 100.)
+    }
+
+    /// The average fuel consumption in miles per US gallon, or `None` before the vehicle has
+    /// moved
+    pub fn mpg(&self) -> Option<f32> {
+        self.liters_per_100km().map(mpg)
+    }
+}
@@ -0,0 +1,292 @@
+//! Finding which PIDs each ECU supports
+//!
+//! Each ECU reports the PIDs it supports for services 1, 2, 5, 6 and 9 as bitmaps, at PIDs 0x00,
+//! 0x20, 0x40 and so on. [scan_supported_pids] walks these bitmaps for every ECU, and can also try
+//! ranges of manufacturer-specific service 0x22 DIDs, which have no bitmaps. With the `serde`
+//! feature the [Capabilities] it returns can be saved, for example keyed by the VIN, and loaded in
+//! later sessions instead of scanning again.
+//!
+//! # Example
+//! ```
+//! use obd2::{
+//!     capabilities::{scan_supported_pids, ScanOptions},
+//!     device::Obd2Simulator,
+//! };
+//!
+//! let mut device = Obd2Simulator::new();
+//! let capabilities = scan_supported_pids(&mut device, &ScanOptions::default())?;
+//!
+//! let engine = capabilities.ecu(0x7E8).unwrap();
+//! assert!(engine.service(0x01).unwrap().supports(0x0C));
+//! assert!(engine.service(0x09).is_none());
+//! assert!(capabilities.supports(0x01, 0x0D));
+//! # Ok::<(), obd2::Error>(())
+//! ```
+
+use alloc::{vec, vec::Vec};
+use core::{ops::RangeInclusive, time::Duration};
+
+use crate::{commands::PidSupport, report::optional, Error, Obd2Device, Result};
+
+/// The services with support bitmaps
+const SERVICES: [u8; 5] = [0x01, 0x02, 0x05, 0x06, 0x09];
+
+/// What [scan_supported_pids] reads besides the support bitmaps
+///
+/// # Example
+/// ```
+/// use core::time::Duration;
+/// use obd2::capabilities::ScanOptions;
+///
+/// // try the standard identification DIDs, waiting 100 ms between requests
+/// let options = ScanOptions::default()
+///     .with_dids(0xF180..=0xF19F)
+///     .with_did_interval(Duration::from_millis(100));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ScanOptions {
+    /// The ranges of service 0x22 DIDs to try, one request for each DID
+    pub dids: Vec<RangeInclusive<u16>>,
+
+    /// How long to wait between DID requests, so as not to flood the bus
+    ///
+    /// Without the `std` feature there is no way to wait, and the DIDs are read as fast as the
+    /// device responds.
+    pub did_interval: Duration,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            dids: Vec::new(),
+            did_interval: Duration::from_millis(50),
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Try each DID in `range`
+    pub fn with_dids(mut self, range: RangeInclusive<u16>) -> Self {
+        self.dids.push(range);
+        self
+    }
+
+    /// Wait `interval` between DID requests
+    pub fn with_did_interval(mut self, interval: Duration) -> Self {
+        self.did_interval = interval;
+        self
+    }
+}
+
+/// What each ECU on the vehicle supports, from [scan_supported_pids]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Each ECU that responded, in the order they first responded
+    pub ecus: Vec<EcuCapabilities>,
+}
+
+impl Capabilities {
+    /// Get the ECU that responds from `address`
+    pub fn ecu(&self, address: u32) -> Option<&EcuCapabilities> {
+        self.ecus.iter().find(|ecu| ecu.address == Some(address))
+    }
+
+    /// Whether any ECU supports `pid` of `service`
+    pub fn supports(&self, service: u8, pid: u8) -> bool {
+        self.ecus.iter().any(|ecu| ecu.supports(service, pid))
+    }
+
+    /// Get the ECU that sent a response, adding it if it has not responded before
+    fn entry(&mut self, address: Option<u32>, index: usize) -> &mut EcuCapabilities {
+        let position = match address {
+            Some(_) => self.ecus.iter().position(|ecu| ecu.address == address),
+            None => (index < self.ecus.len()).then_some(index),
+        };
+        match position {
+            Some(position) => &mut self.ecus[position],
+            None => {
+                self.ecus.push(EcuCapabilities {
+                    address,
+                    ..Default::default()
+                });
+                self.ecus.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+/// What one ECU supports
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EcuCapabilities {
+    /// The address the ECU responds from (see [EcuResponse](crate::EcuResponse))
+    ///
+    /// This is `None` for devices that cannot see where responses come from, where the ECUs are
+    /// told apart only by the order they respond in.
+    pub address: Option<u32>,
+
+    /// The service 1 (current data) PIDs
+    pub service_01: Option<PidSupport>,
+
+    /// The service 2 (freeze frame) PIDs, for frame 0
+    pub service_02: Option<PidSupport>,
+
+    /// The service 5 (oxygen sensor monitoring) test IDs, on protocols other than CAN
+    pub service_05: Option<PidSupport>,
+
+    /// The service 6 (on-board monitoring) test or monitor IDs
+    pub service_06: Option<PidSupport>,
+
+    /// The service 9 (vehicle information) info types
+    pub service_09: Option<PidSupport>,
+
+    /// The service 0x22 DIDs that were tried and answered
+    pub dids: Vec<u16>,
+}
+
+impl EcuCapabilities {
+    /// Get the support for `service`, or `None` if the ECU does not support it
+    pub fn service(&self, service: u8) -> Option<&PidSupport> {
+        match service {
+            0x01 => self.service_01.as_ref(),
+            0x02 => self.service_02.as_ref(),
+            0x05 => self.service_05.as_ref(),
+            0x06 => self.service_06.as_ref(),
+            0x09 => self.service_09.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether the ECU supports `pid` of `service`
+    pub fn supports(&self, service: u8, pid: u8) -> bool {
+        self.service(service)
+            .is_some_and(|support| support.supports(pid))
+    }
+
+    fn service_mut(&mut self, service: u8) -> &mut Option<PidSupport> {
+        match service {
+            0x01 => &mut self.service_01,
+            0x02 => &mut self.service_02,
+            0x05 => &mut self.service_05,
+            0x06 => &mut self.service_06,
+            _ => &mut self.service_09,
+        }
+    }
+}
+
+/// Find the PIDs each ECU supports, and the DIDs in `options` that it answers
+///
+/// The responses are told apart by the ECUs' addresses when the device reports them (see
+/// [obd_request_tagged](Obd2Device::obd_request_tagged)). Services that no ECU supports are
+/// skipped, but the scan stops with an error if the connection is lost.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     capabilities::{scan_supported_pids, ScanOptions},
+///     device::MockObd2Device,
+/// };
+///
+/// // the engine and transmission controllers, where only the engine has a part number DID
+/// let mut device = MockObd2Device::new()
+///     .with_response(0x01, 0x00, [vec![0x98, 0x18, 0x00, 0x00], vec![0x80, 0x00, 0x00, 0x00]])
+///     .with_response(0x09, 0x00, [vec![0x55, 0x40, 0x00, 0x00]]);
+/// device.set_request_response(&[0x22, 0xF1, 0x87], [b"12345".to_vec()]);
+///
+/// let options = ScanOptions::default().with_dids(0xF186..=0xF188);
+/// let capabilities = scan_supported_pids(&mut device, &options)?;
+///
+/// let [engine, transmission] = &capabilities.ecus[..] else { panic!() };
+/// assert_eq!(engine.address, Some(0x7E8));
+/// let pids: Vec<u8> = engine.service_01.as_ref().unwrap().pids().collect();
+/// assert_eq!(pids, [0x00, 0x01, 0x04, 0x05, 0x0C, 0x0D]);
+/// assert!(engine.supports(0x09, 0x02));
+/// assert_eq!(engine.dids, [0xF187]);
+///
+/// assert_eq!(transmission.address, Some(0x7E9));
+/// assert!(transmission.service_09.is_none());
+/// # Ok::<(), obd2::Error>(())
+/// ```
+pub fn scan_supported_pids<T: Obd2Device>(
+    device: &mut T,
+    options: &ScanOptions,
+) -> Result<Capabilities> {
+    let tagged = match device.obd_request_tagged(&[0x01, 0x00]) {
+        Err(Error::UnsupportedByDevice(_)) => false,
+        Err(e) if e.is_connection_lost() => return Err(e),
+        _ => true,
+    };
+
+    let mut capabilities = Capabilities::default();
+    for service in SERVICES {
+        let mut base = 0x00;
+        while let Some(responses) = optional(send(device, &bitmap_request(service, base), tagged))?
+        {
+            let mut more = false;
+            for (index, (address, data)) in responses.into_iter().enumerate() {
+                // on protocols other than CAN, service 9 responses start with a message count
+                let Some(&bitmap) = data.last_chunk() else {
+                    continue;
+                };
+                let bitmap = u32::from_be_bytes(bitmap);
+                capabilities
+                    .entry(address, index)
+                    .service_mut(service)
+                    .get_or_insert_with(PidSupport::default)
+                    .set_range(base, bitmap);
+                more |= bitmap & 1 != 0;
+            }
+            if base == 0xE0 || !more {
+                break;
+            }
+            base += 0x20;
+        }
+    }
+
+    for did in options.dids.iter().cloned().flatten() {
+        #[cfg(feature = "std")]
+        std::thread::sleep(options.did_interval);
+        let [high, low] = did.to_be_bytes();
+        if let Some(responses) = optional(send(device, &[0x22, high, low], tagged))? {
+            for (index, (address, _)) in responses.into_iter().enumerate() {
+                capabilities.entry(address, index).dids.push(did);
+            }
+        }
+    }
+    Ok(capabilities)
+}
+
+/// Get the request for the support bitmap of `service` at `base`
+fn bitmap_request(service: u8, base: u8) -> Vec<u8> {
+    match service {
+        // service 2 also takes the freeze frame number
+        0x02 => vec![0x02, base, 0x00],
+        _ => vec![service, base],
+    }
+}
+
+/// Send `request` and get each response with the address of its ECU, if the device reports it
+fn send<T: Obd2Device>(
+    device: &mut T,
+    request: &[u8],
+    tagged: bool,
+) -> Result<Vec<(Option<u32>, Vec<u8>)>> {
+    Ok(if tagged {
+        device
+            .obd_request_tagged(request)?
+            .into_iter()
+            .map(|response| (Some(response.address), response.data))
+            .collect()
+    } else {
+        device
+            .obd_request(request)?
+            .into_iter()
+            .map(|data| (None, data))
+            .collect()
+    })
+}
@@ -0,0 +1,162 @@
+//! Diesel aftertreatment PIDs
+//!
+//! These PIDs report on the systems that clean up Diesel exhaust: the diesel particulate filter
+//! (DPF), which needs regenerating as it fills with soot, and selective catalytic reduction (SCR),
+//! which uses diesel exhaust fluid (DEF) to reduce NOx. Like the
+//! [turbocharger PIDs](super::turbo), most of them start with a byte showing which of their values
+//! are supported, so those values are `None` if the vehicle does not report them.
+
+use super::{
+    implementation::{percent, supported, temperature, wide_temperature, word},
+    DecodeObd2Values,
+};
+use crate::Result;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Diesel particulate filter pressures for one bank, from PID 0x7A (bank 1) or 0x7B (bank 2)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct DpfPressure {
+    /// Pressure difference across the filter in kPa, which rises as it fills with soot
+    pub delta: Option<f32>,
+    /// Pressure at the filter inlet in kPa
+    pub inlet: Option<f32>,
+    /// Pressure at the filter outlet in kPa
+    pub outlet: Option<f32>,
+}
+
+/// Diesel particulate filter temperatures, from PID 0x7C
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct DpfTemperature {
+    /// Bank 1 inlet temperature in ºC
+    pub bank_1_inlet: Option<f32>,
+    /// Bank 1 outlet temperature in ºC
+    pub bank_1_outlet: Option<f32>,
+    /// Bank 2 inlet temperature in ºC
+    pub bank_2_inlet: Option<f32>,
+    /// Bank 2 outlet temperature in ºC
+    pub bank_2_outlet: Option<f32>,
+}
+
+/// NOx concentrations, from PID 0x83 (measured) or 0xA1 (corrected)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct NoxConcentration {
+    /// Bank 1 sensor 1 in ppm
+    pub bank_1_sensor_1: Option<u16>,
+    /// Bank 1 sensor 2 in ppm
+    pub bank_1_sensor_2: Option<u16>,
+    /// Bank 2 sensor 1 in ppm
+    pub bank_2_sensor_1: Option<u16>,
+    /// Bank 2 sensor 2 in ppm
+    pub bank_2_sensor_2: Option<u16>,
+}
+
+/// The SCR reagent (DEF) system, from PID 0x85
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ReagentSystem {
+    /// Average rate of reagent consumption in L/h
+    pub average_consumption: Option<f32>,
+    /// Average rate of reagent consumption demanded by the engine control in L/h
+    pub average_demanded_consumption: Option<f32>,
+    /// Level of the reagent tank as a percentage
+    pub tank_level: Option<f32>,
+    /// Time the NOx warning indicator has been on, which leads to the SCR inducement (like
+    /// limiting the vehicle's speed) if the problem is not fixed
+    pub nox_warning_time: Option<Duration>,
+}
+
+/// Diesel exhaust fluid sensor data, from PID 0x9B
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct DefSensor {
+    /// The type of DEF in the tank, as the raw code from the vehicle
+    pub def_type: u8,
+    /// Concentration of urea in the DEF as a percentage, which should be about 32.5%
+    pub concentration: f32,
+    /// Temperature of the DEF tank in ºC
+    pub tank_temperature: i16,
+    /// Level of the DEF tank as a percentage
+    pub tank_level: f32,
+}
+
+impl DecodeObd2Values for DpfPressure {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 7]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| DpfPressure {
+                delta: supported(v[0], 0, f32::from(i16::from_be_bytes([v[1], v[2]])) * 0.01),
+                inlet: supported(v[0], 1, word(v[3], v[4]) * 0.01),
+                outlet: supported(v[0], 2, word(v[5], v[6]) * 0.01),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for DpfTemperature {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 9]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| DpfTemperature {
+                bank_1_inlet: supported(v[0], 0, wide_temperature(v[1], v[2])),
+                bank_1_outlet: supported(v[0], 1, wide_temperature(v[3], v[4])),
+                bank_2_inlet: supported(v[0], 2, wide_temperature(v[5], v[6])),
+                bank_2_outlet: supported(v[0], 3, wide_temperature(v[7], v[8])),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for NoxConcentration {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 9]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| NoxConcentration {
+                bank_1_sensor_1: supported(v[0], 0, u16::from_be_bytes([v[1], v[2]])),
+                bank_1_sensor_2: supported(v[0], 1, u16::from_be_bytes([v[3], v[4]])),
+                bank_2_sensor_1: supported(v[0], 2, u16::from_be_bytes([v[5], v[6]])),
+                bank_2_sensor_2: supported(v[0], 3, u16::from_be_bytes([v[7], v[8]])),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for ReagentSystem {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 10]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| ReagentSystem {
+                average_consumption: supported(v[0], 0, word(v[1], v[2]) * 0.005),
+                average_demanded_consumption: supported(v[0], 1, word(v[3], v[4]) * 0.005),
+                tank_level: supported(v[0], 2, percent(f32::from(v[5]))),
+                nox_warning_time: supported(
+                    v[0],
+                    3,
+                    Duration::from_secs(u32::from_be_bytes([v[6], v[7], v[8], v[9]]).into()),
+                ),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for DefSensor {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 4]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| DefSensor {
+                def_type: v[0] & 0x0F,
+                concentration: f32::from(v[1]) * 0.25,
+                tank_temperature: temperature(v[2]),
+                tank_level: percent(f32::from(v[3])),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,712 @@
+//! Descriptions of DTCs
+//!
+//! The descriptions of the generic codes defined by SAE J2012 are included with the `dtc-db`
+//! feature. Manufacturer-specific codes can be added at runtime with
+//! [Dtc::register_descriptions](super::Dtc::register_descriptions), and take priority over the
+//! generic descriptions. Registering needs the `std` feature.
+
+#[cfg(feature = "dtc-db")]
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, RwLock},
+};
+
+/// Descriptions registered at runtime, keyed by code (like `"P1234"`)
+#[cfg(feature = "std")]
+fn registered() -> &'static RwLock<HashMap<String, String>> {
+    static REGISTERED: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+    REGISTERED.get_or_init(Default::default)
+}
+
+#[cfg(feature = "std")]
+pub(super) fn register(descriptions: impl IntoIterator<Item = (String, String)>) {
+    registered()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .extend(descriptions);
+}
+
+#[cfg_attr(not(any(feature = "std", feature = "dtc-db")), allow(unused_variables))]
+pub(super) fn lookup(code: &str) -> Option<String> {
+    #[cfg(feature = "std")]
+    if let Some(description) = registered()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(code)
+    {
+        return Some(description.clone());
+    }
+
+    #[cfg(feature = "dtc-db")]
+    if let Ok(i) = GENERIC.binary_search_by_key(&code, |&(code, _)| code) {
+        return Some(GENERIC[i].1.to_owned());
+    }
+
+    None
+}
+
+/// Generic DTC descriptions, sorted by code
+#[cfg(feature = "dtc-db")]
+const GENERIC: &[(&str, &str)] = &[
+    ("P0010", "\"A\" Camshaft Position Actuator Circuit (Bank 1)"),
+    (
+        "P0011",
+        "\"A\" Camshaft Position - Timing Over-Advanced or System Performance (Bank 1)",
+    ),
+    (
+        "P0012",
+        "\"A\" Camshaft Position - Timing Over-Retarded (Bank 1)",
+    ),
+    (
+        "P0013",
+        "\"B\" Camshaft Position - Actuator Circuit (Bank 1)",
+    ),
+    (
+        "P0014",
+        "\"B\" Camshaft Position - Timing Over-Advanced or System Performance (Bank 1)",
+    ),
+    (
+        "P0016",
+        "Crankshaft Position - Camshaft Position Correlation (Bank 1 Sensor A)",
+    ),
+    (
+        "P0017",
+        "Crankshaft Position - Camshaft Position Correlation (Bank 1 Sensor B)",
+    ),
+    (
+        "P0018",
+        "Crankshaft Position - Camshaft Position Correlation (Bank 2 Sensor A)",
+    ),
+    (
+        "P0019",
+        "Crankshaft Position - Camshaft Position Correlation (Bank 2 Sensor B)",
+    ),
+    ("P0020", "\"A\" Camshaft Position Actuator Circuit (Bank 2)"),
+    (
+        "P0021",
+        "\"A\" Camshaft Position - Timing Over-Advanced or System Performance (Bank 2)",
+    ),
+    (
+        "P0022",
+        "\"A\" Camshaft Position - Timing Over-Retarded (Bank 2)",
+    ),
+    ("P0030", "HO2S Heater Control Circuit (Bank 1 Sensor 1)"),
+    ("P0031", "HO2S Heater Control Circuit Low (Bank 1 Sensor 1)"),
+    (
+        "P0032",
+        "HO2S Heater Control Circuit High (Bank 1 Sensor 1)",
+    ),
+    ("P0036", "HO2S Heater Control Circuit (Bank 1 Sensor 2)"),
+    ("P0037", "HO2S Heater Control Circuit Low (Bank 1 Sensor 2)"),
+    (
+        "P0038",
+        "HO2S Heater Control Circuit High (Bank 1 Sensor 2)",
+    ),
+    ("P0050", "HO2S Heater Control Circuit (Bank 2 Sensor 1)"),
+    ("P0051", "HO2S Heater Control Circuit Low (Bank 2 Sensor 1)"),
+    (
+        "P0052",
+        "HO2S Heater Control Circuit High (Bank 2 Sensor 1)",
+    ),
+    ("P0056", "HO2S Heater Control Circuit (Bank 2 Sensor 2)"),
+    ("P0057", "HO2S Heater Control Circuit Low (Bank 2 Sensor 2)"),
+    (
+        "P0058",
+        "HO2S Heater Control Circuit High (Bank 2 Sensor 2)",
+    ),
+    ("P0100", "Mass or Volume Air Flow Circuit Malfunction"),
+    (
+        "P0101",
+        "Mass or Volume Air Flow Circuit Range/Performance Problem",
+    ),
+    ("P0102", "Mass or Volume Air Flow Circuit Low Input"),
+    ("P0103", "Mass or Volume Air Flow Circuit High Input"),
+    ("P0104", "Mass or Volume Air Flow Circuit Intermittent"),
+    (
+        "P0105",
+        "Manifold Absolute Pressure/Barometric Pressure Circuit Malfunction",
+    ),
+    (
+        "P0106",
+        "Manifold Absolute Pressure/Barometric Pressure Circuit Range/Performance Problem",
+    ),
+    (
+        "P0107",
+        "Manifold Absolute Pressure/Barometric Pressure Circuit Low Input",
+    ),
+    (
+        "P0108",
+        "Manifold Absolute Pressure/Barometric Pressure Circuit High Input",
+    ),
+    (
+        "P0109",
+        "Manifold Absolute Pressure/Barometric Pressure Circuit Intermittent",
+    ),
+    ("P0110", "Intake Air Temperature Circuit Malfunction"),
+    (
+        "P0111",
+        "Intake Air Temperature Circuit Range/Performance Problem",
+    ),
+    ("P0112", "Intake Air Temperature Circuit Low Input"),
+    ("P0113", "Intake Air Temperature Circuit High Input"),
+    ("P0114", "Intake Air Temperature Circuit Intermittent"),
+    ("P0115", "Engine Coolant Temperature Circuit Malfunction"),
+    (
+        "P0116",
+        "Engine Coolant Temperature Circuit Range/Performance Problem",
+    ),
+    ("P0117", "Engine Coolant Temperature Circuit Low Input"),
+    ("P0118", "Engine Coolant Temperature Circuit High Input"),
+    ("P0119", "Engine Coolant Temperature Circuit Intermittent"),
+    (
+        "P0120",
+        "Throttle/Pedal Position Sensor/Switch \"A\" Circuit Malfunction",
+    ),
+    (
+        "P0121",
+        "Throttle/Pedal Position Sensor/Switch \"A\" Circuit Range/Performance Problem",
+    ),
+    (
+        "P0122",
+        "Throttle/Pedal Position Sensor/Switch \"A\" Circuit Low Input",
+    ),
+    (
+        "P0123",
+        "Throttle/Pedal Position Sensor/Switch \"A\" Circuit High Input",
+    ),
+    (
+        "P0124",
+        "Throttle/Pedal Position Sensor/Switch \"A\" Circuit Intermittent",
+    ),
+    (
+        "P0125",
+        "Insufficient Coolant Temperature for Closed Loop Fuel Control",
+    ),
+    (
+        "P0126",
+        "Insufficient Coolant Temperature for Stable Operation",
+    ),
+    (
+        "P0128",
+        "Coolant Thermostat (Coolant Temperature Below Thermostat Regulating Temperature)",
+    ),
+    ("P0130", "O2 Sensor Circuit Malfunction (Bank 1 Sensor 1)"),
+    ("P0131", "O2 Sensor Circuit Low Voltage (Bank 1 Sensor 1)"),
+    ("P0132", "O2 Sensor Circuit High Voltage (Bank 1 Sensor 1)"),
+    ("P0133", "O2 Sensor Circuit Slow Response (Bank 1 Sensor 1)"),
+    (
+        "P0134",
+        "O2 Sensor Circuit No Activity Detected (Bank 1 Sensor 1)",
+    ),
+    (
+        "P0135",
+        "O2 Sensor Heater Circuit Malfunction (Bank 1 Sensor 1)",
+    ),
+    ("P0136", "O2 Sensor Circuit Malfunction (Bank 1 Sensor 2)"),
+    ("P0137", "O2 Sensor Circuit Low Voltage (Bank 1 Sensor 2)"),
+    ("P0138", "O2 Sensor Circuit High Voltage (Bank 1 Sensor 2)"),
+    ("P0139", "O2 Sensor Circuit Slow Response (Bank 1 Sensor 2)"),
+    (
+        "P0140",
+        "O2 Sensor Circuit No Activity Detected (Bank 1 Sensor 2)",
+    ),
+    (
+        "P0141",
+        "O2 Sensor Heater Circuit Malfunction (Bank 1 Sensor 2)",
+    ),
+    ("P0142", "O2 Sensor Circuit Malfunction (Bank 1 Sensor 3)"),
+    ("P0143", "O2 Sensor Circuit Low Voltage (Bank 1 Sensor 3)"),
+    ("P0144", "O2 Sensor Circuit High Voltage (Bank 1 Sensor 3)"),
+    ("P0145", "O2 Sensor Circuit Slow Response (Bank 1 Sensor 3)"),
+    (
+        "P0146",
+        "O2 Sensor Circuit No Activity Detected (Bank 1 Sensor 3)",
+    ),
+    (
+        "P0147",
+        "O2 Sensor Heater Circuit Malfunction (Bank 1 Sensor 3)",
+    ),
+    ("P0150", "O2 Sensor Circuit Malfunction (Bank 2 Sensor 1)"),
+    ("P0151", "O2 Sensor Circuit Low Voltage (Bank 2 Sensor 1)"),
+    ("P0152", "O2 Sensor Circuit High Voltage (Bank 2 Sensor 1)"),
+    ("P0153", "O2 Sensor Circuit Slow Response (Bank 2 Sensor 1)"),
+    (
+        "P0154",
+        "O2 Sensor Circuit No Activity Detected (Bank 2 Sensor 1)",
+    ),
+    (
+        "P0155",
+        "O2 Sensor Heater Circuit Malfunction (Bank 2 Sensor 1)",
+    ),
+    ("P0156", "O2 Sensor Circuit Malfunction (Bank 2 Sensor 2)"),
+    ("P0157", "O2 Sensor Circuit Low Voltage (Bank 2 Sensor 2)"),
+    ("P0158", "O2 Sensor Circuit High Voltage (Bank 2 Sensor 2)"),
+    ("P0159", "O2 Sensor Circuit Slow Response (Bank 2 Sensor 2)"),
+    (
+        "P0160",
+        "O2 Sensor Circuit No Activity Detected (Bank 2 Sensor 2)",
+    ),
+    (
+        "P0161",
+        "O2 Sensor Heater Circuit Malfunction (Bank 2 Sensor 2)",
+    ),
+    ("P0162", "O2 Sensor Circuit Malfunction (Bank 2 Sensor 3)"),
+    ("P0163", "O2 Sensor Circuit Low Voltage (Bank 2 Sensor 3)"),
+    ("P0164", "O2 Sensor Circuit High Voltage (Bank 2 Sensor 3)"),
+    ("P0165", "O2 Sensor Circuit Slow Response (Bank 2 Sensor 3)"),
+    (
+        "P0166",
+        "O2 Sensor Circuit No Activity Detected (Bank 2 Sensor 3)",
+    ),
+    (
+        "P0167",
+        "O2 Sensor Heater Circuit Malfunction (Bank 2 Sensor 3)",
+    ),
+    ("P0170", "Fuel Trim Malfunction (Bank 1)"),
+    ("P0171", "System too Lean (Bank 1)"),
+    ("P0172", "System too Rich (Bank 1)"),
+    ("P0173", "Fuel Trim Malfunction (Bank 2)"),
+    ("P0174", "System too Lean (Bank 2)"),
+    ("P0175", "System too Rich (Bank 2)"),
+    ("P0176", "Fuel Composition Sensor Circuit Malfunction"),
+    ("P0177", "Fuel Composition Sensor Circuit Range/Performance"),
+    ("P0178", "Fuel Composition Sensor Circuit Low Input"),
+    ("P0179", "Fuel Composition Sensor Circuit High Input"),
+    ("P0180", "Fuel Temperature Sensor \"A\" Circuit Malfunction"),
+    (
+        "P0181",
+        "Fuel Temperature Sensor \"A\" Circuit Range/Performance",
+    ),
+    ("P0182", "Fuel Temperature Sensor \"A\" Circuit Low Input"),
+    ("P0183", "Fuel Temperature Sensor \"A\" Circuit High Input"),
+    ("P0190", "Fuel Rail Pressure Sensor Circuit Malfunction"),
+    (
+        "P0191",
+        "Fuel Rail Pressure Sensor Circuit Range/Performance Problem",
+    ),
+    ("P0192", "Fuel Rail Pressure Sensor Circuit Low Input"),
+    ("P0193", "Fuel Rail Pressure Sensor Circuit High Input"),
+    ("P0194", "Fuel Rail Pressure Sensor Circuit Intermittent"),
+    ("P0200", "Injector Circuit Malfunction"),
+    ("P0201", "Injector Circuit Malfunction - Cylinder 1"),
+    ("P0202", "Injector Circuit Malfunction - Cylinder 2"),
+    ("P0203", "Injector Circuit Malfunction - Cylinder 3"),
+    ("P0204", "Injector Circuit Malfunction - Cylinder 4"),
+    ("P0205", "Injector Circuit Malfunction - Cylinder 5"),
+    ("P0206", "Injector Circuit Malfunction - Cylinder 6"),
+    ("P0207", "Injector Circuit Malfunction - Cylinder 7"),
+    ("P0208", "Injector Circuit Malfunction - Cylinder 8"),
+    ("P0209", "Injector Circuit Malfunction - Cylinder 9"),
+    ("P0210", "Injector Circuit Malfunction - Cylinder 10"),
+    ("P0211", "Injector Circuit Malfunction - Cylinder 11"),
+    ("P0212", "Injector Circuit Malfunction - Cylinder 12"),
+    ("P0217", "Engine Overtemperature Condition"),
+    ("P0218", "Transmission Over Temperature Condition"),
+    ("P0219", "Engine Overspeed Condition"),
+    (
+        "P0220",
+        "Throttle/Pedal Position Sensor/Switch \"B\" Circuit Malfunction",
+    ),
+    (
+        "P0221",
+        "Throttle/Pedal Position Sensor/Switch \"B\" Circuit Range/Performance Problem",
+    ),
+    (
+        "P0222",
+        "Throttle/Pedal Position Sensor/Switch \"B\" Circuit Low Input",
+    ),
+    (
+        "P0223",
+        "Throttle/Pedal Position Sensor/Switch \"B\" Circuit High Input",
+    ),
+    ("P0230", "Fuel Pump Primary Circuit Malfunction"),
+    ("P0231", "Fuel Pump Secondary Circuit Low"),
+    ("P0232", "Fuel Pump Secondary Circuit High"),
+    ("P0234", "Engine Overboost Condition"),
+    (
+        "P0235",
+        "Turbocharger Boost Sensor \"A\" Circuit Malfunction",
+    ),
+    (
+        "P0236",
+        "Turbocharger Boost Sensor \"A\" Circuit Range/Performance",
+    ),
+    ("P0237", "Turbocharger Boost Sensor \"A\" Circuit Low"),
+    ("P0238", "Turbocharger Boost Sensor \"A\" Circuit High"),
+    ("P0261", "Cylinder 1 Injector Circuit Low"),
+    ("P0262", "Cylinder 1 Injector Circuit High"),
+    ("P0263", "Cylinder 1 Contribution/Balance Fault"),
+    ("P0264", "Cylinder 2 Injector Circuit Low"),
+    ("P0265", "Cylinder 2 Injector Circuit High"),
+    ("P0266", "Cylinder 2 Contribution/Balance Fault"),
+    ("P0267", "Cylinder 3 Injector Circuit Low"),
+    ("P0268", "Cylinder 3 Injector Circuit High"),
+    ("P0269", "Cylinder 3 Contribution/Balance Fault"),
+    ("P0270", "Cylinder 4 Injector Circuit Low"),
+    ("P0271", "Cylinder 4 Injector Circuit High"),
+    ("P0272", "Cylinder 4 Contribution/Balance Fault"),
+    ("P0273", "Cylinder 5 Injector Circuit Low"),
+    ("P0274", "Cylinder 5 Injector Circuit High"),
+    ("P0275", "Cylinder 5 Contribution/Balance Fault"),
+    ("P0276", "Cylinder 6 Injector Circuit Low"),
+    ("P0277", "Cylinder 6 Injector Circuit High"),
+    ("P0278", "Cylinder 6 Contribution/Balance Fault"),
+    ("P0279", "Cylinder 7 Injector Circuit Low"),
+    ("P0280", "Cylinder 7 Injector Circuit High"),
+    ("P0281", "Cylinder 7 Contribution/Balance Fault"),
+    ("P0282", "Cylinder 8 Injector Circuit Low"),
+    ("P0283", "Cylinder 8 Injector Circuit High"),
+    ("P0284", "Cylinder 8 Contribution/Balance Fault"),
+    ("P0299", "Turbocharger/Supercharger Underboost"),
+    ("P0300", "Random/Multiple Cylinder Misfire Detected"),
+    ("P0301", "Cylinder 1 Misfire Detected"),
+    ("P0302", "Cylinder 2 Misfire Detected"),
+    ("P0303", "Cylinder 3 Misfire Detected"),
+    ("P0304", "Cylinder 4 Misfire Detected"),
+    ("P0305", "Cylinder 5 Misfire Detected"),
+    ("P0306", "Cylinder 6 Misfire Detected"),
+    ("P0307", "Cylinder 7 Misfire Detected"),
+    ("P0308", "Cylinder 8 Misfire Detected"),
+    ("P0309", "Cylinder 9 Misfire Detected"),
+    ("P0310", "Cylinder 10 Misfire Detected"),
+    ("P0311", "Cylinder 11 Misfire Detected"),
+    ("P0312", "Cylinder 12 Misfire Detected"),
+    ("P0313", "Misfire Detected with Low Fuel"),
+    ("P0314", "Single Cylinder Misfire (Cylinder not Specified)"),
+    (
+        "P0320",
+        "Ignition/Distributor Engine Speed Input Circuit Malfunction",
+    ),
+    (
+        "P0325",
+        "Knock Sensor 1 Circuit Malfunction (Bank 1 or Single Sensor)",
+    ),
+    (
+        "P0326",
+        "Knock Sensor 1 Circuit Range/Performance (Bank 1 or Single Sensor)",
+    ),
+    (
+        "P0327",
+        "Knock Sensor 1 Circuit Low Input (Bank 1 or Single Sensor)",
+    ),
+    (
+        "P0328",
+        "Knock Sensor 1 Circuit High Input (Bank 1 or Single Sensor)",
+    ),
+    ("P0330", "Knock Sensor 2 Circuit Malfunction (Bank 2)"),
+    ("P0331", "Knock Sensor 2 Circuit Range/Performance (Bank 2)"),
+    ("P0332", "Knock Sensor 2 Circuit Low Input (Bank 2)"),
+    ("P0333", "Knock Sensor 2 Circuit High Input (Bank 2)"),
+    (
+        "P0335",
+        "Crankshaft Position Sensor \"A\" Circuit Malfunction",
+    ),
+    (
+        "P0336",
+        "Crankshaft Position Sensor \"A\" Circuit Range/Performance Problem",
+    ),
+    (
+        "P0337",
+        "Crankshaft Position Sensor \"A\" Circuit Low Input",
+    ),
+    (
+        "P0338",
+        "Crankshaft Position Sensor \"A\" Circuit High Input",
+    ),
+    (
+        "P0339",
+        "Crankshaft Position Sensor \"A\" Circuit Intermittent",
+    ),
+    (
+        "P0340",
+        "Camshaft Position Sensor \"A\" Circuit Malfunction",
+    ),
+    (
+        "P0341",
+        "Camshaft Position Sensor \"A\" Circuit Range/Performance Problem",
+    ),
+    ("P0342", "Camshaft Position Sensor \"A\" Circuit Low Input"),
+    ("P0343", "Camshaft Position Sensor \"A\" Circuit High Input"),
+    (
+        "P0344",
+        "Camshaft Position Sensor \"A\" Circuit Intermittent",
+    ),
+    (
+        "P0351",
+        "Ignition Coil \"A\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0352",
+        "Ignition Coil \"B\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0353",
+        "Ignition Coil \"C\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0354",
+        "Ignition Coil \"D\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0355",
+        "Ignition Coil \"E\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0356",
+        "Ignition Coil \"F\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0357",
+        "Ignition Coil \"G\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0358",
+        "Ignition Coil \"H\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0359",
+        "Ignition Coil \"I\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0360",
+        "Ignition Coil \"J\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0361",
+        "Ignition Coil \"K\" Primary/Secondary Circuit Malfunction",
+    ),
+    (
+        "P0362",
+        "Ignition Coil \"L\" Primary/Secondary Circuit Malfunction",
+    ),
+    ("P0400", "Exhaust Gas Recirculation Flow Malfunction"),
+    (
+        "P0401",
+        "Exhaust Gas Recirculation Flow Insufficient Detected",
+    ),
+    ("P0402", "Exhaust Gas Recirculation Flow Excessive Detected"),
+    ("P0403", "Exhaust Gas Recirculation Circuit Malfunction"),
+    (
+        "P0404",
+        "Exhaust Gas Recirculation Circuit Range/Performance",
+    ),
+    (
+        "P0405",
+        "Exhaust Gas Recirculation Sensor \"A\" Circuit Low",
+    ),
+    (
+        "P0406",
+        "Exhaust Gas Recirculation Sensor \"A\" Circuit High",
+    ),
+    ("P0410", "Secondary Air Injection System Malfunction"),
+    (
+        "P0411",
+        "Secondary Air Injection System Incorrect Flow Detected",
+    ),
+    (
+        "P0420",
+        "Catalyst System Efficiency Below Threshold (Bank 1)",
+    ),
+    (
+        "P0421",
+        "Warm Up Catalyst Efficiency Below Threshold (Bank 1)",
+    ),
+    (
+        "P0430",
+        "Catalyst System Efficiency Below Threshold (Bank 2)",
+    ),
+    (
+        "P0431",
+        "Warm Up Catalyst Efficiency Below Threshold (Bank 2)",
+    ),
+    ("P0440", "Evaporative Emission Control System Malfunction"),
+    (
+        "P0441",
+        "Evaporative Emission Control System Incorrect Purge Flow",
+    ),
+    (
+        "P0442",
+        "Evaporative Emission Control System Leak Detected (small leak)",
+    ),
+    (
+        "P0443",
+        "Evaporative Emission Control System Purge Control Valve Circuit Malfunction",
+    ),
+    (
+        "P0444",
+        "Evaporative Emission Control System Purge Control Valve Circuit Open",
+    ),
+    (
+        "P0445",
+        "Evaporative Emission Control System Purge Control Valve Circuit Shorted",
+    ),
+    (
+        "P0446",
+        "Evaporative Emission Control System Vent Control Circuit Malfunction",
+    ),
+    (
+        "P0447",
+        "Evaporative Emission Control System Vent Control Circuit Open",
+    ),
+    (
+        "P0448",
+        "Evaporative Emission Control System Vent Control Circuit Shorted",
+    ),
+    (
+        "P0449",
+        "Evaporative Emission Control System Vent Valve/Solenoid Circuit Malfunction",
+    ),
+    (
+        "P0450",
+        "Evaporative Emission Control System Pressure Sensor Malfunction",
+    ),
+    (
+        "P0451",
+        "Evaporative Emission Control System Pressure Sensor Range/Performance",
+    ),
+    (
+        "P0452",
+        "Evaporative Emission Control System Pressure Sensor Low Input",
+    ),
+    (
+        "P0453",
+        "Evaporative Emission Control System Pressure Sensor High Input",
+    ),
+    (
+        "P0455",
+        "Evaporative Emission Control System Leak Detected (gross leak)",
+    ),
+    (
+        "P0456",
+        "Evaporative Emission Control System Leak Detected (very small leak)",
+    ),
+    (
+        "P0457",
+        "Evaporative Emission Control System Leak Detected (fuel cap loose/off)",
+    ),
+    ("P0460", "Fuel Level Sensor Circuit Malfunction"),
+    ("P0461", "Fuel Level Sensor Circuit Range/Performance"),
+    ("P0462", "Fuel Level Sensor Circuit Low Input"),
+    ("P0463", "Fuel Level Sensor Circuit High Input"),
+    ("P0480", "Cooling Fan 1 Control Circuit Malfunction"),
+    ("P0481", "Cooling Fan 2 Control Circuit Malfunction"),
+    ("P0496", "Evaporative Emission System High Purge Flow"),
+    ("P0500", "Vehicle Speed Sensor Malfunction"),
+    ("P0501", "Vehicle Speed Sensor Range/Performance"),
+    ("P0502", "Vehicle Speed Sensor Circuit Low Input"),
+    ("P0503", "Vehicle Speed Sensor Intermittent/Erratic/High"),
+    ("P0505", "Idle Control System Malfunction"),
+    ("P0506", "Idle Control System RPM Lower Than Expected"),
+    ("P0507", "Idle Control System RPM Higher Than Expected"),
+    (
+        "P0520",
+        "Engine Oil Pressure Sensor/Switch Circuit Malfunction",
+    ),
+    (
+        "P0521",
+        "Engine Oil Pressure Sensor/Switch Circuit Range/Performance",
+    ),
+    (
+        "P0522",
+        "Engine Oil Pressure Sensor/Switch Circuit Low Voltage",
+    ),
+    (
+        "P0523",
+        "Engine Oil Pressure Sensor/Switch Circuit High Voltage",
+    ),
+    (
+        "P0530",
+        "A/C Refrigerant Pressure Sensor Circuit Malfunction",
+    ),
+    ("P0560", "System Voltage Malfunction"),
+    ("P0562", "System Voltage Low"),
+    ("P0563", "System Voltage High"),
+    ("P0600", "Serial Communication Link Malfunction"),
+    ("P0601", "Internal Control Module Memory Check Sum Error"),
+    ("P0602", "Control Module Programming Error"),
+    (
+        "P0603",
+        "Internal Control Module Keep Alive Memory (KAM) Error",
+    ),
+    (
+        "P0604",
+        "Internal Control Module Random Access Memory (RAM) Error",
+    ),
+    (
+        "P0605",
+        "Internal Control Module Read Only Memory (ROM) Error",
+    ),
+    ("P0606", "Control Module Processor Fault"),
+    ("P0700", "Transmission Control System Malfunction"),
+    (
+        "P0705",
+        "Transmission Range Sensor Circuit Malfunction (PRNDL Input)",
+    ),
+    (
+        "P0710",
+        "Transmission Fluid Temperature Sensor Circuit Malfunction",
+    ),
+    ("P0715", "Input/Turbine Speed Sensor Circuit Malfunction"),
+    ("P0720", "Output Speed Sensor Circuit Malfunction"),
+    ("P0725", "Engine Speed Input Circuit Malfunction"),
+    ("P0730", "Incorrect Gear Ratio"),
+    ("P0731", "Gear 1 Incorrect Ratio"),
+    ("P0732", "Gear 2 Incorrect Ratio"),
+    ("P0733", "Gear 3 Incorrect Ratio"),
+    ("P0734", "Gear 4 Incorrect Ratio"),
+    ("P0735", "Gear 5 Incorrect Ratio"),
+    ("P0736", "Reverse Incorrect Ratio"),
+    ("P0740", "Torque Converter Clutch Circuit Malfunction"),
+    (
+        "P0741",
+        "Torque Converter Clutch Circuit Performance or Stuck Off",
+    ),
+    ("P0742", "Torque Converter Clutch Circuit Stuck On"),
+    ("P0750", "Shift Solenoid A Malfunction"),
+    ("P0751", "Shift Solenoid A Performance or Stuck Off"),
+    ("P0752", "Shift Solenoid A Stuck On"),
+    ("P0755", "Shift Solenoid B Malfunction"),
+    ("P0756", "Shift Solenoid B Performance or Stuck Off"),
+    ("P0757", "Shift Solenoid B Stuck On"),
+    ("P0760", "Shift Solenoid C Malfunction"),
+    ("P0765", "Shift Solenoid D Malfunction"),
+    ("P0770", "Shift Solenoid E Malfunction"),
+    (
+        "P2002",
+        "Diesel Particulate Filter Efficiency Below Threshold (Bank 1)",
+    ),
+    ("P2096", "Post Catalyst Fuel Trim System Too Lean (Bank 1)"),
+    ("P2097", "Post Catalyst Fuel Trim System Too Rich (Bank 1)"),
+    ("P2098", "Post Catalyst Fuel Trim System Too Lean (Bank 2)"),
+    ("P2099", "Post Catalyst Fuel Trim System Too Rich (Bank 2)"),
+    ("P2187", "System Too Lean at Idle (Bank 1)"),
+    ("P2188", "System Too Rich at Idle (Bank 1)"),
+    ("P2189", "System Too Lean at Idle (Bank 2)"),
+    ("P2190", "System Too Rich at Idle (Bank 2)"),
+    ("P2195", "O2 Sensor Signal Stuck Lean (Bank 1 Sensor 1)"),
+    ("P2196", "O2 Sensor Signal Stuck Rich (Bank 1 Sensor 1)"),
+    ("P2197", "O2 Sensor Signal Stuck Lean (Bank 2 Sensor 1)"),
+    ("P2198", "O2 Sensor Signal Stuck Rich (Bank 2 Sensor 1)"),
+    ("P2270", "O2 Sensor Signal Stuck Lean (Bank 1 Sensor 2)"),
+    ("P2271", "O2 Sensor Signal Stuck Rich (Bank 1 Sensor 2)"),
+    ("P2272", "O2 Sensor Signal Stuck Lean (Bank 2 Sensor 2)"),
+    ("P2273", "O2 Sensor Signal Stuck Rich (Bank 2 Sensor 2)"),
+    ("P2463", "Diesel Particulate Filter - Soot Accumulation"),
+    ("U0001", "High Speed CAN Communication Bus"),
+    ("U0073", "Control Module Communication Bus Off"),
+    ("U0100", "Lost Communication With ECM/PCM \"A\""),
+    ("U0101", "Lost Communication With TCM"),
+    (
+        "U0121",
+        "Lost Communication With Anti-Lock Brake System (ABS) Control Module",
+    ),
+    ("U0140", "Lost Communication With Body Control Module"),
+    ("U0151", "Lost Communication With Restraints Control Module"),
+    (
+        "U0155",
+        "Lost Communication With Instrument Panel Cluster (IPC) Control Module",
+    ),
+];
@@ -1,59 +1,292 @@
-use crate::{Error, Obd2Device, Result};
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+use log::debug;
 
-use super::{Dtc, DtcsInfo, OxygenSensorData};
+use crate::{EcuResponse, Error, Result};
 
-pub(super) fn get_vin<T: Obd2Device>(device: &mut T) -> Result<String> {
-    let mut result = device.obd_command(0x09, 0x02)?.pop().unwrap();
-    result.remove(0); // do not know what this byte is
-    Ok(String::from_utf8(result)?)
+use super::{
+    pid::StandardPid, AuxiliaryInputStatus, Dtc, DtcsInfo, FreezeFrame, FuelMassRate,
+    FuelSystemStatus, FuelType, OxygenSensorData, OxygenSensorsPresent, PermanentDtc, PidSupport,
+    ReadinessStatus, SecondaryAirStatus, WideRangeOxygenSensorData,
+};
+
+/// Scale a single byte value (out of 255) to a percentage
+pub(super) fn percent(v: f32) -> f32 {
+    v * 100. / 255.
+}
+
+/// Convert a time in minutes to a [Duration]
+pub(super) fn minutes(v: u64) -> Duration {
+    Duration::from_secs(v * 60)
+}
+
+/// Get `value` if bit `bit` of `support` is set
+///
+/// Many PIDs with several values start with a byte showing which of them are supported.
+pub(super) fn supported<T>(support: u8, bit: u8, value: T) -> Option<T> {
+    (support & (1 << bit) != 0).then_some(value)
+}
+
+/// Decode a two byte value
+pub(super) fn word(high: u8, low: u8) -> f32 {
+    f32::from(u16::from_be_bytes([high, low]))
+}
+
+/// Decode a temperature stored in one byte, offset by 40 ºC
+pub(super) fn temperature(v: u8) -> i16 {
+    i16::from(v) - 40
+}
+
+/// Decode a temperature stored in two bytes, in units of 0.1 ºC offset by 40 ºC
+pub(super) fn wide_temperature(high: u8, low: u8) -> f32 {
+    word(high, low) / 10. - 40.
+}
+
+/// Get the PID of a fuel trim for `bank`, where `bank_1_pid` is the PID of the trim for bank 1
+///
+/// The trims for bank 2 follow the short and long term trims for bank 1.
+pub(super) fn fuel_trim_pid(bank_1_pid: u8, bank: u8) -> Result<u8> {
+    match bank {
+        1 => Ok(bank_1_pid),
+        2 => Ok(bank_1_pid + 2),
+        _ => Err(Error::InvalidArgument {
+            name: "fuel trim bank",
+            value: bank.into(),
+        }),
+    }
+}
+
+/// Decode fuel trims into percentages from -100 (leaner) to about 99.2 (richer)
+pub(super) fn decode_fuel_trim_percent(responses: Vec<Vec<u8>>) -> Result<Vec<f32>> {
+    Ok(u8::decode_obd2_val(responses)?
+        .into_iter()
+        .map(fuel_trim_percent)
+        .collect())
+}
+
+/// Decode a fuel trim byte into a percentage from -100 (leaner) to about 99.2 (richer)
+fn fuel_trim_percent(v: u8) -> f32 {
+    f32::from(v) * 100. / 128. - 100.
+}
+
+/// Get the PID of `sensor` (1 to 4) of `bank` (1 or 2), for vehicles with up to two banks
+pub(super) fn oxygen_sensor_pid(bank: u8, sensor: u8) -> Result<u8> {
+    if !(1..=2).contains(&bank) {
+        Err(Error::InvalidArgument {
+            name: "oxygen sensor bank",
+            value: bank.into(),
+        })
+    } else if !(1..=4).contains(&sensor) {
+        Err(Error::InvalidArgument {
+            name: "oxygen sensor",
+            value: sensor.into(),
+        })
+    } else {
+        Ok(0x14 + (bank - 1) * 4 + (sensor - 1))
+    }
+}
+
+/// Get the PID of wide-range oxygen sensor `sensor` (1 to 8), where `sensor_1_pid` is the PID of
+/// sensor 1
+pub(super) fn wide_range_oxygen_sensor_pid(sensor_1_pid: u8, sensor: u8) -> Result<u8> {
+    if (1..=8).contains(&sensor) {
+        Ok(sensor_1_pid + sensor - 1)
+    } else {
+        Err(Error::InvalidArgument {
+            name: "wide-range oxygen sensor",
+            value: sensor.into(),
+        })
+    }
+}
+
+/// Decode wide-range oxygen sensor data, with the equivalence ratio in the first two bytes and
+/// either the voltage or current in the last two
+pub(super) fn decode_wide_range_oxygen_sensor(
+    responses: Vec<Vec<u8>>,
+    current: bool,
+) -> Result<Vec<WideRangeOxygenSensorData>> {
+    Ok(<[u8; 4]>::decode_obd2_val(responses)?
+        .into_iter()
+        .map(|v| {
+            let measurement = f32::from(u16::from_be_bytes([v[2], v[3]]));
+            WideRangeOxygenSensorData {
+                equivalence_ratio: f32::from(u16::from_be_bytes([v[0], v[1]])) * 2. / 65536.,
+                voltage: (!current).then(|| measurement * 8. / 65536.),
+                current: current.then(|| measurement / 256. - 128.),
+            }
+        })
+        .collect())
+}
+
+/// Responses from each ECU, with the address of the ECU if the device reports it
+pub(super) type AddressedResponses = Vec<(Option<u32>, Vec<u8>)>;
+
+pub(super) fn with_addresses(responses: Vec<EcuResponse>) -> AddressedResponses {
+    responses
+        .into_iter()
+        .map(|response| (Some(response.address), response.data))
+        .collect()
+}
+
+pub(super) fn without_addresses(responses: Vec<Vec<u8>>) -> AddressedResponses {
+    responses.into_iter().map(|data| (None, data)).collect()
+}
+
+/// Get the entry for the ECU that sent the `index`th response, adding it if it has not responded
+/// before
+///
+/// ECUs are found by their address, or by the order they respond in if it is `None`.
+pub(super) fn ecu_entry<T: Default>(
+    ecus: &mut Vec<(Option<u32>, T)>,
+    address: Option<u32>,
+    index: usize,
+) -> &mut T {
+    let position = ecu_position(ecus, address, index).unwrap_or_else(|| {
+        ecus.push((address, T::default()));
+        ecus.len() - 1
+    });
+    &mut ecus[position].1
+}
+
+/// Find the ECU that sent the `index`th response, if it has responded before
+fn ecu_position<T>(ecus: &[(Option<u32>, T)], address: Option<u32>, index: usize) -> Option<usize> {
+    match address {
+        Some(_) => ecus.iter().position(|(a, _)| *a == address),
+        None => (index < ecus.len()).then_some(index),
+    }
+}
+
+/// Add the support bitmaps for the 32 PIDs after `base` to the support of each ECU, and get
+/// whether any ECU supports the next 32
+pub(super) fn decode_pid_support(
+    support: &mut Vec<(Option<u32>, PidSupport)>,
+    base: u8,
+    responses: AddressedResponses,
+) -> Result<bool> {
+    let mut more = false;
+    for (index, (address, data)) in responses.into_iter().enumerate() {
+        let bitmap = u32::decode_pid(0x01, base, vec![data])?[0];
+        ecu_entry(support, address, index).set_range(base, bitmap);
+        more |= bitmap & 1 != 0;
+    }
+    Ok(more)
+}
+
+/// Service 2 PIDs read into a [FreezeFrame], after PID 2 which identifies the frame
+pub(super) const FREEZE_FRAME_PIDS: [u8; 8] = [0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0C, 0x0D];
+
+/// Build a [FreezeFrame] for each ECU from its response to PID 2 (the DTC) and the responses to
+/// each of [FREEZE_FRAME_PIDS]
+///
+/// The responses to each PID are matched to the ECUs that responded to PID 2 by their addresses.
+/// A PID that failed or has a response of the wrong length is left as `None`, and the values are
+/// decoded like the live values of the same PIDs.
+pub(super) fn decode_freeze_frame(
+    dtc_responses: AddressedResponses,
+    responses: Vec<(u8, Result<AddressedResponses>)>,
+) -> Result<Vec<FreezeFrame>> {
+    let mut frames: Vec<(Option<u32>, FreezeFrame)> = Vec::new();
+    for (index, (address, data)) in dtc_responses.into_iter().enumerate() {
+        let dtc = u16::decode_pid(0x02, 0x02, vec![data])?[0];
+        ecu_entry(&mut frames, address, index).dtc = (dtc != 0).then(|| Dtc::from(dtc));
+    }
+
+    for (pid, result) in responses {
+        let Ok(ecu_responses) = result else {
+            continue;
+        };
+        for (index, (address, data)) in ecu_responses.into_iter().enumerate() {
+            let Some(position) = ecu_position(&frames, address, index) else {
+                continue;
+            };
+            let frame = &mut frames[position].1;
+            let byte = <[u8; 1]>::try_from(data.as_slice()).ok().map(|b| b[0]);
+            let value = StandardPid::from_pid(pid).and_then(|pid| pid.decode(&data));
+            match pid {
+                0x04 => frame.engine_load = byte,
+                0x05 => frame.engine_coolant_temperature = byte.map(temperature),
+                0x06 => frame.short_term_fuel_trim_1 = value,
+                0x07 => frame.long_term_fuel_trim_1 = value,
+                0x08 => frame.short_term_fuel_trim_2 = value,
+                0x09 => frame.long_term_fuel_trim_2 = value,
+                0x0C => frame.rpm = value,
+                0x0D => frame.speed = byte,
+                _ => (),
+            }
+        }
+    }
+    Ok(frames.into_iter().map(|(_, frame)| frame).collect())
 }
 
-pub(super) trait GetObd2Values<T>
+/// A value that is decoded from the responses to a request with a mode and PID
+///
+/// Decoding is separate from sending the request so that the same decoding is used by every kind
+/// of device.
+pub(super) trait DecodeObd2Values
 where
     Self: Sized,
 {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>>;
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>>;
+
+    /// Decode the responses to a request with `mode` and `pid`
+    ///
+    /// If a response cannot be decoded, the error is an [Error::DecodeError] with that response.
+    fn decode_pid(mode: u8, pid: u8, responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        let original = responses.clone();
+        Self::decode_obd2_val(responses).map_err(|e| {
+            debug!("decode_pid: mode {:02X} PID {:02X}: {}", mode, pid, e);
+            let bytes = original
+                .into_iter()
+                .find(|r| Self::decode_obd2_val(vec![r.clone()]).is_err())
+                .unwrap_or_default();
+            Error::DecodeError { mode, pid, bytes }
+        })
+    }
 }
 
-impl<T: Obd2Device> GetObd2Values<T> for u8 {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        Ok(device
-            .obd_command_len::<1>(service, pid)?
+impl DecodeObd2Values for u8 {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 1]>::decode_obd2_val(responses)?
             .into_iter()
             .map(|r| r[0])
             .collect())
     }
 }
 
-impl<T: Obd2Device, const N: usize> GetObd2Values<T> for [u8; N] {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        device.obd_command_len::<N>(service, pid)
+impl<const N: usize> DecodeObd2Values for [u8; N] {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        responses
+            .into_iter()
+            .map(|v| {
+                let l = v.len();
+                v.try_into()
+                    .map_err(|_| Error::IncorrectResponseLength("length", N, l))
+            })
+            .collect()
     }
 }
 
-impl<T: Obd2Device> GetObd2Values<T> for u16 {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        Ok(<[u8; 2]>::get_obd2_val(device, service, pid)?
+impl DecodeObd2Values for u16 {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 2]>::decode_obd2_val(responses)?
             .into_iter()
             .map(Self::from_be_bytes)
             .collect())
     }
 }
 
-impl<T: Obd2Device> GetObd2Values<T> for u32 {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        Ok(<[u8; 4]>::get_obd2_val(device, service, pid)?
+impl DecodeObd2Values for u32 {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 4]>::decode_obd2_val(responses)?
             .into_iter()
             .map(Self::from_be_bytes)
             .collect())
     }
 }
 
-impl<T: Obd2Device> GetObd2Values<T> for DtcsInfo {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        let result = device.obd_command(service, pid)?;
-
-        result
+impl DecodeObd2Values for DtcsInfo {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        responses
             .iter()
             .map(|response| {
                 if response.len() == 4 {
@@ -65,80 +298,270 @@ impl<T: Obd2Device> GetObd2Values<T> for DtcsInfo {
                         is_compression_engine: (response[1] & 0x08) == 0x08,
                         specific_test_availability: ((response[3] as u16) << 8)
                             | (response[2] as u16),
+                        readiness: ReadinessStatus::from_bytes(
+                            response[1],
+                            response[2],
+                            response[3],
+                        ),
                     })
                 } else {
-                    Err(Error::Other(format!(
-                        "get_dtc_info: expected length 4, got {}",
-                        response.len()
-                    )))
+                    Err(Error::IncorrectResponseLength(
+                        "DTC info",
+                        4,
+                        response.len(),
+                    ))
                 }
             })
             .collect()
     }
 }
 
-impl<T: Obd2Device> GetObd2Values<T> for Dtc {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        let resp = u16::get_obd2_val(device, service, pid)?;
+impl DecodeObd2Values for ReadinessStatus {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 4]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| ReadinessStatus::from_bytes(v[1], v[2], v[3]))
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for Dtc {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        let resp = u16::decode_obd2_val(responses)?;
         Ok(resp.into_iter().map(|v| v.into()).collect())
     }
 }
 
-impl<T: Obd2Device> GetObd2Values<T> for OxygenSensorData {
-    fn get_obd2_val(device: &mut T, service: u8, pid: u8) -> Result<Vec<Self>> {
-        let resp = <[u8; 2]>::get_obd2_val(device, service, pid)?;
+impl DecodeObd2Values for OxygenSensorData {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        let resp = <[u8; 2]>::decode_obd2_val(responses)?;
         Ok(resp
             .into_iter()
             .map(|v| OxygenSensorData {
                 voltage: f32::from(v[0]) * 0.005,
-                shrft: f32::from(i16::from(v[1]) - 128) / 128.,
+                // 0xFF if the sensor is not used to calculate the trim
+                shrft: (v[1] != 0xFF).then(|| fuel_trim_percent(v[1])),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for FuelMassRate {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 4]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| FuelMassRate {
+                engine: f32::from(u16::from_be_bytes([v[0], v[1]])) / 50.,
+                vehicle: f32::from(u16::from_be_bytes([v[2], v[3]])) / 50.,
             })
             .collect())
     }
 }
 
-pub(crate) trait GetObd2ValuesMode<T>
+impl DecodeObd2Values for [FuelSystemStatus; 2] {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 2]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| v.map(FuelSystemStatus::from))
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for SecondaryAirStatus {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(u8::decode_obd2_val(responses)?
+            .into_iter()
+            .map(SecondaryAirStatus::from)
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for AuxiliaryInputStatus {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(u8::decode_obd2_val(responses)?
+            .into_iter()
+            .map(AuxiliaryInputStatus::from)
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for FuelType {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(u8::decode_obd2_val(responses)?
+            .into_iter()
+            .map(FuelType::from)
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for OxygenSensorsPresent {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(u8::decode_obd2_val(responses)?
+            .into_iter()
+            .map(OxygenSensorsPresent)
+            .collect())
+    }
+}
+
+/// A value that is decoded from the responses to a request with only a mode
+pub(crate) trait DecodeObd2ValuesMode
 where
     Self: Sized,
 {
-    fn get_obd2_val_mode(device: &mut T, service: u8) -> Result<Vec<Self>>;
+    fn decode_obd2_val_mode(responses: Vec<Vec<u8>>) -> Result<Vec<Self>>;
 }
 
-impl<T: Obd2Device> GetObd2ValuesMode<T> for Vec<Dtc> {
-    fn get_obd2_val_mode(device: &mut T, service: u8) -> Result<Vec<Self>> {
-        let result = device.obd_mode_command(service)?;
-        result
+impl DecodeObd2ValuesMode for Vec<Dtc> {
+    /// Decode the DTCs sent by each ECU
+    ///
+    /// On CAN (ISO 15765-4), each response starts with the number of DTCs, followed by two bytes
+    /// for each DTC, so the response has an odd length. Other protocols send the DTCs in messages
+    /// of three DTCs each without a count, so the response has an even length and unused slots
+    /// are filled with `00 00`.
+    fn decode_obd2_val_mode(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        responses
             .iter()
-            .map(|response| match response.first() {
-                Some(0) => {
-                    if response.len() % 2 == 1 {
-                        let mut ret = Vec::new();
-                        for i in (1..response.len()).step_by(2) {
-                            ret.push(match response[i] >> 6 {
-                                0 => Dtc::Powertrain(0),
-                                1 => Dtc::Chassis(0),
-                                2 => Dtc::Body(0),
-                                3 => Dtc::Network(0),
-                                _ => unreachable!(),
-                            });
-                        }
-                        Ok(ret)
-                    } else {
-                        Err(Error::Other(format!(
-                            "invalid response when getting DTCs {:?}",
-                            response
-                        )))
-                    }
-                }
-                Some(n) if *n <= 3 => todo!(),
-                Some(_) => Err(Error::Other(format!(
-                    "invalid response {:?} when getting DTCs",
-                    response
-                ))),
-                None => Err(Error::Other(
-                    "no response bytes when getting DTCs".to_owned(),
-                )),
+            .map(|response| {
+                let dtc_bytes = if response.len() % 2 == 1 {
+                    let count = usize::from(response[0]);
+                    response.get(1..1 + 2 * count).ok_or(Error::MissingItems {
+                        expected: count,
+                        got: (response.len() - 1) / 2,
+                    })?
+                } else {
+                    response.as_slice()
+                };
+
+                Ok(dtc_bytes
+                    .chunks_exact(2)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                    .filter(|&v| response.len() % 2 == 1 || v != 0)
+                    .map(Dtc::from)
+                    .collect())
             })
             .collect::<Result<Vec<Vec<Dtc>>>>()
     }
 }
+
+impl DecodeObd2ValuesMode for Vec<PermanentDtc> {
+    fn decode_obd2_val_mode(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(Vec::<Dtc>::decode_obd2_val_mode(responses)?
+            .into_iter()
+            .map(|dtcs| dtcs.into_iter().map(PermanentDtc).collect())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    fn codes(dtcs: &[Dtc]) -> Vec<alloc::string::String> {
+        dtcs.iter().map(Dtc::to_string).collect()
+    }
+
+    #[test]
+    fn dtc_letters_and_digits() {
+        let dtcs = Dtc::decode_obd2_val(vec![
+            vec![0x03, 0x01],
+            vec![0x41, 0x23],
+            vec![0x92, 0x34],
+            vec![0xC1, 0x00],
+            vec![0xFF, 0xFF],
+        ])
+        .unwrap();
+        assert_eq!(codes(&dtcs), ["P0301", "C0123", "B1234", "U0100", "U3FFF"]);
+    }
+
+    #[test]
+    fn dtc_wrong_length() {
+        assert!(matches!(
+            Dtc::decode_obd2_val(vec![vec![0x03]]),
+            Err(Error::IncorrectResponseLength(_, 2, 1))
+        ));
+        assert!(matches!(
+            Dtc::decode_obd2_val(vec![vec![0x03, 0x01, 0x00]]),
+            Err(Error::IncorrectResponseLength(_, 2, 3))
+        ));
+    }
+
+    #[test]
+    fn dtcs_on_can() {
+        let dtcs = Vec::<Dtc>::decode_obd2_val_mode(vec![
+            vec![0x02, 0x03, 0x01, 0xC1, 0x00],
+            // no DTCs stored
+            vec![0x00],
+            // a code of zeros is still a code when the count includes it
+            vec![0x01, 0x00, 0x00],
+        ])
+        .unwrap();
+        assert_eq!(codes(&dtcs[0]), ["P0301", "U0100"]);
+        assert!(dtcs[1].is_empty());
+        assert_eq!(codes(&dtcs[2]), ["P0000"]);
+    }
+
+    #[test]
+    fn dtcs_on_other_protocols() {
+        let dtcs = Vec::<Dtc>::decode_obd2_val_mode(vec![
+            vec![0x01, 0x33, 0x00, 0x00, 0x00, 0x00],
+            // two messages of three DTCs
+            vec![
+                0x03, 0x01, 0x03, 0x02, 0x41, 0x23, 0xC1, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+            vec![],
+        ])
+        .unwrap();
+        assert_eq!(codes(&dtcs[0]), ["P0133"]);
+        assert_eq!(codes(&dtcs[1]), ["P0301", "P0302", "C0123", "U0100"]);
+        assert!(dtcs[2].is_empty());
+    }
+
+    #[test]
+    fn dtcs_fewer_than_count() {
+        assert!(Vec::<Dtc>::decode_obd2_val_mode(vec![vec![0x03, 0x03, 0x01]]).is_err());
+    }
+
+    #[test]
+    fn dtc_strings() {
+        assert!(matches!("p0301".parse(), Ok(Dtc::Powertrain(0x0301))));
+        assert!(matches!("U0100".parse(), Ok(Dtc::Network(0x0100))));
+        for invalid in ["", "P", "P030", "P03011", "X0301", "P4000", "P03G1"] {
+            assert!(invalid.parse::<Dtc>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn fuel_trim_percent() {
+        assert_eq!(
+            decode_fuel_trim_percent(vec![vec![0x00], vec![0x80], vec![0xFF]]).unwrap(),
+            [-100., 0., 99.21875]
+        );
+        assert!(decode_fuel_trim_percent(vec![vec![]]).is_err());
+        assert!(decode_fuel_trim_percent(vec![vec![0x80, 0x00]]).is_err());
+    }
+
+    #[test]
+    fn oxygen_sensor_trim_not_used() {
+        let data =
+            OxygenSensorData::decode_obd2_val(vec![vec![0x5A, 0x80], vec![0x14, 0xFF]]).unwrap();
+        assert_eq!(data[0].voltage, 0.45);
+        assert_eq!(data[0].shrft, Some(0.));
+        assert_eq!(data[1].shrft, None);
+    }
+
+    #[test]
+    fn pid_support_wrong_length() {
+        let mut support = Vec::new();
+        let result = decode_pid_support(&mut support, 0x00, vec![(Some(0x7E8), vec![0xBE, 0x1F])]);
+        assert!(matches!(
+            result,
+            Err(Error::DecodeError {
+                mode: 0x01,
+                pid: 0x00,
+                ..
+            })
+        ));
+    }
+}
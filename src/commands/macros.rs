@@ -35,7 +35,7 @@ macro_rules! impl_func {
         fn $name:ident($service:expr) -> $retrieve_type:ty;
     } => {
         fn $name(&mut self) -> Result<Vec<$retrieve_type>> {
-            <$retrieve_type>::get_obd2_val_mode(self, $service)
+            <$retrieve_type>::decode_obd2_val_mode(self.obd_mode_command($service)?)
         }
     };
     {
@@ -43,7 +43,7 @@ macro_rules! impl_func {
         fn $name:ident($service:expr, $pid:expr) -> $retrieve_type:ty;
     } => {
         fn $name(&mut self) -> Result<Vec<$retrieve_type>> {
-            <$retrieve_type>::get_obd2_val(self, $service, $pid)
+            <$retrieve_type>::decode_pid($service, $pid, self.obd_command($service, $pid)?)
         }
     };
     {
@@ -51,7 +51,7 @@ macro_rules! impl_func {
         fn $name:ident($service:expr, $pid:expr, $map:expr) -> $retrieve_type:ty;
     } => {
         fn $name(&mut self) -> Result<Vec<$retrieve_type>> {
-            $map(<$retrieve_type>::get_obd2_val(self, $service, $pid))
+            $map(<$retrieve_type>::decode_pid($service, $pid, self.obd_command($service, $pid)?))
         }
     };
     {
@@ -60,7 +60,7 @@ macro_rules! impl_func {
     } => {
         fn $name(&mut self) -> Result<Vec<$out_type>> {
             Ok(
-                <$retrieve_type>::get_obd2_val(self, $service, $pid)?
+                <$retrieve_type>::decode_pid($service, $pid, self.obd_command($service, $pid)?)?
                     .into_iter()
                     .map(|v| $map(v.into()))
                     .collect()
@@ -69,29 +69,210 @@ macro_rules! impl_func {
     };
 }
 
+#[cfg(feature = "async")]
+macro_rules! impl_async_func {
+    {
+        $(#[$attr:meta])*
+        fn $name:ident($service:expr) -> $retrieve_type:ty;
+    } => {
+        async fn $name(&mut self) -> Result<Vec<$retrieve_type>> {
+            <$retrieve_type>::decode_obd2_val_mode(self.obd_mode_command($service).await?)
+        }
+    };
+    {
+        $(#[$attr:meta])*
+        fn $name:ident($service:expr, $pid:expr) -> $retrieve_type:ty;
+    } => {
+        async fn $name(&mut self) -> Result<Vec<$retrieve_type>> {
+            <$retrieve_type>::decode_pid($service, $pid, self.obd_command($service, $pid).await?)
+        }
+    };
+    {
+        $(#[$attr:meta])*
+        fn $name:ident($service:expr, $pid:expr, $map:expr) -> $retrieve_type:ty;
+    } => {
+        async fn $name(&mut self) -> Result<Vec<$retrieve_type>> {
+            $map(<$retrieve_type>::decode_pid(
+                $service,
+                $pid,
+                self.obd_command($service, $pid).await?,
+            ))
+        }
+    };
+    {
+        $(#[$attr:meta])*
+        fn $name:ident<$retrieve_type:ty>($service:expr, $pid:expr, $map:expr) -> $out_type:ty;
+    } => {
+        async fn $name(&mut self) -> Result<Vec<$out_type>> {
+            Ok(
+                <$retrieve_type>::decode_pid(
+                    $service,
+                    $pid,
+                    self.obd_command($service, $pid).await?,
+                )?
+                    .into_iter()
+                    .map(|v| $map(v.into()))
+                    .collect()
+            )
+        }
+    };
+}
+
+/// Custom functions are either a request with a PID decoded by a function, like
+/// `fn get_vin(0x09, 0x02) -> Result<String> = implementation::decode_vin;`, or a method with
+/// arguments and a body. The body sends requests with `request!(&[mode, ...])`, which is the
+/// [Obd2Device::obd_request](crate::Obd2Device::obd_request) result, so that the same body is used
+/// by the synchronous and asynchronous traits. `request!(multi mode, &pids)` is the
+/// [Obd2Device::obd_multi_command](crate::Obd2Device::obd_multi_command) result instead, and
+/// `request!(tagged &[mode, ...])` is each response with the address of its ECU, or `None` for
+/// devices that do not report it (see
+/// [Obd2Device::obd_request_tagged](crate::Obd2Device::obd_request_tagged)). A method can have one
+/// generic parameter, like
+/// `fn query<P: Pid>(&mut self) -> Result<Vec<P::Output>> { ... }`.
+macro_rules! trait_custom_func {
+    {
+        $(#[$attr:meta])*
+        fn $name:ident$(<$gen:ident: $bound:path>)?(
+            &mut $self:ident $(, $arg:ident: $arg_type:ty)*
+        ) -> $output:ty $body:block
+    } => {
+        $(#[$attr])*
+        fn $name$(<$gen: $bound>)?(&mut $self $(, $arg: $arg_type)*) -> $output;
+    };
+    {
+        $(#[$attr:meta])*
+        fn $name:ident($service:expr, $pid:expr) -> $output:ty = $decode:path;
+    } => {
+        $(#[$attr])*
+        fn $name(&mut self) -> $output;
+    };
+}
+
+macro_rules! impl_custom_func {
+    {
+        fn $name:ident$(<$gen:ident: $bound:path>)?(
+            &mut $self:ident $(, $arg:ident: $arg_type:ty)*
+        ) -> $output:ty $body:block
+    } => {
+        fn $name$(<$gen: $bound>)?(&mut $self $(, $arg: $arg_type)*) -> $output {
+            macro_rules! request {
+                (multi $mode:expr, $pids:expr) => {
+                    $self.obd_multi_command($mode, $pids)
+                };
+                (tagged $request:expr) => {
+                    match $self.obd_request_tagged($request) {
+                        Ok(responses) => Ok(implementation::with_addresses(responses)),
+                        Err(crate::Error::UnsupportedByDevice(_)) => $self
+                            .obd_request($request)
+                            .map(implementation::without_addresses),
+                        Err(e) => Err(e),
+                    }
+                };
+                ($request:expr) => {
+                    $self.obd_request($request)
+                };
+            }
+            $body
+        }
+    };
+    {
+        fn $name:ident($service:expr, $pid:expr) -> $output:ty = $decode:path;
+    } => {
+        fn $name(&mut self) -> $output {
+            $decode(self.obd_command($service, $pid)?)
+        }
+    };
+}
+
+#[cfg(feature = "async")]
+macro_rules! trait_async_custom_func {
+    {
+        $(#[$attr:meta])*
+        fn $name:ident$(<$gen:ident: $bound:path>)?(
+            &mut $self:ident $(, $arg:ident: $arg_type:ty)*
+        ) -> $output:ty $body:block
+    } => {
+        $(#[$attr])*
+        fn $name$(<$gen: $bound>)?(
+            &mut $self
+            $(, $arg: $arg_type)*
+        ) -> impl std::future::Future<Output = $output> + Send;
+    };
+    {
+        $(#[$attr:meta])*
+        fn $name:ident($service:expr, $pid:expr) -> $output:ty = $decode:path;
+    } => {
+        $(#[$attr])*
+        fn $name(&mut self) -> impl std::future::Future<Output = $output> + Send;
+    };
+}
+
+#[cfg(feature = "async")]
+macro_rules! impl_async_custom_func {
+    {
+        fn $name:ident$(<$gen:ident: $bound:path>)?(
+            &mut $self:ident $(, $arg:ident: $arg_type:ty)*
+        ) -> $output:ty $body:block
+    } => {
+        async fn $name$(<$gen: $bound>)?(&mut $self $(, $arg: $arg_type)*) -> $output {
+            macro_rules! request {
+                (multi $mode:expr, $pids:expr) => {
+                    $self.obd_multi_command($mode, $pids).await
+                };
+                (tagged $request:expr) => {
+                    match $self.obd_request_tagged($request).await {
+                        Ok(responses) => Ok(implementation::with_addresses(responses)),
+                        Err(crate::Error::UnsupportedByDevice(_)) => $self
+                            .obd_request($request).await
+                            .map(implementation::without_addresses),
+                        Err(e) => Err(e),
+                    }
+                };
+                ($request:expr) => {
+                    $self.obd_request($request).await
+                };
+            }
+            $body
+        }
+    };
+    {
+        fn $name:ident($service:expr, $pid:expr) -> $output:ty = $decode:path;
+    } => {
+        async fn $name(&mut self) -> $output {
+            $decode(self.obd_command($service, $pid).await?)
+        }
+    };
+}
+
 macro_rules! func {
     {
         $(#[$attr:meta])*
         trait $trait_name:ident;
+        $(#[$async_attr:meta])*
+        async trait $async_trait_name:ident;
 
         $({
             $(
                 $(#[$f_attr_inner:meta])*
-                fn $f_name:ident($self:ident, $f_service:expr$(, $f_pid:expr)?) -> $f_output:ty
-                    $inside:block
+                fn $f_name:ident$(<$f_gen:ident: $f_bound:path>)?($($f_args:tt)*)
+                    -> $f_output:ty $(= $f_decode:path;)? $($f_body:block)?
             )+
         })?
 
         $(
             $(#[$attr_inner:meta])*
-            fn $name:ident$(<$retrieve_type:ty>)?($service:expr$(, $pid:expr$(, $map:expr)?)?) -> $output:ty;
+            fn $name:ident$(<$retrieve_type:ty>)?($service:expr$(, $pid:expr$(, $map:expr)?)?)
+                -> $output:ty;
          )*
     } => {
         $(#[$attr])*
         pub trait $trait_name: private::Sealed {
             $($(
-                $(#[$f_attr_inner])*
-                fn $f_name(&mut self) -> $f_output;
+                trait_custom_func! {
+                    $(#[$f_attr_inner])*
+                    fn $f_name$(<$f_gen: $f_bound>)?($($f_args)*)
+                        -> $f_output $(= $f_decode;)? $($f_body)?
+                }
             )+)?
 
             $(
@@ -110,8 +291,10 @@ macro_rules! func {
 
         impl<T: Obd2Device> $trait_name for T {
             $($(
-                fn $f_name(&mut $self) -> $f_output
-                    $inside
+                impl_custom_func! {
+                    fn $f_name$(<$f_gen: $f_bound>)?($($f_args)*)
+                        -> $f_output $(= $f_decode;)? $($f_body)?
+                }
             )+)?
 
             $(
@@ -121,6 +304,47 @@ macro_rules! func {
                 }
             )*
         }
+
+        $(#[$async_attr])*
+        #[cfg(feature = "async")]
+        pub trait $async_trait_name: private::AsyncSealed {
+            $($(
+                trait_async_custom_func! {
+                    $(#[$f_attr_inner])*
+                    fn $f_name$(<$f_gen: $f_bound>)?($($f_args)*)
+                        -> $f_output $(= $f_decode;)? $($f_body)?
+                }
+            )+)?
+
+            $(
+                $(#[$attr_inner])*
+                ///
+                #[doc=concat!(
+                    "Details: service ", $service,
+                    $(", PID ", $pid,)?
+                    ", read type: `", decode_type!($output $(, $retrieve_type)?), "`"
+                )]
+                fn $name(&mut self)
+                    -> impl std::future::Future<Output = Result<Vec<$output>>> + Send;
+            )*
+        }
+
+        #[cfg(feature = "async")]
+        impl<T: AsyncObd2Device> $async_trait_name for T {
+            $($(
+                impl_async_custom_func! {
+                    fn $f_name$(<$f_gen: $f_bound>)?($($f_args)*)
+                        -> $f_output $(= $f_decode;)? $($f_body)?
+                }
+            )+)?
+
+            $(
+                impl_async_func! {
+                    $(#[$attr_inner])*
+                    fn $name$(<$retrieve_type>)?($service$(, $pid$(, $map)?)?) -> $output;
+                }
+            )*
+        }
     };
 }
 
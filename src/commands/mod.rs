@@ -5,15 +5,39 @@
 //! Wikipedia](https://en.wikipedia.org/wiki/OBD-II_PIDs). This module mostly uses service 1.
 
 mod implementation;
-use implementation::{GetObd2Values, GetObd2ValuesMode};
+use alloc::{string::String, vec::Vec};
+use implementation::DecodeObd2Values;
+#[cfg(feature = "web")]
+pub(crate) use implementation::DecodeObd2ValuesMode;
+#[cfg(not(feature = "web"))]
+use implementation::DecodeObd2ValuesMode;
 
 #[macro_use]
 mod macros;
 
+pub mod aftertreatment;
+mod dtc_db;
+pub mod mode05;
+pub mod mode06;
+pub mod mode08;
+pub mod mode09;
+pub mod pid;
+pub mod turbo;
+#[cfg(feature = "uom")]
+pub mod units;
+
 mod types;
 use types::private;
-pub use types::{Dtc, DtcsInfo, OxygenSensorData};
+pub use types::{
+    AuxiliaryInputStatus, Dtc, DtcsInfo, EngineState, FreezeFrame, FuelMassRate, FuelSystemStatus,
+    FuelType, LiveData, Monitor, MonitorStatus, OxygenSensorData, OxygenSensorsPresent,
+    PermanentDtc, PidSupport, ReadinessStatus, SecondaryAirStatus, WideRangeOxygenSensorData,
+};
+
+use core::time::Duration;
 
+#[cfg(feature = "async")]
+use crate::AsyncObd2Device;
 use crate::{Obd2Device, Result};
 
 func! {
@@ -22,20 +46,524 @@ func! {
     /// Automatically implemented for implementers of [odb2::Obd2Device](crate::Obd2Device), and
     /// currently cannot be otherwise implemented.
     trait Obd2DataRetrieval;
+    /// Trait for asynchronous devices that can retrieve data over OBD-II
+    ///
+    /// The asynchronous version of [Obd2DataRetrieval]. Automatically implemented for
+    /// implementers of [odb2::AsyncObd2Device](crate::AsyncObd2Device), and currently cannot be
+    /// otherwise implemented.
+    async trait AsyncObd2DataRetrieval;
 
     {
         /// Retrieve the VIN (vehicle identification number)
         ///
         /// This should match the number printed on the vehicle, and is a good command for checking
-        /// that the OBD-II interface is working correctly.
-        fn get_vin(self, 0x09, 0x02) -> Result<String> {
-            implementation::get_vin(self)
+        /// that the OBD-II interface is working correctly. [VinInfo](crate::vin::VinInfo) decodes
+        /// the manufacturer, model year and plant from it.
+        ///
+        /// On CAN, each ECU that knows the VIN responds with it, and on older protocols it is
+        /// sent in five numbered messages. The first ECU's VIN is returned, without the padding
+        /// some ECUs add. If no ECU reports a VIN, the error is
+        /// [Error::NoData](crate::Error::NoData).
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// // the engine and transmission controllers both respond, one padding the VIN
+        /// let mut engine = vec![0x01];
+        /// engine.extend(b"1HGCM82633A004352");
+        /// let mut transmission = vec![0x01, 0x00, 0x00, 0x00];
+        /// transmission.extend(b"1HGCM82633A004352");
+        /// let mut device =
+        ///     MockObd2Device::new().with_response(0x09, 0x02, [engine, transmission]);
+        ///
+        /// assert_eq!(device.get_vin().unwrap(), "1HGCM82633A004352");
+        /// ```
+        fn get_vin(0x09, 0x02) -> Result<String> = mode09::decode_vin;
+
+        /// Get the calibration IDs of the software in each ECU
+        ///
+        /// Each ECU can have several calibrations, each identified by a string of up to 16
+        /// characters.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut response = vec![0x01];
+        /// response.extend(b"JMB*36761500\0\0\0\0");
+        /// let mut device = MockObd2Device::new().with_response(0x09, 0x04, [response]);
+        ///
+        /// assert_eq!(device.get_calibration_ids().unwrap(), [["JMB*36761500"]]);
+        /// ```
+        fn get_calibration_ids(0x09, 0x04) -> Result<Vec<Vec<String>>> =
+            mode09::decode_calibration_ids;
+
+        /// Get the calibration verification numbers (CVNs) of the software in each ECU
+        ///
+        /// There is one CVN, a checksum of the calibration, for each of the IDs returned by
+        /// [get_calibration_ids](Self::get_calibration_ids), in the same order. They are used to
+        /// detect whether emissions-related software has been modified.
+        fn get_cvns(0x09, 0x06) -> Result<Vec<Vec<u32>>> = mode09::decode_cvns;
+
+        /// Get in-use performance tracking for each ECU of a spark ignition (gasoline) vehicle
+        ///
+        /// See [get_compression_in_use_performance](Self::get_compression_in_use_performance) for
+        /// compression ignition (Diesel) vehicles.
+        fn get_spark_in_use_performance(0x09, 0x08) -> Result<Vec<mode09::InUsePerformance>> =
+            mode09::decode_spark_in_use_performance;
+
+        /// Get the name of each ECU
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut response = vec![0x01];
+        /// response.extend(b"ECM\0-EngineControl\0\0");
+        /// let mut device = MockObd2Device::new().with_response(0x09, 0x0A, [response]);
+        ///
+        /// let names = device.get_ecu_names().unwrap();
+        /// assert_eq!(names[0].acronym, "ECM");
+        /// assert_eq!(names[0].name, "EngineControl");
+        /// ```
+        fn get_ecu_names(0x09, 0x0A) -> Result<Vec<mode09::EcuName>> = mode09::decode_ecu_names;
+
+        /// Get in-use performance tracking for each ECU of a compression ignition (Diesel) vehicle
+        ///
+        /// See [get_spark_in_use_performance](Self::get_spark_in_use_performance) for spark
+        /// ignition vehicles.
+        fn get_compression_in_use_performance(0x09, 0x0B)
+            -> Result<Vec<mode09::InUsePerformance>> =
+            mode09::decode_compression_in_use_performance;
+
+        /// Request a PID defined with [Pid](pid::Pid), and decode the response from each ECU
+        ///
+        /// This is for PIDs that do not have a getter here; see the [pid] module.
+        fn query<P: pid::Pid>(&mut self) -> Result<Vec<P::Output>> {
+            request!(&[P::MODE, P::PID])?
+                .iter()
+                .map(|response| P::decode(response))
+                .collect()
+        }
+
+        /// Request a manufacturer-specific data identifier defined with
+        /// [ManufacturerPid](pid::ManufacturerPid), and decode the response from each ECU that
+        /// supports it
+        fn query_manufacturer<P: pid::ManufacturerPid>(&mut self) -> Result<Vec<P::Output>> {
+            let [high, low] = P::DID.to_be_bytes();
+            request!(&[0x22, high, low])?
+                .iter()
+                .map(|response| P::decode(response))
+                .collect()
+        }
+
+        /// Get the current values of several PIDs, requesting up to six at once
+        ///
+        /// This is much faster than calling each getter, especially for loggers that poll the same
+        /// PIDs repeatedly. Each value is decoded with
+        /// [StandardPid::decode](pid::StandardPid::decode), with one value for each ECU that
+        /// responds. PIDs that do not hold a single number, and PIDs the vehicle does not support,
+        /// are left out. Only CAN vehicles accept several PIDs in one request, so on other
+        /// protocols each PID is requested separately.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{
+        ///     commands::{pid::StandardPid, Obd2DataRetrieval},
+        ///     device::MockObd2Device,
+        /// };
+        ///
+        /// let mut device = MockObd2Device::new()
+        ///     .with_response(0x01, 0x0C, [vec![0x1A, 0xF8]])
+        ///     .with_response(0x01, 0x0D, [vec![0x32]]);
+        ///
+        /// let snapshot = device
+        ///     .get_snapshot(&[StandardPid::EngineSpeed, StandardPid::VehicleSpeed])
+        ///     .unwrap();
+        /// assert_eq!(snapshot[&StandardPid::EngineSpeed], [1726.]);
+        /// assert_eq!(snapshot[&StandardPid::VehicleSpeed], [50.]);
+        /// ```
+        fn get_snapshot(&mut self, pids: &[pid::StandardPid])
+            -> Result<pid::Snapshot> {
+            let mut snapshot = pid::Snapshot::new();
+            for chunk in pids.chunks(pid::MAX_PIDS_PER_REQUEST) {
+                let numbers: Vec<u8> = chunk.iter().map(|pid| pid.pid()).collect();
+                let responses = match request!(multi 0x01, &numbers) {
+                    Ok(responses) => responses,
+                    // ECUs leave out the PIDs they do not support, so none of these are
+                    Err(crate::Error::NoData) => continue,
+                    // some ECUs reject the whole request over one PID, so the others are
+                    // requested on their own
+                    Err(e) if e.is_declined() && numbers.len() > 1 => {
+                        let mut responses = Vec::new();
+                        for pid in numbers {
+                            match request!(multi 0x01, &[pid]) {
+                                Ok(pid_responses) => responses.extend(pid_responses),
+                                Err(e) if e.is_declined() => (),
+                                Err(e) => return Err(e),
+                            }
+                        }
+                        responses
+                    }
+                    Err(e) => return Err(e),
+                };
+                for response in responses {
+                    pid::decode_snapshot(&response, &mut snapshot)?;
+                }
+            }
+            Ok(snapshot)
+        }
+
+        /// Get the engine speed, vehicle speed, load, throttle, air flow, fuel trims and
+        /// temperatures, requesting them together
+        ///
+        /// Loggers can write the result as one row per sample, instead of calling each getter with
+        /// the values drifting apart in time. Like [get_snapshot](Self::get_snapshot), the PIDs are
+        /// requested six at a time, and values the vehicle does not report are `None`.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::Obd2Simulator};
+        ///
+        /// let mut device = Obd2Simulator::new();
+        ///
+        /// let data = device.get_live_snapshot().unwrap();
+        /// assert!(data.rpm.unwrap() > 0.);
+        /// assert_eq!(data.speed, Some(0.));
+        /// ```
+        fn get_live_snapshot(&mut self) -> Result<LiveData> {
+            let mut snapshot = pid::Snapshot::new();
+            for chunk in LiveData::PIDS.chunks(pid::MAX_PIDS_PER_REQUEST) {
+                let numbers: Vec<u8> = chunk.iter().map(|pid| pid.pid()).collect();
+                match request!(multi 0x01, &numbers) {
+                    Ok(responses) => {
+                        for response in responses {
+                            pid::decode_snapshot(&response, &mut snapshot)?;
+                        }
+                    }
+                    Err(crate::Error::NoData) => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(LiveData::from_snapshot(&snapshot))
+        }
+
+        /// Find out whether the engine is off, cranking, running or cutting off fuel
+        ///
+        /// The engine speed, load and control module voltage are requested together, and the
+        /// state is inferred with [EngineState::infer]. If no ECU responds, the ignition is taken
+        /// to be off.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{
+        ///     commands::{EngineState, Obd2DataRetrieval},
+        ///     device::MockObd2Device,
+        ///     Error,
+        /// };
+        ///
+        /// // idling at 750 rpm, with the alternator charging
+        /// let mut device = MockObd2Device::new()
+        ///     .with_response(0x01, 0x0C, [vec![0x0B, 0xB8]])
+        ///     .with_response(0x01, 0x04, [vec![0x40]])
+        ///     .with_response(0x01, 0x42, [vec![0x36, 0xB0]]);
+        /// assert_eq!(device.get_engine_state().unwrap(), EngineState::Running);
+        ///
+        /// // the ignition was turned off, so no ECU responds
+        /// for pid in [0x0C, 0x04, 0x42] {
+        ///     device.set_error(0x01, pid, || Error::NoData);
+        /// }
+        /// assert_eq!(device.get_engine_state().unwrap(), EngineState::Off);
+        /// ```
+        fn get_engine_state(&mut self) -> Result<EngineState> {
+            let pids = [
+                pid::StandardPid::EngineSpeed,
+                pid::StandardPid::EngineLoad,
+                pid::StandardPid::ControlModuleVoltage,
+            ];
+            let mut snapshot = pid::Snapshot::new();
+            match request!(multi 0x01, &pids.map(|pid| pid.pid())) {
+                Ok(responses) => {
+                    for response in responses {
+                        pid::decode_snapshot(&response, &mut snapshot)?;
+                    }
+                }
+                Err(crate::Error::NoData | crate::Error::Timeout) => return Ok(EngineState::Off),
+                Err(e) => return Err(e),
+            }
+            let value = |pid| snapshot.get(&pid).and_then(|v| v.first().copied());
+            Ok(EngineState::infer(
+                value(pid::StandardPid::EngineSpeed),
+                value(pid::StandardPid::EngineLoad),
+                value(pid::StandardPid::ControlModuleVoltage),
+            ))
+        }
+
+        /// Get the service 1 PIDs supported by each ECU
+        ///
+        /// This requests PID 0x00, then each following multiple of 0x20 (0x20, 0x40, ..., 0xE0) as
+        /// long as some ECU reports that it is supported. The ECUs are in the order they first
+        /// responded, and each ECU's responses are found by its address when the device reports it
+        /// (see [obd_request_tagged](crate::Obd2Device::obd_request_tagged)).
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::Obd2Simulator};
+        ///
+        /// let mut device = Obd2Simulator::new();
+        /// let support = &device.get_pid_support().unwrap()[0];
+        /// assert!(support.supports(0x0C));
+        /// assert!(!support.supports(0x0A));
+        /// ```
+        fn get_pid_support(&mut self) -> Result<Vec<PidSupport>> {
+            let mut support = Vec::new();
+            let mut base = 0x00;
+            loop {
+                let more = implementation::decode_pid_support(
+                    &mut support,
+                    base,
+                    request!(tagged &[0x01, base])?,
+                )?;
+                if base == 0xE0 || !more {
+                    break Ok(support.into_iter().map(|(_, ecu)| ecu).collect());
+                }
+                base += 0x20;
+            }
+        }
+
+        /// Get the short term fuel trim for `bank` (1 or 2) as a percentage
+        ///
+        /// This is for vehicles with closed loop air/fuel ratio control. It ranges from -100% to
+        /// about 99.2%, where negative percentages mean the mix is being made more lean. If the
+        /// fuel system is in open-loop control, this will read 0.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut device = MockObd2Device::new().with_response(0x01, 0x08, [vec![0x8A]]);
+        ///
+        /// assert_eq!(device.get_short_term_fuel_trim(2).unwrap(), vec![7.8125]);
+        /// assert!(device.get_short_term_fuel_trim(3).is_err());
+        /// ```
+        fn get_short_term_fuel_trim(&mut self, bank: u8) -> Result<Vec<f32>> {
+            let pid = implementation::fuel_trim_pid(0x06, bank)?;
+            implementation::decode_fuel_trim_percent(request!(&[0x01, pid])?)
+        }
+
+        /// Get the long term fuel trim for `bank` (1 or 2) as a percentage
+        ///
+        /// Like [get_short_term_fuel_trim](Self::get_short_term_fuel_trim), but for the trim
+        /// saved between shutdowns of the engine. In open-loop control, if this value is not used
+        /// it will read 0.
+        fn get_long_term_fuel_trim(&mut self, bank: u8) -> Result<Vec<f32>> {
+            let pid = implementation::fuel_trim_pid(0x07, bank)?;
+            implementation::decode_fuel_trim_percent(request!(&[0x01, pid])?)
+        }
+
+        /// Get the voltage and associated short term fuel trim of `sensor` (1 to 4) in `bank` (1 or
+        /// 2)
+        ///
+        /// This uses the numbering for vehicles with up to two banks; see
+        /// [get_oxygen_sensors_present](Self::get_oxygen_sensors_present) for which sensors exist.
+        /// For vehicles with four banks, use [get_oxygen_sensor_1](Self::get_oxygen_sensor_1)
+        /// through [get_oxygen_sensor_8](Self::get_oxygen_sensor_8).
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut device = MockObd2Device::new()
+        ///     .with_response(0x01, 0x13, [vec![0x33]])
+        ///     .with_response(0x01, 0x19, [vec![0x5A, 0x80]]);
+        ///
+        /// assert!(device.get_oxygen_sensors_present().unwrap()[0].contains(2, 2));
+        /// assert_eq!(device.get_oxygen_sensor(2, 2).unwrap()[0].voltage, 0.45);
+        /// ```
+        fn get_oxygen_sensor(&mut self, bank: u8, sensor: u8) -> Result<Vec<OxygenSensorData>> {
+            let pid = implementation::oxygen_sensor_pid(bank, sensor)?;
+            OxygenSensorData::decode_obd2_val(request!(&[0x01, pid])?)
+        }
+
+        /// Get the equivalence ratio and voltage of wide-range oxygen sensor `sensor` (1 to 8)
+        ///
+        /// This uses PIDs 0x24 to 0x2B, with sensors numbered like
+        /// [get_oxygen_sensor_1](Self::get_oxygen_sensor_1) through
+        /// [get_oxygen_sensor_8](Self::get_oxygen_sensor_8).
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut device = MockObd2Device::new()
+        ///     .with_response(0x01, 0x24, [vec![0x80, 0x00, 0x40, 0x00]]);
+        ///
+        /// let data = &device.get_wide_range_oxygen_sensor_voltage(1).unwrap()[0];
+        /// assert_eq!(data.equivalence_ratio, 1.);
+        /// assert_eq!(data.voltage, Some(2.));
+        /// ```
+        fn get_wide_range_oxygen_sensor_voltage(
+            &mut self,
+            sensor: u8
+        ) -> Result<Vec<WideRangeOxygenSensorData>> {
+            let pid = implementation::wide_range_oxygen_sensor_pid(0x24, sensor)?;
+            implementation::decode_wide_range_oxygen_sensor(request!(&[0x01, pid])?, false)
+        }
+
+        /// Get the equivalence ratio and current of wide-range oxygen sensor `sensor` (1 to 8)
+        ///
+        /// Like [get_wide_range_oxygen_sensor_voltage](Self::get_wide_range_oxygen_sensor_voltage),
+        /// but using PIDs 0x34 to 0x3B, which report the sensor's current instead of its voltage.
+        fn get_wide_range_oxygen_sensor_current(
+            &mut self,
+            sensor: u8
+        ) -> Result<Vec<WideRangeOxygenSensorData>> {
+            let pid = implementation::wide_range_oxygen_sensor_pid(0x34, sensor)?;
+            implementation::decode_wide_range_oxygen_sensor(request!(&[0x01, pid])?, true)
+        }
+
+        /// Get the freeze frame stored by each ECU
+        ///
+        /// This uses service 2 to read the values of some PIDs at the time the DTC that caused the
+        /// freeze frame was set, to see the conditions that caused it. `frame` is the number of
+        /// the freeze frame; most vehicles only store frame 0.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut device = MockObd2Device::new();
+        /// device.set_request_response(&[0x02, 0x02, 0x00], [vec![0x03, 0x01]]);
+        /// device.set_request_response(&[0x02, 0x0C, 0x00], [vec![0x1A, 0xF8]]);
+        /// device.set_request_response(&[0x02, 0x0D, 0x00], [vec![88]]);
+        ///
+        /// let frame = &device.get_freeze_frame(0).unwrap()[0];
+        /// assert_eq!(frame.dtc.as_ref().unwrap().to_string(), "P0301");
+        /// assert_eq!(frame.rpm, Some(1726.));
+        /// assert_eq!(frame.speed, Some(88));
+        /// assert_eq!(frame.engine_load, None);
+        /// ```
+        fn get_freeze_frame(&mut self, frame: u8) -> Result<Vec<FreezeFrame>> {
+            let dtc_responses = request!(tagged &[0x02, 0x02, frame])?;
+            let mut responses = Vec::new();
+            for pid in implementation::FREEZE_FRAME_PIDS {
+                responses.push((pid, request!(tagged &[0x02, pid, frame])));
+            }
+            implementation::decode_freeze_frame(dtc_responses, responses)
+        }
+
+        /// Get the result of oxygen sensor monitor test `test_id` on `sensor` for each ECU
+        ///
+        /// This uses service 5, which is only supported by vehicles that do not use CAN. See
+        /// [mode05] for the test IDs and how sensors are numbered.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut device = MockObd2Device::new();
+        /// // rich to lean threshold on bank 1 sensor 1: 0.45 V, with limits of 0.3 V and 0.6 V
+        /// device.set_request_response(&[0x05, 0x01, 0x01], [vec![90, 60, 120]]);
+        ///
+        /// let result = &device.get_oxygen_sensor_test_result(0x01, 0x01).unwrap()[0];
+        /// assert_eq!(result.unit, "V");
+        /// assert_eq!(result.passed(), Some(true));
+        /// ```
+        fn get_oxygen_sensor_test_result(
+            &mut self,
+            test_id: u8,
+            sensor: u8
+        ) -> Result<Vec<mode05::OxygenSensorTestResult>> {
+            mode05::decode_result(test_id, sensor, request!(&[0x05, test_id, sensor])?)
+        }
+
+        /// Get the results of the tests run by on-board monitor `mid` for each ECU
+        ///
+        /// See [mode06] for what these tests are. The supported monitors can be found by
+        /// requesting `mid` 0x00, 0x20, 0x40, etc., but that response is a bitmap rather than test
+        /// results.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+        ///
+        /// let mut device = MockObd2Device::new();
+        /// // misfire cylinder 1: 12 misfires in the last drive cycle, with a limit of 50
+        /// device.set_response(0x06, 0xA2, [vec![0x0B, 0x24, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x32]]);
+        ///
+        /// let results = device.get_monitor_test_results(0xA2).unwrap();
+        /// assert_eq!(results[0][0].value, 12.);
+        /// assert_eq!(results[0][0].unit, "counts");
+        /// assert!(results[0][0].passed);
+        /// ```
+        fn get_monitor_test_results(
+            &mut self,
+            mid: u8
+        ) -> Result<Vec<Vec<mode06::MonitorTestResult>>> {
+            mode06::decode_results(mid, request!(&[0x06, mid])?)
+        }
+
+        /// Command each ECU to run the test or operate the component `test_id`
+        ///
+        /// This uses service 8, which changes how the vehicle operates; see [mode08] for the
+        /// standardized tests and when the ECU runs them. The request is sent with five zero data
+        /// bytes, as required by the vehicles that do not use CAN.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{
+        ///     commands::{mode08, Obd2DataRetrieval},
+        ///     device::MockObd2Device,
+        /// };
+        ///
+        /// let mut device = MockObd2Device::new();
+        /// device.set_request_response(&[0x08, 0x01, 0, 0, 0, 0, 0], [vec![0, 0, 0, 0, 0]]);
+        ///
+        /// let responses = device.request_control(mode08::EVAP_LEAK_TEST).unwrap();
+        /// assert!(matches!(responses[0], mode08::ControlResponse::EvapSystemSealed));
+        /// ```
+        fn request_control(&mut self, test_id: u8) -> Result<Vec<mode08::ControlResponse>> {
+            mode08::decode_response(test_id, request!(&[0x08, test_id, 0, 0, 0, 0, 0])?)
         }
     }
 
     /// Get list of DTCs for each ECU
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+    ///
+    /// let mut device = MockObd2Device::new();
+    /// device.set_mode_response(
+    ///     0x03,
+    ///     [
+    ///         // CAN: the number of DTCs, then two bytes for each
+    ///         vec![0x02, 0x03, 0x01, 0xC1, 0x00],
+    ///         // other protocols: groups of three DTCs, padded with zeros
+    ///         vec![0x01, 0x33, 0x00, 0x00, 0x00, 0x00],
+    ///     ],
+    /// );
+    ///
+    /// let dtcs = device.get_dtcs().unwrap();
+    /// assert_eq!(dtcs[0].iter().map(|d| d.to_string()).collect::<Vec<_>>(), ["P0301", "U0100"]);
+    /// assert_eq!(dtcs[1].iter().map(|d| d.to_string()).collect::<Vec<_>>(), ["P0133"]);
+    /// ```
     fn get_dtcs(0x03) -> Vec<Dtc>;
 
+    /// Get list of pending DTCs for each ECU
+    ///
+    /// Pending DTCs have been detected during the current or last drive cycle, but have not yet
+    /// been confirmed, so they have not turned on the malfunction indicator light. They are
+    /// reported separately from [get_dtcs](Self::get_dtcs).
+    fn get_pending_dtcs(0x07) -> Vec<Dtc>;
+
+    /// Get list of permanent DTCs for each ECU
+    ///
+    /// See [PermanentDtc] for how these differ from the codes returned by
+    /// [get_dtcs](Self::get_dtcs).
+    fn get_permanent_dtcs(0x0A) -> Vec<PermanentDtc>;
+
     /// Get service 1 PID support for $01 to $20
     fn get_service_1_pid_support_1(0x01, 0x00) -> u32;
 
@@ -45,20 +573,26 @@ func! {
     /// Get DTC that caused the current freeze frame
     fn get_freeze_frame_dtc(0x01, 0x02) -> Dtc;
 
-    /// Get fuel system status (system A and B)
+    /// Get the status of fuel systems A and B
     ///
-    /// The first value describes the first fuel system. If there is a secondary fuel system, the
-    /// second value can be nonzero. Each value can represent one bank using the bottom four bits,
-    /// or two banks using either the bottom five bits or the whole byte, depending on the
-    /// configuration of the banks.
+    /// The second value is [Off](FuelSystemStatus::Off) unless the vehicle has a second fuel
+    /// system.
     ///
-    /// Valid values for single bank systems:
-    /// - `0`: engine off (including temporarily for vehicles that turn of the engine at idle)
-    /// - `1`: open loop — conditions to go closed loop not yet met
-    /// - `2`: closed loop
-    /// - `4`: open loop — due to current conditions
-    /// - `8`: open loop — due to fault
-    fn get_fuel_system_status(0x01, 0x03) -> [u8; 2];
+    /// # Example
+    /// ```
+    /// use obd2::{
+    ///     commands::{FuelSystemStatus, Obd2DataRetrieval},
+    ///     device::MockObd2Device,
+    /// };
+    ///
+    /// let mut device = MockObd2Device::new().with_response(0x01, 0x03, [vec![0x02, 0x00]]);
+    ///
+    /// assert_eq!(
+    ///     device.get_fuel_system_status().unwrap()[0],
+    ///     [FuelSystemStatus::ClosedLoop, FuelSystemStatus::Off]
+    /// );
+    /// ```
+    fn get_fuel_system_status(0x01, 0x03) -> [FuelSystemStatus; 2];
 
     /// Get the calculated engine load (out of 255)
     fn get_engine_load(0x01, 0x04) -> u8;
@@ -66,24 +600,30 @@ func! {
     /// Get the temperature of the engine's coolant in ºC
     fn get_engine_coolant_temperature<u8>(0x01, 0x05, |v: i16| v - 40) -> i16;
 
-    /// Get the short term fuel trim for bank 1
+    /// Get the short term fuel trim for bank 1 as a fraction
     ///
     /// This is for vehicles with closed loop air/fuel ratio control. It ranges from about -1 to 1,
-    /// where negative percentages mean the mix is being made more lean. If the fuel system is in
+    /// where negative fractions mean the mix is being made more lean. If the fuel system is in
     /// open-loop control, this will read 0.
+    #[deprecated(note = "use `get_short_term_fuel_trim(1)`, which reads a percentage")]
     fn get_short_term_fuel_trim_1<u8>(0x01, 0x06, |v: f32| (v / 128.) - 1.) -> f32;
 
-    /// Get the long term fuel trim for bank 1
+    /// Get the long term fuel trim for bank 1 as a fraction
     ///
     /// This is for vehicles with closed loop air/fuel ratio control. It ranges from about -1 to 1,
-    /// where negative percentages mean the mix is being made more lean. This long term trim value
+    /// where negative fractions mean the mix is being made more lean. This long term trim value
     /// represents a value saved between shutdowns of the engine. In open-loop control, if this
     /// value is not used it will read 0.
+    #[deprecated(note = "use `get_long_term_fuel_trim(1)`, which reads a percentage")]
     fn get_long_term_fuel_trim_1<u8>(0x01, 0x07, |v: f32| (v / 128.) - 1.) -> f32;
 
-    /// Like [get_short_term_fuel_trim_1](Self::get_short_term_fuel_trim_1) but for bank 2
+    /// Like [get_short_term_fuel_trim_1](Self::get_short_term_fuel_trim_1) but for bank 2, as a
+    /// fraction
+    #[deprecated(note = "use `get_short_term_fuel_trim(2)`, which reads a percentage")]
     fn get_short_term_fuel_trim_2<u8>(0x01, 0x08, |v: f32| (v / 128.) - 1.) -> f32;
-    /// Like [get_long_term_fuel_trim_1](Self::get_long_term_fuel_trim_1) but for bank 2
+    /// Like [get_long_term_fuel_trim_1](Self::get_long_term_fuel_trim_1) but for bank 2, as a
+    /// fraction
+    #[deprecated(note = "use `get_long_term_fuel_trim(2)`, which reads a percentage")]
     fn get_long_term_fuel_trim_2<u8>(0x01, 0x09, |v: f32| (v / 128.) - 1.) -> f32;
 
     /// Get the fuel pressure in kPa
@@ -94,7 +634,16 @@ func! {
     /// Get the intake manifold pressure in kPa
     ///
     /// This measurement is absolute pressure.
-    fn get_engine_manifold_pressure<u16>(0x01, 0x0B, |v: f32| v) -> f32;
+    #[deprecated(note = "use `get_intake_manifold_pressure`")]
+    fn get_engine_manifold_pressure<u8>(0x01, 0x0B, |v: f32| v) -> f32;
+
+    /// Get the intake manifold pressure in kPa
+    ///
+    /// This measurement is absolute pressure. Together with
+    /// [get_intake_air_temperature](Self::get_intake_air_temperature) and
+    /// [get_rpm](Self::get_rpm), it can be used to calculate the air flow on vehicles without a
+    /// MAF sensor.
+    fn get_intake_manifold_pressure(0x01, 0x0B) -> u8;
 
     /// Get the RPM of the engine in increments of 0.25
     fn get_rpm<u16>(0x01, 0x0C, |v: f32| v / 4.0) -> f32;
@@ -112,7 +661,14 @@ func! {
     fn get_intake_air_temperature<u8>(0x01, 0x0F, |v: i16| v - 40) -> i16;
 
     /// Get air flow rate in g/s
-    fn get_air_flow_rate<u16>(0x01, 0x10, |v: f32| v * 0.01) -> f32; // TODO: scaling
+    #[deprecated(note = "use `get_maf`")]
+    fn get_air_flow_rate<u16>(0x01, 0x10, |v: f32| v * 0.01) -> f32;
+
+    /// Get the air flow rate measured by the MAF (mass air flow) sensor in g/s
+    ///
+    /// This ranges from 0 to 655.35 g/s. The fuel flow can be estimated from it by dividing by the
+    /// air/fuel ratio, which is about 14.7 for gasoline.
+    fn get_maf<u16>(0x01, 0x10, |v: f32| v / 100.) -> f32;
 
     /// Get absolute throttle position (out of 255)
     ///
@@ -120,14 +676,14 @@ func! {
     /// throttle will probably be less than 255.
     fn get_throttle_position(0x01, 0x11) -> u8;
 
+    /// Get absolute throttle position as a percentage
+    ///
+    /// Like [get_throttle_position](Self::get_throttle_position), but scaled from 0 to 100%.
+    fn get_throttle_position_percent<u8>(0x01, 0x11, implementation::percent) -> f32;
+
     /// Get commanded secondary air status (bitfield)
     ///
-    /// This describes where the secondary air system has been commanded to inject air. The valid
-    /// values are:
-    /// - `1`: Upstream of the first catalytic converter inlet
-    /// - `2`: Downstream of the first catalytic converter inlet
-    /// - `4`: Off (or atmosphere)
-    /// - `8`: On for diagnostics
+    /// This describes where the secondary air system has been commanded to inject air.
     ///
     /// This system exists to reduce emissions. By injecting air in front of the catalytic
     /// converter, extra fuel in the exhaust combusts, heating the catalytic converter. Once the
@@ -135,7 +691,7 @@ func! {
     /// help it catalyze unburned fuel.
     ///
     /// See: <https://en.wikipedia.org/wiki/Secondary_air_injection>
-    fn get_commanded_secondary_air_status(0x01, 0x12) -> u8;
+    fn get_commanded_secondary_air_status(0x01, 0x12) -> SecondaryAirStatus;
 
     /// Get location of oxygen sensors
     ///
@@ -147,6 +703,12 @@ func! {
     /// least significant bit.
     fn get_oxygen_sensors_2_bank(0x01, 0x13) -> u8;
 
+    /// Get the oxygen sensors present, for vehicles with up to two banks
+    ///
+    /// This is the same data as [get_oxygen_sensors_2_bank](Self::get_oxygen_sensors_2_bank),
+    /// decoded into [OxygenSensorsPresent].
+    fn get_oxygen_sensors_present(0x01, 0x13) -> OxygenSensorsPresent;
+
     /// Get oxygen sensor 1 voltage and associated air/fuel short term trim
     ///
     /// This is bank 1, sensor 1.
@@ -206,18 +768,411 @@ func! {
     /// the less significant bit.
     fn get_oxygen_sensors_4_bank(0x01, 0x1D) -> u8;
 
-    /// Get auxiliary input status
-    ///
-    /// The least significant bit indicates whether [power
-    /// take-off](https://en.wikipedia.org/wiki/Power_Take_Off) is active.
-    fn get_auxiliary_input_status(0x01, 0x1E) -> u8;
+    /// Get auxiliary input status, which shows whether power take-off is active
+    fn get_auxiliary_input_status(0x01, 0x1E) -> AuxiliaryInputStatus;
 
     /// Get the amount of time since the engine was started in seconds
     ///
-    /// This should saturate—not roll over—after the engine has been running for [u16::MAX] seconds
-    /// (≈18.2 hours).
+    /// This should saturate—not roll over—after the engine has been running for [u16::MAX]
+    /// seconds (≈18.2 hours).
+    #[deprecated(note = "use `get_run_time_since_start`")]
     fn get_run_time(0x01, 0x1F) -> u16;
 
+    /// Get the amount of time since the engine was started
+    ///
+    /// This has a resolution of one second, and should saturate—not roll over—after the engine
+    /// has been running for [u16::MAX] seconds (≈18.2 hours).
+    fn get_run_time_since_start<u16>(0x01, 0x1F, Duration::from_secs) -> Duration;
+
     /// Get service 1 PID support for $21 to $40
     fn get_service_1_pid_support_2(0x01, 0x20) -> u32;
+
+    /// Get the distance traveled with the malfunction indicator light on in km
+    fn get_distance_with_mil(0x01, 0x21) -> u16;
+
+    /// Get the fuel rail pressure in kPa, relative to the intake manifold vacuum
+    fn get_fuel_rail_pressure<u16>(0x01, 0x22, |v: f32| v * 0.079) -> f32;
+
+    /// Get the fuel rail gauge pressure in kPa
+    ///
+    /// This is for Diesel or gasoline direct injection, where the rail pressure is much higher
+    /// than [get_fuel_pressure](Self::get_fuel_pressure) can report.
+    fn get_fuel_rail_gauge_pressure<u16>(0x01, 0x23, |v: f32| v * 10.) -> f32;
+
+    /// Get the commanded evaporative purge as a percentage
+    ///
+    /// This is how far the ECU is commanding the purge valve to open, which draws fuel vapor from
+    /// the charcoal canister into the intake.
+    fn get_evap_purge<u8>(0x01, 0x2E, implementation::percent) -> f32;
+
+    /// Get the fuel tank level as a percentage
+    ///
+    /// This is how full the fuel tank is, from 0% (empty) to 100% (full).
+    fn get_fuel_level<u8>(0x01, 0x2F, implementation::percent) -> f32;
+
+    /// Get the distance traveled since the DTCs were cleared in km
+    ///
+    /// Like [get_time_since_dtcs_cleared](Self::get_time_since_dtcs_cleared), a small distance
+    /// means the DTCs were cleared recently. This saturates at [u16::MAX] km.
+    fn get_distance_since_codes_cleared(0x01, 0x31) -> u16;
+
+    /// Get the evaporative system vapor pressure in Pa
+    ///
+    /// This is gauge pressure, from -8192 to 8192 Pa. See
+    /// [get_evap_system_vapor_pressure_wide](Self::get_evap_system_vapor_pressure_wide) for
+    /// vehicles with a wider range sensor.
+    fn get_evap_system_vapor_pressure<u16>(0x01, 0x32, |v: u16| f32::from(v as i16) / 4.) -> f32;
+
+    /// Get the status of each monitor during the current drive cycle
+    ///
+    /// Unlike the [readiness](DtcsInfo::readiness) from [get_dtc_info](Self::get_dtc_info),
+    /// which covers the time since DTCs were cleared, this is reset at the start of each drive
+    /// cycle.
+    fn get_drive_cycle_readiness(0x01, 0x41) -> ReadinessStatus;
+
+    /// Get the absolute load value as a percentage
+    ///
+    /// This is the air mass per intake stroke relative to the maximum at wide open throttle, so it
+    /// can go above 100% on engines with forced induction. Unlike
+    /// [get_engine_load](Self::get_engine_load), it is proportional to the engine's torque.
+    fn get_absolute_load<u16>(0x01, 0x43, implementation::percent) -> f32;
+
+    /// Get the commanded air/fuel equivalence ratio (λ)
+    ///
+    /// 1 is stoichiometric, and values less than 1 are rich. To get the commanded air/fuel ratio,
+    /// multiply this by the stoichiometric ratio of the fuel (about 14.7 for gasoline).
+    fn get_commanded_equivalence_ratio<u16>(0x01, 0x44, |v: f32| v * 2. / 65536.) -> f32;
+
+    /// Get relative throttle position as a percentage
+    ///
+    /// This is the throttle position relative to the learned closed position, so idle throttle
+    /// should read close to 0%.
+    fn get_relative_throttle_position<u8>(0x01, 0x45, implementation::percent) -> f32;
+
+    /// Get ambient air temperature in ºC
+    ///
+    /// Like [get_intake_air_temperature](Self::get_intake_air_temperature), but for the air
+    /// outside the vehicle.
+    fn get_ambient_air_temperature<u8>(0x01, 0x46, |v: i16| v - 40) -> i16;
+
+    /// Get absolute throttle position B as a percentage
+    ///
+    /// Vehicles with electronic throttle control have redundant throttle position sensors, which
+    /// are reported separately from [get_throttle_position](Self::get_throttle_position).
+    fn get_absolute_throttle_position_b<u8>(0x01, 0x47, implementation::percent) -> f32;
+
+    /// Get absolute throttle position C as a percentage
+    fn get_absolute_throttle_position_c<u8>(0x01, 0x48, implementation::percent) -> f32;
+
+    /// Get accelerator pedal position D as a percentage
+    ///
+    /// Like the throttle position sensors, the accelerator pedal has redundant sensors, labeled D,
+    /// E, and F. Each is the raw sensor value, so a released pedal will probably read more than 0%.
+    fn get_accelerator_pedal_position_d<u8>(0x01, 0x49, implementation::percent) -> f32;
+
+    /// Get accelerator pedal position E as a percentage
+    fn get_accelerator_pedal_position_e<u8>(0x01, 0x4A, implementation::percent) -> f32;
+
+    /// Get accelerator pedal position F as a percentage
+    fn get_accelerator_pedal_position_f<u8>(0x01, 0x4B, implementation::percent) -> f32;
+
+    /// Get commanded throttle actuator position as a percentage
+    ///
+    /// This is the position the ECU is commanding the electronic throttle to open to, from 0%
+    /// (closed) to 100% (wide open).
+    fn get_commanded_throttle_actuator<u8>(0x01, 0x4C, implementation::percent) -> f32;
+
+    /// Get the amount of time the engine has run with the malfunction indicator light on
+    ///
+    /// This has a resolution of one minute.
+    fn get_time_with_mil_on<u16>(0x01, 0x4D, implementation::minutes) -> Duration;
+
+    /// Get the amount of time the engine has run since the DTCs were cleared
+    ///
+    /// This has a resolution of one minute. A short time means the DTCs were cleared recently,
+    /// which could hide a problem until the monitors run again.
+    fn get_time_since_dtcs_cleared<u16>(0x01, 0x4E, implementation::minutes) -> Duration;
+
+    /// Get the type of fuel the vehicle uses
+    fn get_fuel_type(0x01, 0x51) -> FuelType;
+
+    /// Get the percentage of ethanol in the fuel
+    ///
+    /// This is measured by flex-fuel vehicles, which can run on blends of gasoline and ethanol.
+    fn get_ethanol_fuel_percent<u8>(0x01, 0x52, implementation::percent) -> f32;
+
+    /// Get the absolute evaporative system vapor pressure in kPa
+    fn get_evap_system_vapor_pressure_absolute<u16>(0x01, 0x53, |v: f32| v / 200.) -> f32;
+
+    /// Get the evaporative system vapor pressure in Pa, with a wide range
+    ///
+    /// Like [get_evap_system_vapor_pressure](Self::get_evap_system_vapor_pressure), but from
+    /// -32767 to 32768 Pa.
+    fn get_evap_system_vapor_pressure_wide<u16>(0x01, 0x54, |v: i32| v - 32767) -> i32;
+
+    /// Get the fuel rail absolute pressure in kPa
+    ///
+    /// Like [get_fuel_rail_gauge_pressure](Self::get_fuel_rail_gauge_pressure), but absolute
+    /// rather than relative to the atmosphere.
+    fn get_fuel_rail_absolute_pressure<u16>(0x01, 0x59, |v: f32| v * 10.) -> f32;
+
+    /// Get the remaining life of the hybrid battery pack as a percentage
+    ///
+    /// SAE J1979 calls this the remaining life of the pack, but many vehicles report its state of
+    /// charge instead, so compare it at the same charge level when tracking degradation.
+    fn get_hybrid_battery_remaining<u8>(0x01, 0x5B, implementation::percent) -> f32;
+
+    /// Get the fuel injection timing in degrees
+    ///
+    /// This ranges from -210º to about 302º, where positive values mean fuel is injected before
+    /// the piston reaches the top of the cylinder.
+    fn get_fuel_injection_timing<u16>(0x01, 0x5D, |v: f32| v / 128. - 210.) -> f32;
+
+    /// Get the rate of fuel flow into the engine in L/h
+    fn get_engine_fuel_rate<u16>(0x01, 0x5E, |v: f32| v / 20.) -> f32;
+
+    /// Get the commanded and measured boost pressure
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+    ///
+    /// let mut device = MockObd2Device::new().with_response(
+    ///     0x01,
+    ///     0x70,
+    ///     [vec![0x03, 0x0C, 0x80, 0x0C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]],
+    /// );
+    ///
+    /// let boost = &device.get_boost_pressure_control().unwrap()[0];
+    /// assert_eq!(boost.commanded_a, Some(100.));
+    /// assert_eq!(boost.actual_a, Some(96.));
+    /// assert_eq!(boost.commanded_b, None);
+    /// ```
+    fn get_boost_pressure_control(0x01, 0x70) -> turbo::BoostPressureControl;
+
+    /// Get the commanded and measured variable geometry turbo vane positions
+    fn get_vgt_control(0x01, 0x71) -> turbo::VgtControl;
+
+    /// Get the commanded and measured wastegate positions
+    fn get_wastegate_control(0x01, 0x72) -> turbo::WastegateControl;
+
+    /// Get the exhaust pressure for each bank
+    fn get_exhaust_pressure(0x01, 0x73) -> turbo::ExhaustPressure;
+
+    /// Get the speed of each turbocharger
+    fn get_turbocharger_rpm(0x01, 0x74) -> turbo::TurbochargerRpm;
+
+    /// Get the compressor and turbine temperatures of turbocharger A
+    fn get_turbocharger_a_temperature(0x01, 0x75) -> turbo::TurbochargerTemperature;
+
+    /// Get the compressor and turbine temperatures of turbocharger B
+    fn get_turbocharger_b_temperature(0x01, 0x76) -> turbo::TurbochargerTemperature;
+
+    /// Get the charge air cooler (intercooler) temperatures
+    fn get_charge_air_cooler_temperature(0x01, 0x77) -> turbo::ChargeAirCoolerTemperature;
+
+    /// Get the exhaust gas temperatures for bank 1
+    fn get_exhaust_gas_temperature_1(0x01, 0x78) -> turbo::ExhaustGasTemperature;
+
+    /// Get the exhaust gas temperatures for bank 2
+    fn get_exhaust_gas_temperature_2(0x01, 0x79) -> turbo::ExhaustGasTemperature;
+
+    /// Get the diesel particulate filter pressures for bank 1
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+    ///
+    /// let mut device = MockObd2Device::new().with_response(
+    ///     0x01,
+    ///     0x7A,
+    ///     [vec![0x01, 0x04, 0xB0, 0x00, 0x00, 0x00, 0x00]],
+    /// );
+    ///
+    /// let dpf = &device.get_dpf_pressure_1().unwrap()[0];
+    /// assert_eq!(dpf.delta, Some(12.));
+    /// assert_eq!(dpf.inlet, None);
+    /// ```
+    fn get_dpf_pressure_1(0x01, 0x7A) -> aftertreatment::DpfPressure;
+
+    /// Get the diesel particulate filter pressures for bank 2
+    fn get_dpf_pressure_2(0x01, 0x7B) -> aftertreatment::DpfPressure;
+
+    /// Get the diesel particulate filter inlet and outlet temperatures
+    fn get_dpf_temperature(0x01, 0x7C) -> aftertreatment::DpfTemperature;
+
+    /// Get the NOx concentration measured by each sensor
+    fn get_nox_concentration(0x01, 0x83) -> aftertreatment::NoxConcentration;
+
+    /// Get the SCR reagent (diesel exhaust fluid) consumption and tank level
+    fn get_reagent_system(0x01, 0x85) -> aftertreatment::ReagentSystem;
+
+    /// Get the diesel exhaust fluid type, quality, temperature, and level
+    fn get_def_sensor(0x01, 0x9B) -> aftertreatment::DefSensor;
+
+    /// Get the rate of fuel flow by mass for the engine and for the whole vehicle
+    ///
+    /// This is the newer version of [get_engine_fuel_rate](Self::get_engine_fuel_rate), which is
+    /// more accurate for calculating fuel consumption since it does not depend on the density of
+    /// the fuel.
+    fn get_fuel_mass_rate(0x01, 0x9D) -> FuelMassRate;
+
+    /// Get the NOx concentration from each sensor, corrected for the sensors' known errors
+    fn get_nox_concentration_corrected(0x01, 0xA1) -> aftertreatment::NoxConcentration;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{device::fake::FakeDevice, Error};
+
+    #[test]
+    fn pid_support_by_ecu_address() {
+        // the transmission responds first to PID 0x20, and does not support PID 0x40
+        let mut device = FakeDevice::new()
+            .with_tagged_response(
+                &[0x01, 0x00],
+                "7E8 06 41 00 BE 1F A8 13\n7E9 06 41 00 80 00 00 01",
+            )
+            .with_tagged_response(
+                &[0x01, 0x20],
+                "7E9 06 41 20 40 00 00 00\n7E8 06 41 20 80 00 00 01",
+            )
+            .with_tagged_response(&[0x01, 0x40], "7E8 06 41 40 00 00 00 80")
+            .into_obd2();
+
+        let [engine, transmission] = &device.get_pid_support().unwrap()[..] else {
+            panic!();
+        };
+        assert!(engine.supports(0x0C));
+        assert!(engine.supports(0x21));
+        assert!(!engine.supports(0x22));
+        assert!(engine.supports(0x59));
+        assert!(!transmission.supports(0x0C));
+        assert!(transmission.supports(0x22));
+        assert!(!transmission.supports(0x21));
+    }
+
+    #[test]
+    fn freeze_frame_by_ecu_address() {
+        // the transmission stored no freeze frame, and responds first to the fuel trim
+        let mut device = FakeDevice::new()
+            .with_tagged_response(
+                &[0x02, 0x02, 0x00],
+                "7E8 05 42 02 00 03 01\n7E9 05 42 02 00 00 00",
+            )
+            .with_tagged_response(
+                &[0x02, 0x06, 0x00],
+                "7E9 04 42 06 00 80\n7E8 04 42 06 00 8A",
+            )
+            .with_tagged_response(&[0x02, 0x0C, 0x00], "7E8 05 42 0C 00 1A F8")
+            .with_tagged_response(&[0x02, 0x0D, 0x00], "7E8 03 42 0D 00")
+            .into_obd2();
+
+        let [engine, transmission] = &device.get_freeze_frame(0).unwrap()[..] else {
+            panic!();
+        };
+        assert_eq!(engine.dtc.as_ref().unwrap().to_string(), "P0301");
+        assert_eq!(engine.short_term_fuel_trim_1, Some(7.8125));
+        assert_eq!(engine.rpm, Some(1726.));
+        // too short
+        assert_eq!(engine.speed, None);
+        assert!(transmission.dtc.is_none());
+        assert_eq!(transmission.short_term_fuel_trim_1, Some(0.));
+        assert_eq!(transmission.rpm, None);
+    }
+
+    #[test]
+    fn snapshot_requests_each_pid_without_can() {
+        let mut device = FakeDevice::new()
+            .with_protocol(crate::Protocol::Iso9141_2)
+            .with_tagged_response(&[0x01, 0x0C], "48 6B 10 41 0C 1A F8 22")
+            .with_tagged_response(&[0x01, 0x0D], "48 6B 10 41 0D 32 43")
+            .into_obd2();
+
+        let snapshot = device
+            .get_snapshot(&[
+                pid::StandardPid::EngineSpeed,
+                pid::StandardPid::EngineCoolantTemperature,
+                pid::StandardPid::VehicleSpeed,
+            ])
+            .unwrap();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[&pid::StandardPid::EngineSpeed], [1726.]);
+        assert_eq!(snapshot[&pid::StandardPid::VehicleSpeed], [50.]);
+        assert_eq!(
+            device.device().requests,
+            [[0x01, 0x0C], [0x01, 0x05], [0x01, 0x0D]]
+        );
+    }
+
+    #[test]
+    fn snapshot_joins_each_pid_by_ecu() {
+        // the engine (10) and another ECU (18) respond in a different order to each request
+        let mut device = FakeDevice::new()
+            .with_protocol(crate::Protocol::Iso9141_2)
+            .with_tagged_response(
+                &[0x01, 0x0C],
+                "48 6B 10 41 0C 1A F8 22\n48 6B 18 41 0C 00 00 18",
+            )
+            .with_tagged_response(&[0x01, 0x0D], "48 6B 18 41 0D 00 19\n48 6B 10 41 0D 32 43")
+            .into_obd2();
+
+        let snapshot = device
+            .get_snapshot(&[
+                pid::StandardPid::EngineSpeed,
+                pid::StandardPid::VehicleSpeed,
+            ])
+            .unwrap();
+        assert_eq!(snapshot[&pid::StandardPid::EngineSpeed], [1726., 0.]);
+        assert_eq!(snapshot[&pid::StandardPid::VehicleSpeed], [50., 0.]);
+    }
+
+    #[test]
+    fn snapshot_drops_rejected_pids() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x01, 0x0C, 0x0D], "7F 01 12")
+            .with_response(&[0x01, 0x0C], "41 0C 1A F8")
+            .with_response(&[0x01, 0x0D], "7F 01 12")
+            .into_obd2();
+
+        let snapshot = device
+            .get_snapshot(&[
+                pid::StandardPid::EngineSpeed,
+                pid::StandardPid::VehicleSpeed,
+            ])
+            .unwrap();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[&pid::StandardPid::EngineSpeed], [1726.]);
+
+        // no ECU supports any of them
+        let snapshot = device
+            .get_snapshot(&[
+                pid::StandardPid::FuelPressure,
+                pid::StandardPid::TimingAdvance,
+            ])
+            .unwrap();
+        assert!(snapshot.is_empty());
+    }
+
+    #[test]
+    fn vin_missing_or_short() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x09, 0x02], "49 02")
+            .into_obd2();
+        assert!(matches!(device.get_vin(), Err(Error::NoData)));
+
+        let mut device = FakeDevice::new()
+            .with_response(&[0x09, 0x02], "49 02 01 31 48 47 43")
+            .into_obd2();
+        assert!(matches!(device.get_vin(), Err(Error::NoData)));
+    }
+
+    #[test]
+    fn calibration_ids_empty() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x09, 0x04], "49 04")
+            .into_obd2();
+        assert!(matches!(device.get_calibration_ids(), Err(Error::NoData)));
+    }
 }
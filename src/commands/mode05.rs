@@ -0,0 +1,105 @@
+//! Service 5 oxygen sensor monitoring test results
+//!
+//! On vehicles that do not use CAN (like ISO 9141-2 and ISO 14230-4 KWP2000), the oxygen sensor
+//! monitor reports its test results through service 5 instead of [service 6](super::mode06). Each
+//! result is identified by a test ID and the oxygen sensor it was measured on. Sensors are
+//! numbered like in
+//! [get_oxygen_sensors_2_bank](super::Obd2DataRetrieval::get_oxygen_sensors_2_bank): `0x01` is
+//! bank 1 sensor 1, `0x02` is bank 1 sensor 2, and so on through `0x08` for bank 2 sensor 4.
+
+use crate::{Error, Result};
+use alloc::vec::Vec;
+
+/// The latest result of one oxygen sensor monitor test
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct OxygenSensorTestResult {
+    /// The test ID; IDs from `0x01` to `0x0A` are standardized (see [test_name])
+    pub id: u8,
+
+    /// The number of the oxygen sensor that was tested
+    pub sensor: u8,
+
+    /// The value measured by the test
+    pub value: f32,
+
+    /// The minimum value for the test to pass, if the ECU reports limits
+    pub min: Option<f32>,
+
+    /// The maximum value for the test to pass, if the ECU reports limits
+    pub max: Option<f32>,
+
+    /// The unit of the value and limits, which is empty for manufacturer-defined tests (in which
+    /// case the values are not scaled)
+    pub unit: &'static str,
+}
+
+impl OxygenSensorTestResult {
+    /// Whether the value is within the limits, or `None` if the ECU did not report limits
+    pub fn passed(&self) -> Option<bool> {
+        Some((self.min?..=self.max?).contains(&self.value))
+    }
+}
+
+/// Get the name of a standardized test ID, if it is known
+pub fn test_name(id: u8) -> Option<&'static str> {
+    Some(match id {
+        0x01 => "Rich to lean sensor threshold voltage",
+        0x02 => "Lean to rich sensor threshold voltage",
+        0x03 => "Low sensor voltage for switch time calculation",
+        0x04 => "High sensor voltage for switch time calculation",
+        0x05 => "Rich to lean sensor switch time",
+        0x06 => "Lean to rich sensor switch time",
+        0x07 => "Minimum sensor voltage for test cycle",
+        0x08 => "Maximum sensor voltage for test cycle",
+        0x09 => "Time between sensor transitions",
+        0x0A => "Sensor period",
+        _ => return None,
+    })
+}
+
+/// Get the scaling of a test's values and their unit
+fn scaling(id: u8) -> (f32, &'static str) {
+    match id {
+        0x01..=0x04 | 0x07 | 0x08 => (0.005, "V"),
+        0x05 | 0x06 => (0.004, "s"),
+        0x09 | 0x0A => (0.04, "s"),
+        _ => (1., ""),
+    }
+}
+
+/// Decode the result of test `id` on `sensor` sent by each ECU
+///
+/// Each response is the test value, optionally followed by the minimum and maximum limits.
+pub(super) fn decode_result(
+    id: u8,
+    sensor: u8,
+    responses: Vec<Vec<u8>>,
+) -> Result<Vec<OxygenSensorTestResult>> {
+    let (scale, unit) = scaling(id);
+    responses
+        .into_iter()
+        .map(|response| {
+            let (value, limits) = match *response.as_slice() {
+                [value] => (value, None),
+                [value, min, max] => (value, Some((min, max))),
+                _ => {
+                    return Err(Error::DecodeError {
+                        mode: 0x05,
+                        pid: id,
+                        bytes: response,
+                    })
+                }
+            };
+            Ok(OxygenSensorTestResult {
+                id,
+                sensor,
+                value: f32::from(value) * scale,
+                min: limits.map(|(min, _)| f32::from(min) * scale),
+                max: limits.map(|(_, max)| f32::from(max) * scale),
+                unit,
+            })
+        })
+        .collect()
+}
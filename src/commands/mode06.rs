@@ -0,0 +1,239 @@
+//! Service 6 on-board monitoring test results
+//!
+//! The ECU runs monitors which test emissions-related systems, like each cylinder for misfires or
+//! the catalytic converter's efficiency. Service 6 reports the latest result of each test along
+//! with the limits it must be within to pass, which shows how close a system is to failing before
+//! any DTC is set. Monitors are identified by OBDMIDs (on-board diagnostic monitor IDs), and each
+//! monitor can run several tests.
+//!
+//! Only the format used on CAN (ISO 15765-4) is supported, where each result has a unit and
+//! scaling ID to convert it to physical units.
+
+use crate::{Error, Result};
+use alloc::{vec, vec::Vec};
+
+/// The latest result of one test run by an on-board monitor
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct MonitorTestResult {
+    /// The OBDMID of the monitor that ran the test
+    pub mid: u8,
+
+    /// The test ID, which is either standardized or manufacturer-defined
+    pub id: u8,
+
+    /// The value measured by the test
+    pub value: f32,
+
+    /// The minimum value for the test to pass
+    pub min: f32,
+
+    /// The maximum value for the test to pass
+    pub max: f32,
+
+    /// The unit of the value and limits, which is empty for unitless values and for unknown unit
+    /// and scaling IDs (in which case the values are not scaled)
+    pub unit: &'static str,
+
+    /// Whether the value is within the limits
+    pub passed: bool,
+}
+
+/// A standardized unit and scaling ID, which describes how to convert a test's raw value
+struct Uas {
+    scale: f32,
+    offset: f32,
+    unit: &'static str,
+    signed: bool,
+}
+
+impl Uas {
+    /// Look up the unit and scaling from SAE J1979 Appendix E
+    fn from_id(id: u8) -> Option<Self> {
+        let (scale, offset, unit) = match id & 0x7f {
+            0x01 => (1., 0., ""),
+            0x02 => (0.1, 0., ""),
+            0x03 => (0.01, 0., ""),
+            0x04 => (0.001, 0., ""),
+            0x05 => (0.000_030_5, 0., ""),
+            0x06 => (0.000_305, 0., ""),
+            0x07 => (0.25, 0., "rpm"),
+            0x08 => (0.01, 0., "km/h"),
+            0x09 => (1., 0., "km/h"),
+            0x0A => (0.122, 0., "mV"),
+            0x0B => (0.001, 0., "V"),
+            0x0C => (0.01, 0., "V"),
+            0x0D => (0.003_906_25, 0., "mA"),
+            0x0E => (0.001, 0., "A"),
+            0x0F => (0.01, 0., "A"),
+            0x10 => (1., 0., "ms"),
+            0x11 => (100., 0., "ms"),
+            0x12 => (1., 0., "s"),
+            0x13 => (1., 0., "mΩ"),
+            0x14 => (1., 0., "Ω"),
+            0x15 => (1., 0., "kΩ"),
+            0x16 if id & 0x80 == 0 => (0.1, -40., "ºC"),
+            0x16 => (0.1, 0., "ºC"),
+            0x17 => (0.01, 0., "kPa"),
+            0x18 => (0.0117, 0., "kPa"),
+            0x19 => (0.079, 0., "kPa"),
+            0x1A => (1., 0., "kPa"),
+            0x1B => (10., 0., "kPa"),
+            0x1C => (0.01, 0., "º"),
+            0x1D => (0.5, 0., "º"),
+            0x1E => (0.000_030_5, 0., "λ"),
+            0x1F => (0.05, 0., "A/F"),
+            0x20 => (0.003_906_2, 0., ""),
+            0x21 => (1., 0., "mHz"),
+            0x22 => (1., 0., "Hz"),
+            0x23 => (1., 0., "kHz"),
+            0x24 => (1., 0., "counts"),
+            0x25 => (1., 0., "km"),
+            0x26 => (0.1, 0., "mV/ms"),
+            0x27 => (0.01, 0., "g/s"),
+            0x28 => (1., 0., "g/s"),
+            0x29 => (0.25, 0., "Pa/s"),
+            0x2A => (0.001, 0., "kg/h"),
+            0x2B => (1., 0., "switches"),
+            0x2C => (0.01, 0., "g/cyl"),
+            0x2D => (0.01, 0., "mg/stroke"),
+            0x2E => (1., 0., ""),
+            0x2F => (0.01, 0., "%"),
+            0x30 => (0.001_526, 0., "%"),
+            0x31 => (0.001, 0., "L"),
+            _ => return None,
+        };
+        Some(Uas {
+            scale,
+            offset,
+            unit,
+            signed: id & 0x80 != 0,
+        })
+    }
+
+    fn raw(&self, bytes: [u8; 2]) -> i32 {
+        if self.signed {
+            i32::from(i16::from_be_bytes(bytes))
+        } else {
+            i32::from(u16::from_be_bytes(bytes))
+        }
+    }
+
+    fn scale(&self, raw: i32) -> f32 {
+        raw as f32 * self.scale + self.offset
+    }
+}
+
+/// Get the name of a standardized OBDMID, if it is known
+pub fn mid_name(mid: u8) -> Option<&'static str> {
+    Some(match mid {
+        0x01 => "Oxygen sensor monitor bank 1 sensor 1",
+        0x02 => "Oxygen sensor monitor bank 1 sensor 2",
+        0x03 => "Oxygen sensor monitor bank 1 sensor 3",
+        0x04 => "Oxygen sensor monitor bank 1 sensor 4",
+        0x05 => "Oxygen sensor monitor bank 2 sensor 1",
+        0x06 => "Oxygen sensor monitor bank 2 sensor 2",
+        0x07 => "Oxygen sensor monitor bank 2 sensor 3",
+        0x08 => "Oxygen sensor monitor bank 2 sensor 4",
+        0x21 => "Catalyst monitor bank 1",
+        0x22 => "Catalyst monitor bank 2",
+        0x31 => "EGR monitor bank 1",
+        0x32 => "EGR monitor bank 2",
+        0x39 => "EVAP monitor (cap off / 0.150\")",
+        0x3A => "EVAP monitor (0.090\")",
+        0x3B => "EVAP monitor (0.040\")",
+        0x3C => "EVAP monitor (0.020\")",
+        0x3D => "Purge flow monitor",
+        0x41 => "Oxygen sensor heater monitor bank 1 sensor 1",
+        0x42 => "Oxygen sensor heater monitor bank 1 sensor 2",
+        0x45 => "Oxygen sensor heater monitor bank 2 sensor 1",
+        0x46 => "Oxygen sensor heater monitor bank 2 sensor 2",
+        0x61 => "Heated catalyst monitor bank 1",
+        0x62 => "Heated catalyst monitor bank 2",
+        0x71 => "Secondary air monitor 1",
+        0x72 => "Secondary air monitor 2",
+        0x81 => "Fuel system monitor bank 1",
+        0x82 => "Fuel system monitor bank 2",
+        0xA1 => "Misfire monitor general data",
+        0xA2 => "Misfire cylinder 1 data",
+        0xA3 => "Misfire cylinder 2 data",
+        0xA4 => "Misfire cylinder 3 data",
+        0xA5 => "Misfire cylinder 4 data",
+        0xA6 => "Misfire cylinder 5 data",
+        0xA7 => "Misfire cylinder 6 data",
+        0xA8 => "Misfire cylinder 7 data",
+        0xA9 => "Misfire cylinder 8 data",
+        0xAA => "Misfire cylinder 9 data",
+        0xAB => "Misfire cylinder 10 data",
+        0xAC => "Misfire cylinder 11 data",
+        0xAD => "Misfire cylinder 12 data",
+        _ => return None,
+    })
+}
+
+/// Length of one test result: the OBDMID, test ID, unit and scaling ID, and three two byte values
+const RECORD_LENGTH: usize = 9;
+
+/// Decode the results sent by each ECU for `mid`
+///
+/// Each response holds one or more records, each made of the OBDMID, test ID, unit and scaling ID,
+/// value, minimum, and maximum. The OBDMID of the first record has already been removed, since it
+/// is the echoed request.
+pub(super) fn decode_results(
+    mid: u8,
+    responses: Vec<Vec<u8>>,
+) -> Result<Vec<Vec<MonitorTestResult>>> {
+    responses
+        .into_iter()
+        .map(|response| {
+            let mut data = vec![mid];
+            data.extend_from_slice(&response);
+            if data.len() % RECORD_LENGTH != 0 {
+                return Err(Error::DecodeError {
+                    mode: 0x06,
+                    pid: mid,
+                    bytes: response,
+                });
+            }
+
+            Ok(data
+                .chunks_exact(RECORD_LENGTH)
+                .map(|r| {
+                    let uas = Uas::from_id(r[2]);
+                    let value = [r[3], r[4]];
+                    let min = [r[5], r[6]];
+                    let max = [r[7], r[8]];
+                    match uas {
+                        Some(uas) => {
+                            let (value, min, max) = (uas.raw(value), uas.raw(min), uas.raw(max));
+                            MonitorTestResult {
+                                mid: r[0],
+                                id: r[1],
+                                value: uas.scale(value),
+                                min: uas.scale(min),
+                                max: uas.scale(max),
+                                unit: uas.unit,
+                                passed: (min..=max).contains(&value),
+                            }
+                        }
+                        None => {
+                            let value = u16::from_be_bytes(value);
+                            let min = u16::from_be_bytes(min);
+                            let max = u16::from_be_bytes(max);
+                            MonitorTestResult {
+                                mid: r[0],
+                                id: r[1],
+                                value: f32::from(value),
+                                min: f32::from(min),
+                                max: f32::from(max),
+                                unit: "",
+                                passed: (min..=max).contains(&value),
+                            }
+                        }
+                    }
+                })
+                .collect())
+        })
+        .collect()
+}
@@ -0,0 +1,62 @@
+//! Service 8 control of on-board systems
+//!
+//! Service 8 commands the ECU to run a test or operate a component, so that a technician can
+//! check the system with the vehicle in the shop. Tests are identified by a test ID. Only
+//! [EVAP_LEAK_TEST] is standardized; the others are defined by the manufacturer.
+//!
+//! The ECU only runs a test while the conditions for it are met. A positive response means the
+//! request was accepted, but the ECU ends the test on its own if the conditions stop being met
+//! (like the engine being started) or after a timeout.
+
+use crate::{Error, Result};
+use alloc::vec::Vec;
+
+/// Test ID for the evaporative system leak test
+///
+/// The ECU closes the evaporative system's vent valve, sealing it so a leak test can be done with
+/// external equipment, like a smoke machine.
+pub const EVAP_LEAK_TEST: u8 = 0x01;
+
+/// An ECU's response to a service 8 request
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ControlResponse {
+    /// The ECU accepted the request for [EVAP_LEAK_TEST] and has sealed the evaporative system
+    EvapSystemSealed,
+
+    /// The ECU accepted the request for a test that does not have standardized response data
+    Other {
+        /// The test ID that was requested
+        test_id: u8,
+
+        /// The data sent with the response, which is defined by the manufacturer
+        data: Vec<u8>,
+    },
+}
+
+/// Decode the response sent by each ECU for `test_id`
+///
+/// Vehicles that do not use CAN always send five data bytes, which are zero for
+/// [EVAP_LEAK_TEST]; CAN vehicles may send no data.
+pub(super) fn decode_response(
+    test_id: u8,
+    responses: Vec<Vec<u8>>,
+) -> Result<Vec<ControlResponse>> {
+    responses
+        .into_iter()
+        .map(|data| {
+            if data.len() > 5 {
+                return Err(Error::DecodeError {
+                    mode: 0x08,
+                    pid: test_id,
+                    bytes: data,
+                });
+            }
+            Ok(match test_id {
+                EVAP_LEAK_TEST if data.iter().all(|&b| b == 0) => ControlResponse::EvapSystemSealed,
+                _ => ControlResponse::Other { test_id, data },
+            })
+        })
+        .collect()
+}
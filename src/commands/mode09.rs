@@ -0,0 +1,251 @@
+//! Service 9 vehicle information
+//!
+//! Besides the [VIN](super::Obd2DataRetrieval::get_vin), service 9 reports information used to
+//! check that a vehicle complies with emissions regulations: which software each ECU runs, and
+//! how often its monitors have been able to run. Each response starts with the number of data
+//! items, followed by the items themselves.
+
+use crate::{Error, Result};
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use log::debug;
+
+/// An ECU's name, from service 9 PID 0x0A
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EcuName {
+    /// The standardized acronym for the ECU, like `ECM` for the engine control module
+    pub acronym: String,
+
+    /// The full name of the ECU, like `EngineControl`
+    pub name: String,
+}
+
+/// How often one monitor has run, from service 9 PID 0x08 or 0x0B
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct MonitorPerformance {
+    /// The name of the monitor, like `"Catalyst bank 1"`
+    pub name: &'static str,
+
+    /// The number of times the monitor has completed and detected whether there is a fault
+    pub completions: u16,
+
+    /// The number of times the vehicle has been driven in the conditions needed for the monitor to
+    /// run
+    pub conditions: u16,
+}
+
+impl MonitorPerformance {
+    /// Get the ratio of completions to conditions, which regulations require to be above a
+    /// minimum (like 0.336 for most monitors in the US)
+    ///
+    /// This is `None` if the conditions have not been met yet.
+    pub fn ratio(&self) -> Option<f32> {
+        (self.conditions != 0).then(|| f32::from(self.completions) / f32::from(self.conditions))
+    }
+}
+
+/// In-use performance tracking: how often each monitor has been able to run
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct InUsePerformance {
+    /// The number of times the vehicle has been driven in the general conditions for monitoring
+    pub obd_monitoring_conditions: u16,
+
+    /// The number of ignition cycles
+    pub ignition_cycles: u16,
+
+    /// The counters for each monitor
+    pub monitors: Vec<MonitorPerformance>,
+}
+
+/// Monitors tracked by spark ignition vehicles, in the order of their counters
+const SPARK_MONITORS: [&str; 9] = [
+    "Catalyst bank 1",
+    "Catalyst bank 2",
+    "Oxygen sensor bank 1",
+    "Oxygen sensor bank 2",
+    "EGR and/or VVT",
+    "Secondary air",
+    "Evaporative system",
+    "Secondary oxygen sensor bank 1",
+    "Secondary oxygen sensor bank 2",
+];
+
+/// Monitors tracked by compression ignition vehicles, in the order of their counters
+const COMPRESSION_MONITORS: [&str; 7] = [
+    "NMHC catalyst",
+    "NOx/SCR catalyst",
+    "NOx adsorber",
+    "PM filter",
+    "Exhaust gas sensor",
+    "EGR and/or VVT",
+    "Boost pressure",
+];
+
+/// The number of characters in a VIN
+const VIN_LEN: usize = 17;
+
+/// Split a response into its data items, each `len` bytes long
+///
+/// An empty response is [Error::NoData], and one with fewer items than its count is
+/// [Error::MissingItems].
+fn data_items(response: &[u8], len: usize) -> Result<Vec<&[u8]>> {
+    let (&count, data) = response.split_first().ok_or(Error::NoData)?;
+    let data = data
+        .get(..usize::from(count) * len)
+        .ok_or(Error::MissingItems {
+            expected: count.into(),
+            got: data.len() / len,
+        })?;
+    Ok(data.chunks_exact(len).collect())
+}
+
+/// Decode ASCII text padded with null bytes, from the response to service 9 `pid`
+fn decode_text(pid: u8, bytes: &[u8]) -> Result<String> {
+    let text = String::from_utf8(bytes.to_vec()).map_err(|e| Error::DecodeError {
+        mode: 0x09,
+        pid,
+        bytes: e.into_bytes(),
+    })?;
+    Ok(text.trim_end_matches('\0').to_owned())
+}
+
+/// Decode the VIN from the first ECU that reported one
+///
+/// On CAN, each ECU responds with the number of data items (one) and the 17 characters. Older
+/// protocols send five messages, each starting with its number from 1 to 5, with four bytes of
+/// the VIN after three bytes of padding. VINs shorter than 17 characters, from responses that were
+/// cut short, are skipped.
+pub(super) fn decode_vin(responses: Vec<Vec<u8>>) -> Result<String> {
+    let numbered = responses.len() > 1
+        && responses
+            .iter()
+            .all(|r| r.len() == 5 && (1..=5).contains(&r[0]));
+    let mut vins: Vec<Vec<u8>> = Vec::new();
+    for response in &responses {
+        match response.split_first() {
+            Some((1, data)) if numbered => vins.push(data.to_vec()),
+            Some((_, data)) if numbered => match vins.last_mut() {
+                Some(vin) => vin.extend(data),
+                None => vins.push(data.to_vec()),
+            },
+            Some((_, data)) => vins.push(data.to_vec()),
+            None => (),
+        }
+    }
+
+    for vin in &vins {
+        let Some(start) = vin.iter().position(u8::is_ascii_alphanumeric) else {
+            continue;
+        };
+        let end = vin
+            .iter()
+            .rposition(u8::is_ascii_alphanumeric)
+            .unwrap_or(start);
+        if end + 1 - start < VIN_LEN {
+            debug!("decode_vin: skipping short VIN {:02X?}", vin);
+            continue;
+        }
+        return String::from_utf8(vin[start..=end].to_vec()).map_err(|_| Error::DecodeError {
+            mode: 0x09,
+            pid: 0x02,
+            bytes: vin.clone(),
+        });
+    }
+    Err(Error::NoData)
+}
+
+pub(super) fn decode_calibration_ids(responses: Vec<Vec<u8>>) -> Result<Vec<Vec<String>>> {
+    responses
+        .iter()
+        .map(|r| {
+            data_items(r, 16)?
+                .into_iter()
+                .map(|id| decode_text(0x04, id))
+                .collect()
+        })
+        .collect()
+}
+
+pub(super) fn decode_cvns(responses: Vec<Vec<u8>>) -> Result<Vec<Vec<u32>>> {
+    responses
+        .iter()
+        .map(|r| {
+            Ok(data_items(r, 4)?
+                .into_iter()
+                .map(|cvn| u32::from_be_bytes([cvn[0], cvn[1], cvn[2], cvn[3]]))
+                .collect())
+        })
+        .collect()
+}
+
+pub(crate) fn decode_ecu_names(responses: Vec<Vec<u8>>) -> Result<Vec<EcuName>> {
+    responses
+        .iter()
+        .map(|r| {
+            let name = data_items(r, 20)?
+                .into_iter()
+                .next()
+                .ok_or(Error::MissingItems {
+                    expected: 1,
+                    got: 0,
+                })?;
+            Ok(EcuName {
+                acronym: decode_text(0x0A, &name[..4])?,
+                name: decode_text(0x0A, &name[5..])?,
+            })
+        })
+        .collect()
+}
+
+fn decode_in_use_performance(
+    responses: Vec<Vec<u8>>,
+    monitors: &[&'static str],
+) -> Result<Vec<InUsePerformance>> {
+    responses
+        .iter()
+        .map(|r| {
+            let counters: Vec<_> = data_items(r, 2)?
+                .into_iter()
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            let [obd_monitoring_conditions, ignition_cycles, ref monitor_counters @ ..] =
+                counters[..]
+            else {
+                return Err(Error::MissingItems {
+                    expected: 2,
+                    got: counters.len(),
+                });
+            };
+            Ok(InUsePerformance {
+                obd_monitoring_conditions,
+                ignition_cycles,
+                monitors: monitor_counters
+                    .chunks_exact(2)
+                    .zip(monitors)
+                    .map(|(c, &name)| MonitorPerformance {
+                        name,
+                        completions: c[0],
+                        conditions: c[1],
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+pub(super) fn decode_spark_in_use_performance(
+    responses: Vec<Vec<u8>>,
+) -> Result<Vec<InUsePerformance>> {
+    decode_in_use_performance(responses, &SPARK_MONITORS)
+}
+
+pub(super) fn decode_compression_in_use_performance(
+    responses: Vec<Vec<u8>>,
+) -> Result<Vec<InUsePerformance>> {
+    decode_in_use_performance(responses, &COMPRESSION_MONITORS)
+}
@@ -0,0 +1,513 @@
+//! Typed PID definitions
+//!
+//! The getters in [Obd2DataRetrieval](super::Obd2DataRetrieval) cover the common PIDs. Others
+//! can be defined by implementing [Pid], and then read with
+//! [query](super::Obd2DataRetrieval::query), which sends the request and passes each ECU's
+//! response to [Pid::decode]. Manufacturers expose most of their extra data (like the
+//! transmission temperature or battery state of charge) through service 0x22 with 16-bit data
+//! identifiers instead, which are defined by implementing [ManufacturerPid].
+//!
+//! [StandardPid] lists the PIDs defined by SAE J1979, with their names, units and formulas.
+
+use super::implementation::{percent, wide_temperature, word};
+use crate::{Error, Result};
+use alloc::vec::Vec;
+
+/// A PID that can be requested and decoded
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::{pid::Pid, Obd2DataRetrieval},
+///     device::MockObd2Device,
+///     Error,
+/// };
+///
+/// /// Engine friction as a percentage of the engine's reference torque
+/// struct EngineFrictionTorque;
+///
+/// impl Pid for EngineFrictionTorque {
+///     const MODE: u8 = 0x01;
+///     const PID: u8 = 0x8E;
+///     type Output = i16;
+///
+///     fn decode(response: &[u8]) -> Result<i16, Error> {
+///         match response {
+///             [a] => Ok(i16::from(*a) - 125),
+///             _ => Err(Error::IncorrectResponseLength("engine friction", 1, response.len())),
+///         }
+///     }
+/// }
+///
+/// let mut device = MockObd2Device::new().with_response(0x01, 0x8E, [vec![0x8C]]);
+/// assert_eq!(device.query::<EngineFrictionTorque>().unwrap(), [15]);
+/// ```
+pub trait Pid {
+    /// The service (mode) to send the request in
+    const MODE: u8;
+
+    /// The PID to request
+    const PID: u8;
+
+    /// The decoded value
+    type Output;
+
+    /// Decode one ECU's response, which has had the mode and PID removed
+    fn decode(response: &[u8]) -> Result<Self::Output>;
+}
+
+/// A manufacturer-specific data identifier (DID), read with service 0x22
+///
+/// The identifiers and their formulas differ between manufacturers, and are usually only
+/// supported by one of the ECUs. Read the value with
+/// [query_manufacturer](super::Obd2DataRetrieval::query_manufacturer), or request the raw data
+/// with [obd_command_22](crate::Obd2Device::obd_command_22).
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::{pid::ManufacturerPid, Obd2DataRetrieval},
+///     device::MockObd2Device,
+///     Error,
+/// };
+///
+/// /// Transmission fluid temperature in ºC, for a hypothetical transmission controller
+/// struct TransmissionTemperature;
+///
+/// impl ManufacturerPid for TransmissionTemperature {
+///     const DID: u16 = 0x1940;
+///     type Output = i16;
+///
+///     fn decode(response: &[u8]) -> Result<i16, Error> {
+///         match response {
+///             [a, b] => Ok(i16::from_be_bytes([*a, *b]) / 8),
+///             _ => Err(Error::IncorrectResponseLength("temperature", 2, response.len())),
+///         }
+///     }
+/// }
+///
+/// let mut device = MockObd2Device::new();
+/// device.set_request_response(&[0x22, 0x19, 0x40], [vec![0x02, 0x80]]);
+/// assert_eq!(device.query_manufacturer::<TransmissionTemperature>().unwrap(), [80]);
+/// ```
+pub trait ManufacturerPid {
+    /// The data identifier to request
+    const DID: u16;
+
+    /// The decoded value
+    type Output;
+
+    /// Decode one ECU's response, which has had the service and data identifier removed
+    fn decode(response: &[u8]) -> Result<Self::Output>;
+}
+
+/// Define [StandardPid] from a table of PIDs
+///
+/// Each entry is `Variant = pid, length, name`, followed by `, unit, [min, max], decode` for PIDs
+/// that hold a single number.
+macro_rules! standard_pids {
+    {
+        $(
+            $(#[$attr:meta])*
+            $variant:ident = $pid:literal, $len:literal, $name:literal
+            $(, $unit:literal, [$min:expr, $max:expr], $decode:expr)?;
+        )*
+    } => {
+        /// A service 1 PID defined by SAE J1979, with metadata for displaying it
+        ///
+        /// This is meant for generic tools like dashboards and loggers, which need to list PIDs and
+        /// label their values. The getters in [Obd2DataRetrieval](super::Obd2DataRetrieval) give
+        /// more detailed, typed values.
+        ///
+        /// # Example
+        /// ```
+        /// use obd2::{
+        ///     commands::{pid::StandardPid, Obd2DataRetrieval},
+        ///     device::Obd2Simulator,
+        /// };
+        ///
+        /// let mut device = Obd2Simulator::new();
+        /// let support = &device.get_pid_support().unwrap()[0];
+        /// let pids: Vec<_> = support.pids().filter_map(StandardPid::from_pid).collect();
+        /// assert!(pids.contains(&StandardPid::EngineSpeed));
+        ///
+        /// let pid = StandardPid::EngineSpeed;
+        /// assert_eq!(pid.name(), "Engine speed");
+        /// assert_eq!(pid.unit(), Some("rpm"));
+        /// assert_eq!(pid.decode(&[0x1A, 0xF8]), Some(1726.));
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[non_exhaustive]
+        pub enum StandardPid {
+            $(
+                $(#[$attr])*
+                #[doc = $name]
+                $variant,
+            )*
+        }
+
+        impl StandardPid {
+            /// Every standard PID, in order
+            pub const ALL: &'static [StandardPid] = &[$(Self::$variant),*];
+
+            /// Get the standard PID with the number `pid`, if there is one
+            pub fn from_pid(pid: u8) -> Option<Self> {
+                match pid {
+                    $($pid => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Get the PID's number
+            pub fn pid(self) -> u8 {
+                match self {
+                    $(Self::$variant => $pid,)*
+                }
+            }
+
+            /// Get the PID's name, like `"Engine speed"`
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// Get the number of data bytes in a response
+            pub fn response_len(self) -> usize {
+                match self {
+                    $(Self::$variant => $len,)*
+                }
+            }
+
+            /// Get the unit of the decoded value, or `None` if the PID does not hold a single
+            /// number
+            pub fn unit(self) -> Option<&'static str> {
+                match self {
+                    $($(Self::$variant => Some($unit),)?)*
+                    _ => None,
+                }
+            }
+
+            /// Get the minimum and maximum of the decoded value, or `None` if the PID does not
+            /// hold a single number
+            pub fn range(self) -> Option<(f32, f32)> {
+                match self {
+                    $($(Self::$variant => Some(($min, $max)),)?)*
+                    _ => None,
+                }
+            }
+
+            /// Decode a response with the PID's formula
+            ///
+            /// This is `None` if the PID does not hold a single number, or if `response` is not
+            /// [response_len](Self::response_len) bytes long.
+            pub fn decode(self, response: &[u8]) -> Option<f32> {
+                if response.len() != self.response_len() {
+                    return None;
+                }
+                match self {
+                    $($(Self::$variant => Some(($decode)(response)),)?)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+fn byte(d: &[u8]) -> f32 {
+    f32::from(d[0])
+}
+
+fn byte_percent(d: &[u8]) -> f32 {
+    percent(byte(d))
+}
+
+fn byte_temperature(d: &[u8]) -> f32 {
+    byte(d) - 40.
+}
+
+fn fuel_trim(d: &[u8]) -> f32 {
+    byte(d) / 1.28 - 100.
+}
+
+fn torque_percent(d: &[u8]) -> f32 {
+    byte(d) - 125.
+}
+
+fn two_bytes(d: &[u8]) -> f32 {
+    word(d[0], d[1])
+}
+
+fn equivalence_ratio(d: &[u8]) -> f32 {
+    two_bytes(d) * 2. / 65536.
+}
+
+fn wide_temperature_pid(d: &[u8]) -> f32 {
+    wide_temperature(d[0], d[1])
+}
+
+standard_pids! {
+    SupportedPids01To20 = 0x00, 4, "PIDs supported [01-20]";
+    MonitorStatus = 0x01, 4, "Monitor status since DTCs cleared";
+    FreezeFrameDtc = 0x02, 2, "DTC that caused freeze frame";
+    FuelSystemStatus = 0x03, 2, "Fuel system status";
+    EngineLoad = 0x04, 1, "Calculated engine load", "%", [0., 100.], byte_percent;
+    EngineCoolantTemperature = 0x05, 1, "Engine coolant temperature", "ºC", [-40., 215.],
+        byte_temperature;
+    ShortTermFuelTrim1 = 0x06, 1, "Short term fuel trim bank 1", "%", [-100., 99.2], fuel_trim;
+    LongTermFuelTrim1 = 0x07, 1, "Long term fuel trim bank 1", "%", [-100., 99.2], fuel_trim;
+    ShortTermFuelTrim2 = 0x08, 1, "Short term fuel trim bank 2", "%", [-100., 99.2], fuel_trim;
+    LongTermFuelTrim2 = 0x09, 1, "Long term fuel trim bank 2", "%", [-100., 99.2], fuel_trim;
+    FuelPressure = 0x0A, 1, "Fuel pressure", "kPa", [0., 765.], |d| byte(d) * 3.;
+    IntakeManifoldPressure = 0x0B, 1, "Intake manifold absolute pressure", "kPa", [0., 255.],
+        byte;
+    EngineSpeed = 0x0C, 2, "Engine speed", "rpm", [0., 16383.75], |d| two_bytes(d) / 4.;
+    VehicleSpeed = 0x0D, 1, "Vehicle speed", "km/h", [0., 255.], byte;
+    TimingAdvance = 0x0E, 1, "Timing advance", "º before TDC", [-64., 63.5],
+        |d| byte(d) / 2. - 64.;
+    IntakeAirTemperature = 0x0F, 1, "Intake air temperature", "ºC", [-40., 215.],
+        byte_temperature;
+    MafAirFlowRate = 0x10, 2, "Mass air flow rate", "g/s", [0., 655.35],
+        |d| two_bytes(d) / 100.;
+    ThrottlePosition = 0x11, 1, "Throttle position", "%", [0., 100.], byte_percent;
+    SecondaryAirStatus = 0x12, 1, "Commanded secondary air status";
+    OxygenSensorsPresent2Banks = 0x13, 1, "Oxygen sensors present (2 banks)";
+    OxygenSensor1 = 0x14, 2, "Oxygen sensor 1 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor2 = 0x15, 2, "Oxygen sensor 2 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor3 = 0x16, 2, "Oxygen sensor 3 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor4 = 0x17, 2, "Oxygen sensor 4 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor5 = 0x18, 2, "Oxygen sensor 5 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor6 = 0x19, 2, "Oxygen sensor 6 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor7 = 0x1A, 2, "Oxygen sensor 7 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    OxygenSensor8 = 0x1B, 2, "Oxygen sensor 8 voltage", "V", [0., 1.275], |d| byte(d) / 200.;
+    ObdStandards = 0x1C, 1, "OBD standards this vehicle conforms to";
+    OxygenSensorsPresent4Banks = 0x1D, 1, "Oxygen sensors present (4 banks)";
+    AuxiliaryInputStatus = 0x1E, 1, "Auxiliary input status";
+    RunTimeSinceStart = 0x1F, 2, "Run time since engine start", "s", [0., 65535.], two_bytes;
+    SupportedPids21To40 = 0x20, 4, "PIDs supported [21-40]";
+    DistanceWithMil = 0x21, 2, "Distance traveled with MIL on", "km", [0., 65535.], two_bytes;
+    FuelRailPressure = 0x22, 2, "Fuel rail pressure (relative to manifold vacuum)", "kPa",
+        [0., 5177.265], |d| two_bytes(d) * 0.079;
+    FuelRailGaugePressure = 0x23, 2, "Fuel rail gauge pressure", "kPa", [0., 655350.],
+        |d| two_bytes(d) * 10.;
+    WideRangeOxygenSensor1Voltage = 0x24, 4, "Oxygen sensor 1 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor2Voltage = 0x25, 4, "Oxygen sensor 2 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor3Voltage = 0x26, 4, "Oxygen sensor 3 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor4Voltage = 0x27, 4, "Oxygen sensor 4 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor5Voltage = 0x28, 4, "Oxygen sensor 5 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor6Voltage = 0x29, 4, "Oxygen sensor 6 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor7Voltage = 0x2A, 4, "Oxygen sensor 7 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor8Voltage = 0x2B, 4, "Oxygen sensor 8 equivalence ratio (voltage)",
+        "ratio", [0., 2.], equivalence_ratio;
+    CommandedEgr = 0x2C, 1, "Commanded EGR", "%", [0., 100.], byte_percent;
+    EgrError = 0x2D, 1, "EGR error", "%", [-100., 99.2], fuel_trim;
+    CommandedEvapPurge = 0x2E, 1, "Commanded evaporative purge", "%", [0., 100.], byte_percent;
+    FuelTankLevel = 0x2F, 1, "Fuel tank level input", "%", [0., 100.], byte_percent;
+    WarmUpsSinceCodesCleared = 0x30, 1, "Warm-ups since codes cleared", "count", [0., 255.],
+        byte;
+    DistanceSinceCodesCleared = 0x31, 2, "Distance traveled since codes cleared", "km",
+        [0., 65535.], two_bytes;
+    EvapSystemVaporPressure = 0x32, 2, "Evaporative system vapor pressure", "Pa",
+        [-8192., 8191.75], |d: &[u8]| f32::from(i16::from_be_bytes([d[0], d[1]])) / 4.;
+    BarometricPressure = 0x33, 1, "Absolute barometric pressure", "kPa", [0., 255.], byte;
+    WideRangeOxygenSensor1Current = 0x34, 4, "Oxygen sensor 1 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor2Current = 0x35, 4, "Oxygen sensor 2 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor3Current = 0x36, 4, "Oxygen sensor 3 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor4Current = 0x37, 4, "Oxygen sensor 4 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor5Current = 0x38, 4, "Oxygen sensor 5 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor6Current = 0x39, 4, "Oxygen sensor 6 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor7Current = 0x3A, 4, "Oxygen sensor 7 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    WideRangeOxygenSensor8Current = 0x3B, 4, "Oxygen sensor 8 equivalence ratio (current)",
+        "ratio", [0., 2.], equivalence_ratio;
+    CatalystTemperature1Sensor1 = 0x3C, 2, "Catalyst temperature bank 1 sensor 1", "ºC",
+        [-40., 6513.5], wide_temperature_pid;
+    CatalystTemperature2Sensor1 = 0x3D, 2, "Catalyst temperature bank 2 sensor 1", "ºC",
+        [-40., 6513.5], wide_temperature_pid;
+    CatalystTemperature1Sensor2 = 0x3E, 2, "Catalyst temperature bank 1 sensor 2", "ºC",
+        [-40., 6513.5], wide_temperature_pid;
+    CatalystTemperature2Sensor2 = 0x3F, 2, "Catalyst temperature bank 2 sensor 2", "ºC",
+        [-40., 6513.5], wide_temperature_pid;
+    SupportedPids41To60 = 0x40, 4, "PIDs supported [41-60]";
+    DriveCycleMonitorStatus = 0x41, 4, "Monitor status this drive cycle";
+    ControlModuleVoltage = 0x42, 2, "Control module voltage", "V", [0., 65.535],
+        |d| two_bytes(d) / 1000.;
+    AbsoluteLoad = 0x43, 2, "Absolute load value", "%", [0., 25700.],
+        |d| percent(two_bytes(d));
+    CommandedEquivalenceRatio = 0x44, 2, "Commanded air-fuel equivalence ratio", "ratio",
+        [0., 2.], equivalence_ratio;
+    RelativeThrottlePosition = 0x45, 1, "Relative throttle position", "%", [0., 100.],
+        byte_percent;
+    AmbientAirTemperature = 0x46, 1, "Ambient air temperature", "ºC", [-40., 215.],
+        byte_temperature;
+    AbsoluteThrottlePositionB = 0x47, 1, "Absolute throttle position B", "%", [0., 100.],
+        byte_percent;
+    AbsoluteThrottlePositionC = 0x48, 1, "Absolute throttle position C", "%", [0., 100.],
+        byte_percent;
+    AcceleratorPedalPositionD = 0x49, 1, "Accelerator pedal position D", "%", [0., 100.],
+        byte_percent;
+    AcceleratorPedalPositionE = 0x4A, 1, "Accelerator pedal position E", "%", [0., 100.],
+        byte_percent;
+    AcceleratorPedalPositionF = 0x4B, 1, "Accelerator pedal position F", "%", [0., 100.],
+        byte_percent;
+    CommandedThrottleActuator = 0x4C, 1, "Commanded throttle actuator", "%", [0., 100.],
+        byte_percent;
+    TimeRunWithMil = 0x4D, 2, "Time run with MIL on", "min", [0., 65535.], two_bytes;
+    TimeSinceCodesCleared = 0x4E, 2, "Time since trouble codes cleared", "min", [0., 65535.],
+        two_bytes;
+    MaximumValues = 0x4F, 4,
+        "Maximum value for equivalence ratio, oxygen sensor voltage and current, and intake \
+         manifold absolute pressure";
+    MaximumMafAirFlowRate = 0x50, 4, "Maximum value for mass air flow rate", "g/s",
+        [0., 2550.], |d| byte(d) * 10.;
+    FuelType = 0x51, 1, "Fuel type";
+    EthanolFuel = 0x52, 1, "Ethanol fuel percentage", "%", [0., 100.], byte_percent;
+    AbsoluteEvapSystemVaporPressure = 0x53, 2, "Absolute evaporative system vapor pressure",
+        "kPa", [0., 327.675], |d| two_bytes(d) / 200.;
+    EvapSystemVaporPressureWide = 0x54, 2, "Evaporative system vapor pressure", "Pa",
+        [-32768., 32767.], |d: &[u8]| f32::from(i16::from_be_bytes([d[0], d[1]]));
+    ShortTermSecondaryOxygenTrim13 = 0x55, 2, "Short term secondary oxygen sensor trim";
+    LongTermSecondaryOxygenTrim13 = 0x56, 2, "Long term secondary oxygen sensor trim";
+    ShortTermSecondaryOxygenTrim24 = 0x57, 2, "Short term secondary oxygen sensor trim";
+    LongTermSecondaryOxygenTrim24 = 0x58, 2, "Long term secondary oxygen sensor trim";
+    FuelRailAbsolutePressure = 0x59, 2, "Fuel rail absolute pressure", "kPa", [0., 655350.],
+        |d| two_bytes(d) * 10.;
+    RelativeAcceleratorPedalPosition = 0x5A, 1, "Relative accelerator pedal position", "%",
+        [0., 100.], byte_percent;
+    HybridBatteryRemaining = 0x5B, 1, "Hybrid battery pack remaining life", "%", [0., 100.],
+        byte_percent;
+    EngineOilTemperature = 0x5C, 1, "Engine oil temperature", "ºC", [-40., 215.],
+        byte_temperature;
+    FuelInjectionTiming = 0x5D, 2, "Fuel injection timing", "º", [-210., 301.992],
+        |d| two_bytes(d) / 128. - 210.;
+    EngineFuelRate = 0x5E, 2, "Engine fuel rate", "L/h", [0., 3276.75], |d| two_bytes(d) / 20.;
+    EmissionRequirements = 0x5F, 1, "Emission requirements to which vehicle is designed";
+    SupportedPids61To80 = 0x60, 4, "PIDs supported [61-80]";
+    DriverDemandTorque = 0x61, 1, "Driver's demand engine percent torque", "%", [-125., 130.],
+        torque_percent;
+    ActualTorque = 0x62, 1, "Actual engine percent torque", "%", [-125., 130.], torque_percent;
+    ReferenceTorque = 0x63, 2, "Engine reference torque", "N·m", [0., 65535.], two_bytes;
+    EnginePercentTorqueData = 0x64, 5, "Engine percent torque data";
+    AuxiliaryInputOutputSupported = 0x65, 2, "Auxiliary input/output supported";
+    MafSensor = 0x66, 5, "Mass air flow sensor";
+    EngineCoolantTemperatureSensors = 0x67, 3, "Engine coolant temperature sensors";
+    IntakeAirTemperatureSensors = 0x68, 7, "Intake air temperature sensors";
+    EgrControl = 0x69, 7, "Actual EGR, commanded EGR, and EGR error";
+    DieselIntakeAirFlowControl = 0x6A, 5,
+        "Commanded Diesel intake air flow control and relative intake air flow position";
+    EgrTemperature = 0x6B, 5, "Exhaust gas recirculation temperature";
+    ThrottleActuatorControl = 0x6C, 5,
+        "Commanded throttle actuator control and relative throttle position";
+    FuelPressureControl = 0x6D, 11, "Fuel pressure control system";
+    InjectionPressureControl = 0x6E, 9, "Injection pressure control system";
+    TurbochargerCompressorInletPressure = 0x6F, 3, "Turbocharger compressor inlet pressure";
+    BoostPressureControl = 0x70, 10, "Boost pressure control";
+    VgtControl = 0x71, 6, "Variable geometry turbo control";
+    WastegateControl = 0x72, 5, "Wastegate control";
+    ExhaustPressure = 0x73, 5, "Exhaust pressure";
+    TurbochargerRpm = 0x74, 5, "Turbocharger RPM";
+    TurbochargerATemperature = 0x75, 7, "Turbocharger A temperature";
+    TurbochargerBTemperature = 0x76, 7, "Turbocharger B temperature";
+    ChargeAirCoolerTemperature = 0x77, 5, "Charge air cooler temperature";
+    ExhaustGasTemperature1 = 0x78, 9, "Exhaust gas temperature bank 1";
+    ExhaustGasTemperature2 = 0x79, 9, "Exhaust gas temperature bank 2";
+    DpfPressure1 = 0x7A, 7, "Diesel particulate filter bank 1";
+    DpfPressure2 = 0x7B, 7, "Diesel particulate filter bank 2";
+    DpfTemperature = 0x7C, 9, "Diesel particulate filter temperature";
+    NoxNteStatus = 0x7D, 1, "NOx NTE control area status";
+    PmNteStatus = 0x7E, 1, "PM NTE control area status";
+    EngineRunTime = 0x7F, 13, "Engine run time";
+    SupportedPids81ToA0 = 0x80, 4, "PIDs supported [81-A0]";
+    AecdRunTime1 = 0x81, 41, "Engine run time for AECD #1-#5";
+    AecdRunTime2 = 0x82, 41, "Engine run time for AECD #6-#10";
+    NoxSensor = 0x83, 9, "NOx sensor";
+    ManifoldSurfaceTemperature = 0x84, 1, "Manifold surface temperature", "ºC", [-40., 215.],
+        byte_temperature;
+    NoxReagentSystem = 0x85, 10, "NOx reagent system";
+    PmSensor = 0x86, 5, "Particulate matter sensor";
+    IntakeManifoldPressureSensors = 0x87, 5, "Intake manifold absolute pressure sensors";
+    ScrInducementSystem = 0x88, 13, "SCR inducement system";
+    AecdRunTime3 = 0x89, 41, "Engine run time for AECD #11-#15";
+    AecdRunTime4 = 0x8A, 41, "Engine run time for AECD #16-#20";
+    DieselAftertreatment = 0x8B, 7, "Diesel aftertreatment";
+    WideRangeOxygenSensors = 0x8C, 17, "Oxygen sensors (wide range)";
+    ThrottlePositionG = 0x8D, 1, "Throttle position G", "%", [0., 100.], byte_percent;
+    EngineFrictionTorque = 0x8E, 1, "Engine friction percent torque", "%", [-125., 130.],
+        torque_percent;
+    PmSensors = 0x8F, 7, "Particulate matter sensors bank 1 and 2";
+    WwhObdSystemInformation1 = 0x90, 3, "WWH-OBD vehicle OBD system information";
+    WwhObdSystemInformation2 = 0x91, 5, "WWH-OBD vehicle OBD system information";
+    FuelSystemControl = 0x92, 2, "Fuel system control";
+    WwhObdCounters = 0x93, 3, "WWH-OBD vehicle OBD counters support";
+    NoxWarningSystem = 0x94, 12, "NOx warning and inducement system";
+    ExhaustGasTemperatureSensor1 = 0x98, 9, "Exhaust gas temperature sensor";
+    ExhaustGasTemperatureSensor2 = 0x99, 9, "Exhaust gas temperature sensor";
+    HybridSystemData = 0x9A, 6, "Hybrid/EV vehicle system data";
+    DefSensor = 0x9B, 4, "Diesel exhaust fluid sensor data";
+    OxygenSensorData = 0x9C, 17, "Oxygen sensor data";
+    FuelMassRate = 0x9D, 4, "Engine fuel rate";
+    ExhaustFlowRate = 0x9E, 2, "Engine exhaust flow rate", "kg/h", [0., 13107.],
+        |d| two_bytes(d) / 5.;
+    FuelSystemPercentageUse = 0x9F, 9, "Fuel system percentage use";
+    SupportedPidsA1ToC0 = 0xA0, 4, "PIDs supported [A1-C0]";
+    NoxSensorCorrected = 0xA1, 9, "NOx sensor corrected data";
+    CylinderFuelRate = 0xA2, 2, "Cylinder fuel rate", "mg/stroke", [0., 2047.97],
+        |d| two_bytes(d) / 32.;
+    EvapSystemVaporPressureSensors = 0xA3, 9, "Evaporative system vapor pressure";
+    TransmissionGear = 0xA4, 4, "Transmission actual gear";
+    DefDosing = 0xA5, 4, "Commanded diesel exhaust fluid dosing";
+    Odometer = 0xA6, 4, "Odometer", "km", [0., 429496729.5],
+        |d: &[u8]| u32::from_be_bytes([d[0], d[1], d[2], d[3]]) as f32 / 10.;
+    SupportedPidsC1ToE0 = 0xC0, 4, "PIDs supported [C1-E0]";
+}
+
+impl core::fmt::Display for StandardPid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The most PIDs that can be sent in one request
+pub(crate) const MAX_PIDS_PER_REQUEST: usize = 6;
+
+/// The values of several PIDs, from [get_snapshot](super::Obd2DataRetrieval::get_snapshot)
+///
+/// Without the `std` feature there is no `HashMap`, so this is a `BTreeMap`.
+#[cfg(feature = "std")]
+pub(crate) type Snapshot = std::collections::HashMap<StandardPid, Vec<f32>>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Snapshot = alloc::collections::BTreeMap<StandardPid, Vec<f32>>;
+
+/// Decode a response to a request for several PIDs, adding each value to `snapshot`
+///
+/// The response is each PID followed by its data, so every PID must be a [StandardPid] to know
+/// how long its data is. PIDs that do not hold a single number are skipped.
+pub(crate) fn decode_snapshot(mut response: &[u8], snapshot: &mut Snapshot) -> Result<()> {
+    while let [pid, rest @ ..] = response {
+        let pid = StandardPid::from_pid(*pid).ok_or(Error::UnknownPid(*pid))?;
+        let len = pid.response_len();
+        let data =
+            rest.get(..len)
+                .ok_or(Error::IncorrectResponseLength(pid.name(), len, rest.len()))?;
+        if let Some(value) = pid.decode(data) {
+            snapshot.entry(pid).or_default().push(value);
+        }
+        response = &rest[len..];
+    }
+    Ok(())
+}
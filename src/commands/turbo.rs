@@ -0,0 +1,261 @@
+//! Turbocharger and boost PIDs
+//!
+//! PIDs 0x70 to 0x79 report on forced induction systems, mostly on Diesel engines. Each of these
+//! PIDs starts with a byte showing which of its values are supported, so every value is an
+//! [Option] which is `None` if the vehicle does not report it. Vehicles with two turbochargers
+//! label them A and B.
+
+use super::{
+    implementation::{percent, supported, temperature, wide_temperature, word},
+    DecodeObd2Values,
+};
+use crate::Result;
+use alloc::vec::Vec;
+
+/// Whether a turbocharger control system is running open or closed loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControlStatus {
+    /// Open loop, with no fault
+    OpenLoop,
+    /// Closed loop, with no fault
+    ClosedLoop,
+    /// A fault is present, so the data is not reliable
+    Fault,
+}
+
+impl ControlStatus {
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0x03 {
+            1 => Some(Self::OpenLoop),
+            2 => Some(Self::ClosedLoop),
+            3 => Some(Self::Fault),
+            _ => None,
+        }
+    }
+}
+
+/// Boost pressure control, from PID 0x70
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct BoostPressureControl {
+    /// Commanded boost pressure for turbocharger A in kPa
+    pub commanded_a: Option<f32>,
+    /// Measured boost pressure for turbocharger A in kPa
+    pub actual_a: Option<f32>,
+    /// Commanded boost pressure for turbocharger B in kPa
+    pub commanded_b: Option<f32>,
+    /// Measured boost pressure for turbocharger B in kPa
+    pub actual_b: Option<f32>,
+    /// Status of the boost pressure control for turbocharger A
+    pub status_a: Option<ControlStatus>,
+    /// Status of the boost pressure control for turbocharger B
+    pub status_b: Option<ControlStatus>,
+}
+
+/// Variable geometry turbo (VGT) control, from PID 0x71
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct VgtControl {
+    /// Commanded vane position for turbocharger A as a percentage
+    pub commanded_a: Option<f32>,
+    /// Measured vane position for turbocharger A as a percentage
+    pub actual_a: Option<f32>,
+    /// Commanded vane position for turbocharger B as a percentage
+    pub commanded_b: Option<f32>,
+    /// Measured vane position for turbocharger B as a percentage
+    pub actual_b: Option<f32>,
+    /// Status of the VGT control for turbocharger A
+    pub status_a: Option<ControlStatus>,
+    /// Status of the VGT control for turbocharger B
+    pub status_b: Option<ControlStatus>,
+}
+
+/// Wastegate control, from PID 0x72
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct WastegateControl {
+    /// Commanded wastegate position for turbocharger A as a percentage
+    pub commanded_a: Option<f32>,
+    /// Measured wastegate position for turbocharger A as a percentage
+    pub actual_a: Option<f32>,
+    /// Commanded wastegate position for turbocharger B as a percentage
+    pub commanded_b: Option<f32>,
+    /// Measured wastegate position for turbocharger B as a percentage
+    pub actual_b: Option<f32>,
+}
+
+/// Exhaust pressure, from PID 0x73
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ExhaustPressure {
+    /// Exhaust pressure for bank 1 in kPa
+    pub bank_1: Option<f32>,
+    /// Exhaust pressure for bank 2 in kPa
+    pub bank_2: Option<f32>,
+}
+
+/// Turbocharger speed, from PID 0x74
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TurbochargerRpm {
+    /// Speed of turbocharger A in RPM
+    pub a: Option<u32>,
+    /// Speed of turbocharger B in RPM
+    pub b: Option<u32>,
+}
+
+/// Temperatures of one turbocharger, from PID 0x75 (turbocharger A) or 0x76 (turbocharger B)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TurbochargerTemperature {
+    /// Temperature of the air entering the compressor in ºC
+    pub compressor_inlet: Option<i16>,
+    /// Temperature of the air leaving the compressor in ºC
+    pub compressor_outlet: Option<i16>,
+    /// Temperature of the exhaust entering the turbine in ºC
+    pub turbine_inlet: Option<f32>,
+    /// Temperature of the exhaust leaving the turbine in ºC
+    pub turbine_outlet: Option<f32>,
+}
+
+/// Charge air cooler temperatures, from PID 0x77
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ChargeAirCoolerTemperature {
+    /// Bank 1 sensor 1 in ºC
+    pub bank_1_sensor_1: Option<i16>,
+    /// Bank 1 sensor 2 in ºC
+    pub bank_1_sensor_2: Option<i16>,
+    /// Bank 2 sensor 1 in ºC
+    pub bank_2_sensor_1: Option<i16>,
+    /// Bank 2 sensor 2 in ºC
+    pub bank_2_sensor_2: Option<i16>,
+}
+
+/// Exhaust gas temperatures for one bank, from PID 0x78 (bank 1) or 0x79 (bank 2)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ExhaustGasTemperature {
+    /// Temperatures of sensors 1 to 4 in ºC
+    pub sensors: [Option<f32>; 4],
+}
+
+impl DecodeObd2Values for BoostPressureControl {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 10]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| BoostPressureControl {
+                commanded_a: supported(v[0], 0, word(v[1], v[2]) * 0.03125),
+                actual_a: supported(v[0], 1, word(v[3], v[4]) * 0.03125),
+                commanded_b: supported(v[0], 2, word(v[5], v[6]) * 0.03125),
+                actual_b: supported(v[0], 3, word(v[7], v[8]) * 0.03125),
+                status_a: supported(v[0], 4, ControlStatus::from_bits(v[9])).flatten(),
+                status_b: supported(v[0], 5, ControlStatus::from_bits(v[9] >> 2)).flatten(),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for VgtControl {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 6]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| VgtControl {
+                commanded_a: supported(v[0], 0, percent(f32::from(v[1]))),
+                actual_a: supported(v[0], 1, percent(f32::from(v[2]))),
+                commanded_b: supported(v[0], 2, percent(f32::from(v[3]))),
+                actual_b: supported(v[0], 3, percent(f32::from(v[4]))),
+                status_a: supported(v[0], 4, ControlStatus::from_bits(v[5])).flatten(),
+                status_b: supported(v[0], 5, ControlStatus::from_bits(v[5] >> 2)).flatten(),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for WastegateControl {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 5]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| WastegateControl {
+                commanded_a: supported(v[0], 0, percent(f32::from(v[1]))),
+                actual_a: supported(v[0], 1, percent(f32::from(v[2]))),
+                commanded_b: supported(v[0], 2, percent(f32::from(v[3]))),
+                actual_b: supported(v[0], 3, percent(f32::from(v[4]))),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for ExhaustPressure {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 5]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| ExhaustPressure {
+                bank_1: supported(v[0], 0, word(v[1], v[2]) * 0.01),
+                bank_2: supported(v[0], 1, word(v[3], v[4]) * 0.01),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for TurbochargerRpm {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 5]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| TurbochargerRpm {
+                a: supported(v[0], 0, u32::from(u16::from_be_bytes([v[1], v[2]])) * 10),
+                b: supported(v[0], 1, u32::from(u16::from_be_bytes([v[3], v[4]])) * 10),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for TurbochargerTemperature {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 7]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| TurbochargerTemperature {
+                compressor_inlet: supported(v[0], 0, temperature(v[1])),
+                compressor_outlet: supported(v[0], 1, temperature(v[2])),
+                turbine_inlet: supported(v[0], 2, wide_temperature(v[3], v[4])),
+                turbine_outlet: supported(v[0], 3, wide_temperature(v[5], v[6])),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for ChargeAirCoolerTemperature {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 5]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| ChargeAirCoolerTemperature {
+                bank_1_sensor_1: supported(v[0], 0, temperature(v[1])),
+                bank_1_sensor_2: supported(v[0], 1, temperature(v[2])),
+                bank_2_sensor_1: supported(v[0], 2, temperature(v[3])),
+                bank_2_sensor_2: supported(v[0], 3, temperature(v[4])),
+            })
+            .collect())
+    }
+}
+
+impl DecodeObd2Values for ExhaustGasTemperature {
+    fn decode_obd2_val(responses: Vec<Vec<u8>>) -> Result<Vec<Self>> {
+        Ok(<[u8; 9]>::decode_obd2_val(responses)?
+            .into_iter()
+            .map(|v| ExhaustGasTemperature {
+                sensors: core::array::from_fn(|i| {
+                    supported(v[0], i as u8, wide_temperature(v[1 + 2 * i], v[2 + 2 * i]))
+                }),
+            })
+            .collect())
+    }
+}
@@ -1,7 +1,33 @@
-use std::fmt;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, str::FromStr};
+
+use super::pid::{Snapshot, StandardPid};
+use crate::{units::UnitSystem, Error, Result};
 
 /// DTC (diagnostic trouble code) metadata
-#[derive(Debug)]
+///
+/// Read with [get_dtc_info](super::Obd2DataRetrieval::get_dtc_info). The raw bit fields are kept
+/// alongside [readiness](Self::readiness), which decodes them into the status of each monitor.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::{Monitor, Obd2DataRetrieval}, device::MockObd2Device};
+///
+/// // MIL on with two DTCs, spark ignition, catalyst monitor available and incomplete
+/// let mut device =
+///     MockObd2Device::new().with_response(0x01, 0x01, [vec![0x82, 0x00, 0x01, 0x01]]);
+///
+/// let info = &device.get_dtc_info().unwrap()[0];
+/// assert!(info.malfunction_indicator_light);
+/// assert_eq!(info.dtc_count, 2);
+/// assert!(!info.is_compression_engine);
+/// assert!(!info.readiness.get(Monitor::Catalyst).unwrap().complete);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub struct DtcsInfo {
     /// Whether the "check engine" light is illuminated
@@ -19,10 +45,218 @@ pub struct DtcsInfo {
     /// Bit field showing availability of sixteen engine-specific tests. What the tests are is
     /// based on the value of `is_compression_engine`.
     pub specific_test_availability: u16,
+
+    /// The status of each monitor since the DTCs were cleared, decoded from the bit fields above
+    pub readiness: ReadinessStatus,
+}
+
+/// An emissions monitor: a test the ECU runs on the vehicle's emissions systems while driving
+///
+/// The first three are continuous monitors that every vehicle has. The rest depend on whether the
+/// engine is spark or compression ignition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Monitor {
+    /// Misfire detection
+    Misfire,
+    /// Fuel system
+    FuelSystem,
+    /// Comprehensive component monitoring
+    Components,
+
+    /// Catalyst (spark ignition)
+    Catalyst,
+    /// Heated catalyst (spark ignition)
+    HeatedCatalyst,
+    /// Evaporative system (spark ignition)
+    EvaporativeSystem,
+    /// Secondary air system (spark ignition)
+    SecondaryAirSystem,
+    /// A/C refrigerant (spark ignition)
+    AcRefrigerant,
+    /// Oxygen sensor (spark ignition)
+    OxygenSensor,
+    /// Oxygen sensor heater (spark ignition)
+    OxygenSensorHeater,
+
+    /// Non-methane hydrocarbon catalyst (compression ignition)
+    NmhcCatalyst,
+    /// NOx/SCR aftertreatment (compression ignition)
+    NoxScr,
+    /// Boost pressure (compression ignition)
+    BoostPressure,
+    /// Exhaust gas sensor (compression ignition)
+    ExhaustGasSensor,
+    /// Particulate matter filter (compression ignition)
+    PmFilter,
+
+    /// EGR (exhaust gas recirculation) and/or VVT (variable valve timing)
+    EgrVvt,
+}
+
+impl Monitor {
+    /// Get the monitor's name, like `"Catalyst"`
+    pub fn name(self) -> &'static str {
+        match self {
+            Monitor::Misfire => "Misfire",
+            Monitor::FuelSystem => "Fuel system",
+            Monitor::Components => "Comprehensive components",
+            Monitor::Catalyst => "Catalyst",
+            Monitor::HeatedCatalyst => "Heated catalyst",
+            Monitor::EvaporativeSystem => "Evaporative system",
+            Monitor::SecondaryAirSystem => "Secondary air system",
+            Monitor::AcRefrigerant => "A/C refrigerant",
+            Monitor::OxygenSensor => "Oxygen sensor",
+            Monitor::OxygenSensorHeater => "Oxygen sensor heater",
+            Monitor::NmhcCatalyst => "NMHC catalyst",
+            Monitor::NoxScr => "NOx/SCR aftertreatment",
+            Monitor::BoostPressure => "Boost pressure",
+            Monitor::ExhaustGasSensor => "Exhaust gas sensor",
+            Monitor::PmFilter => "Particulate filter",
+            Monitor::EgrVvt => "EGR/VVT",
+        }
+    }
+}
+
+/// Monitors for spark ignition engines, indexed by their bit in the monitor status
+const SPARK_MONITORS: [Option<Monitor>; 8] = [
+    Some(Monitor::Catalyst),
+    Some(Monitor::HeatedCatalyst),
+    Some(Monitor::EvaporativeSystem),
+    Some(Monitor::SecondaryAirSystem),
+    Some(Monitor::AcRefrigerant),
+    Some(Monitor::OxygenSensor),
+    Some(Monitor::OxygenSensorHeater),
+    Some(Monitor::EgrVvt),
+];
+
+/// Monitors for compression ignition engines, indexed by their bit in the monitor status
+const COMPRESSION_MONITORS: [Option<Monitor>; 8] = [
+    Some(Monitor::NmhcCatalyst),
+    Some(Monitor::NoxScr),
+    None,
+    Some(Monitor::BoostPressure),
+    None,
+    Some(Monitor::ExhaustGasSensor),
+    Some(Monitor::PmFilter),
+    Some(Monitor::EgrVvt),
+];
+
+/// Whether one monitor is supported and whether it has completed
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct MonitorStatus {
+    /// The monitor
+    pub monitor: Monitor,
+
+    /// Whether the vehicle has this monitor
+    pub available: bool,
+
+    /// Whether the monitor has run and completed
+    pub complete: bool,
+}
+
+/// The readiness of each emissions monitor
+///
+/// Emissions inspections usually check that the monitors have completed, since clearing DTCs
+/// resets them. This comes from PID 0x01 as part of [DtcsInfo], for the time since DTCs were
+/// cleared, or from PID 0x41 with
+/// [get_drive_cycle_readiness](super::Obd2DataRetrieval::get_drive_cycle_readiness), for the
+/// current drive cycle.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::{Monitor, Obd2DataRetrieval}, device::MockObd2Device};
+///
+/// // catalyst and oxygen sensor monitors available, and the oxygen sensor monitor incomplete
+/// let mut device =
+///     MockObd2Device::new().with_response(0x01, 0x01, [vec![0x00, 0x07, 0x21, 0x20]]);
+///
+/// let readiness = &device.get_dtc_info().unwrap()[0].readiness;
+/// assert!(readiness.get(Monitor::Catalyst).unwrap().complete);
+/// assert!(!readiness.get(Monitor::OxygenSensor).unwrap().complete);
+/// assert!(!readiness.get(Monitor::EvaporativeSystem).unwrap().available);
+/// assert!(!readiness.all_complete());
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ReadinessStatus {
+    /// Whether the engine is Diesel, which determines which monitors are listed
+    pub is_compression_engine: bool,
+
+    /// The status of each monitor
+    pub monitors: Vec<MonitorStatus>,
+}
+
+impl ReadinessStatus {
+    /// Decode the last three bytes of PID 0x01 or 0x41
+    ///
+    /// In each byte pair, a set bit means a monitor is available, or that it is not complete.
+    pub(super) fn from_bytes(b: u8, c: u8, d: u8) -> Self {
+        let is_compression_engine = b & 0x08 != 0;
+        let common = [Monitor::Misfire, Monitor::FuelSystem, Monitor::Components]
+            .into_iter()
+            .enumerate()
+            .map(|(bit, monitor)| MonitorStatus {
+                monitor,
+                available: b & (1 << bit) != 0,
+                complete: b & (0x10 << bit) == 0,
+            });
+        let specific = if is_compression_engine {
+            COMPRESSION_MONITORS
+        } else {
+            SPARK_MONITORS
+        };
+        let specific = specific
+            .into_iter()
+            .enumerate()
+            .filter_map(|(bit, monitor)| {
+                Some(MonitorStatus {
+                    monitor: monitor?,
+                    available: c & (1 << bit) != 0,
+                    complete: d & (1 << bit) == 0,
+                })
+            });
+        ReadinessStatus {
+            is_compression_engine,
+            monitors: common.chain(specific).collect(),
+        }
+    }
+
+    /// Get the status of `monitor`, or `None` if it does not apply to this engine type
+    pub fn get(&self, monitor: Monitor) -> Option<&MonitorStatus> {
+        self.monitors.iter().find(|m| m.monitor == monitor)
+    }
+
+    /// Whether every available monitor has completed
+    pub fn all_complete(&self) -> bool {
+        self.monitors.iter().all(|m| !m.available || m.complete)
+    }
 }
 
 /// An individual trouble code from an ECU
-#[derive(Debug)]
+///
+/// Each variant holds the four digits of the code after the letter, which are displayed in
+/// hexadecimal. The first digit is 0–3, and indicates whether the code is defined by SAE J2012
+/// (like 0) or by the manufacturer (like 1).
+///
+/// # Example
+/// ```
+/// use obd2::commands::Dtc;
+///
+/// assert_eq!(Dtc::from(0x0301).to_string(), "P0301");
+/// assert_eq!(Dtc::from(0x4123).to_string(), "C0123");
+/// assert_eq!(Dtc::from(0x9234).to_string(), "B1234");
+/// assert_eq!(Dtc::from(0xC100).to_string(), "U0100");
+///
+/// assert_eq!("P0301".parse::<Dtc>().unwrap(), Dtc::Powertrain(0x0301));
+/// assert!("P4301".parse::<Dtc>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dtc {
     /// Powertrain, represented with `'P'`
     Powertrain(u16),
@@ -35,8 +269,11 @@ pub enum Dtc {
 }
 
 impl From<u16> for Dtc {
+    /// Decode the two bytes used for a DTC in an OBD-II response
+    ///
+    /// The top two bits select the letter, and the remaining 14 bits are the digits.
     fn from(val: u16) -> Self {
-        let n = val & 0x3f;
+        let n = val & 0x3fff;
         match val >> 14 {
             0 => Dtc::Powertrain(n),
             1 => Dtc::Chassis(n),
@@ -47,31 +284,616 @@ impl From<u16> for Dtc {
     }
 }
 
-impl fmt::Display for Dtc {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (c, n) = match self {
+impl FromStr for Dtc {
+    type Err = Error;
+
+    /// Parse a code like `"P0301"`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDtc(s.into());
+        let mut chars = s.chars();
+        let letter = chars.next().ok_or_else(invalid)?;
+        let digits = chars.as_str();
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let n = u16::from_str_radix(digits, 16).map_err(|_| invalid())?;
+        if n > 0x3fff {
+            return Err(invalid());
+        }
+        match letter.to_ascii_uppercase() {
+            'P' => Ok(Dtc::Powertrain(n)),
+            'C' => Ok(Dtc::Chassis(n)),
+            'B' => Ok(Dtc::Body(n)),
+            'U' => Ok(Dtc::Network(n)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Dtc {
+    /// Get a description of the code, like `"Cylinder 1 Misfire Detected"` for P0301
+    ///
+    /// Descriptions registered with [register_descriptions](Self::register_descriptions) are
+    /// used first. With the `dtc-db` feature, the generic codes defined by SAE J2012 are also
+    /// known. The alternate format (`{:#}`) includes the description after the code.
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::commands::Dtc;
+    ///
+    /// Dtc::register_descriptions([("P1299", "Cylinder Head Overtemperature Protection Active")]);
+    ///
+    /// let dtc = Dtc::from(0x1299);
+    /// assert_eq!(
+    ///     dtc.description().as_deref(),
+    ///     Some("Cylinder Head Overtemperature Protection Active")
+    /// );
+    /// assert_eq!(
+    ///     format!("{:#}", dtc),
+    ///     "P1299 – Cylinder Head Overtemperature Protection Active"
+    /// );
+    /// ```
+    pub fn description(&self) -> Option<String> {
+        super::dtc_db::lookup(&self.to_string())
+    }
+
+    /// Add descriptions for codes, like a manufacturer's table of specific codes
+    ///
+    /// Each item is a code (like `"P1299"`) and its description. These apply for the rest of the
+    /// program, and replace any existing description of the same code.
+    #[cfg(feature = "std")]
+    pub fn register_descriptions(
+        descriptions: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) {
+        super::dtc_db::register(
+            descriptions
+                .into_iter()
+                .map(|(code, description)| (code.into(), description.into())),
+        );
+    }
+}
+
+impl Dtc {
+    /// The letter of the category and the number of the code, like `('P', 0x0301)`
+    fn letter_and_number(&self) -> (char, u16) {
+        match *self {
             Self::Powertrain(n) => ('P', n),
             Self::Chassis(n) => ('C', n),
             Self::Body(n) => ('B', n),
             Self::Network(n) => ('U', n),
-        };
-        f.write_fmt(format_args!("{}{:03X}", c, n))
+        }
+    }
+}
+
+impl fmt::Display for Dtc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (c, n) = self.letter_and_number();
+        f.write_fmt(format_args!("{}{:04X}", c, n))?;
+        if f.alternate() {
+            if let Some(description) = self.description() {
+                f.write_fmt(format_args!(" – {}", description))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Logged like its [Display](fmt::Display) form, as `P0301`
+#[cfg(feature = "defmt")]
+impl defmt::Format for Dtc {
+    fn format(&self, f: defmt::Formatter) {
+        let (c, n) = self.letter_and_number();
+        defmt::write!(f, "{=char}{=u16:04X}", c, n)
+    }
+}
+
+/// A permanent trouble code from an ECU
+///
+/// Permanent DTCs are confirmed DTCs that cannot be cleared with service 4 or by disconnecting the
+/// battery. The ECU only erases one once its monitor has run and passed, which may take several
+/// drive cycles. This is a separate type so they are not mixed up with the codes from
+/// [get_dtcs](super::Obd2DataRetrieval::get_dtcs) and
+/// [get_pending_dtcs](super::Obd2DataRetrieval::get_pending_dtcs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PermanentDtc(pub Dtc);
+
+impl fmt::Display for PermanentDtc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The conditions an ECU stored when it set a DTC
+///
+/// When a DTC is set, the ECU saves a snapshot of the PIDs at that moment, so that the problem can
+/// be diagnosed later. Each value is `None` if the ECU did not store it.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct FreezeFrame {
+    /// The DTC that caused the freeze frame to be stored, or `None` if no freeze frame is stored
+    pub dtc: Option<Dtc>,
+
+    /// The calculated engine load (out of 255)
+    pub engine_load: Option<u8>,
+
+    /// The temperature of the engine's coolant in ºC
+    pub engine_coolant_temperature: Option<i16>,
+
+    /// The short term fuel trim for bank 1 as a percentage, from -100 (leaner) to about 99.2
+    /// (richer)
+    pub short_term_fuel_trim_1: Option<f32>,
+
+    /// The long term fuel trim for bank 1 as a percentage, from -100 (leaner) to about 99.2
+    /// (richer)
+    pub long_term_fuel_trim_1: Option<f32>,
+
+    /// The short term fuel trim for bank 2 as a percentage, from -100 (leaner) to about 99.2
+    /// (richer)
+    pub short_term_fuel_trim_2: Option<f32>,
+
+    /// The long term fuel trim for bank 2 as a percentage, from -100 (leaner) to about 99.2
+    /// (richer)
+    pub long_term_fuel_trim_2: Option<f32>,
+
+    /// The RPM of the engine
+    pub rpm: Option<f32>,
+
+    /// The speed of the vehicle in km/h
+    pub speed: Option<u8>,
+}
+
+/// The common live values of the engine, read together
+///
+/// Read with [get_live_snapshot](super::Obd2DataRetrieval::get_live_snapshot), which requests
+/// several PIDs at once so the values are from nearly the same moment. Each value is from the
+/// first ECU that reported it, and is `None` if no ECU did.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct LiveData {
+    /// The RPM of the engine
+    pub rpm: Option<f32>,
+
+    /// The speed of the vehicle in km/h
+    pub speed: Option<f32>,
+
+    /// The calculated engine load in percent
+    pub engine_load: Option<f32>,
+
+    /// The position of the throttle in percent
+    pub throttle_position: Option<f32>,
+
+    /// The mass air flow rate in g/s
+    pub maf: Option<f32>,
+
+    /// The short term fuel trim for bank 1 in percent
+    pub short_term_fuel_trim: Option<f32>,
+
+    /// The long term fuel trim for bank 1 in percent
+    pub long_term_fuel_trim: Option<f32>,
+
+    /// The temperature of the engine's coolant in ºC
+    pub coolant_temperature: Option<f32>,
+
+    /// The temperature of the intake air in ºC
+    pub intake_air_temperature: Option<f32>,
+
+    /// When the values were received
+    #[cfg(feature = "std")]
+    pub time: std::time::SystemTime,
+}
+
+impl LiveData {
+    /// The PIDs of the values, with the ones that change fastest first so they are requested
+    /// together
+    pub(super) const PIDS: [StandardPid; 9] = [
+        StandardPid::EngineSpeed,
+        StandardPid::VehicleSpeed,
+        StandardPid::EngineLoad,
+        StandardPid::ThrottlePosition,
+        StandardPid::MafAirFlowRate,
+        StandardPid::ShortTermFuelTrim1,
+        StandardPid::LongTermFuelTrim1,
+        StandardPid::EngineCoolantTemperature,
+        StandardPid::IntakeAirTemperature,
+    ];
+
+    pub(super) fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let value = |pid| snapshot.get(&pid).and_then(|v| v.first().copied());
+        LiveData {
+            rpm: value(StandardPid::EngineSpeed),
+            speed: value(StandardPid::VehicleSpeed),
+            engine_load: value(StandardPid::EngineLoad),
+            throttle_position: value(StandardPid::ThrottlePosition),
+            maf: value(StandardPid::MafAirFlowRate),
+            short_term_fuel_trim: value(StandardPid::ShortTermFuelTrim1),
+            long_term_fuel_trim: value(StandardPid::LongTermFuelTrim1),
+            coolant_temperature: value(StandardPid::EngineCoolantTemperature),
+            intake_air_temperature: value(StandardPid::IntakeAirTemperature),
+            #[cfg(feature = "std")]
+            time: std::time::SystemTime::now(),
+        }
+    }
+
+    /// Convert the values to `units`, so that the speed is in the system's speed unit, the
+    /// temperatures in its temperature unit, and the air flow rate in its mass flow unit
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{commands::Obd2DataRetrieval, device::Obd2Simulator, units::UnitSystem};
+    ///
+    /// let mut device = Obd2Simulator::new();
+    /// let data = device.get_live_snapshot().unwrap();
+    /// let imperial = data.to_units(UnitSystem::UsCustomary);
+    /// assert_eq!(imperial.rpm, data.rpm);
+    /// assert!(imperial.coolant_temperature > data.coolant_temperature);
+    /// ```
+    pub fn to_units(&self, units: UnitSystem) -> Self {
+        let convert = |value: Option<f32>, unit| value.map(|v| units.convert(v, unit).0);
+        LiveData {
+            speed: convert(self.speed, "km/h"),
+            maf: convert(self.maf, "g/s"),
+            coolant_temperature: convert(self.coolant_temperature, "ºC"),
+            intake_air_temperature: convert(self.intake_air_temperature, "ºC"),
+            ..self.clone()
+        }
+    }
+}
+
+/// What the engine is doing, inferred from its speed, its load and the voltage
+///
+/// Read with [get_engine_state](super::Obd2DataRetrieval::get_engine_state), or inferred from
+/// values read some other way with [infer](Self::infer). Data loggers can use it to start
+/// recording when the engine starts, and stop when the ignition is turned off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum EngineState {
+    /// The ignition is off, so no ECU responds
+    Off,
+    /// The ignition is on, but the engine is not turning
+    KeyOn,
+    /// The starter is turning the engine
+    Cranking,
+    /// The engine is running
+    Running,
+    /// The engine is turning above idle with no load, with the fuel cut off while the vehicle
+    /// slows down with the throttle closed
+    DecelFuelCut,
+}
+
+impl EngineState {
+    /// Infer the state of an engine that responded, from the engine speed in rpm (PID 0x0C), the
+    /// calculated load in percent (PID 0x04) and the control module voltage (PID 0x42)
+    ///
+    /// Engines never idle below 400 rpm, so slower turning is cranking, as is turning below 700
+    /// rpm while the starter pulls the voltage below 11 V. Above 1200 rpm, a load under 1% means
+    /// the fuel is cut off. Without the engine speed, a voltage above 13.2 V means the alternator
+    /// is charging, so the engine is running.
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::commands::EngineState;
+    ///
+    /// assert_eq!(EngineState::infer(Some(0.), None, Some(12.4)), EngineState::KeyOn);
+    /// assert_eq!(EngineState::infer(Some(250.), None, Some(10.2)), EngineState::Cranking);
+    /// assert_eq!(EngineState::infer(Some(780.), Some(22.), Some(14.1)), EngineState::Running);
+    /// assert_eq!(
+    ///     EngineState::infer(Some(2100.), Some(0.), Some(14.1)),
+    ///     EngineState::DecelFuelCut
+    /// );
+    /// ```
+    pub fn infer(rpm: Option<f32>, engine_load: Option<f32>, voltage: Option<f32>) -> Self {
+        match rpm {
+            Some(rpm) if rpm <= 0. => Self::KeyOn,
+            Some(rpm) if rpm < 400. || rpm < 700. && voltage.is_some_and(|v| v < 11.) => {
+                Self::Cranking
+            }
+            Some(rpm) if rpm > 1200. && engine_load.is_some_and(|load| load < 1.) => {
+                Self::DecelFuelCut
+            }
+            Some(_) => Self::Running,
+            None if voltage.is_some_and(|v| v > 13.2) => Self::Running,
+            None => Self::KeyOn,
+        }
+    }
+}
+
+/// The service 1 PIDs supported by an ECU
+///
+/// Read with [get_pid_support](super::Obd2DataRetrieval::get_pid_support). The same bitmaps
+/// give the support for the other services in [capabilities](crate::capabilities).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PidSupport {
+    bitmaps: [u32; 8],
+}
+
+impl PidSupport {
+    /// Whether the ECU supports `pid`
+    ///
+    /// PID 0x00 is always supported, since it is how support is found.
+    pub fn supports(&self, pid: u8) -> bool {
+        match pid {
+            0 => true,
+            pid => {
+                let n = usize::from(pid - 1);
+                self.bitmaps[n / 32] & (1 << (31 - n % 32)) != 0
+            }
+        }
+    }
+
+    /// Get every supported PID, in order
+    pub fn pids(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(|&pid| self.supports(pid))
+    }
+
+    /// Get the raw bitmap for the 32 PIDs after `base`, as returned for PID `base`
+    ///
+    /// `base` is rounded down to a multiple of 0x20. Ranges the ECU did not report are zero.
+    pub fn bitmap(&self, base: u8) -> u32 {
+        self.bitmaps[usize::from(base / 32)]
+    }
+
+    /// Set the support for the 32 PIDs after `base` from the response to PID `base`
+    pub(crate) fn set_range(&mut self, base: u8, bitmap: u32) {
+        self.bitmaps[usize::from(base / 32)] = bitmap;
     }
 }
 
 /// Data retrieved when reading an oxygen sensor
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OxygenSensorData {
     /// The current voltage reading (V)
     pub voltage: f32,
 
-    /// The current associated short term fuel trim (%)
+    /// The current associated short term fuel trim as a percentage, from -100 (leaner) to about
+    /// 99.2 (richer)
     ///
-    /// The range of this value is approximately -1 to 1. This will be `127./128.` if not
-    /// applicable for the sensor.
-    pub shrft: f32,
+    /// This is `None` if the sensor is not used to calculate the trim.
+    pub shrft: Option<f32>,
+}
+
+/// Data retrieved when reading a wide-range oxygen sensor
+///
+/// Wide-range (or wideband) sensors measure the air/fuel ratio directly, rather than only whether
+/// the mixture is rich or lean. Depending on the PID it was read from, the sensor's voltage or
+/// current is included with the ratio.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct WideRangeOxygenSensorData {
+    /// The equivalence ratio (λ) measured by the sensor, from 0 to 2
+    ///
+    /// 1 is stoichiometric, and values less than 1 are rich.
+    pub equivalence_ratio: f32,
+
+    /// The sensor voltage (V), from 0 to 8 V
+    pub voltage: Option<f32>,
+
+    /// The sensor current (mA), from -128 to 128 mA
+    pub current: Option<f32>,
+}
+
+/// The status of one fuel system, from PID 0x03
+///
+/// In open loop, the ECU sets the air/fuel ratio without feedback from the oxygen sensors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FuelSystemStatus {
+    /// The engine is off (including temporarily, for vehicles that turn off the engine at idle),
+    /// or there is no such fuel system
+    Off,
+    /// Open loop because the conditions for closed loop (like engine temperature) are not met yet
+    OpenLoopCold,
+    /// Closed loop, using the oxygen sensors as feedback
+    ClosedLoop,
+    /// Open loop because of the engine load or deceleration fuel cut-off
+    OpenLoopLoad,
+    /// Open loop because of a system fault
+    OpenLoopFault,
+    /// Closed loop, but at least one oxygen sensor has a fault, so only one is used
+    ClosedLoopFault,
+    /// A value not defined by SAE J1979
+    Other(u8),
+}
+
+impl From<u8> for FuelSystemStatus {
+    fn from(val: u8) -> Self {
+        match val {
+            0 => Self::Off,
+            1 => Self::OpenLoopCold,
+            2 => Self::ClosedLoop,
+            4 => Self::OpenLoopLoad,
+            8 => Self::OpenLoopFault,
+            16 => Self::ClosedLoopFault,
+            _ => Self::Other(val),
+        }
+    }
+}
+
+/// Where the secondary air system has been commanded to inject air, from PID 0x12
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SecondaryAirStatus {
+    /// Upstream of the first catalytic converter inlet
+    Upstream,
+    /// Downstream of the first catalytic converter inlet
+    Downstream,
+    /// Off, or into the atmosphere
+    Atmosphere,
+    /// On for diagnostics
+    Diagnostics,
+    /// A value not defined by SAE J1979
+    Other(u8),
+}
+
+impl From<u8> for SecondaryAirStatus {
+    fn from(val: u8) -> Self {
+        match val {
+            1 => Self::Upstream,
+            2 => Self::Downstream,
+            4 => Self::Atmosphere,
+            8 => Self::Diagnostics,
+            _ => Self::Other(val),
+        }
+    }
+}
+
+/// The status of the auxiliary input, from PID 0x1E
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AuxiliaryInputStatus {
+    /// [Power take-off](https://en.wikipedia.org/wiki/Power_take-off) is not active
+    PowerTakeOffInactive,
+    /// Power take-off is active
+    PowerTakeOffActive,
+}
+
+impl From<u8> for AuxiliaryInputStatus {
+    /// Decode the status from the least significant bit; the others are reserved
+    fn from(val: u8) -> Self {
+        if val & 0x01 == 0 {
+            Self::PowerTakeOffInactive
+        } else {
+            Self::PowerTakeOffActive
+        }
+    }
+}
+
+/// The type of fuel a vehicle uses, from PID 0x51
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FuelType {
+    /// The fuel type is not available
+    NotAvailable,
+    /// Gasoline
+    Gasoline,
+    /// Methanol
+    Methanol,
+    /// Ethanol
+    Ethanol,
+    /// Diesel
+    Diesel,
+    /// Liquefied petroleum gas
+    Lpg,
+    /// Compressed natural gas
+    Cng,
+    /// Propane
+    Propane,
+    /// Battery electric
+    Electric,
+    /// Bifuel vehicle running on gasoline
+    BifuelGasoline,
+    /// Bifuel vehicle running on methanol
+    BifuelMethanol,
+    /// Bifuel vehicle running on ethanol
+    BifuelEthanol,
+    /// Bifuel vehicle running on LPG
+    BifuelLpg,
+    /// Bifuel vehicle running on CNG
+    BifuelCng,
+    /// Bifuel vehicle running on propane
+    BifuelPropane,
+    /// Bifuel vehicle running on electricity
+    BifuelElectric,
+    /// Bifuel vehicle running on electricity and a combustion engine
+    BifuelElectricAndCombustion,
+    /// Hybrid vehicle with a gasoline engine
+    HybridGasoline,
+    /// Hybrid vehicle with an ethanol engine
+    HybridEthanol,
+    /// Hybrid vehicle with a Diesel engine
+    HybridDiesel,
+    /// Hybrid vehicle running on electricity
+    HybridElectric,
+    /// Hybrid vehicle running on electricity and a combustion engine
+    HybridElectricAndCombustion,
+    /// Hybrid vehicle using regenerative braking
+    HybridRegenerative,
+    /// Bifuel vehicle running on Diesel
+    BifuelDiesel,
+    /// A value that is reserved by SAE J1979
+    Other(u8),
+}
+
+impl From<u8> for FuelType {
+    fn from(val: u8) -> Self {
+        match val {
+            0 => Self::NotAvailable,
+            1 => Self::Gasoline,
+            2 => Self::Methanol,
+            3 => Self::Ethanol,
+            4 => Self::Diesel,
+            5 => Self::Lpg,
+            6 => Self::Cng,
+            7 => Self::Propane,
+            8 => Self::Electric,
+            9 => Self::BifuelGasoline,
+            10 => Self::BifuelMethanol,
+            11 => Self::BifuelEthanol,
+            12 => Self::BifuelLpg,
+            13 => Self::BifuelCng,
+            14 => Self::BifuelPropane,
+            15 => Self::BifuelElectric,
+            16 => Self::BifuelElectricAndCombustion,
+            17 => Self::HybridGasoline,
+            18 => Self::HybridEthanol,
+            19 => Self::HybridDiesel,
+            20 => Self::HybridElectric,
+            21 => Self::HybridElectricAndCombustion,
+            22 => Self::HybridRegenerative,
+            23 => Self::BifuelDiesel,
+            n => Self::Other(n),
+        }
+    }
+}
+
+/// The rate of fuel flow, from PID 0x9D
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FuelMassRate {
+    /// The rate of fuel flow into the engine in g/s
+    pub engine: f32,
+
+    /// The rate of fuel flow for the whole vehicle in g/s, including fuel used by other systems
+    /// like Diesel exhaust aftertreatment
+    pub vehicle: f32,
+}
+
+/// The oxygen sensors present in a vehicle with up to two banks of four sensors
+///
+/// Read with
+/// [get_oxygen_sensors_present](super::Obd2DataRetrieval::get_oxygen_sensors_present). The less
+/// significant nibble is bank 1, with sensor 1 in the least significant bit.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OxygenSensorsPresent(pub u8);
+
+impl OxygenSensorsPresent {
+    /// Whether `sensor` (1 to 4) of `bank` (1 or 2) is present
+    pub fn contains(&self, bank: u8, sensor: u8) -> bool {
+        (1..=2).contains(&bank)
+            && (1..=4).contains(&sensor)
+            && self.0 & (1 << ((bank - 1) * 4 + sensor - 1)) != 0
+    }
 }
 
 pub(super) mod private {
     pub trait Sealed {}
     impl<T: crate::Obd2Device> Sealed for T {}
+
+    #[cfg(feature = "async")]
+    pub trait AsyncSealed {}
+    #[cfg(feature = "async")]
+    impl<T: crate::AsyncObd2Device> AsyncSealed for T {}
 }
@@ -0,0 +1,177 @@
+//! Getters returning typed quantities
+//!
+//! With the `uom` feature, [Obd2UnitRetrieval] wraps the getters from [Obd2DataRetrieval] that
+//! have physical units, returning [uom] quantities instead of bare numbers. This makes it
+//! impossible to mix up units like km/h and mph, or kPa and psi, in the code using the values.
+//! Each method is named after the getter it wraps, without the `get_` prefix.
+//!
+//! # Example
+//! ```
+//! use obd2::{commands::units::Obd2UnitRetrieval, device::MockObd2Device};
+//! use uom::si::velocity::mile_per_hour;
+//!
+//! let mut device = MockObd2Device::new().with_response(0x01, 0x0D, [vec![100]]);
+//!
+//! let speed = device.speed().unwrap()[0];
+//! assert_eq!(speed.get::<mile_per_hour>().round(), 62.);
+//! ```
+
+use alloc::vec::Vec;
+use uom::si::{
+    angle::degree,
+    angular_velocity::revolution_per_minute,
+    f32::{
+        Angle, AngularVelocity, Length, MassRate, Pressure, ThermodynamicTemperature, Time,
+        Velocity, VolumeRate,
+    },
+    length::kilometer,
+    mass_rate::gram_per_second,
+    pressure::{kilopascal, pascal},
+    thermodynamic_temperature::degree_celsius,
+    time::second,
+    velocity::kilometer_per_hour,
+    volume_rate::liter_per_minute,
+};
+
+#[cfg(feature = "async")]
+use super::AsyncObd2DataRetrieval;
+use super::Obd2DataRetrieval;
+use crate::Result;
+
+fn celsius(v: i16) -> ThermodynamicTemperature {
+    ThermodynamicTemperature::new::<degree_celsius>(f32::from(v))
+}
+
+fn kilopascals(v: f32) -> Pressure {
+    Pressure::new::<kilopascal>(v)
+}
+
+fn pascals(v: f32) -> Pressure {
+    Pressure::new::<pascal>(v)
+}
+
+fn kilometers(v: u16) -> Length {
+    Length::new::<kilometer>(f32::from(v))
+}
+
+fn degrees(v: f32) -> Angle {
+    Angle::new::<degree>(v)
+}
+
+macro_rules! unit_funcs {
+    {
+        $(
+            $(#[$attr:meta])*
+            fn $name:ident = $getter:ident -> $quantity:ty, $convert:expr;
+        )*
+    } => {
+        /// Getters returning typed quantities
+        ///
+        /// Automatically implemented for implementers of [Obd2DataRetrieval]. See the
+        /// [module](self) documentation.
+        pub trait Obd2UnitRetrieval: Obd2DataRetrieval {
+            $(
+                $(#[$attr])*
+                #[doc = ""]
+                #[doc = concat!("Like [", stringify!($getter), "](Obd2DataRetrieval::",
+                    stringify!($getter), ") but with units")]
+                fn $name(&mut self) -> Result<Vec<$quantity>> {
+                    Ok(self.$getter()?.into_iter().map($convert).collect())
+                }
+            )*
+        }
+
+        impl<T: Obd2DataRetrieval> Obd2UnitRetrieval for T {}
+
+        /// Asynchronous getters returning typed quantities
+        ///
+        /// The asynchronous version of [Obd2UnitRetrieval]. Automatically implemented for
+        /// implementers of [AsyncObd2DataRetrieval].
+        #[cfg(feature = "async")]
+        pub trait AsyncObd2UnitRetrieval: AsyncObd2DataRetrieval + Send {
+            $(
+                $(#[$attr])*
+                #[doc = ""]
+                #[doc = concat!("Like [", stringify!($getter), "](AsyncObd2DataRetrieval::",
+                    stringify!($getter), ") but with units")]
+                fn $name(
+                    &mut self,
+                ) -> impl std::future::Future<Output = Result<Vec<$quantity>>> + Send {
+                    async move { Ok(self.$getter().await?.into_iter().map($convert).collect()) }
+                }
+            )*
+        }
+
+        #[cfg(feature = "async")]
+        impl<T: AsyncObd2DataRetrieval + Send> AsyncObd2UnitRetrieval for T {}
+    };
+}
+
+unit_funcs! {
+    /// Get the temperature of the engine's coolant
+    fn engine_coolant_temperature = get_engine_coolant_temperature -> ThermodynamicTemperature,
+        celsius;
+
+    /// Get the fuel pressure (gauge pressure)
+    fn fuel_pressure = get_fuel_pressure -> Pressure, |v| kilopascals(f32::from(v));
+
+    /// Get the intake manifold absolute pressure
+    fn intake_manifold_pressure = get_intake_manifold_pressure -> Pressure,
+        |v| kilopascals(f32::from(v));
+
+    /// Get the speed of the engine
+    fn rpm = get_rpm -> AngularVelocity, AngularVelocity::new::<revolution_per_minute>;
+
+    /// Get the speed of the vehicle
+    fn speed = get_speed -> Velocity, |v| Velocity::new::<kilometer_per_hour>(f32::from(v));
+
+    /// Get the timing advance before top dead center
+    fn timing_advance = get_timing_advance -> Angle, degrees;
+
+    /// Get the temperature of the intake air
+    fn intake_air_temperature = get_intake_air_temperature -> ThermodynamicTemperature, celsius;
+
+    /// Get the mass air flow rate
+    fn maf = get_maf -> MassRate, MassRate::new::<gram_per_second>;
+
+    /// Get the time since the engine was started
+    fn run_time_since_start = get_run_time_since_start -> Time,
+        |v: core::time::Duration| Time::new::<second>(v.as_secs_f32());
+
+    /// Get the distance traveled with the MIL (check engine light) on
+    fn distance_with_mil = get_distance_with_mil -> Length, kilometers;
+
+    /// Get the fuel rail pressure, relative to the manifold vacuum
+    fn fuel_rail_pressure = get_fuel_rail_pressure -> Pressure, kilopascals;
+
+    /// Get the fuel rail gauge pressure
+    fn fuel_rail_gauge_pressure = get_fuel_rail_gauge_pressure -> Pressure, kilopascals;
+
+    /// Get the distance traveled since DTCs were cleared
+    fn distance_since_codes_cleared = get_distance_since_codes_cleared -> Length, kilometers;
+
+    /// Get the evaporative system vapor pressure
+    fn evap_system_vapor_pressure = get_evap_system_vapor_pressure -> Pressure, pascals;
+
+    /// Get the temperature of the ambient air
+    fn ambient_air_temperature = get_ambient_air_temperature -> ThermodynamicTemperature,
+        celsius;
+
+    /// Get the absolute evaporative system vapor pressure
+    fn evap_system_vapor_pressure_absolute = get_evap_system_vapor_pressure_absolute
+        -> Pressure, kilopascals;
+
+    /// Get the evaporative system vapor pressure, for vehicles with a wider range sensor
+    fn evap_system_vapor_pressure_wide = get_evap_system_vapor_pressure_wide -> Pressure,
+        |v| pascals(v as f32);
+
+    /// Get the fuel rail absolute pressure
+    fn fuel_rail_absolute_pressure = get_fuel_rail_absolute_pressure -> Pressure, kilopascals;
+
+    /// Get the fuel injection timing, where positive values are before top dead center
+    fn fuel_injection_timing = get_fuel_injection_timing -> Angle, degrees;
+
+    /// Get the rate of fuel flow into the engine
+    fn engine_fuel_rate = get_engine_fuel_rate -> VolumeRate,
+        |v| VolumeRate::new::<liter_per_minute>(v / 60.);
+}
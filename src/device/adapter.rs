@@ -0,0 +1,141 @@
+use std::fmt;
+
+use super::{Error, Result};
+
+/// The versions of the ELM327 that ELM Electronics released
+const GENUINE_VERSIONS: [(u8, u8); 9] = [
+    (1, 0),
+    (1, 1),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 0),
+    (2, 1),
+    (2, 2),
+    (2, 3),
+];
+
+/// The commands [AdapterInfo] probes for, with the first ELM327 version that has each one
+///
+/// The commands only print information. `STI` is the firmware ID of STN chips, which genuine
+/// ELM327s do not have.
+pub(super) const PROBED_COMMANDS: [(&str, Option<(u8, u8)>); 6] = [
+    ("ATRV", Some((1, 0))),
+    ("ATDPN", Some((1, 0))),
+    ("ATPPS", Some((1, 1))),
+    ("ATIGN", Some((1, 4))),
+    ("ATAMC", Some((2, 0))),
+    ("STI", None),
+];
+
+/// A way an adapter differs from a genuine ELM327
+///
+/// Most cheap adapters are clones running copied firmware, which claim a version but do not
+/// support everything that version has.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum AdapterQuirk {
+    /// The adapter reports a version ELM Electronics never released, usually v1.5
+    UnreleasedVersion,
+    /// The adapter does not support a command that the version it reports has
+    MissingCommand(String),
+}
+
+impl fmt::Display for AdapterQuirk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnreleasedVersion => write!(f, "reports a version that was never released"),
+            Self::MissingCommand(command) => write!(f, "does not support {}", command),
+        }
+    }
+}
+
+/// What an ELM327 adapter reports about itself
+///
+/// Get it with [Elm327::adapter_info](super::Elm327::adapter_info).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AdapterInfo {
+    /// The identifier the adapter printed for `ATI`, like `ELM327 v1.4b`
+    pub identifier: String,
+    /// The major and minor version from the identifier, like `(1, 4)`
+    pub version: Option<(u8, u8)>,
+    /// The device description (`AT@1`), if the adapter has one
+    pub description: Option<String>,
+    /// Which of the probed commands (`ATRV`, `ATDPN`, `ATPPS`, `ATIGN`, `ATAMC` and the STN
+    /// command `STI`) the adapter supports
+    pub supported_commands: Vec<String>,
+    /// How the adapter differs from a genuine ELM327 of its version
+    pub quirks: Vec<AdapterQuirk>,
+}
+
+impl AdapterInfo {
+    /// Build the info from the adapter's identifier and description, and which of
+    /// [PROBED_COMMANDS] it supports
+    pub(super) fn new(
+        identifier: String,
+        description: Option<String>,
+        supported_commands: Vec<String>,
+    ) -> Self {
+        let version = parse_version(&identifier);
+        let mut quirks = Vec::new();
+        if let Some(version) = version {
+            if !GENUINE_VERSIONS.contains(&version) {
+                quirks.push(AdapterQuirk::UnreleasedVersion);
+            }
+            quirks.extend(
+                PROBED_COMMANDS
+                    .iter()
+                    .filter(|(_, since)| since.is_some_and(|since| since <= version))
+                    .filter(|(command, _)| !supported_commands.iter().any(|c| c == command))
+                    .map(|(command, _)| AdapterQuirk::MissingCommand(command.to_string())),
+            );
+        }
+        AdapterInfo {
+            identifier,
+            version,
+            description,
+            supported_commands,
+            quirks,
+        }
+    }
+
+    /// Whether the adapter seems to be a clone, from its quirks
+    pub fn is_clone(&self) -> bool {
+        !self.quirks.is_empty()
+    }
+
+    /// Whether the adapter supports the probed `command`, like `ATRV`
+    pub fn supports(&self, command: &str) -> bool {
+        self.supported_commands
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(command))
+    }
+}
+
+/// Get the version from an identifier like `ELM327 v1.4b`
+fn parse_version(identifier: &str) -> Option<(u8, u8)> {
+    let (_, version) = identifier.rsplit_once('v')?;
+    let (major, minor) = version.split_once('.')?;
+    let minor: String = minor.chars().take_while(char::is_ascii_digit).collect();
+    Some((major.trim().parse().ok()?, minor.parse().ok()?))
+}
+
+/// Check the adapter's response to an AT command
+///
+/// Adapters print `?` for a command they do not know or cannot run.
+pub(super) fn at_response(command: &str, response: Option<String>) -> Result<String> {
+    match response.as_deref().map(str::trim) {
+        Some("?") => Err(Error::Communication(format!(
+            "at_command: the adapter does not support {:?}",
+            command
+        ))),
+        Some(response) => Ok(response.to_owned()),
+        None => Err(Error::Communication(format!(
+            "at_command: no response to {:?}",
+            command
+        ))),
+    }
+}
@@ -0,0 +1,74 @@
+//! The AT commands and responses shared by the ELM327 devices
+
+use alloc::{format, string::String};
+use core::time::Duration;
+
+use super::{Error, Result};
+use crate::Protocol;
+
+/// How long to wait for the adapter to finish responding, if the configuration does not say
+pub(super) const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Encode `data` as the hex digits the adapter takes a request in, like `010C`
+///
+/// Requests are sent many times a second while polling, so this builds the string directly
+/// instead of formatting each byte.
+pub(super) fn encode_hex(data: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let mut hex = String::with_capacity(data.len() * 2);
+    for b in data {
+        hex.push(char::from(DIGITS[usize::from(b >> 4)]));
+        hex.push(char::from(DIGITS[usize::from(b & 0xF)]));
+    }
+    hex
+}
+
+/// The AT command selecting `protocol`, or automatic protocol selection if there is none
+pub(super) fn select_protocol_command(protocol: Option<Protocol>) -> String {
+    format!(
+        "ATSP{:X}",
+        protocol.and_then(Protocol::elm327_number).unwrap_or(0)
+    )
+}
+
+/// Decode the adapter's response to `ATDPN`, like `A6` (found automatically) or `6` (selected)
+pub(super) fn parse_protocol_number(response: Option<String>) -> Result<Protocol> {
+    response
+        .as_deref()
+        .map(str::trim)
+        .map(|number| number.strip_prefix('A').unwrap_or(number))
+        .and_then(|number| u8::from_str_radix(number, 16).ok())
+        .and_then(Protocol::from_elm327)
+        .ok_or_else(|| {
+            Error::Communication(format!(
+                "protocol: adapter has not found a protocol (got {:?})",
+                response
+            ))
+        })
+}
+
+/// Decode the adapter's response to `ATRV`, like `12.6V`
+pub(super) fn parse_voltage(response: Option<String>) -> Result<f32> {
+    response
+        .as_deref()
+        .map(str::trim)
+        .and_then(|voltage| voltage.strip_suffix(['V', 'v']))
+        .and_then(|voltage| voltage.trim().parse().ok())
+        .ok_or_else(|| {
+            Error::Communication(format!(
+                "adapter_voltage: could not read the voltage from {:?}",
+                response
+            ))
+        })
+}
+
+/// Check that the ELM327 supports `protocol`
+pub(super) fn check_elm327_protocol(protocol: Option<Protocol>) -> Result<()> {
+    match protocol {
+        Some(protocol) if protocol.elm327_number().is_none() => Err(Error::Communication(format!(
+            "the ELM327 does not support {}",
+            protocol
+        ))),
+        _ => Ok(()),
+    }
+}
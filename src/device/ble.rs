@@ -0,0 +1,238 @@
+use btleplug::{
+    api::{
+        bleuuid::uuid_from_u16, Central, CharPropFlags, Characteristic, Manager as _,
+        Peripheral as _, ScanFilter, WriteType,
+    },
+    platform::{Manager, Peripheral},
+};
+use futures::StreamExt;
+use log::{debug, info, trace};
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    sync::mpsc,
+    time,
+};
+
+use super::{DeviceConfig, Elm327, Elm327Transport, Error, Result};
+
+/// An ELM327 OBD-II adapter connected over Bluetooth Low Energy
+///
+/// Many newer adapters only expose a GATT "UART" service: the ELM327's output is delivered as
+/// notifications on one characteristic, and commands are written to another. See
+/// [Elm327Ble::connect] for how the adapter is found.
+pub type Elm327Ble = Elm327<BleSerial>;
+
+/// Services used by common BLE adapters, in order of preference
+const UART_SERVICES: [u16; 2] = [0xFFF0, 0xFFE0];
+
+/// Largest write that fits in a single packet with the default MTU
+const MAX_WRITE_LEN: usize = 20;
+
+/// A byte stream over the GATT UART service of a BLE adapter
+///
+/// Notifications are split at arbitrary points by the adapter, so they are queued as a stream of
+/// bytes. The [Elm327] reassembles them into lines and complete responses.
+pub struct BleSerial {
+    runtime: tokio::runtime::Runtime,
+    peripheral: Peripheral,
+    write_characteristic: Characteristic,
+    notifications: mpsc::Receiver<Vec<u8>>,
+    buffer: VecDeque<u8>,
+}
+
+impl BleSerial {
+    /// Find an adapter with a local name starting with `name` and open its UART service
+    ///
+    /// The first Bluetooth controller on the system is used to scan for `scan_time`.
+    pub fn open(name: &str, scan_time: time::Duration) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+
+        let (peripheral, write_characteristic, notifications) =
+            runtime.block_on(async { Self::connect_peripheral(name, scan_time).await })?;
+
+        Ok(BleSerial {
+            runtime,
+            peripheral,
+            write_characteristic,
+            notifications,
+            buffer: VecDeque::new(),
+        })
+    }
+
+    async fn connect_peripheral(
+        name: &str,
+        scan_time: time::Duration,
+    ) -> Result<(Peripheral, Characteristic, mpsc::Receiver<Vec<u8>>)> {
+        let central = Manager::new()
+            .await?
+            .adapters()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                Error::Communication("connect_peripheral: no Bluetooth controller".to_owned())
+            })?;
+
+        info!("Scanning for BLE adapter {:?}", name);
+        central.start_scan(ScanFilter::default()).await?;
+        tokio::time::sleep(scan_time).await;
+        central.stop_scan().await?;
+
+        let mut peripheral = None;
+        for p in central.peripherals().await? {
+            let local_name = p.properties().await?.and_then(|p| p.local_name);
+            debug!("connect_peripheral: found {:?}", local_name);
+            if local_name.is_some_and(|n| n.starts_with(name)) {
+                peripheral = Some(p);
+                break;
+            }
+        }
+        let peripheral = peripheral.ok_or_else(|| {
+            Error::Communication(format!("connect_peripheral: {:?} not found", name))
+        })?;
+
+        peripheral.connect().await?;
+        peripheral.discover_services().await?;
+
+        let (notify, write) =
+            find_uart_characteristics(&peripheral.characteristics()).ok_or_else(|| {
+                Error::Communication("connect_peripheral: no UART service found".to_owned())
+            })?;
+        info!(
+            "Using BLE characteristics {} (notify) and {} (write)",
+            notify.uuid, write.uuid
+        );
+
+        peripheral.subscribe(&notify).await?;
+        let mut stream = peripheral.notifications().await?;
+        let (tx, rx) = mpsc::channel();
+        tokio::spawn(async move {
+            while let Some(n) = stream.next().await {
+                if n.uuid == notify.uuid && tx.send(n.value).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok((peripheral, write, rx))
+    }
+}
+
+impl Drop for BleSerial {
+    fn drop(&mut self) {
+        let _ = self.runtime.block_on(self.peripheral.disconnect());
+    }
+}
+
+impl Read for BleSerial {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.is_empty() {
+            match self
+                .notifications
+                .recv_timeout(time::Duration::from_millis(100))
+            {
+                Ok(v) => {
+                    trace!("BleSerial::read: notification {:?}", v);
+                    self.buffer.extend(v)
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => return Err(io::ErrorKind::TimedOut.into()),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(io::ErrorKind::NotConnected.into())
+                }
+            }
+        }
+
+        let len = buf.len().min(self.buffer.len());
+        for (b, v) in buf.iter_mut().zip(self.buffer.drain(..len)) {
+            *b = v;
+        }
+        Ok(len)
+    }
+}
+
+impl Write for BleSerial {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let write_type = if self
+            .write_characteristic
+            .properties
+            .contains(CharPropFlags::WRITE_WITHOUT_RESPONSE)
+        {
+            WriteType::WithoutResponse
+        } else {
+            WriteType::WithResponse
+        };
+
+        let chunk = &buf[..buf.len().min(MAX_WRITE_LEN)];
+        self.runtime
+            .block_on(
+                self.peripheral
+                    .write(&self.write_characteristic, chunk, write_type),
+            )
+            .map_err(io::Error::other)?;
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Elm327Transport for BleSerial {}
+
+impl Elm327<BleSerial> {
+    /// Connect to an ELM327 adapter over Bluetooth Low Energy
+    ///
+    /// The adapter is found by scanning for five seconds for a device whose advertised name
+    /// starts with `name` (like `"OBDII"` or `"OBDLink CX"`). It does not need to be paired first.
+    pub fn connect(name: &str) -> Result<Self> {
+        Self::connect_with_config(name, DeviceConfig::default())
+    }
+
+    /// Connect to an ELM327 adapter over Bluetooth Low Energy with the timeouts and retries in
+    /// `config`
+    ///
+    /// The connect timeout is how long to scan for the adapter. See [connect](Self::connect).
+    pub fn connect_with_config(name: &str, config: DeviceConfig) -> Result<Self> {
+        let scan_time = config
+            .connect_timeout()
+            .unwrap_or(time::Duration::from_secs(5));
+        Elm327::with_transport_and_config(BleSerial::open(name, scan_time)?, 38400, None, config)
+    }
+}
+
+/// Pick the notify and write characteristics of the adapter's UART service
+///
+/// Known UART services are preferred, but any service with both a notify and a write
+/// characteristic is accepted.
+fn find_uart_characteristics(
+    characteristics: &std::collections::BTreeSet<Characteristic>,
+) -> Option<(Characteristic, Characteristic)> {
+    let in_service = |service, flags| {
+        characteristics
+            .iter()
+            .find(|c| c.service_uuid == service && c.properties.intersects(flags))
+            .cloned()
+    };
+    let uart_pair = |service| {
+        Some((
+            in_service(service, CharPropFlags::NOTIFY)?,
+            in_service(
+                service,
+                CharPropFlags::WRITE | CharPropFlags::WRITE_WITHOUT_RESPONSE,
+            )?,
+        ))
+    };
+
+    UART_SERVICES
+        .iter()
+        .find_map(|s| uart_pair(uuid_from_u16(*s)))
+        .or_else(|| {
+            characteristics
+                .iter()
+                .find_map(|c| uart_pair(c.service_uuid))
+        })
+}
@@ -0,0 +1,174 @@
+use log::info;
+use serialport::SerialPort;
+use std::io::{self, Read, Write};
+
+use super::{elm327::new_serial_port, DeviceConfig, Elm327, Elm327Transport, Error, Result};
+
+/// An ELM327 OBD-II adapter connected over classic Bluetooth
+///
+/// Cheap Bluetooth adapters expose the ELM327 over the serial port profile (SPP), which is carried
+/// on an RFCOMM channel. See [Elm327Bluetooth::connect] for how the link is set up on each
+/// platform.
+pub type Elm327Bluetooth = Elm327<BluetoothSerial>;
+
+/// A classic Bluetooth serial port profile (SPP) link
+///
+/// The operating system presents the RFCOMM channel as a serial device, which this wraps.
+pub struct BluetoothSerial {
+    port: Box<dyn SerialPort>,
+    path: String,
+}
+
+impl BluetoothSerial {
+    /// Open the serial device for a paired adapter
+    ///
+    /// On Linux, `device` is the adapter's Bluetooth address (like `"00:1D:A5:68:98:8B"`). The
+    /// RFCOMM channel of the adapter's serial port service is looked up with `sdptool`, falling
+    /// back to channel 1, and a `/dev/rfcommN` device is bound with `rfcomm` if one does not
+    /// already exist for the adapter. Binding usually requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// On macOS, `device` is the name the adapter was paired with (like `"OBDII"`). The system
+    /// creates a `/dev/cu.<name>` device for each paired serial port service, which is opened.
+    pub fn open(device: &str) -> Result<Self> {
+        let path = rfcomm_device_path(device)?;
+        info!("Opening Bluetooth adapter {} at {}", device, path);
+        Ok(BluetoothSerial {
+            port: new_serial_port(&path, 38400)?,
+            path,
+        })
+    }
+
+    /// Get the path of the serial device backing this link
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Read for BluetoothSerial {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.port.read(buf)
+    }
+}
+
+impl Write for BluetoothSerial {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Elm327Transport for BluetoothSerial {}
+
+impl Elm327<BluetoothSerial> {
+    /// Connect to a paired ELM327 adapter over classic Bluetooth
+    ///
+    /// See [BluetoothSerial::open] for the meaning of `device` on each platform.
+    pub fn connect(device: &str) -> Result<Self> {
+        Self::connect_with_config(device, DeviceConfig::default())
+    }
+
+    /// Connect to a paired ELM327 adapter over classic Bluetooth with the timeouts and retries in
+    /// `config`
+    ///
+    /// See [connect](Self::connect).
+    pub fn connect_with_config(device: &str, config: DeviceConfig) -> Result<Self> {
+        Elm327::with_transport_and_config(BluetoothSerial::open(device)?, 38400, None, config)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn rfcomm_device_path(address: &str) -> Result<String> {
+    use log::debug;
+    use std::process::Command;
+
+    let address = address.to_uppercase();
+
+    let bound = run_tool(&mut Command::new("rfcomm"))?;
+    if let Some(dev) = find_rfcomm_binding(&bound, &address) {
+        debug!("rfcomm_device_path: {} already bound to {}", address, dev);
+        return Ok(format!("/dev/{}", dev));
+    }
+
+    let channel =
+        match run_tool(Command::new("sdptool").args(["search", "--bdaddr", &address, "SP"])) {
+            Ok(services) => find_rfcomm_channel(&services).unwrap_or(1),
+            Err(e) => {
+                debug!(
+                    "rfcomm_device_path: service lookup failed ({}), using channel 1",
+                    e
+                );
+                1
+            }
+        };
+
+    let dev = (0..)
+        .map(|n| format!("rfcomm{}", n))
+        .find(|dev| !bound.lines().any(|l| l.starts_with(&format!("{}:", dev))))
+        .expect("infinite iterator");
+    info!("Binding {} channel {} to /dev/{}", address, channel, dev);
+    run_tool(Command::new("rfcomm").args(["bind", &dev, &address, &channel.to_string()]))?;
+
+    Ok(format!("/dev/{}", dev))
+}
+
+#[cfg(target_os = "macos")]
+fn rfcomm_device_path(name: &str) -> Result<String> {
+    let wanted = format!("cu.{}", name);
+    std::fs::read_dir("/dev")?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .find(|f| f.starts_with(&wanted))
+        .map(|f| format!("/dev/{}", f))
+        .ok_or_else(|| {
+            Error::Communication(format!(
+                "rfcomm_device_path: no serial device for paired adapter {:?}",
+                name
+            ))
+        })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn rfcomm_device_path(_device: &str) -> Result<String> {
+    Err(Error::Communication(
+        "rfcomm_device_path: Bluetooth adapters are not supported on this platform".to_owned(),
+    ))
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn run_tool(command: &mut std::process::Command) -> Result<String> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::Communication(format!(
+            "run_tool: {:?} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Find the device bound to `address` in the output of `rfcomm`
+///
+/// Lines are of the format "rfcomm0: 00:1D:A5:68:98:8B channel 1 clean".
+#[cfg(target_os = "linux")]
+fn find_rfcomm_binding<'a>(bound: &'a str, address: &str) -> Option<&'a str> {
+    bound.lines().find_map(|l| {
+        let (dev, rest) = l.split_once(": ")?;
+        rest.to_uppercase().starts_with(address).then_some(dev)
+    })
+}
+
+/// Find the RFCOMM channel in the output of `sdptool search`
+///
+/// The channel is listed under the protocol descriptors as "Channel: 1".
+#[cfg(target_os = "linux")]
+fn find_rfcomm_channel(services: &str) -> Option<u8> {
+    services
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("Channel:"))
+        .and_then(|c| c.trim().parse().ok())
+}
@@ -0,0 +1,145 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::{EcuResponse, Error, Obd2Device, Protocol, Result};
+
+/// A flag shared between threads to stop long operations
+///
+/// Clones share the same flag, so one can be given to a device wrapper like [CancellableDevice]
+/// or [ManagedDevice](super::ManagedDevice), and another kept by the application to call
+/// [cancel](Self::cancel) when the user disconnects or closes it. Operations stop with
+/// [Error::Cancelled] at the next point they check the token.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a [`CancellationToken`] that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the operations using this token, or any clone of it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [cancel](Self::cancel) was called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return [Error::Cancelled] if [cancel](Self::cancel) was called
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Wraps an [Obd2Device] and stops sending requests once a [CancellationToken] is cancelled
+///
+/// The token is checked before each request, so operations made of many requests, like reading
+/// the DTCs of every ECU or all of the vehicle information in service 9, stop between requests
+/// with [Error::Cancelled] instead of running to the end. A request that was already sent still
+/// waits for its responses.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Obd2DataRetrieval,
+///     device::{CancellableDevice, CancellationToken, Obd2Simulator},
+///     Error,
+/// };
+///
+/// let token = CancellationToken::new();
+/// let mut device = CancellableDevice::new(Obd2Simulator::new(), token.clone());
+/// assert!(device.get_rpm().is_ok());
+///
+/// // the user disconnected, from another thread
+/// token.cancel();
+/// assert!(matches!(device.get_rpm(), Err(Error::Cancelled)));
+/// ```
+pub struct CancellableDevice<T: Obd2Device> {
+    device: T,
+    token: CancellationToken,
+}
+
+impl<T: Obd2Device> CancellableDevice<T> {
+    /// Wrap `device`, stopping its requests when `token` is cancelled
+    pub fn new(device: T, token: CancellationToken) -> Self {
+        CancellableDevice { device, token }
+    }
+
+    /// Get the token that stops requests
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Get the wrapped device
+    pub fn device(&self) -> &T {
+        &self.device
+    }
+
+    /// Get the wrapped device mutably
+    pub fn device_mut(&mut self) -> &mut T {
+        &mut self.device
+    }
+
+    /// Get back the wrapped device
+    pub fn into_inner(self) -> T {
+        self.device
+    }
+}
+
+impl<T: Obd2Device> Obd2Device for CancellableDevice<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.token.check()?;
+        self.device.obd_command(mode, pid)
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.token.check()?;
+        self.device.obd_mode_command(mode)
+    }
+
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.token.check()?;
+        self.device.obd_multi_command(mode, pids)
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.token.check()?;
+        self.device.obd_request(request)
+    }
+
+    fn obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>> {
+        self.token.check()?;
+        self.device.obd_command_22(did)
+    }
+
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        self.token.check()?;
+        self.device.obd_request_to(address, request)
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        self.token.check()?;
+        self.device.obd_request_tagged(request)
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        self.token.check()?;
+        self.device.protocol()
+    }
+
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        self.token.check()?;
+        self.device.get_adapter_voltage()
+    }
+}
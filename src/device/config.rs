@@ -0,0 +1,138 @@
+use core::time::Duration;
+
+/// How an adapter adjusts the time it waits for the vehicle to respond
+///
+/// The ELM327 measures how quickly the vehicle responds, and waits less when it can (`ATAT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdaptiveTiming {
+    /// Always wait for the full timeout (`ATAT0`), for vehicles that respond irregularly
+    Off,
+    /// Adjust the wait to the vehicle (`ATAT1`), the adapter's default
+    #[default]
+    Normal,
+    /// Adjust the wait more aggressively (`ATAT2`), which is faster but can miss slow ECUs
+    Aggressive,
+}
+
+impl AdaptiveTiming {
+    /// The ELM327 command selecting this mode
+    #[cfg(any(feature = "std", feature = "embedded", feature = "web"))]
+    pub(crate) fn elm327_command(self) -> &'static str {
+        match self {
+            Self::Off => "ATAT0",
+            Self::Normal => "ATAT1",
+            Self::Aggressive => "ATAT2",
+        }
+    }
+}
+
+/// Timeouts and retries for a device
+///
+/// Start from the defaults and change them with the `with_` methods, then pass the configuration
+/// to the device's `with_config` constructor. Timeouts that are not set use the device's own
+/// default, which suits how it communicates.
+///
+/// When a device times out waiting for a response, the request is sent again up to the number of
+/// retries, waiting the retry backoff before the first retry and twice as long before each
+/// following one. If it still times out, the error is [Error::Timeout](crate::Error::Timeout).
+///
+/// # Example
+/// ```
+/// use obd2::device::{AdaptiveTiming, DeviceConfig};
+/// use core::time::Duration;
+///
+/// let config = DeviceConfig::new()
+///     .with_command_timeout(Duration::from_secs(2))
+///     .with_retries(2)
+///     .with_retry_backoff(Duration::from_millis(100))
+///     .with_adaptive_timing(AdaptiveTiming::Off);
+/// assert_eq!(config.command_timeout(), Some(Duration::from_secs(2)));
+/// assert_eq!(config.retry_delay(0), Duration::from_millis(100));
+/// assert_eq!(config.retry_delay(1), Duration::from_millis(200));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceConfig {
+    connect_timeout: Option<Duration>,
+    command_timeout: Option<Duration>,
+    retries: u32,
+    retry_backoff: Duration,
+    adaptive_timing: AdaptiveTiming,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        DeviceConfig {
+            connect_timeout: None,
+            command_timeout: None,
+            retries: 0,
+            retry_backoff: Duration::from_millis(200),
+            adaptive_timing: AdaptiveTiming::Normal,
+        }
+    }
+}
+
+impl DeviceConfig {
+    /// Create a [`DeviceConfig`] with the defaults: the device's own timeouts, no retries, and
+    /// normal adaptive timing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long connecting to the adapter or vehicle may take
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how long to wait for the response to a request
+    pub fn with_command_timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how many times a request that timed out is sent again
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set how long to wait before the first retry, which doubles for each following one
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Set how the adapter adjusts its wait for the vehicle, on adapters that can
+    pub fn with_adaptive_timing(mut self, adaptive_timing: AdaptiveTiming) -> Self {
+        self.adaptive_timing = adaptive_timing;
+        self
+    }
+
+    /// Get the connect timeout, if it was set
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Get the command timeout, if it was set
+    pub fn command_timeout(&self) -> Option<Duration> {
+        self.command_timeout
+    }
+
+    /// Get how many times a request that timed out is sent again
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Get how long to wait before retry number `attempt`, counting from 0
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        self.retry_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)))
+    }
+
+    /// Get the adaptive timing mode
+    pub fn adaptive_timing(&self) -> AdaptiveTiming {
+        self.adaptive_timing
+    }
+}
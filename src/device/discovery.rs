@@ -0,0 +1,261 @@
+use log::debug;
+use serialport::SerialPortType;
+use std::{fmt, time::Duration};
+
+use super::{
+    DeviceConfig, Elm327, Elm327Bluetooth, Elm327Transport, Result, TcpElm327, DEFAULT_TCP_ADDRESS,
+};
+
+/// The USB vendor and product IDs of the serial chips used in common adapters
+///
+/// These are FTDI chips (OBDLink and most genuine ELM327 adapters), the CH340 (most cheap clones),
+/// and the Silicon Labs CP210x and Prolific PL2303.
+const ADAPTER_USB_IDS: [(u16, u16); 7] = [
+    (0x0403, 0x6001),
+    (0x0403, 0x6015),
+    (0x1A86, 0x7523),
+    (0x1A86, 0x5523),
+    (0x10C4, 0xEA60),
+    (0x067B, 0x2303),
+    (0x0403, 0x6010),
+];
+
+/// Words in the names of adapters, matched without case
+const ADAPTER_NAMES: [&str; 8] = [
+    "OBD", "ELM", "VLINK", "V-LINK", "VGATE", "KONNWEI", "VEEPEAK", "CARISTA",
+];
+
+/// How long probing waits to connect to an adapter and for it to respond
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How to reach an adapter found by [discover]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum AdapterLink {
+    /// A serial port, like `/dev/ttyUSB0` or `COM3`, for [Elm327::new]
+    Serial(String),
+    /// A paired classic Bluetooth adapter, for [Elm327Bluetooth::connect]
+    ///
+    /// On Linux this is the adapter's address, and on macOS its name.
+    Bluetooth(String),
+    /// A Wi-Fi adapter's address, for [TcpElm327::connect]
+    Wifi(String),
+}
+
+impl fmt::Display for AdapterLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serial(path) => write!(f, "serial port {}", path),
+            Self::Bluetooth(device) => write!(f, "Bluetooth adapter {}", device),
+            Self::Wifi(address) => write!(f, "Wi-Fi adapter at {}", address),
+        }
+    }
+}
+
+/// A possible adapter found by [discover]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AdapterCandidate {
+    /// How to connect to the adapter
+    pub link: AdapterLink,
+    /// The name of the device, like the USB product or the Bluetooth name, if it has one
+    pub name: Option<String>,
+    /// Whether the device looks like an adapter, from its USB IDs or name
+    pub known_adapter: bool,
+    /// The identifier the adapter printed for `ATI` when probed, like `ELM327 v1.5`, or `None` if
+    /// it was not probed or did not respond
+    pub identifier: Option<String>,
+}
+
+impl AdapterCandidate {
+    fn new(link: AdapterLink, name: Option<String>, known_adapter: bool) -> Self {
+        AdapterCandidate {
+            link,
+            known_adapter: known_adapter || name.as_deref().is_some_and(is_adapter_name),
+            name,
+            identifier: None,
+        }
+    }
+
+    /// Whether the adapter responded when probed
+    pub fn responded(&self) -> bool {
+        self.identifier.is_some()
+    }
+
+    /// Connect to the candidate and ask it to identify itself (`ATI`), and return whether it did
+    ///
+    /// The identifier is stored in [identifier](Self::identifier). Connecting resets the adapter,
+    /// and sends ELM327 commands to whatever is on a serial port.
+    pub fn probe(&mut self) -> bool {
+        let config = DeviceConfig::new()
+            .with_connect_timeout(PROBE_TIMEOUT)
+            .with_command_timeout(PROBE_TIMEOUT);
+        let identifier = match &self.link {
+            AdapterLink::Serial(path) => {
+                Elm327::with_config(path, config).and_then(|mut d| identify(&mut d))
+            }
+            AdapterLink::Bluetooth(device) => Elm327Bluetooth::connect_with_config(device, config)
+                .and_then(|mut d| identify(&mut d)),
+            AdapterLink::Wifi(address) => TcpElm327::connect_with_config(address.as_str(), config)
+                .and_then(|mut d| identify(&mut d)),
+        };
+        match identifier {
+            Ok(identifier) => self.identifier = Some(identifier),
+            Err(e) => debug!("probe: no adapter on {}: {}", self.link, e),
+        }
+        self.responded()
+    }
+}
+
+fn identify<T: Elm327Transport>(device: &mut Elm327<T>) -> Result<String> {
+    device.at_command("ATI")
+}
+
+fn is_adapter_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    ADAPTER_NAMES.iter().any(|n| name.contains(n))
+}
+
+/// Find adapters on every kind of link, probe each one, and return them best first
+///
+/// This finds serial ports ([discover_serial]), paired Bluetooth adapters
+/// ([discover_bluetooth]) and a Wi-Fi adapter at the usual address ([discover_wifi]). Adapters
+/// that responded come first, then devices that look like adapters, then the rest. Probing each
+/// candidate takes up to a few seconds.
+///
+/// # Example
+/// ```no_run
+/// use obd2::{device::{self, AdapterLink, Elm327}, Obd2};
+///
+/// let candidates = device::discover();
+/// if let Some(AdapterLink::Serial(path)) = candidates.first().map(|c| &c.link) {
+///     let device = Obd2::new(Elm327::new(path).unwrap());
+/// }
+/// ```
+pub fn discover() -> Vec<AdapterCandidate> {
+    let mut candidates = discover_serial();
+    candidates.extend(discover_bluetooth());
+    candidates.extend(discover_wifi());
+    rank(&mut candidates);
+    candidates
+}
+
+/// Find serial ports, probe each one, and return them best first
+///
+/// USB ports are recognized as adapters from the vendor and product IDs of the FTDI, CH340,
+/// CP210x and PL2303 serial chips that adapters use, or a product name like `OBDLink`.
+pub fn discover_serial() -> Vec<AdapterCandidate> {
+    let ports = match serialport::available_ports() {
+        Ok(ports) => ports,
+        Err(e) => {
+            debug!("discover_serial: cannot list serial ports: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut candidates: Vec<_> = ports
+        .into_iter()
+        .map(|port| match port.port_type {
+            SerialPortType::UsbPort(usb) => AdapterCandidate::new(
+                AdapterLink::Serial(port.port_name),
+                usb.product,
+                ADAPTER_USB_IDS.contains(&(usb.vid, usb.pid)),
+            ),
+            _ => AdapterCandidate::new(AdapterLink::Serial(port.port_name), None, false),
+        })
+        .collect();
+    probe_all(&mut candidates);
+    candidates
+}
+
+/// Find paired classic Bluetooth adapters, probe each one, and return them best first
+///
+/// Only devices with a name like an adapter's (containing `OBD`, `ELM`, `Vgate` and so on) are
+/// returned. On Linux, paired devices are listed with `bluetoothctl`, and on macOS they are found
+/// from their serial devices. Other platforms find none.
+pub fn discover_bluetooth() -> Vec<AdapterCandidate> {
+    let mut candidates: Vec<_> = paired_devices()
+        .into_iter()
+        .filter(|(_, name)| is_adapter_name(name))
+        .map(|(device, name)| {
+            AdapterCandidate::new(AdapterLink::Bluetooth(device), Some(name), true)
+        })
+        .collect();
+    probe_all(&mut candidates);
+    candidates
+}
+
+/// Probe a Wi-Fi adapter at [DEFAULT_TCP_ADDRESS], and return it if it responded
+///
+/// The computer must already be connected to the adapter's Wi-Fi network.
+pub fn discover_wifi() -> Vec<AdapterCandidate> {
+    let mut candidate = AdapterCandidate::new(
+        AdapterLink::Wifi(DEFAULT_TCP_ADDRESS.to_owned()),
+        None,
+        true,
+    );
+    if candidate.probe() {
+        vec![candidate]
+    } else {
+        Vec::new()
+    }
+}
+
+fn probe_all(candidates: &mut [AdapterCandidate]) {
+    for candidate in candidates.iter_mut() {
+        candidate.probe();
+    }
+    rank(candidates);
+}
+
+/// Sort adapters that responded first, then devices that look like adapters
+fn rank(candidates: &mut [AdapterCandidate]) {
+    candidates.sort_by_key(|c| (!c.responded(), !c.known_adapter));
+}
+
+/// List the paired Bluetooth devices, with the value to connect to each and its name
+///
+/// `bluetoothctl` prints a line like "Device 00:1D:A5:68:98:8B OBDII" for each device.
+#[cfg(target_os = "linux")]
+fn paired_devices() -> Vec<(String, String)> {
+    use super::bluetooth::run_tool;
+    use std::process::Command;
+
+    let output = run_tool(Command::new("bluetoothctl").args(["devices", "Paired"]))
+        .or_else(|_| run_tool(Command::new("bluetoothctl").arg("paired-devices")));
+    match output {
+        Ok(output) => output
+            .lines()
+            .filter_map(|l| {
+                let (address, name) = l.trim().strip_prefix("Device ")?.split_once(' ')?;
+                Some((address.to_owned(), name.trim().to_owned()))
+            })
+            .collect(),
+        Err(e) => {
+            debug!("paired_devices: cannot list Bluetooth devices: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// List the paired Bluetooth devices, with the value to connect to each and its name
+///
+/// The system creates a `/dev/cu.<name>` device for each paired serial port service.
+#[cfg(target_os = "macos")]
+fn paired_devices() -> Vec<(String, String)> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|port| !matches!(port.port_type, SerialPortType::UsbPort(_)))
+        .filter_map(|port| {
+            let name = port.port_name.strip_prefix("/dev/cu.")?.to_owned();
+            Some((name.clone(), name))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn paired_devices() -> Vec<(String, String)> {
+    Vec::new()
+}
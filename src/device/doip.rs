@@ -0,0 +1,554 @@
+use log::{debug, info, trace};
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use super::{DeviceConfig, Error, PipelinedDevice, Result};
+use crate::{
+    interface::{
+        pending_error, response_header, single_response, strip_header, strip_tagged_header,
+    },
+    EcuResponse, Obd2Device, Protocol,
+};
+
+/// The UDP and TCP port DoIP entities listen on
+pub const DOIP_PORT: u16 = 13400;
+
+/// The logical address used by default for this tester, the first one for external test equipment
+pub const DEFAULT_TESTER_ADDRESS: u16 = 0x0E00;
+
+/// The logical address for functionally addressed (broadcast) OBD requests
+const FUNCTIONAL_ADDRESS: u16 = 0xE400;
+
+/// The DoIP protocol version sent, ISO 13400-2:2012
+const PROTOCOL_VERSION: u8 = 0x02;
+
+/// The protocol version for vehicle identification requests, which any DoIP entity accepts
+const DISCOVERY_PROTOCOL_VERSION: u8 = 0xFF;
+
+/// Length of the generic DoIP header: version, inverted version, payload type, payload length
+const HEADER_LENGTH: usize = 8;
+
+/// DoIP payload types
+mod payload {
+    pub const GENERIC_NACK: u16 = 0x0000;
+    pub const VEHICLE_IDENTIFICATION_REQUEST: u16 = 0x0001;
+    pub const VEHICLE_ANNOUNCEMENT: u16 = 0x0004;
+    pub const ROUTING_ACTIVATION_REQUEST: u16 = 0x0005;
+    pub const ROUTING_ACTIVATION_RESPONSE: u16 = 0x0006;
+    pub const ALIVE_CHECK_REQUEST: u16 = 0x0007;
+    pub const ALIVE_CHECK_RESPONSE: u16 = 0x0008;
+    pub const DIAGNOSTIC_MESSAGE: u16 = 0x8001;
+    pub const DIAGNOSTIC_MESSAGE_ACK: u16 = 0x8002;
+    pub const DIAGNOSTIC_MESSAGE_NACK: u16 = 0x8003;
+}
+
+/// Routing activation response code for a successful activation
+const ROUTING_ACTIVATED: u8 = 0x10;
+
+/// How long to wait for the first response to a request (A_DoIP_Diagnostic_Message)
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to keep waiting for other ECUs after one responds to a functional request
+const FUNCTIONAL_RESPONSE_WAIT: Duration = Duration::from_millis(200);
+
+/// How long to wait for an ECU that responded that its response is pending (P2* in ISO 14229-2)
+const RESPONSE_PENDING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A DoIP entity that responded to [DoIpDevice::discover]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct VehicleAnnouncement {
+    /// The IP address of the entity, to pass to [DoIpDevice::connect]
+    pub ip: IpAddr,
+    /// The VIN of the vehicle
+    pub vin: String,
+    /// The logical address of the entity, usually the vehicle's gateway
+    pub logical_address: u16,
+    /// The entity ID, usually its MAC address
+    pub eid: [u8; 6],
+    /// The group ID, which is the same for the entities of one vehicle
+    pub gid: [u8; 6],
+}
+
+/// An OBD-II interface using Diagnostics over IP (DoIP, ISO 13400)
+///
+/// Vehicles with an automotive Ethernet diagnostic port (and many EVs) accept UDS and OBD-II
+/// requests over TCP, with no CAN adapter needed. Find vehicles on the network with
+/// [discover](Self::discover), then [connect](Self::connect) to one, which activates routing so
+/// that requests are forwarded to the ECUs.
+///
+/// Requests from [Obd2Device] are sent to the functional OBD address, so every emissions related
+/// ECU responds. The addresses for [obd_request_to](Obd2Device::obd_request_to) and in
+/// [EcuResponse] are DoIP logical addresses (like `0x0010`) instead of CAN identifiers.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::DoIpDevice};
+/// use std::{
+///     io::{Read, Write},
+///     net::TcpListener,
+///     thread,
+/// };
+///
+/// fn message(payload_type: u16, payload: &[u8]) -> Vec<u8> {
+///     let mut message = vec![0x02, 0xFD];
+///     message.extend(payload_type.to_be_bytes());
+///     message.extend((payload.len() as u32).to_be_bytes());
+///     message.extend(payload);
+///     message
+/// }
+///
+/// // a DoIP entity with logical address 0x1000, whose engine ECU (0x0010) runs at 1000 RPM
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let address = listener.local_addr().unwrap();
+/// thread::spawn(move || {
+///     let (mut stream, _) = listener.accept().unwrap();
+///     let mut buf = [0; 64];
+///     stream.read(&mut buf).unwrap(); // routing activation request
+///     let response = [0x0E, 0x00, 0x10, 0x00, 0x10, 0, 0, 0, 0];
+///     stream.write_all(&message(0x0006, &response)).unwrap();
+///
+///     stream.read(&mut buf).unwrap(); // request for RPM, sent to every ECU
+///     stream.write_all(&message(0x8002, &[0xE4, 0x00, 0x0E, 0x00, 0x00])).unwrap();
+///     let response = [0x00, 0x10, 0x0E, 0x00, 0x41, 0x0C, 0x0F, 0xA0];
+///     stream.write_all(&message(0x8001, &response)).unwrap();
+///     stream.read(&mut buf).unwrap(); // wait for the tester to disconnect
+/// });
+///
+/// let mut device = DoIpDevice::connect(address).unwrap();
+/// assert_eq!(device.entity_address(), 0x1000);
+/// assert_eq!(device.get_rpm().unwrap(), [1000.]);
+/// ```
+pub struct DoIpDevice {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    tester_address: u16,
+    entity_address: u16,
+    config: DeviceConfig,
+}
+
+impl DoIpDevice {
+    /// Find DoIP entities on the local network, waiting `timeout` for them to respond
+    ///
+    /// A vehicle identification request is broadcast over UDP, and each entity that responds is
+    /// returned.
+    pub fn discover(timeout: Duration) -> Result<Vec<VehicleAnnouncement>> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_broadcast(true)?;
+        socket.send_to(
+            &encode(
+                DISCOVERY_PROTOCOL_VERSION,
+                payload::VEHICLE_IDENTIFICATION_REQUEST,
+                &[],
+            ),
+            (Ipv4Addr::BROADCAST, DOIP_PORT),
+        )?;
+
+        let mut announcements = Vec::new();
+        let deadline = Instant::now() + timeout;
+        let mut buf = [0; 1024];
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            socket.set_read_timeout(Some(remaining.max(Duration::from_millis(1))))?;
+            let (len, from) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if is_timeout(&e) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if let Some((payload::VEHICLE_ANNOUNCEMENT, data)) = decode(&buf[..len]) {
+                match parse_announcement(from.ip(), data) {
+                    Some(announcement) => {
+                        info!("Found DoIP entity {:?}", announcement);
+                        announcements.push(announcement);
+                    }
+                    None => debug!("discover: bad announcement from {}: {:02X?}", from, data),
+                }
+            }
+        }
+        Ok(announcements)
+    }
+
+    /// Connect to the DoIP entity at `address` and activate routing, with the
+    /// [DEFAULT_TESTER_ADDRESS]
+    ///
+    /// If `address` has no port, like an [IpAddr], use `(ip, DOIP_PORT)`.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self> {
+        Self::connect_as(address, DEFAULT_TESTER_ADDRESS)
+    }
+
+    /// Connect to the DoIP entity at `address` and activate routing, using the logical address
+    /// `tester_address` for this tester
+    pub fn connect_as(address: impl ToSocketAddrs, tester_address: u16) -> Result<Self> {
+        Self::connect_with_config(address, tester_address, DeviceConfig::default())
+    }
+
+    /// Connect to the DoIP entity at `address` and activate routing, using the logical address
+    /// `tester_address` and the timeouts and retries in `config`
+    ///
+    /// Both the connect timeout and the command timeout (how long to wait for the first response
+    /// to a request) are two seconds by default. A request is only retried when the entity did not
+    /// even acknowledge it.
+    pub fn connect_with_config(
+        address: impl ToSocketAddrs,
+        tester_address: u16,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let address: SocketAddr = address.to_socket_addrs()?.next().ok_or_else(|| {
+            Error::Communication("DoIpDevice::connect: no address to connect to".to_owned())
+        })?;
+        info!("Connecting to DoIP entity at {}", address);
+        let stream = TcpStream::connect_timeout(
+            &address,
+            config.connect_timeout().unwrap_or(RESPONSE_TIMEOUT),
+        )?;
+        stream.set_nodelay(true)?;
+
+        let mut device = DoIpDevice {
+            stream,
+            buffer: Vec::new(),
+            tester_address,
+            entity_address: 0,
+            config,
+        };
+        device.activate_routing()?;
+        Ok(device)
+    }
+
+    /// Get the logical address of the DoIP entity, from routing activation
+    pub fn entity_address(&self) -> u16 {
+        self.entity_address
+    }
+
+    /// Get the logical address of this tester
+    pub fn tester_address(&self) -> u16 {
+        self.tester_address
+    }
+
+    fn activate_routing(&mut self) -> Result<()> {
+        let [high, low] = self.tester_address.to_be_bytes();
+        // default activation type, then the reserved bytes
+        self.send(
+            payload::ROUTING_ACTIVATION_REQUEST,
+            &[high, low, 0, 0, 0, 0, 0],
+        )?;
+
+        let deadline = Instant::now() + self.response_timeout();
+        loop {
+            match self.receive(deadline)? {
+                Some((payload::ROUTING_ACTIVATION_RESPONSE, data)) => {
+                    return match *data.as_slice() {
+                        [_, _, entity_high, entity_low, ROUTING_ACTIVATED, ..] => {
+                            self.entity_address = u16::from_be_bytes([entity_high, entity_low]);
+                            info!("Routing activated by entity {:04X}", self.entity_address);
+                            Ok(())
+                        }
+                        [_, _, _, _, code, ..] => Err(Error::Communication(format!(
+                            "activate_routing: routing activation denied with code {:02X}",
+                            code
+                        ))),
+                        _ => Err(Error::Communication(format!(
+                            "activate_routing: bad routing activation response {:02X?}",
+                            data
+                        ))),
+                    };
+                }
+                Some(_) => (),
+                None => {
+                    return Err(Error::Communication(
+                        "activate_routing: no routing activation response".to_owned(),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, payload_type: u16, data: &[u8]) -> Result<()> {
+        trace!("send: payload type {:04X}: {:02X?}", payload_type, data);
+        self.stream
+            .write_all(&encode(PROTOCOL_VERSION, payload_type, data))?;
+        Ok(())
+    }
+
+    /// Receive the next message, or `None` if none is received before `deadline`
+    ///
+    /// Alive checks from the entity are answered here, and generic negative acknowledgements are
+    /// returned as errors.
+    fn receive(&mut self, deadline: Instant) -> Result<Option<(u16, Vec<u8>)>> {
+        loop {
+            if let Some(total) = message_length(&self.buffer) {
+                let message: Vec<u8> = self.buffer.drain(..total).collect();
+                let payload_type = u16::from_be_bytes([message[2], message[3]]);
+                let data = message[HEADER_LENGTH..].to_vec();
+                trace!("receive: payload type {:04X}: {:02X?}", payload_type, data);
+                match payload_type {
+                    payload::ALIVE_CHECK_REQUEST => {
+                        let address = self.tester_address.to_be_bytes();
+                        self.send(payload::ALIVE_CHECK_RESPONSE, &address)?;
+                        continue;
+                    }
+                    payload::GENERIC_NACK => {
+                        return Err(Error::Communication(format!(
+                            "receive: DoIP entity rejected message with code {:02X?}",
+                            data.first()
+                        )))
+                    }
+                    _ => return Ok(Some((payload_type, data))),
+                }
+            }
+
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                return Ok(None);
+            };
+            self.stream
+                .set_read_timeout(Some(remaining.max(Duration::from_millis(1))))?;
+            let mut buf = [0; 4096];
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    return Err(Error::Disconnected(
+                        "receive: DoIP entity closed the connection".to_owned(),
+                    ))
+                }
+                Ok(len) => self.buffer.extend_from_slice(&buf[..len]),
+                Err(e) if is_timeout(&e) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Send `request` to `target` in a diagnostic message, from this tester
+    fn send_diagnostic_message(&mut self, target: u16, request: &[u8]) -> Result<()> {
+        let mut message = Vec::with_capacity(4 + request.len());
+        message.extend(self.tester_address.to_be_bytes());
+        message.extend(target.to_be_bytes());
+        message.extend_from_slice(request);
+        self.send(payload::DIAGNOSTIC_MESSAGE, &message)
+    }
+
+    /// How long to wait for the first response to a request
+    fn response_timeout(&self) -> Duration {
+        self.config.command_timeout().unwrap_or(RESPONSE_TIMEOUT)
+    }
+
+    /// Like [request_once](Self::request_once), sending the request again while the entity does
+    /// not acknowledge it
+    fn request(&mut self, target: u16, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        for attempt in 0..=self.config.retries() {
+            if attempt > 0 {
+                let delay = self.config.retry_delay(attempt - 1);
+                debug!("request: timed out, retrying in {:?}", delay);
+                std::thread::sleep(delay);
+            }
+            match self.request_once(target, request) {
+                Err(crate::Error::Timeout) => continue,
+                result => return result,
+            }
+        }
+        Err(crate::Error::Timeout)
+    }
+
+    /// Send a diagnostic message to `target` and collect each ECU's response
+    ///
+    /// For a functional request, responses are collected until no ECU has responded for a short
+    /// time. For a physical request, the first response is returned. While an ECU has responded
+    /// that its response is pending (`7F <service> 78`), the wait is extended. If the entity sends
+    /// nothing at all, not even an acknowledgement, the error is [Timeout](crate::Error::Timeout).
+    fn request_once(&mut self, target: u16, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        self.send_diagnostic_message(target, request)?;
+
+        let functional = target == FUNCTIONAL_ADDRESS;
+        let mut responses = Vec::new();
+        let mut pending = false;
+        let mut acknowledged = false;
+        let mut deadline = Instant::now() + self.response_timeout();
+        while let Some((payload_type, data)) = self.receive(deadline)? {
+            acknowledged = true;
+            match (payload_type, data.as_slice()) {
+                (
+                    payload::DIAGNOSTIC_MESSAGE,
+                    &[source_high, source_low, _, _, ref message @ ..],
+                ) => {
+                    let source = u16::from_be_bytes([source_high, source_low]);
+                    debug!("request: ECU {:04X} responded {:02X?}", source, message);
+                    if matches!(message, [0x7F, _, 0x78, ..]) {
+                        pending = true;
+                        deadline = Instant::now() + RESPONSE_PENDING_TIMEOUT;
+                        continue;
+                    }
+                    pending = false;
+                    responses.push(EcuResponse {
+                        address: source.into(),
+                        data: message.to_vec(),
+                    });
+                    if !functional {
+                        break;
+                    }
+                    deadline = Instant::now() + FUNCTIONAL_RESPONSE_WAIT;
+                }
+                (payload::DIAGNOSTIC_MESSAGE_ACK, _) => (),
+                (payload::DIAGNOSTIC_MESSAGE_NACK, &[_, _, _, _, code, ..]) => {
+                    return Err(Error::Communication(format!(
+                        "request: diagnostic message to {:04X} rejected with code {:02X}",
+                        target, code
+                    ))
+                    .into())
+                }
+                _ => debug!(
+                    "request: ignoring payload type {:04X}: {:02X?}",
+                    payload_type, data
+                ),
+            }
+        }
+
+        if !acknowledged {
+            Err(crate::Error::Timeout)
+        } else if pending && responses.is_empty() {
+            Err(pending_error(request))
+        } else if responses.is_empty() {
+            Err(crate::Error::NoData)
+        } else {
+            Ok(responses)
+        }
+    }
+
+    fn request_untagged(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(self
+            .request(FUNCTIONAL_ADDRESS, request)?
+            .into_iter()
+            .map(|r| r.data)
+            .collect())
+    }
+}
+
+/// Requests are sent to the functional address, so every ECU behind the entity receives them
+impl PipelinedDevice for DoIpDevice {
+    fn send_request(&mut self, request: &[u8]) -> crate::Result<()> {
+        Ok(self.send_diagnostic_message(FUNCTIONAL_ADDRESS, request)?)
+    }
+
+    fn receive_response(&mut self, deadline: Instant) -> crate::Result<Option<EcuResponse>> {
+        while let Some((payload_type, data)) = self.receive(deadline)? {
+            match (payload_type, data.as_slice()) {
+                (
+                    payload::DIAGNOSTIC_MESSAGE,
+                    &[source_high, source_low, _, _, ref message @ ..],
+                ) => {
+                    return Ok(Some(EcuResponse {
+                        address: u16::from_be_bytes([source_high, source_low]).into(),
+                        data: message.to_vec(),
+                    }))
+                }
+                (payload::DIAGNOSTIC_MESSAGE_NACK, &[_, _, _, _, code, ..]) => {
+                    return Err(Error::Communication(format!(
+                        "receive_response: diagnostic message rejected with code {:02X}",
+                        code
+                    ))
+                    .into())
+                }
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    fn first_response_timeout(&self) -> Duration {
+        self.response_timeout()
+    }
+
+    fn next_response_timeout(&self) -> Duration {
+        FUNCTIONAL_RESPONSE_WAIT
+    }
+}
+
+impl Obd2Device for DoIpDevice {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[mode, pid])?;
+        strip_header(responses, &[0x40 | mode, pid])
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[mode])?;
+        strip_header(responses, &[0x40 | mode])
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(request)?;
+        strip_header(responses, &response_header(request))
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        let responses = self.request(FUNCTIONAL_ADDRESS, request)?;
+        strip_tagged_header(responses, request)
+    }
+
+    /// `address` is the DoIP logical address of the ECU
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> crate::Result<Vec<u8>> {
+        let target = u16::try_from(address).map_err(|_| crate::Error::InvalidArgument {
+            name: "DoIP logical address",
+            value: address,
+        })?;
+        let responses = self
+            .request(target, request)?
+            .into_iter()
+            .map(|r| r.data)
+            .collect();
+        single_response(strip_header(responses, &response_header(request))?)
+    }
+
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[&[mode], pids].concat())?;
+        strip_header(responses, &[0x40 | mode])
+    }
+
+    fn protocol(&mut self) -> crate::Result<Protocol> {
+        Ok(Protocol::DoIp)
+    }
+}
+
+/// Build a DoIP message
+fn encode(version: u8, payload_type: u16, data: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LENGTH + data.len());
+    message.extend([version, !version]);
+    message.extend(payload_type.to_be_bytes());
+    message.extend((data.len() as u32).to_be_bytes());
+    message.extend_from_slice(data);
+    message
+}
+
+/// Get the payload type and data of a DoIP message, if it is one
+fn decode(message: &[u8]) -> Option<(u16, &[u8])> {
+    let total = message_length(message)?;
+    let payload_type = u16::from_be_bytes([message[2], message[3]]);
+    Some((payload_type, &message[HEADER_LENGTH..total]))
+}
+
+/// Get the length of the message at the start of `buffer`, if all of it has been received
+fn message_length(buffer: &[u8]) -> Option<usize> {
+    let header = buffer.get(..HEADER_LENGTH)?;
+    let length = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    let total = HEADER_LENGTH.checked_add(usize::try_from(length).ok()?)?;
+    (buffer.len() >= total).then_some(total)
+}
+
+/// Parse a vehicle announcement (or vehicle identification response) from `ip`
+fn parse_announcement(ip: IpAddr, data: &[u8]) -> Option<VehicleAnnouncement> {
+    let vin = data.get(..17)?;
+    let logical_address = data.get(17..19)?;
+    Some(VehicleAnnouncement {
+        ip,
+        vin: String::from_utf8_lossy(vin).into_owned(),
+        logical_address: u16::from_be_bytes([logical_address[0], logical_address[1]]),
+        eid: data.get(19..25)?.try_into().ok()?,
+        gid: data.get(25..31)?.try_into().ok()?,
+    })
+}
+
+/// Whether `error` is a read timing out, which is reported differently on different platforms
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
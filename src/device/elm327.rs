@@ -1,12 +1,51 @@
 use log::{debug, info, trace};
-use serial_rs::{posix::TTYPort, FlowControl, SerialPort, SerialPortSettings};
+use serialport::{FlowControl, SerialPort};
 use std::{
-    collections::VecDeque,
-    io::{Read, Write},
+    io::{self, Read, Write},
     thread, time,
 };
 
-use super::{Error, Obd2BaseDevice, Obd2Reader, Result};
+use super::{
+    adapter::{at_response, PROBED_COMMANDS},
+    at::{encode_hex, parse_protocol_number, parse_voltage, select_protocol_command},
+    elm327_core::{check_echo, Elm327Core},
+    raw_can::{matches_any, parse_monitor_line},
+    stn::{elm327_filter_commands, stn_filter_commands, stpx_command},
+    AdapterInfo, CanFilter, CanFrame, DeviceConfig, Error, Obd2BaseDevice, Obd2Reader,
+    RawCanAccess, Result, StnExtensions,
+};
+use crate::Protocol;
+
+/// A bidirectional byte stream that an [Elm327] can communicate over
+///
+/// This is implemented for serial ports, and allows the same ELM327 protocol handling to be used
+/// for adapters connected in other ways, like over Bluetooth.
+pub trait Elm327Transport: Read + Write {
+    /// Change the baud rate of the link to the adapter
+    ///
+    /// Links without a baud rate, like network and wireless connections, keep this default,
+    /// which returns an error.
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> Result<()> {
+        Err(Error::Communication(
+            "set_baud_rate: transport does not have a baud rate".to_owned(),
+        ))
+    }
+
+    /// Whether the link to the adapter was reopened since this was last called
+    ///
+    /// Reopening the link can reset the adapter, so the [Elm327] initializes it again before its
+    /// next request. Transports that never reopen the link return `false`.
+    fn take_reconnected(&mut self) -> bool {
+        false
+    }
+}
+
+impl Elm327Transport for Box<dyn SerialPort> {
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<()> {
+        SerialPort::set_baud_rate(self.as_mut(), baud_rate)?;
+        Ok(())
+    }
+}
 
 /// An ELM327 OBD-II adapter
 ///
@@ -17,13 +56,16 @@ use super::{Error, Obd2BaseDevice, Obd2Reader, Result};
 ///
 /// [Datasheet for v1.4b](https://github.com/rsammelson/obd2/blob/master/docs/ELM327DSH.pdf), and
 /// the [source](https://www.elmelectronics.com/products/dsheets/).
-pub struct Elm327 {
-    device: TTYPort,
-    buffer: VecDeque<u8>,
+///
+/// By default the adapter is connected over a serial port, but any [Elm327Transport] can be used.
+pub struct Elm327<T: Elm327Transport = Box<dyn SerialPort>> {
+    device: T,
+    core: Elm327Core,
     baud_rate: u32,
+    stn: Option<Option<String>>,
 }
 
-impl Obd2BaseDevice for Elm327 {
+impl<T: Elm327Transport> Obd2BaseDevice for Elm327<T> {
     fn reset(&mut self) -> Result<()> {
         self.flush_buffers()?;
         self.reset_ic()?;
@@ -34,16 +76,67 @@ impl Obd2BaseDevice for Elm327 {
 
     fn send_cmd(&mut self, data: &[u8]) -> Result<()> {
         trace!("send_cmd: sending {:?}", std::str::from_utf8(data));
-        self.send_serial_str(
-            data.into_iter()
-                .flat_map(|v| format!("{:02X}", v).chars().collect::<Vec<char>>())
-                .collect::<String>()
-                .as_str(),
-        )
+        self.send_serial_str(&encode_hex(data))
+    }
+
+    /// Broadcast the command, first undoing any settings left by requests to a single ECU
+    fn cmd(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.broadcast())?;
+        self.request(cmd)
+    }
+
+    /// Read the reply straight into `response`, without copying it
+    fn cmd_into(&mut self, cmd: &[u8], response: &mut Vec<u8>) -> Result<bool> {
+        self.apply_settings(self.core.settings.broadcast())?;
+        self.send_cmd(cmd)?;
+        Ok(self.read_until(b'>', true, response)? && std::str::from_utf8(response).is_ok())
+    }
+
+    /// Turn on headers (`ATH1`) for the command
+    ///
+    /// Headers stay on until a request without them, so repeated requests only send the command.
+    fn cmd_with_headers(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.broadcast().with_headers())?;
+        self.request(cmd)
+    }
+
+    /// Set the header of the request (`ATSH`) to `address` for the command, and on CAN only
+    /// receive responses from the ECU's identifier (`ATCRA`)
+    ///
+    /// The settings stay until a request needs different ones, so repeated requests to the same
+    /// ECU only send the command.
+    fn cmd_to(&mut self, address: u32, cmd: &[u8]) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.to(address))?;
+        self.request(cmd)
+    }
+
+    /// Select the SAE J1939 protocol (`ATSPA`) for the request, which stays selected until a
+    /// request needs the protocol the adapter was configured with
+    ///
+    /// Given the PGN with its least significant byte first, the adapter sends the request message
+    /// itself.
+    fn cmd_j1939(&mut self, pgn: u32) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.broadcast().with_j1939())?;
+        let [low, middle, high, _] = pgn.to_le_bytes();
+        self.request(&[low, middle, high])
+    }
+
+    /// Ask the adapter which protocol it is using (`ATDPN`)
+    fn protocol(&mut self) -> Result<Protocol> {
+        parse_protocol_number(self.serial_cmd("ATDPN")?)
+    }
+
+    fn config(&self) -> DeviceConfig {
+        self.core.config
+    }
+
+    /// Read the voltage of the adapter's supply pin (`ATRV`)
+    fn adapter_voltage(&mut self) -> Result<f32> {
+        parse_voltage(self.serial_cmd("ATRV")?)
     }
 }
 
-impl Obd2Reader for Elm327 {
+impl<T: Elm327Transport> Obd2Reader for Elm327<T> {
     fn get_line(&mut self) -> Result<Option<Vec<u8>>> {
         self.get_until(b'\n', false)
     }
@@ -59,42 +152,367 @@ impl Obd2Reader for Elm327 {
     }
 }
 
-fn new_ttyport(path: impl Into<String>, baud: u32) -> Result<TTYPort> {
-    TTYPort::new(
-        path.into(),
-        Some(
-            SerialPortSettings::default()
-                .baud(baud)
-                .read_timeout(Some(100))
-                .write_timeout(Some(100))
-                .set_flow_control(FlowControl::None),
-        ),
-    )
-    .map_err(Into::into)
+/// Frames are sent by setting the header to their identifier with the adapter's CAN formatting
+/// and responses turned off (`ATCAF0`, `ATR0`), and received by monitoring the bus (`ATMA`) with
+/// headers on. This also works with STN adapters. The adapter's settings are restored afterwards,
+/// even if sending or receiving fails.
+///
+/// The filters are applied by the adapter (see
+/// [set_pass_filters](StnExtensions::set_pass_filters)), so it is not flooded by the rest of the
+/// traffic on a busy bus. An ELM327 has a single filter, so with several filters the frames it
+/// lets through are checked against them again here.
+impl<T: Elm327Transport> RawCanAccess for Elm327<T> {
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<()> {
+        self.apply_settings(self.core.settings.broadcast().with_header(frame.id))?;
+        let response = self
+            .at_commands(&["ATCAF0", "ATR0"])
+            .and_then(|()| self.request(&frame.data));
+        let restored = self.at_commands(&["ATR1", "ATCAF1"]);
+        response?;
+        restored
+    }
+
+    fn receive_frames(
+        &mut self,
+        filters: &[CanFilter],
+        duration: time::Duration,
+    ) -> Result<Vec<CanFrame>> {
+        self.apply_settings(self.core.settings.broadcast().with_headers())?;
+        let stn = self.stn_identifier()?.is_some();
+
+        let output = self.monitor(filters, duration, stn);
+        let mut restored = self.at_commands(&["ATCAF1"]);
+        if !filters.is_empty() {
+            restored = restored.and(self.set_pass_filters(&[]));
+        }
+        let output = output?;
+        restored?;
+
+        // the ELM327's single filter can let through frames that none of the filters match
+        let exact = stn || filters.len() < 2;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|line| {
+                let frame = parse_monitor_line(line);
+                if frame.is_none() {
+                    trace!("receive_frames: skipping {:?}", line);
+                }
+                frame
+            })
+            .filter(|frame| exact || matches_any(filters, frame.id))
+            .collect())
+    }
+}
+
+impl<T: Elm327Transport> StnExtensions for Elm327<T> {
+    /// The adapter is only asked once, and the answer is remembered
+    fn stn_identifier(&mut self) -> Result<Option<String>> {
+        if let Some(identifier) = &self.stn {
+            return Ok(identifier.clone());
+        }
+        let response = self.serial_cmd("STI")?;
+        let identifier = at_response("STI", response).ok();
+        info!("STN identifier: {:?}", identifier);
+        self.stn = Some(identifier.clone());
+        Ok(identifier)
+    }
+
+    fn cmd_with_response_count(&mut self, cmd: &[u8], responses: u8) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.broadcast())?;
+        let command = if self.stn_identifier()?.is_some() {
+            stpx_command(cmd, responses)
+        } else if (1..=0xF).contains(&responses) {
+            format!("{}{:X}", encode_hex(cmd), responses)
+        } else {
+            return self.request(cmd);
+        };
+        self.send_serial_str(&command)?;
+        self.get_response()
+            .map(|o| o.and_then(|resp| String::from_utf8(resp).ok()))
+    }
+
+    fn set_pass_filters(&mut self, filters: &[CanFilter]) -> Result<()> {
+        let commands = if self.stn_identifier()?.is_some() {
+            stn_filter_commands(filters)
+        } else {
+            elm327_filter_commands(filters)
+        };
+        for command in commands {
+            at_response(&command, self.serial_cmd(&command)?)?;
+        }
+        // the ELM327's filter replaces the one set for requests to a single ECU
+        self.core.settings.receive_address = None;
+        Ok(())
+    }
+}
+
+/// The AT commands that set the CAN identifier of requests to `address`
+///
+/// 11-bit identifiers are set with `ATSH`. For 29-bit identifiers, the top byte is the CAN
+/// priority (`ATCP`) and the rest is set with `ATSH`.
+pub(super) fn header_commands(address: u32) -> Vec<String> {
+    if address <= 0x7FF {
+        vec![format!("ATSH{:03X}", address)]
+    } else {
+        vec![
+            format!("ATCP{:02X}", address >> 24),
+            format!("ATSH{:06X}", address & 0xFF_FFFF),
+        ]
+    }
+}
+
+/// The functional (broadcast) request address with the same identifier length as `address`
+pub(super) fn functional_address(address: u32) -> u32 {
+    if address <= 0x7FF {
+        0x7DF
+    } else {
+        0x18DB33F1
+    }
+}
+
+/// The CAN identifier an ECU responds from, given its physical request `address`
+///
+/// 11-bit requests to `0x7E0` to `0x7E7` are answered from 8 higher, and 29-bit requests like
+/// `0x18DA10F1` from the same identifier with the addresses swapped, like `0x18DAF110`.
+fn response_address(address: u32) -> Option<u32> {
+    match address {
+        0x7E0..=0x7E7 => Some(address + 8),
+        _ if address >> 16 == 0x18DA => {
+            Some(0x18DA_0000 | (address & 0xFF) << 8 | (address >> 8) & 0xFF)
+        }
+        _ => None,
+    }
+}
+
+/// The adapter settings that requests change
+///
+/// The adapter keeps them until it is reset, so the current settings are remembered and commands
+/// are only sent for the ones a request needs changed. Each command is a round trip to the
+/// adapter, so sending them for every request can halve how fast a cheap adapter can poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) struct RequestSettings {
+    /// Whether responses start with their header (`ATH1`)
+    headers: bool,
+    /// The identifier requests are sent to (`ATSH`), or `None` for the adapter's default
+    header: Option<u32>,
+    /// The only CAN identifier responses are received from (`ATCRA`), or `None` for any
+    receive_address: Option<u32>,
+    /// Whether SAE J1939 is selected (`ATSPA`) instead of the configured protocol
+    pub(super) j1939: bool,
+}
+
+impl RequestSettings {
+    /// The settings for a request broadcast to every ECU, changed from `self`
+    ///
+    /// A header that was changed is set to the functional address with the same length.
+    pub(super) fn broadcast(self) -> Self {
+        RequestSettings {
+            headers: false,
+            header: self.header.map(functional_address),
+            receive_address: None,
+            j1939: false,
+        }
+    }
+
+    /// The settings for a request to the ECU with the physical request `address`, which only
+    /// receive responses from that ECU on CAN
+    pub(super) fn to(self, address: u32) -> Self {
+        RequestSettings {
+            header: Some(address),
+            receive_address: response_address(address),
+            ..self.broadcast()
+        }
+    }
+
+    /// These settings, with the header of each response shown
+    pub(super) fn with_headers(self) -> Self {
+        RequestSettings {
+            headers: true,
+            ..self
+        }
+    }
+
+    /// These settings, with SAE J1939 selected
+    pub(super) fn with_j1939(self) -> Self {
+        RequestSettings {
+            j1939: true,
+            ..self
+        }
+    }
+
+    /// These settings, with requests sent to `header`
+    pub(super) fn with_header(self, header: u32) -> Self {
+        RequestSettings {
+            header: Some(header),
+            ..self
+        }
+    }
+
+    /// The AT commands changing the adapter from these settings to `wanted`
+    ///
+    /// `protocol` is the protocol the adapter was configured with, which is selected again when
+    /// leaving J1939.
+    pub(super) fn commands(&self, wanted: &Self, protocol: Option<Protocol>) -> Vec<String> {
+        let mut commands = Vec::new();
+        if wanted.j1939 != self.j1939 {
+            commands.push(if wanted.j1939 {
+                "ATSPA".to_owned()
+            } else {
+                select_protocol_command(protocol)
+            });
+        }
+        if let Some(header) = wanted.header.filter(|_| wanted.header != self.header) {
+            commands.extend(header_commands(header));
+        }
+        if wanted.receive_address != self.receive_address {
+            commands.push(match wanted.receive_address {
+                Some(address) if address <= 0x7FF => format!("ATCRA{:03X}", address),
+                Some(address) => format!("ATCRA{:08X}", address),
+                None => "ATCRA".to_owned(),
+            });
+        }
+        if wanted.headers != self.headers {
+            commands.push(if wanted.headers { "ATH1" } else { "ATH0" }.to_owned());
+        }
+        commands
+    }
+}
+
+pub(super) fn new_serial_port(path: &str, baud: u32) -> Result<Box<dyn SerialPort>> {
+    serialport::new(path, baud)
+        .timeout(time::Duration::from_millis(100))
+        .flow_control(FlowControl::None)
+        .open()
+        .map_err(Into::into)
 }
 
 impl Elm327 {
-    /// Create a [`Elm327`] object
-    pub fn new(dev_path: impl Into<String>) -> Result<Self> {
-        let serial_interface = new_ttyport(dev_path, 38400)?;
+    /// Connect to the ELM327 on the serial port at `dev_path`, like `/dev/ttyUSB0`
+    pub fn new(dev_path: impl AsRef<str>) -> Result<Self> {
+        let serial_interface = new_serial_port(dev_path.as_ref(), 38400)?;
+        Elm327::with_transport(serial_interface, 38400)
+    }
+
+    /// Create a [`Elm327`] object that always uses `protocol`, instead of the adapter finding the
+    /// vehicle's protocol automatically
+    pub fn with_protocol(dev_path: impl AsRef<str>, protocol: Protocol) -> Result<Self> {
+        let serial_interface = new_serial_port(dev_path.as_ref(), 38400)?;
+        Elm327::with_transport_and_protocol(serial_interface, 38400, Some(protocol))
+    }
+
+    /// Create a [`Elm327`] object with the timeouts and retries in `config`
+    pub fn with_config(dev_path: impl AsRef<str>, config: DeviceConfig) -> Result<Self> {
+        let serial_interface = new_serial_port(dev_path.as_ref(), 38400)?;
+        Elm327::with_transport_and_config(serial_interface, 38400, None, config)
+    }
+}
+
+impl<T: Elm327Transport> Elm327<T> {
+    /// Create a [`Elm327`] object that communicates over an already opened transport
+    ///
+    /// The `baud_rate` is the rate the transport is currently using, if it has one.
+    pub fn with_transport(transport: T, baud_rate: u32) -> Result<Self> {
+        Self::with_transport_and_protocol(transport, baud_rate, None)
+    }
+
+    /// Create a [`Elm327`] object that communicates over an already opened transport, and uses
+    /// `protocol` if it is given
+    ///
+    /// See [with_transport](Self::with_transport) and [set_protocol](Self::set_protocol).
+    pub fn with_transport_and_protocol(
+        transport: T,
+        baud_rate: u32,
+        protocol: Option<Protocol>,
+    ) -> Result<Self> {
+        Self::with_transport_and_config(transport, baud_rate, protocol, DeviceConfig::default())
+    }
 
+    /// Create a [`Elm327`] object that communicates over an already opened transport, uses
+    /// `protocol` if it is given, and has the timeouts and retries in `config`
+    ///
+    /// The command timeout is how long to wait for the adapter to finish responding, which is 5
+    /// seconds by default. See [with_transport_and_protocol](Self::with_transport_and_protocol).
+    pub fn with_transport_and_config(
+        transport: T,
+        baud_rate: u32,
+        protocol: Option<Protocol>,
+        config: DeviceConfig,
+    ) -> Result<Self> {
         let mut device = Elm327 {
-            device: serial_interface,
-            buffer: VecDeque::new(),
-            baud_rate: 38400,
+            device: transport,
+            core: Elm327Core::new(protocol, config)?,
+            baud_rate,
+            stn: None,
         };
 
-        device.connect(false)?;
+        device.initialize(false)?;
         device.flush()?;
 
         Ok(device)
     }
 
+    /// Always use `protocol` to communicate with the vehicle, or find it automatically if `None`
+    ///
+    /// Automatic detection tries each protocol in turn, which takes several seconds and can pick
+    /// the wrong one on some vehicles. The protocol stays selected when the device is reset.
+    pub fn set_protocol(&mut self, protocol: Option<Protocol>) -> Result<()> {
+        self.core.set_protocol(protocol)?;
+        self.reset_protocol()
+    }
+
+    /// Change the timeouts and retries
+    pub fn set_config(&mut self, config: DeviceConfig) -> Result<()> {
+        self.core.config = config;
+        let at = config.adaptive_timing().elm327_command();
+        debug!("set_config: {} got {:?}", at, self.serial_cmd(at)?);
+        Ok(())
+    }
+
+    /// Send the AT command `command`, like `ATSP6` or `ATRV`, to the adapter and get its response
+    ///
+    /// The command is sent as it is, so commands for other chips, like the `ST` commands of STN
+    /// chips, work too. The response is returned without the prompt, and a `?` from the adapter (an
+    /// unknown or invalid command) is an error. Commands that change how responses are formatted,
+    /// like `ATH1`, `ATS0`, `ATE0` or `ATL1`, break the parsing of OBD-II responses. The request
+    /// header and receive filter are remembered between requests, so changing them here (with
+    /// `ATSH` or `ATCRA`) can send later requests to the wrong ECU.
+    pub fn at_command(&mut self, command: &str) -> Result<String> {
+        let response = self.serial_cmd(command.trim())?;
+        self.core.at_command_sent(command);
+        at_response(command, response)
+    }
+
+    /// Find out what the adapter is, and which extended commands it supports
+    ///
+    /// This asks for the identifier (`ATI`) and the description (`AT@1`), then tries several
+    /// commands that only print information, to find clones that are missing commands from the
+    /// version they report.
+    pub fn adapter_info(&mut self) -> Result<AdapterInfo> {
+        let identifier = self.at_command("ATI")?;
+        let description = self.at_command("AT@1").ok();
+        let mut supported = Vec::new();
+        for (command, _) in PROBED_COMMANDS {
+            match self.at_command(command) {
+                Ok(response) => {
+                    debug!("adapter_info: {} got {:?}", command, response);
+                    supported.push(command.to_owned());
+                }
+                Err(e) => debug!("adapter_info: {} failed: {}", command, e),
+            }
+        }
+        let info = AdapterInfo::new(identifier, description, supported);
+        info!(
+            "Adapter is {} with quirks {:?}",
+            info.identifier, info.quirks
+        );
+        Ok(info)
+    }
+
     /// Flush the device's buffer
     pub fn flush(&mut self) -> Result<()> {
         thread::sleep(time::Duration::from_millis(500));
         self.read_into_queue()?;
-        self.buffer.clear();
+        self.core.clear();
         thread::sleep(time::Duration::from_millis(500));
         Ok(())
     }
@@ -104,7 +522,11 @@ impl Elm327 {
         Ok(())
     }
 
-    fn connect(&mut self, check_baud_rate: bool) -> Result<()> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip(self), err)
+    )]
+    fn initialize(&mut self, check_baud_rate: bool) -> Result<()> {
         self.flush_buffers()?;
         thread::sleep(time::Duration::from_millis(500));
         self.serial_cmd(" ")?;
@@ -125,6 +547,7 @@ impl Elm327 {
     fn reset_ic(&mut self) -> Result<()> {
         info!("Performing IC reset");
         self.send_serial_str("ATZ")?;
+        self.core.at_command_sent("ATZ");
         debug!(
             "reset_ic: got response {:?}",
             self.get_response()?
@@ -134,12 +557,15 @@ impl Elm327 {
         Ok(())
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip_all, fields(protocol = ?self.core.protocol), err)
+    )]
     fn reset_protocol(&mut self) -> Result<()> {
         info!("Performing protocol reset");
-        debug!(
-            "reset_protocol: got response {:?}",
-            self.serial_cmd("ATSP0")?
-        );
+        for at in self.core.protocol_reset_commands() {
+            debug!("reset_protocol: {} got {:?}", at, self.serial_cmd(&at)?);
+        }
         debug!(
             "reset_protocol: got OBD response {:?}",
             self.cmd(&[0x01, 0x00])?
@@ -149,7 +575,7 @@ impl Elm327 {
     }
 
     fn set_baud(&mut self, new_baud: u32) -> Result<()> {
-        self.device = new_ttyport(self.device.get_path(), new_baud)?;
+        self.device.set_baud_rate(new_baud)?;
         Ok(())
     }
 
@@ -203,75 +629,56 @@ impl Elm327 {
     }
 
     fn get_until(&mut self, end_byte: u8, allow_empty: bool) -> Result<Option<Vec<u8>>> {
-        const TIMEOUT: time::Duration = time::Duration::from_secs(5);
-
-        trace!("get_until: getting until {}", end_byte);
+        // enough for most responses without growing
+        let mut buf = Vec::with_capacity(64);
+        Ok(self
+            .read_until(end_byte, allow_empty, &mut buf)?
+            .then_some(buf))
+    }
 
-        let mut buf = Vec::new();
+    /// Read into `buf` until `end_byte`, which is left out, returning whether it was found
+    ///
+    /// `buf` is cleared first. Empty lines are skipped unless `allow_empty` is set.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            skip(self, buf),
+            fields(end_byte = %char::from(end_byte))
+        )
+    )]
+    fn read_until(&mut self, end_byte: u8, allow_empty: bool, buf: &mut Vec<u8>) -> Result<bool> {
+        let timeout = self.core.command_timeout();
         let start = time::Instant::now();
-        while start.elapsed() < TIMEOUT {
-            let Some(b) = self.get_byte()? else { continue };
-            let b = match b {
-                b'\r' => Some(b'\n'),
-                b'\n' => None, // no push here
-                _ => Some(b),
-            };
-            if let Some(b) = b {
-                buf.push(b);
-                if b == end_byte {
-                    break;
-                }
-            }
-        }
-
-        trace!(
-            "get_until: got {:?} ({:?})",
-            buf,
-            std::str::from_utf8(buf.as_slice())
-        );
-
-        match buf.pop() {
-            Some(b) if b == end_byte => {
-                if allow_empty || !buf.is_empty() {
-                    Ok(Some(buf))
-                } else {
-                    // empty line, try again
-                    self.get_until(end_byte, allow_empty)
-                }
-            } // we got it
-            Some(f) => {
-                // incomplete line read
-                for b in buf.iter().rev() {
-                    self.buffer.push_front(*b);
-                }
-                self.buffer.push_front(f);
-                Ok(None)
+        loop {
+            if self.core.take_until(end_byte, allow_empty, buf) {
+                return Ok(true);
             }
-            None => Ok(None),
-        }
-    }
-
-    fn get_byte(&mut self) -> Result<Option<u8>> {
-        match self.buffer.pop_front() {
-            Some(b'\0') => Ok(None),
-            Some(b) => Ok(Some(b)),
-            None => {
-                self.read_into_queue()?;
-                Ok(None)
+            if start.elapsed() >= timeout {
+                // an incomplete line stays received, for the next read
+                return Ok(false);
             }
+            self.read_into_queue()?;
         }
     }
 
     fn read_into_queue(&mut self) -> Result<()> {
         let mut buf = [0u8; 16];
         loop {
-            let len = self.device.read(&mut buf)?;
+            let len = match self.device.read(&mut buf) {
+                Ok(len) => len,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                    ) =>
+                {
+                    0
+                }
+                Err(e) => return Err(e.into()),
+            };
             if len > 0 {
-                self.buffer.extend(&buf[0..len]);
-                trace!(
-                    "read_into_queue: values {:?}",
-                    std::str::from_utf8(&buf[0..len])
-                );
+                self.core.receive(&buf[0..len]);
             } else {
                 trace!("read_into_queue: no values left to read");
                 break;
@@ -280,6 +687,81 @@ impl Elm327 {
         Ok(())
     }
 
+    /// Send an OBD-II command and get the reply, with the adapter's settings as they are
+    fn request(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        self.send_cmd(cmd)?;
+        self.get_response()
+            .map(|o| o.and_then(|resp| String::from_utf8(resp).ok()))
+    }
+
+    /// Change the adapter's settings to `wanted`, only sending the ones that are different
+    ///
+    /// If the transport reopened the link, the adapter is initialized again first, since it may
+    /// have lost the settings it was sent.
+    fn apply_settings(&mut self, wanted: RequestSettings) -> Result<()> {
+        if self.device.take_reconnected() {
+            info!("Link to the adapter was reopened, initializing it again");
+            self.core.clear();
+            self.reset()?;
+        }
+        for at in self.core.settings.commands(&wanted, self.core.protocol) {
+            debug!("apply_settings: {} got {:?}", at, self.serial_cmd(&at)?);
+        }
+        self.core.settings = wanted;
+        Ok(())
+    }
+
+    /// Send each of `commands`, even after one fails, and return the first error
+    ///
+    /// Used to change settings that must be changed back whatever happens in between.
+    fn at_commands(&mut self, commands: &[&str]) -> Result<()> {
+        let mut result = Ok(());
+        for at in commands {
+            match self.serial_cmd(at) {
+                Ok(response) => debug!("at_commands: {} got {:?}", at, response),
+                Err(e) => result = result.and(Err(e)),
+            }
+        }
+        result
+    }
+
+    /// Monitor the bus for `duration`, with the adapter filtering the frames, and get what the
+    /// adapter printed
+    ///
+    /// STN chips monitor with only their pass filters (`STM`).
+    fn monitor(
+        &mut self,
+        filters: &[CanFilter],
+        duration: time::Duration,
+        stn: bool,
+    ) -> Result<Vec<u8>> {
+        self.at_commands(&["ATCAF0"])?;
+        if !filters.is_empty() {
+            self.set_pass_filters(filters)?;
+        }
+        let monitor = match (stn, filters.is_empty()) {
+            (true, false) => "STM",
+            (true, true) => "STMA",
+            (false, _) => "ATMA",
+        };
+
+        self.send_serial_str(monitor)?;
+        let mut output = Vec::new();
+        let mut line = Vec::new();
+        let deadline = time::Instant::now() + duration;
+        while time::Instant::now() < deadline {
+            self.read_into_queue()?;
+            while self.core.take_until(b'\n', false, &mut line) {
+                output.extend_from_slice(&line);
+                output.push(b'\n');
+            }
+        }
+        // any character stops monitoring, then the adapter prints its prompt
+        self.device.write_all(b"\r")?;
+        output.extend(self.get_response()?.unwrap_or_default());
+        Ok(output)
+    }
+
     fn serial_cmd(&mut self, cmd: &str) -> Result<Option<String>> {
         self.send_serial_str(cmd)?;
         self.get_response()
@@ -287,21 +769,15 @@ impl Elm327 {
     }
 
     /// Function for sending a raw string, without encoding into ASCII hex
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip(self), err)
+    )]
     fn send_serial_str(&mut self, data: &str) -> Result<()> {
         trace!("send_serial_str: sending {:?}", data);
 
-        let data = data.as_bytes();
-
-        self.device.write_all(data)?;
+        self.device.write_all(data.as_bytes())?;
         self.device.write_all(b"\r\n")?;
-        let line = self.get_line()?;
-        if line.as_ref().is_some_and(|v| v == data) {
-            Ok(())
-        } else {
-            Err(Error::Communication(format!(
-                "send_serial_str: got {:?} instead of echoed command ({:?})",
-                line, data
-            )))
-        }
+        check_echo(data, self.get_line()?.as_deref())
     }
 }
@@ -0,0 +1,369 @@
+use log::{debug, info, trace};
+use std::time;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::{
+    adapter::{at_response, PROBED_COMMANDS},
+    at::{encode_hex, parse_protocol_number, parse_voltage},
+    elm327::RequestSettings,
+    elm327_core::{check_echo, Elm327Core},
+    AdapterInfo, DeviceConfig, Error, Result,
+};
+use crate::{
+    interface::{
+        decode_response, decode_tagged_response, pending_error, remove_pending, response_header,
+        single_response, strip_header, strip_tagged_header, PENDING_RETRIES, PENDING_RETRY_DELAY,
+    },
+    obd2_device::request_each_pid_async,
+    AsyncObd2Device, EcuResponse, Protocol,
+};
+
+/// An ELM327 OBD-II adapter used asynchronously with tokio
+///
+/// This speaks the same protocol as [Elm327](super::Elm327), over any asynchronous byte stream,
+/// sharing its handling of the adapter's state and responses.
+/// It implements [AsyncObd2Device](crate::AsyncObd2Device) directly, so the
+/// [AsyncObd2DataRetrieval](crate::commands::AsyncObd2DataRetrieval) functions can be used on it.
+pub struct AsyncElm327<S> {
+    stream: S,
+    core: Elm327Core,
+}
+
+/// An [AsyncElm327] connected over a serial port
+pub type AsyncSerialElm327 = AsyncElm327<tokio_serial::SerialStream>;
+
+/// An [AsyncElm327] connected over Wi-Fi
+pub type AsyncTcpElm327 = AsyncElm327<tokio::net::TcpStream>;
+
+impl AsyncElm327<tokio_serial::SerialStream> {
+    /// Open the ELM327 connected to the serial port at `dev_path`
+    pub async fn open(dev_path: impl AsRef<str>) -> Result<Self> {
+        Self::open_with_config(dev_path, DeviceConfig::default()).await
+    }
+
+    /// Open the ELM327 connected to the serial port at `dev_path`, with the timeouts and retries
+    /// in `config`
+    pub async fn open_with_config(dev_path: impl AsRef<str>, config: DeviceConfig) -> Result<Self> {
+        use tokio_serial::SerialPortBuilderExt;
+
+        let port = tokio_serial::new(dev_path.as_ref(), 38400)
+            .flow_control(tokio_serial::FlowControl::None)
+            .open_native_async()?;
+        AsyncElm327::with_transport_and_config(port, None, config).await
+    }
+}
+
+impl AsyncElm327<tokio::net::TcpStream> {
+    /// Connect to an ELM327 adapter over Wi-Fi at `address`
+    ///
+    /// Most adapters use [DEFAULT_TCP_ADDRESS](super::DEFAULT_TCP_ADDRESS).
+    pub async fn connect(address: impl tokio::net::ToSocketAddrs) -> Result<Self> {
+        Self::connect_with_config(address, DeviceConfig::default()).await
+    }
+
+    /// Connect to an ELM327 adapter over Wi-Fi at `address`, with the timeouts and retries in
+    /// `config`
+    ///
+    /// The connection must be made within the connect timeout, five seconds by default.
+    pub async fn connect_with_config(
+        address: impl tokio::net::ToSocketAddrs,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let stream = tokio::time::timeout(
+            config
+                .connect_timeout()
+                .unwrap_or(time::Duration::from_secs(5)),
+            tokio::net::TcpStream::connect(address),
+        )
+        .await
+        .map_err(|_| Error::Communication("connect: timed out".to_owned()))??;
+        stream.set_nodelay(true)?;
+        AsyncElm327::with_transport_and_config(stream, None, config).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> AsyncElm327<S> {
+    /// Create a [`AsyncElm327`] object that communicates over an already opened stream
+    pub async fn with_transport(stream: S) -> Result<Self> {
+        Self::with_transport_and_protocol(stream, None).await
+    }
+
+    /// Create a [`AsyncElm327`] object that communicates over an already opened stream, and uses
+    /// `protocol` if it is given
+    ///
+    /// See [Elm327::set_protocol](super::Elm327::set_protocol).
+    pub async fn with_transport_and_protocol(
+        stream: S,
+        protocol: Option<Protocol>,
+    ) -> Result<Self> {
+        Self::with_transport_and_config(stream, protocol, DeviceConfig::default()).await
+    }
+
+    /// Create a [`AsyncElm327`] object that communicates over an already opened stream, uses
+    /// `protocol` if it is given, and has the timeouts and retries in `config`
+    ///
+    /// See [Elm327::with_transport_and_config](super::Elm327::with_transport_and_config).
+    pub async fn with_transport_and_config(
+        stream: S,
+        protocol: Option<Protocol>,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let mut device = AsyncElm327 {
+            stream,
+            core: Elm327Core::new(protocol, config)?,
+        };
+        device.reset().await?;
+        Ok(device)
+    }
+
+    /// Always use `protocol` to communicate with the vehicle, or find it automatically if `None`
+    ///
+    /// See [Elm327::set_protocol](super::Elm327::set_protocol).
+    pub async fn set_protocol(&mut self, protocol: Option<Protocol>) -> Result<()> {
+        self.core.set_protocol(protocol)?;
+        self.reset_protocol().await
+    }
+
+    /// Ask the adapter which protocol it is using
+    ///
+    /// See [Obd2BaseDevice::protocol](super::Obd2BaseDevice::protocol).
+    pub async fn protocol(&mut self) -> Result<Protocol> {
+        parse_protocol_number(self.serial_cmd("ATDPN").await?)
+    }
+
+    /// Send the AT command `command` to the adapter and get its response
+    ///
+    /// See [Elm327::at_command](super::Elm327::at_command).
+    pub async fn at_command(&mut self, command: &str) -> Result<String> {
+        let response = self.serial_cmd(command.trim()).await?;
+        self.core.at_command_sent(command);
+        at_response(command, response)
+    }
+
+    /// Find out what the adapter is, and which extended commands it supports
+    ///
+    /// See [Elm327::adapter_info](super::Elm327::adapter_info).
+    pub async fn adapter_info(&mut self) -> Result<AdapterInfo> {
+        let identifier = self.at_command("ATI").await?;
+        let description = self.at_command("AT@1").await.ok();
+        let mut supported = Vec::new();
+        for (command, _) in PROBED_COMMANDS {
+            match self.at_command(command).await {
+                Ok(response) => {
+                    debug!("adapter_info: {} got {:?}", command, response);
+                    supported.push(command.to_owned());
+                }
+                Err(e) => debug!("adapter_info: {} failed: {}", command, e),
+            }
+        }
+        Ok(AdapterInfo::new(identifier, description, supported))
+    }
+
+    /// Reset the device and the OBD-II interface
+    ///
+    /// See [Obd2BaseDevice::reset](super::Obd2BaseDevice::reset).
+    pub async fn reset(&mut self) -> Result<()> {
+        self.stream.write_all(b" \r\n").await?;
+        self.get_response().await?;
+        self.core.clear();
+
+        info!("Performing IC reset");
+        self.send_serial_str("ATZ").await?;
+        self.core.at_command_sent("ATZ");
+        debug!("reset: got response {:?}", self.get_response().await?);
+        tokio::time::sleep(time::Duration::from_millis(500)).await;
+
+        self.reset_protocol().await
+    }
+
+    async fn reset_protocol(&mut self) -> Result<()> {
+        info!("Performing protocol reset");
+        for at in self.core.protocol_reset_commands() {
+            debug!("reset: {} got {:?}", at, self.serial_cmd(&at).await?);
+        }
+        debug!(
+            "reset: got OBD response {:?}",
+            self.cmd(&[0x01, 0x00]).await?
+        );
+        Ok(())
+    }
+
+    /// Send an OBD-II command and get the reply
+    ///
+    /// See [Obd2BaseDevice::cmd](super::Obd2BaseDevice::cmd).
+    pub async fn cmd(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.broadcast()).await?;
+        self.request(cmd).await
+    }
+
+    /// Send an OBD-II command and get the reply, with the header of each message
+    ///
+    /// See [Obd2BaseDevice::cmd_with_headers](super::Obd2BaseDevice::cmd_with_headers).
+    pub async fn cmd_with_headers(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.broadcast().with_headers())
+            .await?;
+        self.request(cmd).await
+    }
+
+    /// Send an OBD-II command to the ECU with the physical request `address` and get the reply
+    ///
+    /// See [Obd2BaseDevice::cmd_to](super::Obd2BaseDevice::cmd_to).
+    pub async fn cmd_to(&mut self, address: u32, cmd: &[u8]) -> Result<Option<String>> {
+        self.apply_settings(self.core.settings.to(address)).await?;
+        self.request(cmd).await
+    }
+
+    /// Send an OBD-II command and get the reply, with the adapter's settings as they are
+    async fn request(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        self.serial_cmd(&encode_hex(cmd)).await
+    }
+
+    /// Change the adapter's settings to `wanted`, only sending the ones that are different
+    async fn apply_settings(&mut self, wanted: RequestSettings) -> Result<()> {
+        for at in self.core.settings.commands(&wanted, self.core.protocol) {
+            debug!(
+                "apply_settings: {} got {:?}",
+                at,
+                self.serial_cmd(&at).await?
+            );
+        }
+        self.core.settings = wanted;
+        Ok(())
+    }
+
+    async fn serial_cmd(&mut self, cmd: &str) -> Result<Option<String>> {
+        self.send_serial_str(cmd).await?;
+        Ok(self
+            .get_response()
+            .await?
+            .and_then(|resp| String::from_utf8(resp).ok()))
+    }
+
+    /// Send a raw string, without encoding into ASCII hex, and check that it is echoed
+    async fn send_serial_str(&mut self, data: &str) -> Result<()> {
+        trace!("send_serial_str: sending {:?}", data);
+
+        self.stream.write_all(data.as_bytes()).await?;
+        self.stream.write_all(b"\r\n").await?;
+        check_echo(data, self.get_until(b'\n', false).await?.as_deref())
+    }
+
+    /// Read data until the ELM327's prompt character is printed
+    async fn get_response(&mut self) -> Result<Option<Vec<u8>>> {
+        self.get_until(b'>', true).await
+    }
+
+    async fn get_until(&mut self, end_byte: u8, allow_empty: bool) -> Result<Option<Vec<u8>>> {
+        let deadline = tokio::time::Instant::now() + self.core.command_timeout();
+        let mut line = Vec::new();
+        loop {
+            if self.core.take_until(end_byte, allow_empty, &mut line) {
+                return Ok(Some(line));
+            }
+
+            let mut buf = [0u8; 64];
+            match tokio::time::timeout_at(deadline, self.stream.read(&mut buf)).await {
+                Ok(len) => match len? {
+                    0 => {
+                        return Err(Error::Disconnected(
+                            "get_until: connection closed".to_owned(),
+                        ))
+                    }
+                    len => self.core.receive(&buf[..len]),
+                },
+                // an incomplete line stays received, for the next read
+                Err(_) => return Ok(None),
+            }
+        }
+    }
+
+    /// Send `command` and get the response, sending it again while the adapter times out
+    ///
+    /// See [Obd2BaseDevice::config](super::Obd2BaseDevice::config).
+    async fn cmd_with_retries(&mut self, command: &[u8]) -> crate::Result<String> {
+        for attempt in 0..=self.core.config.retries() {
+            if attempt > 0 {
+                let delay = self.core.config.retry_delay(attempt - 1);
+                debug!("Request timed out, retrying in {:?}", delay);
+                tokio::time::sleep(delay).await;
+            }
+            if let Some(response) = self.cmd(command).await? {
+                return Ok(response);
+            }
+        }
+        Err(crate::Error::Timeout)
+    }
+
+    async fn command(&mut self, command: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        for _ in 0..=PENDING_RETRIES {
+            let response = self.cmd_with_retries(command).await?;
+            let data = decode_response(response)?;
+            debug!("Sent OBD command {:?} and got data {:?}", command, data);
+            match remove_pending(data) {
+                Some(data) => return Ok(data),
+                None => tokio::time::sleep(PENDING_RETRY_DELAY).await,
+            }
+        }
+        Err(pending_error(command))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> AsyncObd2Device for AsyncElm327<S> {
+    async fn obd_command(&mut self, mode: u8, pid: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let result = self.command(&[mode, pid]).await?;
+        strip_header(result, &[0x40 | mode, pid])
+    }
+
+    async fn obd_mode_command(&mut self, mode: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let result = self.command(std::slice::from_ref(&mode)).await?;
+        strip_header(result, &[0x40 | mode])
+    }
+
+    async fn obd_request(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let result = self.command(request).await?;
+        strip_header(result, &response_header(request))
+    }
+
+    async fn obd_request_tagged(&mut self, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        let response = self
+            .cmd_with_headers(request)
+            .await?
+            .ok_or(crate::Error::Timeout)?;
+        let data = decode_tagged_response(response)?;
+        debug!("Sent OBD command {:?} and got data {:?}", request, data);
+        strip_tagged_header(data, request)
+    }
+
+    async fn obd_request_to(&mut self, address: u32, request: &[u8]) -> crate::Result<Vec<u8>> {
+        let response = self
+            .cmd_to(address, request)
+            .await?
+            .ok_or(crate::Error::Timeout)?;
+        let data = decode_response(response)?;
+        debug!(
+            "Sent OBD command {:?} to {:X} and got data {:?}",
+            request, address, data
+        );
+        let data = remove_pending(data).ok_or_else(|| pending_error(request))?;
+        single_response(strip_header(data, &response_header(request))?)
+    }
+
+    /// Only the CAN protocols accept several PIDs in one request, so on other protocols each PID
+    /// is requested separately
+    async fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        if pids.len() > 1 && !AsyncElm327::protocol(self).await?.is_can() {
+            return request_each_pid_async(self, mode, pids).await;
+        }
+        let result = self.command(&[&[mode], pids].concat()).await?;
+        strip_header(result, &[0x40 | mode])
+    }
+
+    async fn protocol(&mut self) -> crate::Result<Protocol> {
+        Ok(AsyncElm327::protocol(self).await?)
+    }
+
+    /// Read the voltage of the adapter's supply pin (`ATRV`)
+    async fn get_adapter_voltage(&mut self) -> crate::Result<f32> {
+        Ok(parse_voltage(self.serial_cmd("ATRV").await?)?)
+    }
+}
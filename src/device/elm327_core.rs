@@ -0,0 +1,168 @@
+//! The ELM327 protocol handling shared by [Elm327](super::Elm327) and
+//! [AsyncElm327](super::AsyncElm327)
+//!
+//! The core keeps track of the adapter's state and reads the lines and responses out of what it
+//! printed, without doing any I/O itself. Each front end moves the bytes over its own transport,
+//! blocking or asynchronously, and hands what it received to the core.
+
+use log::trace;
+use std::{collections::VecDeque, time::Duration};
+
+use super::{
+    at::{check_elm327_protocol, select_protocol_command, DEFAULT_COMMAND_TIMEOUT},
+    elm327::RequestSettings,
+    DeviceConfig, Error, Result,
+};
+use crate::Protocol;
+
+/// The state of an ELM327 adapter, and the bytes received from it that have not been read yet
+pub(super) struct Elm327Core {
+    buffer: VecDeque<u8>,
+    /// The protocol the adapter is configured with, or `None` to find it automatically
+    pub(super) protocol: Option<Protocol>,
+    pub(super) config: DeviceConfig,
+    /// The settings the adapter was last sent, which requests change as they need
+    pub(super) settings: RequestSettings,
+}
+
+impl Elm327Core {
+    pub(super) fn new(protocol: Option<Protocol>, config: DeviceConfig) -> Result<Self> {
+        check_elm327_protocol(protocol)?;
+        Ok(Elm327Core {
+            buffer: VecDeque::new(),
+            protocol,
+            config,
+            settings: RequestSettings::default(),
+        })
+    }
+
+    /// Use `protocol`, or find it automatically if `None`, from the next protocol reset
+    pub(super) fn set_protocol(&mut self, protocol: Option<Protocol>) -> Result<()> {
+        check_elm327_protocol(protocol)?;
+        self.protocol = protocol;
+        Ok(())
+    }
+
+    /// How long to wait for the adapter to finish responding
+    pub(super) fn command_timeout(&self) -> Duration {
+        self.config
+            .command_timeout()
+            .unwrap_or(DEFAULT_COMMAND_TIMEOUT)
+    }
+
+    /// Add bytes received from the adapter
+    pub(super) fn receive(&mut self, bytes: &[u8]) {
+        trace!("receive: {:?}", String::from_utf8_lossy(bytes));
+        self.buffer.extend(bytes);
+    }
+
+    /// Drop everything received that has not been read
+    pub(super) fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Read what was received up to `end_byte` into `buf`, which is cleared first, returning
+    /// whether `end_byte` was received
+    ///
+    /// `end_byte` is left out, and carriage returns are read as the end of a line (`\n`). Empty
+    /// lines are skipped unless `allow_empty` is set. Nothing is read until `end_byte` has been
+    /// received, so a line that is still arriving is kept whole for the next read.
+    pub(super) fn take_until(
+        &mut self,
+        end_byte: u8,
+        allow_empty: bool,
+        buf: &mut Vec<u8>,
+    ) -> bool {
+        loop {
+            let Some(end) = self
+                .buffer
+                .iter()
+                .position(|&b| received(b) == Some(end_byte))
+            else {
+                return false;
+            };
+            buf.clear();
+            buf.extend(self.buffer.drain(..end).filter_map(received));
+            self.buffer.pop_front();
+            if allow_empty || !buf.is_empty() {
+                trace!("take_until: got {:?}", String::from_utf8_lossy(buf));
+                return true;
+            }
+        }
+    }
+
+    /// Note that the AT command `command` was sent, forgetting the settings if it reset them
+    pub(super) fn at_command_sent(&mut self, command: &str) {
+        if ["ATZ", "ATD", "ATWS"]
+            .iter()
+            .any(|c| command.trim().eq_ignore_ascii_case(c))
+        {
+            self.settings = RequestSettings::default();
+        }
+    }
+
+    /// The AT commands selecting the configured protocol and adaptive timing again
+    pub(super) fn protocol_reset_commands(&mut self) -> [String; 2] {
+        self.settings.j1939 = false;
+        [
+            select_protocol_command(self.protocol),
+            self.config.adaptive_timing().elm327_command().to_owned(),
+        ]
+    }
+}
+
+/// How a byte printed by the adapter is read
+///
+/// Carriage returns end lines, while line feeds and the null bytes some adapters print are
+/// dropped.
+fn received(b: u8) -> Option<u8> {
+    match b {
+        b'\0' | b'\n' => None,
+        b'\r' => Some(b'\n'),
+        b => Some(b),
+    }
+}
+
+/// Check that the adapter echoed the command `sent` as the first `line` of its response
+pub(super) fn check_echo(sent: &str, line: Option<&[u8]>) -> Result<()> {
+    if line == Some(sent.as_bytes()) {
+        Ok(())
+    } else {
+        Err(Error::Communication(format!(
+            "send_serial_str: got {:?} instead of echoed command ({:?})",
+            line.map(String::from_utf8_lossy),
+            sent
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_wait_for_their_end() {
+        let mut core = Elm327Core::new(None, DeviceConfig::default()).unwrap();
+        let mut buf = Vec::new();
+        core.receive(b"010C\r41 0C");
+        assert!(core.take_until(b'\n', false, &mut buf));
+        assert_eq!(buf, b"010C");
+        assert!(!core.take_until(b'>', true, &mut buf));
+
+        core.receive(b" 1A F8\r\0\r>");
+        assert!(core.take_until(b'>', true, &mut buf));
+        assert_eq!(buf, b"41 0C 1A F8\n\n");
+    }
+
+    #[test]
+    fn empty_lines_skipped() {
+        let mut core = Elm327Core::new(None, DeviceConfig::default()).unwrap();
+        let mut buf = Vec::new();
+        core.receive(b"\r\n\r\nATRV\r\n12.6V\r\n\r\n>");
+        assert!(core.take_until(b'\n', false, &mut buf));
+        assert_eq!(buf, b"ATRV");
+        assert!(core.take_until(b'>', true, &mut buf));
+        assert_eq!(buf, b"12.6V\n\n");
+        assert!(!core.take_until(b'\n', false, &mut buf));
+    }
+}
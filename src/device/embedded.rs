@@ -0,0 +1,294 @@
+use alloc::{format, string::String, vec::Vec};
+use core::{mem, time::Duration};
+use embedded_hal::delay::DelayNs;
+use embedded_hal_nb::{
+    nb,
+    serial::{Error as _, Read, Write},
+};
+use log::{debug, info, trace};
+
+use super::{
+    at::{
+        check_elm327_protocol, encode_hex, parse_protocol_number, parse_voltage,
+        select_protocol_command, DEFAULT_COMMAND_TIMEOUT,
+    },
+    DeviceConfig, Error, Obd2BaseDevice, Obd2Reader, Result,
+};
+use crate::Protocol;
+
+/// How long to wait between checks of the serial port for a received byte, in microseconds
+const POLL_INTERVAL_US: u32 = 100;
+
+/// An ELM327 adapter connected to a serial port of a microcontroller, without `std`
+///
+/// This is for dongles that log from the vehicle with an ESP32, STM32 or similar chip wired to an
+/// ELM327 (or compatible) adapter. The serial port is any [embedded_hal_nb::serial] port, and
+/// `delay` times the waits for the adapter. Like [Elm327](super::Elm327), each request is
+/// broadcast and decoded by [Obd2](crate::Obd2); requests to a single ECU, headers and raw CAN
+/// access are not supported.
+///
+/// Without the `std` feature the crate still needs an allocator, which embedded targets provide
+/// with crates like `embedded-alloc`.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::EmbeddedElm327, Obd2};
+/// use embedded_hal_nb::{nb, serial};
+/// use std::collections::VecDeque;
+///
+/// /// An adapter that echoes each command, and answers requests for the engine speed
+/// #[derive(Default)]
+/// struct Uart {
+///     command: Vec<u8>,
+///     received: VecDeque<u8>,
+/// }
+///
+/// impl serial::ErrorType for Uart {
+///     type Error = serial::ErrorKind;
+/// }
+///
+/// impl serial::Write for Uart {
+///     fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+///         if byte == b'\n' {
+///             let response: &[u8] = match self.command.as_slice() {
+///                 b"010C" => b"41 0C 0F A0\r\r>",
+///                 b"ATDPN" => b"A6\r\r>",
+///                 _ => b"OK\r\r>",
+///             };
+///             self.received.extend(self.command.drain(..));
+///             self.received.extend(b"\r");
+///             self.received.extend(response);
+///         } else if byte != b'\r' {
+///             self.command.push(byte);
+///         }
+///         Ok(())
+///     }
+///
+///     fn flush(&mut self) -> nb::Result<(), Self::Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// impl serial::Read for Uart {
+///     fn read(&mut self) -> nb::Result<u8, Self::Error> {
+///         self.received.pop_front().ok_or(nb::Error::WouldBlock)
+///     }
+/// }
+///
+/// /// A delay that returns at once
+/// struct NoDelay;
+///
+/// impl embedded_hal::delay::DelayNs for NoDelay {
+///     fn delay_ns(&mut self, _: u32) {}
+/// }
+///
+/// let mut device = Obd2::new(EmbeddedElm327::new(Uart::default(), NoDelay).unwrap());
+/// assert_eq!(device.get_rpm().unwrap(), [1000.]);
+/// ```
+pub struct EmbeddedElm327<S, D> {
+    serial: S,
+    delay: D,
+    protocol: Option<Protocol>,
+    config: DeviceConfig,
+    received: Vec<u8>,
+}
+
+impl<S: Read + Write, D: DelayNs> EmbeddedElm327<S, D> {
+    /// Create a [`EmbeddedElm327`] communicating over `serial`, and reset the adapter
+    ///
+    /// The serial port must already be set to the adapter's baud rate, which is 38400 for most
+    /// adapters.
+    pub fn new(serial: S, delay: D) -> Result<Self> {
+        Self::with_protocol_and_config(serial, delay, None, DeviceConfig::default())
+    }
+
+    /// Create a [`EmbeddedElm327`] that always uses `protocol`
+    ///
+    /// See [Elm327::with_protocol](super::Elm327::with_protocol).
+    pub fn with_protocol(serial: S, delay: D, protocol: Protocol) -> Result<Self> {
+        Self::with_protocol_and_config(serial, delay, Some(protocol), DeviceConfig::default())
+    }
+
+    /// Create a [`EmbeddedElm327`] with the timeouts and retries in `config`
+    pub fn with_config(serial: S, delay: D, config: DeviceConfig) -> Result<Self> {
+        Self::with_protocol_and_config(serial, delay, None, config)
+    }
+
+    /// Create a [`EmbeddedElm327`] that uses `protocol` if it is given, and has the timeouts and
+    /// retries in `config`
+    pub fn with_protocol_and_config(
+        serial: S,
+        delay: D,
+        protocol: Option<Protocol>,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        check_elm327_protocol(protocol)?;
+        let mut device = EmbeddedElm327 {
+            serial,
+            delay,
+            protocol,
+            config,
+            received: Vec::new(),
+        };
+
+        device.delay.delay_ms(500);
+        device.serial_cmd(" ")?;
+        device.delay.delay_ms(500);
+        device.reset()?;
+        device.flush()?;
+
+        Ok(device)
+    }
+
+    /// Always use `protocol` to communicate with the vehicle, or find it automatically if `None`
+    pub fn set_protocol(&mut self, protocol: Option<Protocol>) -> Result<()> {
+        check_elm327_protocol(protocol)?;
+        self.protocol = protocol;
+        self.reset_protocol()
+    }
+
+    /// Get back the serial port and the delay
+    pub fn into_inner(self) -> (S, D) {
+        (self.serial, self.delay)
+    }
+
+    /// Throw away anything the adapter sent that was not read
+    pub fn flush(&mut self) -> Result<()> {
+        self.delay.delay_ms(500);
+        loop {
+            match self.serial.read() {
+                Ok(_) => (),
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(Error::EmbeddedSerial(e.kind())),
+            }
+        }
+        self.received.clear();
+        Ok(())
+    }
+
+    fn reset_ic(&mut self) -> Result<()> {
+        info!("Performing IC reset");
+        self.send_serial_str("ATZ")?;
+        debug!("reset_ic: got response {:?}", self.get_response()?);
+        Ok(())
+    }
+
+    fn reset_protocol(&mut self) -> Result<()> {
+        info!("Performing protocol reset");
+        let at = select_protocol_command(self.protocol);
+        debug!("reset_protocol: {} got {:?}", at, self.serial_cmd(&at)?);
+        let at = self.config.adaptive_timing().elm327_command();
+        debug!("reset_protocol: {} got {:?}", at, self.serial_cmd(at)?);
+        debug!(
+            "reset_protocol: got OBD response {:?}",
+            self.cmd(&[0x01, 0x00])?
+        );
+        Ok(())
+    }
+
+    /// Read until `end_byte`, or `None` if the command timeout passes first
+    ///
+    /// Bytes received before a timeout are kept for the next call, so a slow response is not
+    /// lost.
+    fn get_until(&mut self, end_byte: u8, allow_empty: bool) -> Result<Option<Vec<u8>>> {
+        let timeout = self
+            .config
+            .command_timeout()
+            .unwrap_or(DEFAULT_COMMAND_TIMEOUT);
+        let mut waited = Duration::ZERO;
+        while waited < timeout {
+            let b = match self.serial.read() {
+                Ok(b) => b,
+                Err(nb::Error::WouldBlock) => {
+                    self.delay.delay_us(POLL_INTERVAL_US);
+                    waited += Duration::from_micros(POLL_INTERVAL_US.into());
+                    continue;
+                }
+                Err(nb::Error::Other(e)) => return Err(Error::EmbeddedSerial(e.kind())),
+            };
+            let b = match b {
+                b'\r' => b'\n',
+                b'\n' | b'\0' => continue,
+                _ => b,
+            };
+            if b != end_byte {
+                self.received.push(b);
+            } else if allow_empty || !self.received.is_empty() {
+                trace!("get_until: got {:?}", core::str::from_utf8(&self.received));
+                return Ok(Some(mem::take(&mut self.received)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
+        for &b in data {
+            nb::block!(self.serial.write(b)).map_err(|e| Error::EmbeddedSerial(e.kind()))?;
+        }
+        nb::block!(self.serial.flush()).map_err(|e| Error::EmbeddedSerial(e.kind()))
+    }
+
+    fn serial_cmd(&mut self, cmd: &str) -> Result<Option<String>> {
+        self.send_serial_str(cmd)?;
+        self.get_response()
+            .map(|o| o.and_then(|resp| String::from_utf8(resp).ok()))
+    }
+
+    fn send_serial_str(&mut self, data: &str) -> Result<()> {
+        trace!("send_serial_str: sending {:?}", data);
+
+        self.write_bytes(data.as_bytes())?;
+        self.write_bytes(b"\r\n")?;
+        let line = self.get_line()?;
+        if line.as_deref() == Some(data.as_bytes()) {
+            Ok(())
+        } else {
+            Err(Error::Communication(format!(
+                "send_serial_str: got {:?} instead of echoed command ({:?})",
+                line, data
+            )))
+        }
+    }
+}
+
+impl<S: Read + Write, D: DelayNs> Obd2BaseDevice for EmbeddedElm327<S, D> {
+    fn reset(&mut self) -> Result<()> {
+        self.reset_ic()?;
+        self.delay.delay_ms(500);
+        self.reset_protocol()
+    }
+
+    fn send_cmd(&mut self, data: &[u8]) -> Result<()> {
+        self.send_serial_str(&encode_hex(data))
+    }
+
+    /// Ask the adapter which protocol it is using (`ATDPN`)
+    fn protocol(&mut self) -> Result<Protocol> {
+        parse_protocol_number(self.serial_cmd("ATDPN")?)
+    }
+
+    fn config(&self) -> DeviceConfig {
+        self.config
+    }
+
+    /// Read the voltage of the adapter's supply pin (`ATRV`)
+    fn adapter_voltage(&mut self) -> Result<f32> {
+        parse_voltage(self.serial_cmd("ATRV")?)
+    }
+
+    fn delay(&mut self, duration: Duration) {
+        self.delay
+            .delay_ms(duration.as_millis().try_into().unwrap_or(u32::MAX));
+    }
+}
+
+impl<S: Read + Write, D: DelayNs> Obd2Reader for EmbeddedElm327<S, D> {
+    fn get_line(&mut self) -> Result<Option<Vec<u8>>> {
+        self.get_until(b'\n', false)
+    }
+
+    /// Read data until the ELM327's prompt character is printed
+    fn get_response(&mut self) -> Result<Option<Vec<u8>>> {
+        self.get_until(b'>', true)
+    }
+}
@@ -0,0 +1,100 @@
+//! A base device for tests, which answers with the text an ELM327 would print
+//!
+//! Unlike [MockObd2Device](super::MockObd2Device), requests go through [Obd2](crate::Obd2), so
+//! the response decoding and header checks are tested too.
+
+use std::collections::HashMap;
+
+use super::{Obd2BaseDevice, Obd2Reader, Result};
+use crate::{Obd2, Protocol};
+
+/// Answers requests from a table of responses, and `NO DATA` to any other request
+pub(crate) struct FakeDevice {
+    responses: HashMap<Vec<u8>, String>,
+    tagged: HashMap<Vec<u8>, String>,
+    protocol: Protocol,
+    /// Every request sent, in order
+    pub(crate) requests: Vec<Vec<u8>>,
+    response: Option<Vec<u8>>,
+}
+
+impl FakeDevice {
+    pub(crate) fn new() -> Self {
+        FakeDevice {
+            responses: HashMap::new(),
+            tagged: HashMap::new(),
+            protocol: Protocol::Can11Bit500,
+            requests: Vec::new(),
+            response: None,
+        }
+    }
+
+    /// Answer `request` with `text`, as printed with headers off
+    pub(crate) fn with_response(mut self, request: &[u8], text: &str) -> Self {
+        self.responses.insert(request.to_vec(), text.to_owned());
+        self
+    }
+
+    pub(crate) fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Answer `request` with `text`, as printed with headers on, when the request is sent with
+    /// [cmd_with_headers](Obd2BaseDevice::cmd_with_headers)
+    pub(crate) fn with_tagged_response(mut self, request: &[u8], text: &str) -> Self {
+        self.tagged.insert(request.to_vec(), text.to_owned());
+        self
+    }
+
+    pub(crate) fn into_obd2(self) -> Obd2<Self> {
+        Obd2::new(self)
+    }
+
+    fn answer(&mut self, table: bool, request: &[u8]) -> Option<String> {
+        self.requests.push(request.to_vec());
+        let table = if table { &self.tagged } else { &self.responses };
+        Some(
+            table
+                .get(request)
+                .cloned()
+                .unwrap_or_else(|| "NO DATA".to_owned()),
+        )
+    }
+}
+
+impl Obd2BaseDevice for FakeDevice {
+    fn reset(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn send_cmd(&mut self, data: &[u8]) -> Result<()> {
+        self.response = self.answer(false, data).map(String::into_bytes);
+        Ok(())
+    }
+
+    fn cmd_with_headers(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        Ok(self.answer(true, cmd))
+    }
+
+    /// The table has no addresses, so every request is answered as if it was broadcast
+    fn cmd_to(&mut self, _address: u32, cmd: &[u8]) -> Result<Option<String>> {
+        Ok(self.answer(false, cmd))
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        Ok(self.protocol)
+    }
+
+    fn delay(&mut self, _duration: core::time::Duration) {}
+}
+
+impl Obd2Reader for FakeDevice {
+    fn get_line(&mut self) -> Result<Option<Vec<u8>>> {
+        self.get_response()
+    }
+
+    fn get_response(&mut self) -> Result<Option<Vec<u8>>> {
+        Ok(self.response.take())
+    }
+}
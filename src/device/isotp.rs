@@ -0,0 +1,113 @@
+//! ISO 15765-2 (ISO-TP) framing of OBD-II messages over CAN
+//!
+//! Requests from a tester always fit in a single frame. Responses longer than seven bytes are sent
+//! as a first frame followed by consecutive frames, after the tester sends a flow control frame.
+//! Adapters that show CAN headers print these frames as they are, so they are reassembled here
+//! too.
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+use super::CanFrame;
+use super::{Error, Result};
+#[cfg(all(feature = "std", target_os = "linux"))]
+use alloc::vec;
+use alloc::{borrow::ToOwned, format, vec::Vec};
+
+/// Byte used to fill unused bytes of transmitted frames
+#[cfg(all(feature = "std", target_os = "linux"))]
+const PADDING: u8 = 0x00;
+
+/// Build the single frame carrying a request of up to seven bytes
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub(super) fn single_frame(id: u32, payload: &[u8]) -> Result<CanFrame> {
+    if payload.is_empty() || payload.len() > 7 {
+        return Err(Error::Communication(format!(
+            "single_frame: cannot send {} bytes in a single frame",
+            payload.len()
+        )));
+    }
+    let mut data = vec![payload.len() as u8];
+    data.extend_from_slice(payload);
+    data.resize(8, PADDING);
+    Ok(CanFrame { id, data })
+}
+
+/// Build a flow control frame telling the sender to send all remaining frames without delay
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub(super) fn flow_control(id: u32) -> CanFrame {
+    let mut data = vec![0x30, 0x00, 0x00];
+    data.resize(8, PADDING);
+    CanFrame { id, data }
+}
+
+/// What to do after a frame has been given to a [Reassembler]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Progress {
+    /// The message is complete
+    Complete(Vec<u8>),
+    /// A first frame was received, so a flow control frame must be sent
+    SendFlowControl,
+    /// More consecutive frames are needed
+    Pending,
+}
+
+/// Reassembles the frames sent by one ECU into a complete message
+#[derive(Debug, Default)]
+pub(crate) struct Reassembler {
+    expected_len: usize,
+    next_sequence: u8,
+    data: Vec<u8>,
+}
+
+impl Reassembler {
+    /// Handle the data bytes of a frame from the ECU
+    pub fn push(&mut self, frame: &[u8]) -> Result<Progress> {
+        let pci = *frame.first().ok_or_else(|| {
+            Error::Communication("Reassembler::push: received empty frame".to_owned())
+        })?;
+        match pci >> 4 {
+            0 => {
+                let len = usize::from(pci & 0x0f);
+                let data = frame.get(1..=len).ok_or_else(|| truncated(frame))?;
+                Ok(Progress::Complete(data.to_vec()))
+            }
+            1 => {
+                let len_low = *frame.get(1).ok_or_else(|| truncated(frame))?;
+                self.expected_len = (usize::from(pci & 0x0f) << 8) | usize::from(len_low);
+                self.next_sequence = 1;
+                self.data = frame[2..].to_vec();
+                Ok(Progress::SendFlowControl)
+            }
+            2 => {
+                if self.expected_len == 0 {
+                    return Err(Error::Communication(
+                        "Reassembler::push: consecutive frame without first frame".to_owned(),
+                    ));
+                }
+                if pci & 0x0f != self.next_sequence {
+                    return Err(Error::Communication(format!(
+                        "Reassembler::push: expected sequence number {}, got {}",
+                        self.next_sequence,
+                        pci & 0x0f
+                    )));
+                }
+                self.next_sequence = (self.next_sequence + 1) % 0x10;
+                self.data.extend_from_slice(&frame[1..]);
+                if self.data.len() >= self.expected_len {
+                    self.data.truncate(self.expected_len);
+                    self.expected_len = 0;
+                    Ok(Progress::Complete(core::mem::take(&mut self.data)))
+                } else {
+                    Ok(Progress::Pending)
+                }
+            }
+            _ => Err(Error::Communication(format!(
+                "Reassembler::push: unexpected frame type {:02X}",
+                pci
+            ))),
+        }
+    }
+}
+
+fn truncated(frame: &[u8]) -> Error {
+    Error::Communication(format!("Reassembler::push: truncated frame {:02X?}", frame))
+}
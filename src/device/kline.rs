@@ -0,0 +1,634 @@
+use log::{debug, info, trace};
+use serialport::SerialPort;
+use std::{
+    io::{self, Read, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{elm327::new_serial_port, DeviceConfig, Error, Result};
+use crate::{
+    interface::{checksum, response_header, single_response, strip_header, strip_tagged_header},
+    EcuResponse, Obd2Device, Protocol,
+};
+
+/// The baud rate of the K-line once it is initialized
+const KLINE_BAUD_RATE: u32 = 10400;
+
+/// The address of the OBD-II ECUs, sent by the init and as the target of KWP2000 requests
+const OBD_ADDRESS: u8 = 0x33;
+
+/// The address of this tester
+const TESTER_ADDRESS: u8 = 0xF1;
+
+/// How long each bit of the address sent at 5 baud by a slow init lasts
+const SLOW_INIT_BIT: Duration = Duration::from_millis(200);
+
+/// How long the K-line must be idle before an init (W5)
+const IDLE_BEFORE_INIT: Duration = Duration::from_millis(300);
+
+/// Longest wait for the sync byte after the address of a slow init (W1)
+const SYNC_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Longest wait for each key byte after the sync byte (W2 and W3)
+const KEY_BYTE_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// How long to wait before sending the inverted second key byte (W4, 25 to 50 ms)
+const KEY_BYTE_REPLY_DELAY: Duration = Duration::from_millis(30);
+
+/// How long the K-line is held low, then released, by a fast init (TiniL, half of TWup)
+const FAST_INIT_PULSE: Duration = Duration::from_millis(25);
+
+/// How long to wait for an ECU that responded that its response is pending (P2* in ISO 14230-4)
+const RESPONSE_PENDING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a K-line connection is started
+///
+/// ISO 9141-2 vehicles only accept a slow init, and ISO 14230-4 (KWP2000) vehicles accept either,
+/// depending on the vehicle. To use a particular init with an [Elm327](super::Elm327), select the
+/// matching [Protocol] with [set_protocol](super::Elm327::set_protocol).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KLineInit {
+    /// The OBD-II address is sent at 5 baud, and the ECUs answer with key bytes that say whether
+    /// they use ISO 9141-2 or KWP2000. This takes about 2.5 seconds.
+    Slow,
+    /// The K-line is pulsed low for 25 ms, then a KWP2000 StartCommunication request is sent
+    Fast,
+}
+
+/// The timing parameters of a K-line connection
+///
+/// The defaults are the limits from ISO 9141-2 and ISO 14230-4. USB serial adapters add latency to
+/// every read, so with some adapters the response timeout has to be raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KLineTiming {
+    /// The longest wait for a response, and for further responses after one ends (P2max)
+    pub response_timeout: Duration,
+    /// The shortest gap between the end of a response and the next request (P3min)
+    pub request_gap: Duration,
+    /// How long the ECUs keep the connection without a request (P3max). After this, the
+    /// connection is initialized again before the next request.
+    pub idle_timeout: Duration,
+    /// The gap between the bytes of a request (P4)
+    pub inter_byte_delay: Duration,
+}
+
+impl Default for KLineTiming {
+    fn default() -> Self {
+        KLineTiming {
+            response_timeout: Duration::from_millis(50),
+            request_gap: Duration::from_millis(55),
+            idle_timeout: Duration::from_secs(5),
+            inter_byte_delay: Duration::from_millis(5),
+        }
+    }
+}
+
+/// A connection to a K-line, like a "KKL" USB cable, that a [KLineDevice] can communicate over
+///
+/// The K-line is a single wire, so everything sent is also received.
+pub trait KLineTransport: Read + Write {
+    /// Change the baud rate
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<()>;
+
+    /// Hold the line low (a break) if `on`, or release it
+    fn set_break(&mut self, on: bool) -> Result<()>;
+
+    /// Change how long reads wait for data before failing with [io::ErrorKind::TimedOut]
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()>;
+}
+
+impl KLineTransport for Box<dyn SerialPort> {
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<()> {
+        SerialPort::set_baud_rate(self.as_mut(), baud_rate)?;
+        Ok(())
+    }
+
+    fn set_break(&mut self, on: bool) -> Result<()> {
+        if on {
+            SerialPort::set_break(self.as_ref())?;
+        } else {
+            SerialPort::clear_break(self.as_ref())?;
+        }
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        SerialPort::set_timeout(self.as_mut(), timeout)?;
+        Ok(())
+    }
+}
+
+/// An OBD-II interface that talks to a K-line (ISO 9141-2 or ISO 14230-4 KWP2000) vehicle
+/// directly, without an adapter chip
+///
+/// Most vehicles from before 2008 that are not on CAN use the K-line. This does the init sequence
+/// itself and keeps to the timing between bytes and messages, which makes it work with cheap
+/// "KKL" cables and with vehicles that adapters handle badly. The checksum of every message is
+/// checked, and a message that fails it is an [InvalidChecksum](crate::Error::InvalidChecksum)
+/// error.
+///
+/// If the vehicle has not been sent a request within the idle timeout, the connection is
+/// initialized again before the next request.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Obd2DataRetrieval,
+///     device::{KLineDevice, KLineInit, KLineTiming, KLineTransport},
+///     Obd2Device, Protocol,
+/// };
+/// use std::{
+///     collections::VecDeque,
+///     io::{self, Read, Write},
+///     time::Duration,
+/// };
+///
+/// /// A KWP2000 message from the engine ECU (0x10) to the tester, with its checksum
+/// fn message(data: &[u8]) -> Vec<u8> {
+///     let mut message = vec![0x80 | data.len() as u8, 0xF1, 0x10];
+///     message.extend(data);
+///     message.push(message.iter().fold(0, |sum: u8, b| sum.wrapping_add(*b)));
+///     message
+/// }
+///
+/// /// A K-line with an engine ECU that accepts a fast init and runs at 1000 RPM
+/// #[derive(Default)]
+/// struct Vehicle {
+///     line: VecDeque<u8>,
+///     request: Vec<u8>,
+/// }
+///
+/// impl Write for Vehicle {
+///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+///         self.line.extend(buf); // the line echoes everything sent
+///         self.request.extend(buf);
+///         if self.request.len() == usize::from(self.request[0] & 0x3F) + 4 {
+///             let response = match self.request[3] {
+///                 0x81 => message(&[0xC1, 0xEF, 0x8F]), // StartCommunication
+///                 _ => message(&[0x41, 0x0C, 0x0F, 0xA0]),
+///             };
+///             self.line.extend(response);
+///             self.request.clear();
+///         }
+///         Ok(buf.len())
+///     }
+///
+///     fn flush(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// impl Read for Vehicle {
+///     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+///         match self.line.pop_front() {
+///             Some(byte) => {
+///                 buf[0] = byte;
+///                 Ok(1)
+///             }
+///             None => Err(io::ErrorKind::TimedOut.into()),
+///         }
+///     }
+/// }
+///
+/// impl KLineTransport for Vehicle {
+///     fn set_baud_rate(&mut self, _: u32) -> Result<(), obd2::device::Error> {
+///         Ok(())
+///     }
+///
+///     fn set_break(&mut self, _: bool) -> Result<(), obd2::device::Error> {
+///         Ok(())
+///     }
+///
+///     fn set_timeout(&mut self, _: Duration) -> Result<(), obd2::device::Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// let vehicle = Vehicle::default();
+/// let mut device =
+///     KLineDevice::with_transport(vehicle, KLineInit::Fast, KLineTiming::default()).unwrap();
+/// assert_eq!(device.protocol().unwrap(), Protocol::Kwp2000FastInit);
+/// assert_eq!(device.key_bytes(), [0xEF, 0x8F]);
+/// assert_eq!(device.get_rpm().unwrap(), [1000.]);
+/// ```
+pub struct KLineDevice<T: KLineTransport = Box<dyn SerialPort>> {
+    transport: T,
+    init: KLineInit,
+    timing: KLineTiming,
+    protocol: Protocol,
+    key_bytes: [u8; 2],
+    last_activity: Instant,
+    config: DeviceConfig,
+}
+
+impl KLineDevice {
+    /// Connect to the vehicle with a K-line cable at `dev_path`, using `init` and the default
+    /// timing
+    pub fn new(dev_path: impl AsRef<str>, init: KLineInit) -> Result<Self> {
+        let port = new_serial_port(dev_path.as_ref(), KLINE_BAUD_RATE)?;
+        KLineDevice::with_transport(port, init, KLineTiming::default())
+    }
+
+    /// Connect to the vehicle with a K-line cable at `dev_path`, using `init` and the timeouts and
+    /// retries in `config`
+    ///
+    /// See [with_transport_and_config](KLineDevice::with_transport_and_config).
+    pub fn with_config(
+        dev_path: impl AsRef<str>,
+        init: KLineInit,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let port = new_serial_port(dev_path.as_ref(), KLINE_BAUD_RATE)?;
+        KLineDevice::with_transport_and_config(port, init, KLineTiming::default(), config)
+    }
+}
+
+impl<T: KLineTransport> KLineDevice<T> {
+    /// Connect to the vehicle over an already opened transport
+    pub fn with_transport(transport: T, init: KLineInit, timing: KLineTiming) -> Result<Self> {
+        Self::with_transport_and_config(transport, init, timing, DeviceConfig::default())
+    }
+
+    /// Connect to the vehicle over an already opened transport, with the timeouts and retries in
+    /// `config`
+    ///
+    /// The command timeout, if set, replaces the response timeout of `timing`. A request that no
+    /// ECU responds to is sent again up to the number of retries, and if none respond it is
+    /// [NoData](crate::Error::NoData), since a silent K-line cannot be told apart from ECUs that
+    /// do not support the request. The init's timing is set by the standards, so the connect
+    /// timeout and adaptive timing are not used.
+    pub fn with_transport_and_config(
+        transport: T,
+        init: KLineInit,
+        timing: KLineTiming,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let mut device = KLineDevice {
+            transport,
+            init,
+            timing: KLineTiming {
+                response_timeout: config.command_timeout().unwrap_or(timing.response_timeout),
+                ..timing
+            },
+            protocol: match init {
+                KLineInit::Slow => Protocol::Iso9141_2,
+                KLineInit::Fast => Protocol::Kwp2000FastInit,
+            },
+            key_bytes: [0; 2],
+            last_activity: Instant::now(),
+            config,
+        };
+        device.initialize()?;
+        Ok(device)
+    }
+
+    /// Change the timing parameters
+    pub fn set_timing(&mut self, timing: KLineTiming) {
+        self.timing = timing;
+    }
+
+    /// The key bytes the ECUs sent during the init
+    ///
+    /// `08 08` or `94 94` is ISO 9141-2, and a second key byte of `8F` is KWP2000.
+    pub fn key_bytes(&self) -> [u8; 2] {
+        self.key_bytes
+    }
+
+    /// Initialize the connection again
+    pub fn initialize(&mut self) -> Result<()> {
+        info!("Performing K-line {:?} init", self.init);
+        self.transport.set_baud_rate(KLINE_BAUD_RATE)?;
+        self.transport.set_break(false)?;
+        thread::sleep(IDLE_BEFORE_INIT);
+        self.drain()?;
+        match self.init {
+            KLineInit::Slow => self.slow_init()?,
+            KLineInit::Fast => self.fast_init()?,
+        }
+        info!(
+            "K-line connected with key bytes {:02X?}, using {}",
+            self.key_bytes, self.protocol
+        );
+        self.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Send the OBD-II address at 5 baud, then exchange the key bytes
+    fn slow_init(&mut self) -> Result<()> {
+        // a start bit, the address least significant bit first, and a stop bit, where the line
+        // is low (a break) for zeros
+        self.transport.set_break(true)?;
+        thread::sleep(SLOW_INIT_BIT);
+        for bit in 0..8 {
+            self.transport.set_break(OBD_ADDRESS >> bit & 1 == 0)?;
+            thread::sleep(SLOW_INIT_BIT);
+        }
+        self.transport.set_break(false)?;
+
+        // some cables receive the breaks as zero bytes, so skip anything before the sync byte
+        let deadline = Instant::now() + SLOW_INIT_BIT + SYNC_TIMEOUT;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.read_byte(timeout)? {
+                Some(0x55) => break,
+                Some(byte) => trace!("slow_init: skipping {:02X}", byte),
+                None => {
+                    return Err(Error::Communication(
+                        "slow_init: no sync byte from the vehicle".to_owned(),
+                    ))
+                }
+            }
+        }
+
+        let mut key_bytes = [0; 2];
+        for key_byte in &mut key_bytes {
+            *key_byte = self
+                .read_byte(KEY_BYTE_TIMEOUT)?
+                .ok_or_else(|| Error::Communication("slow_init: missing key byte".to_owned()))?;
+        }
+        debug!("slow_init: got key bytes {:02X?}", key_bytes);
+
+        thread::sleep(KEY_BYTE_REPLY_DELAY);
+        self.send(&[!key_bytes[1]])?;
+        match self.read_byte(self.timing.response_timeout)? {
+            Some(byte) if byte == !OBD_ADDRESS => (),
+            other => {
+                return Err(Error::Communication(format!(
+                    "slow_init: expected inverted address {:02X}, got {:02X?}",
+                    !OBD_ADDRESS, other
+                )))
+            }
+        }
+
+        self.key_bytes = key_bytes;
+        self.protocol = if key_bytes[1] == 0x8F {
+            Protocol::Kwp2000SlowInit
+        } else {
+            Protocol::Iso9141_2
+        };
+        thread::sleep(self.timing.request_gap);
+        Ok(())
+    }
+
+    /// Pulse the line low, then send a StartCommunication request
+    fn fast_init(&mut self) -> Result<()> {
+        self.transport.set_break(true)?;
+        thread::sleep(FAST_INIT_PULSE);
+        self.transport.set_break(false)?;
+        thread::sleep(FAST_INIT_PULSE);
+        self.drain()?;
+
+        self.protocol = Protocol::Kwp2000FastInit;
+        self.send(&encode(self.protocol, &[0x81]))?;
+        let bytes = self.receive(self.timing.response_timeout)?;
+        let messages = decode_messages(self.protocol, &bytes).map_err(|e| {
+            Error::Communication(format!("fast_init: bad StartCommunication response: {}", e))
+        })?;
+        match messages.first().map(|(_, data)| data.as_slice()) {
+            Some(&[0xC1, key_byte_1, key_byte_2, ..]) => {
+                self.key_bytes = [key_byte_1, key_byte_2];
+                Ok(())
+            }
+            other => Err(Error::Communication(format!(
+                "fast_init: StartCommunication was not accepted, got {:02X?}",
+                other
+            ))),
+        }
+    }
+
+    /// Read a single byte, or `None` if there is none within `timeout`
+    fn read_byte(&mut self, timeout: Duration) -> Result<Option<u8>> {
+        self.transport.set_timeout(timeout)?;
+        let mut byte = [0];
+        match self.transport.read(&mut byte) {
+            Ok(1) => Ok(Some(byte[0])),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Throw away anything already received
+    fn drain(&mut self) -> Result<()> {
+        while let Some(byte) = self.read_byte(Duration::from_millis(1))? {
+            trace!("drain: discarding {:02X}", byte);
+        }
+        Ok(())
+    }
+
+    /// Send `bytes` with the inter-byte delay, and check that each one is echoed back unchanged
+    fn send(&mut self, bytes: &[u8]) -> Result<()> {
+        trace!("send: sending {:02X?}", bytes);
+        for (i, &byte) in bytes.iter().enumerate() {
+            if i > 0 {
+                thread::sleep(self.timing.inter_byte_delay);
+            }
+            self.transport.write_all(&[byte])?;
+            self.transport.flush()?;
+            match self.read_byte(self.timing.response_timeout)? {
+                Some(echo) if echo == byte => (),
+                echo => {
+                    return Err(Error::Communication(format!(
+                        "send: sent {:02X} but the line had {:02X?}, another node may be sending",
+                        byte, echo
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive bytes until the line has been quiet for the response timeout, waiting up to
+    /// `timeout` for the first one
+    fn receive(&mut self, timeout: Duration) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut timeout = timeout;
+        while let Some(byte) = self.read_byte(timeout)? {
+            bytes.push(byte);
+            timeout = self.timing.response_timeout;
+        }
+        trace!("receive: got {:02X?}", bytes);
+        self.last_activity = Instant::now();
+        Ok(bytes)
+    }
+
+    /// Send `request` to every OBD-II ECU, and get each response with the address of the ECU
+    fn request(&mut self, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        let idle = self.last_activity.elapsed();
+        if idle > self.timing.idle_timeout {
+            debug!("request: idle for {:?}, initializing again", idle);
+            self.initialize()?;
+        } else if idle < self.timing.request_gap {
+            thread::sleep(self.timing.request_gap - idle);
+        }
+
+        for attempt in 0..=self.config.retries() {
+            if attempt > 0 {
+                // the gap before a request still applies
+                let delay = self
+                    .config
+                    .retry_delay(attempt - 1)
+                    .max(self.timing.request_gap);
+                debug!("request: no response, retrying in {:?}", delay);
+                thread::sleep(delay);
+            }
+            let responses = self.exchange(request)?;
+            if !responses.is_empty() {
+                return Ok(responses);
+            }
+        }
+        Err(crate::Error::NoData)
+    }
+
+    /// Send `request` once, and get the responses, which are empty if no ECU responded
+    fn exchange(&mut self, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        self.send(&encode(self.protocol, request))?;
+        let mut responses = Vec::new();
+        let mut timeout = self.timing.response_timeout;
+        loop {
+            let bytes = self.receive(timeout)?;
+            let mut pending = false;
+            for (source, data) in decode_messages(self.protocol, &bytes)? {
+                debug!("request: ECU {:02X} responded {:02X?}", source, data);
+                if matches!(data.as_slice(), [0x7F, _, 0x78, ..]) {
+                    pending = true;
+                    continue;
+                }
+                responses.push(EcuResponse {
+                    address: source.into(),
+                    data,
+                });
+            }
+            if !pending {
+                break;
+            }
+            timeout = RESPONSE_PENDING_TIMEOUT;
+        }
+        Ok(responses)
+    }
+
+    fn request_untagged(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(self.request(request)?.into_iter().map(|r| r.data).collect())
+    }
+}
+
+impl<T: KLineTransport> Obd2Device for KLineDevice<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[mode, pid])?;
+        strip_header(responses, &[0x40 | mode, pid])
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[mode])?;
+        strip_header(responses, &[0x40 | mode])
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(request)?;
+        strip_header(responses, &response_header(request))
+    }
+
+    /// Messages from the same ECU are joined
+    fn obd_request_tagged(&mut self, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        let mut responses: Vec<EcuResponse> = Vec::new();
+        for response in self.request(request)? {
+            match responses.iter_mut().find(|r| r.address == response.address) {
+                Some(joined) => joined.data.extend(response.data),
+                None => responses.push(response),
+            }
+        }
+        strip_tagged_header(responses, request)
+    }
+
+    /// `address` is the K-line address of the ECU, like `0x10` for the engine
+    ///
+    /// The request is still sent to every OBD-II ECU, and only the response from `address` is
+    /// kept.
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> crate::Result<Vec<u8>> {
+        let responses = self
+            .request(request)?
+            .into_iter()
+            .filter(|r| r.address == address)
+            .map(|r| r.data)
+            .collect();
+        single_response(strip_header(responses, &response_header(request))?)
+    }
+
+    fn protocol(&mut self) -> crate::Result<Protocol> {
+        Ok(self.protocol)
+    }
+}
+
+/// Build a functionally addressed request message with its header and checksum
+fn encode(protocol: Protocol, data: &[u8]) -> Vec<u8> {
+    let mut message = match protocol {
+        Protocol::Iso9141_2 => vec![0x68, 0x6A, TESTER_ADDRESS],
+        // KWP2000 requests from OBD-II testers are at most 7 bytes, so the length always fits in
+        // the format byte
+        _ => vec![
+            0xC0 | (data.len() as u8 & 0x3F),
+            OBD_ADDRESS,
+            TESTER_ADDRESS,
+        ],
+    };
+    message.extend_from_slice(data);
+    message.push(checksum(&message));
+    message
+}
+
+/// Split the bytes received after a request into each message's source address and data,
+/// checking their checksums
+///
+/// KWP2000 messages give their length in the header. ISO 9141-2 messages do not, so a message
+/// ends at the first byte that is the checksum of the bytes before it and is followed by the start
+/// of another message or the end of the data.
+fn decode_messages(protocol: Protocol, bytes: &[u8]) -> crate::Result<Vec<(u8, Vec<u8>)>> {
+    let mut messages = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let length = match protocol {
+            Protocol::Iso9141_2 => iso9141_length(rest),
+            _ => kwp2000_length(rest),
+        }
+        .ok_or(crate::Error::InvalidChecksum)?;
+        let (message, next) = rest.split_at(length);
+        let (body, sum) = message.split_at(length - 1);
+        if checksum(body) != sum[0] {
+            return Err(crate::Error::InvalidChecksum);
+        }
+        let data_start = match protocol {
+            Protocol::Iso9141_2 => 3,
+            _ if body[0] & 0x3F == 0 => 4,
+            _ => 3,
+        };
+        messages.push((body[2], body[data_start..].to_vec()));
+        rest = next;
+    }
+    Ok(messages)
+}
+
+/// The length of the KWP2000 message at the start of `bytes`, including its checksum
+fn kwp2000_length(bytes: &[u8]) -> Option<usize> {
+    let format = *bytes.first()?;
+    let length = match format & 0x3F {
+        0 => 5 + usize::from(*bytes.get(3)?),
+        length => 4 + usize::from(length),
+    };
+    (bytes.len() >= length).then_some(length)
+}
+
+/// The length of the ISO 9141-2 message at the start of `bytes`, including its checksum
+fn iso9141_length(bytes: &[u8]) -> Option<usize> {
+    const RESPONSE_HEADER: [u8; 2] = [0x48, 0x6B];
+    if !bytes.starts_with(&RESPONSE_HEADER) {
+        return None;
+    }
+    // a header, then 1 to 7 data bytes
+    (5..=bytes.len().min(11)).find(|&length| {
+        checksum(&bytes[..length - 1]) == bytes[length - 1]
+            && (length == bytes.len() || bytes[length..].starts_with(&RESPONSE_HEADER))
+    })
+}
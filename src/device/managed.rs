@@ -0,0 +1,231 @@
+use std::{thread, time::Duration, time::Instant};
+
+use super::CancellationToken;
+use crate::{EcuResponse, Error, Obd2Device, Protocol, Result};
+
+/// The state of the connection of a [ManagedDevice]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct ConnectionHealth {
+    /// Whether the device is connected, as far as is known
+    ///
+    /// This only becomes false when a request fails because the connection was lost, and true
+    /// again after reconnecting.
+    pub connected: bool,
+    /// How many times the device reconnected
+    pub reconnects: u32,
+    /// How many times connecting again failed
+    pub failed_reconnects: u32,
+    /// When a request last succeeded
+    pub last_success: Option<Instant>,
+    /// The last error that lost the connection
+    pub last_error: Option<String>,
+}
+
+/// Wraps an [Obd2Device] and reconnects it when the connection is lost
+///
+/// The device is created by the `connect` function, which runs again to reconnect when a request
+/// fails because the serial, TCP or Bluetooth link dropped, the adapter cannot reach the vehicle
+/// (`UNABLE TO CONNECT`), or the adapter stopped responding ([Error::Timeout]). Creating the device
+/// runs its whole init sequence, so `connect` should also select the protocol and any other
+/// settings the device needs, to restore them after reconnecting. The request that failed is then
+/// sent again, up to the maximum number of reconnects, before its error is returned.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Obd2DataRetrieval,
+///     device::{self, ManagedDevice, MockObd2Device},
+/// };
+/// use std::time::Duration;
+///
+/// let mut connections = 0;
+/// let mut device = ManagedDevice::new(move || {
+///     connections += 1;
+///     let mut device = MockObd2Device::new();
+///     if connections == 1 {
+///         // the first connection drops on the first request
+///         device.set_error(0x01, 0x0C, || {
+///             device::Error::Disconnected("adapter unplugged".to_owned()).into()
+///         });
+///     } else {
+///         device.set_response(0x01, 0x0C, [vec![0x0F, 0xA0]]);
+///     }
+///     Ok(device)
+/// })
+/// .unwrap()
+/// .with_reconnect_delay(Duration::ZERO);
+///
+/// assert_eq!(device.get_rpm().unwrap(), [1000.]);
+/// assert_eq!(device.health().reconnects, 1);
+/// assert!(device.health().connected);
+/// ```
+pub struct ManagedDevice<T: Obd2Device> {
+    connect: Box<dyn FnMut() -> Result<T> + Send>,
+    device: Option<T>,
+    max_reconnects: u32,
+    reconnect_delay: Duration,
+    cancellation: Option<CancellationToken>,
+    health: ConnectionHealth,
+}
+
+impl<T: Obd2Device> ManagedDevice<T> {
+    /// Connect with `connect`, and wrap the device
+    ///
+    /// By default, each request reconnects up to 3 times, waiting 500 ms before each attempt.
+    pub fn new(mut connect: impl FnMut() -> Result<T> + Send + 'static) -> Result<Self> {
+        let device = connect()?;
+        Ok(ManagedDevice {
+            connect: Box::new(connect),
+            device: Some(device),
+            max_reconnects: 3,
+            reconnect_delay: Duration::from_millis(500),
+            cancellation: None,
+            health: ConnectionHealth {
+                connected: true,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// Set how many times a request may reconnect before its error is returned
+    pub fn with_max_reconnects(mut self, max_reconnects: u32) -> Self {
+        self.max_reconnects = max_reconnects;
+        self
+    }
+
+    /// Set how long to wait before reconnecting, to give the adapter time to come back
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Stop reconnecting when `token` is cancelled
+    ///
+    /// Requests that lost the connection then fail with [Error::Cancelled] instead of waiting for
+    /// the remaining reconnects, so an application can shut down while the adapter is unreachable.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Get the state of the connection
+    pub fn health(&self) -> &ConnectionHealth {
+        &self.health
+    }
+
+    /// Get the wrapped device, if it is connected
+    pub fn device(&self) -> Option<&T> {
+        self.device.as_ref()
+    }
+
+    /// Get the wrapped device mutably, if it is connected
+    pub fn device_mut(&mut self) -> Option<&mut T> {
+        self.device.as_mut()
+    }
+
+    /// Drop the current connection and connect again
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.device = None;
+        self.connected().map(|_| ())
+    }
+
+    /// Check that the vehicle still responds, reconnecting if needed
+    ///
+    /// This requests the supported PIDs of service 1, which every vehicle responds to, so it can be
+    /// called while idle to find a lost connection before the next real request.
+    pub fn check_connection(&mut self) -> Result<()> {
+        self.run(|device| device.obd_command(0x01, 0x00))
+            .map(|_| ())
+    }
+
+    /// Get the device, connecting first if the connection was lost
+    fn connected(&mut self) -> Result<&mut T> {
+        let device = match self.device.take() {
+            Some(device) => device,
+            None => match (self.connect)() {
+                Ok(device) => {
+                    self.health.reconnects += 1;
+                    device
+                }
+                Err(e) => {
+                    self.health.failed_reconnects += 1;
+                    return Err(e);
+                }
+            },
+        };
+        self.health.connected = true;
+        Ok(self.device.insert(device))
+    }
+
+    /// Run `request` on the device, reconnecting and running it again when the connection is lost
+    fn run<R>(&mut self, mut request: impl FnMut(&mut T) -> Result<R>) -> Result<R> {
+        let mut reconnects = 0;
+        loop {
+            match self.connected().and_then(&mut request) {
+                Ok(response) => {
+                    self.health.last_success = Some(Instant::now());
+                    return Ok(response);
+                }
+                Err(e) if e.is_connection_lost() || matches!(e, Error::Timeout) => {
+                    log::debug!("ManagedDevice: connection lost: {}", e);
+                    self.device = None;
+                    self.health.connected = false;
+                    self.health.last_error = Some(e.to_string());
+                    if reconnects >= self.max_reconnects {
+                        return Err(e);
+                    }
+                    reconnects += 1;
+                    self.check_cancelled()?;
+                    thread::sleep(self.reconnect_delay);
+                    self.check_cancelled()?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        self.cancellation
+            .as_ref()
+            .map_or(Ok(()), CancellationToken::check)
+    }
+}
+
+impl<T: Obd2Device> Obd2Device for ManagedDevice<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.run(|device| device.obd_command(mode, pid))
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.run(|device| device.obd_mode_command(mode))
+    }
+
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.run(|device| device.obd_multi_command(mode, pids))
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.run(|device| device.obd_request(request))
+    }
+
+    fn obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>> {
+        self.run(|device| device.obd_command_22(did))
+    }
+
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        self.run(|device| device.obd_request_to(address, request))
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        self.run(|device| device.obd_request_tagged(request))
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        self.run(|device| device.protocol())
+    }
+
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        self.run(|device| device.get_adapter_voltage())
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use crate::{EcuResponse, Error, Obd2Device, Result};
+
+type ErrorFn = Box<dyn Fn() -> Error + Send>;
+
+enum MockResponse {
+    Data(Vec<Vec<u8>>),
+    Error(ErrorFn),
+}
+
+/// An OBD-II device that answers from a programmable table, for testing
+///
+/// Each request is identified by its bytes: the mode and, for requests that have them, the PID and
+/// other parameters. The table maps these to the data each ECU responds with (with the echoed
+/// request bytes already removed, as [Obd2Device::obd_command] returns them), or to an error to
+/// return instead. Requests that are not in the table return an error.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::MockObd2Device};
+///
+/// let mut device = MockObd2Device::new()
+///     .with_response(0x01, 0x0D, [vec![88]])
+///     .with_response(0x01, 0x0C, [vec![0x1A, 0xF8], vec![0x1A, 0xF8]]);
+///
+/// assert_eq!(device.get_speed().unwrap(), vec![88]);
+/// assert_eq!(device.get_rpm().unwrap(), vec![1726., 1726.]);
+/// assert!(device.get_engine_load().is_err());
+/// assert_eq!(device.requests(), [[0x01, 0x0D], [0x01, 0x0C], [0x01, 0x04]]);
+/// ```
+#[derive(Default)]
+pub struct MockObd2Device {
+    table: HashMap<Vec<u8>, MockResponse>,
+    requests: Vec<Vec<u8>>,
+}
+
+impl MockObd2Device {
+    /// Create a [`MockObd2Device`] that does not respond to any requests
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Respond to `mode` and `pid` with `responses`, one element for each ECU
+    pub fn with_response(
+        mut self,
+        mode: u8,
+        pid: u8,
+        responses: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Self {
+        self.set_response(mode, pid, responses);
+        self
+    }
+
+    /// Respond to `mode` and `pid` with `responses`, one element for each ECU
+    pub fn set_response(
+        &mut self,
+        mode: u8,
+        pid: u8,
+        responses: impl IntoIterator<Item = Vec<u8>>,
+    ) {
+        self.set_request_response(&[mode, pid], responses);
+    }
+
+    /// Respond to requests of `mode` without a PID with `responses`, one element for each ECU
+    pub fn set_mode_response(&mut self, mode: u8, responses: impl IntoIterator<Item = Vec<u8>>) {
+        self.set_request_response(&[mode], responses);
+    }
+
+    /// Respond to the request made of the bytes in `request` with `responses`, one element for
+    /// each ECU
+    pub fn set_request_response(
+        &mut self,
+        request: &[u8],
+        responses: impl IntoIterator<Item = Vec<u8>>,
+    ) {
+        self.table.insert(
+            request.to_vec(),
+            MockResponse::Data(responses.into_iter().collect()),
+        );
+    }
+
+    /// Return the error created by `error` when `mode` and `pid` are requested
+    pub fn set_error(&mut self, mode: u8, pid: u8, error: impl Fn() -> Error + Send + 'static) {
+        self.set_request_error(&[mode, pid], error);
+    }
+
+    /// Return the error created by `error` when `mode` is requested without a PID
+    pub fn set_mode_error(&mut self, mode: u8, error: impl Fn() -> Error + Send + 'static) {
+        self.set_request_error(&[mode], error);
+    }
+
+    /// Return the error created by `error` when the request made of the bytes in `request` is
+    /// sent
+    pub fn set_request_error(
+        &mut self,
+        request: &[u8],
+        error: impl Fn() -> Error + Send + 'static,
+    ) {
+        self.table
+            .insert(request.to_vec(), MockResponse::Error(Box::new(error)));
+    }
+
+    /// Stop responding to `mode` and `pid`
+    pub fn remove_response(&mut self, mode: u8, pid: Option<u8>) {
+        let mut request = vec![mode];
+        request.extend(pid);
+        self.table.remove(&request);
+    }
+
+    /// Get the bytes of every request made so far, in order
+    pub fn requests(&self) -> &[Vec<u8>] {
+        &self.requests
+    }
+
+    fn respond(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.requests.push(request.to_vec());
+        match self.table.get(request) {
+            Some(MockResponse::Data(responses)) => Ok(responses.clone()),
+            Some(MockResponse::Error(error)) => Err(error()),
+            None => Err(Error::NoRecordedResponse(request.to_vec())),
+        }
+    }
+}
+
+impl Obd2Device for MockObd2Device {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.respond(&[mode, pid])
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.respond(&[mode])
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.respond(request)
+    }
+
+    /// The ECUs are given the standard CAN request addresses in order, starting from `0x7E0`
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        let index = address.checked_sub(0x7E0).ok_or(Error::NoData)?;
+        self.respond(request)?
+            .into_iter()
+            .nth(index as usize)
+            .ok_or(Error::NoData)
+    }
+
+    /// The ECUs are given the standard CAN addresses in order, starting from `0x7E8`
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        Ok(self
+            .respond(request)?
+            .into_iter()
+            .zip(0x7E8..)
+            .map(|(data, address)| EcuResponse { address, data })
+            .collect())
+    }
+}
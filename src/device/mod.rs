@@ -1,9 +1,137 @@
 //! Lower level OBD-II interfacing structures
 
+#[cfg(feature = "std")]
 mod elm327;
-pub use elm327::Elm327;
+#[cfg(feature = "std")]
+pub use elm327::{Elm327, Elm327Transport};
+#[cfg(feature = "std")]
+mod elm327_core;
 
-type Result<T> = std::result::Result<T, Error>;
+#[cfg(any(feature = "std", feature = "embedded", feature = "web"))]
+mod at;
+
+#[cfg(feature = "std")]
+mod adapter;
+#[cfg(feature = "std")]
+pub use adapter::{AdapterInfo, AdapterQuirk};
+
+mod config;
+pub use config::{AdaptiveTiming, DeviceConfig};
+
+#[cfg(feature = "std")]
+mod stn;
+#[cfg(feature = "std")]
+pub use stn::StnExtensions;
+
+#[cfg(feature = "std")]
+mod cancel;
+#[cfg(feature = "std")]
+pub use cancel::{CancellableDevice, CancellationToken};
+
+#[cfg(feature = "std")]
+mod managed;
+#[cfg(feature = "std")]
+pub use managed::{ConnectionHealth, ManagedDevice};
+
+#[cfg(feature = "async")]
+mod elm327_async;
+#[cfg(feature = "async")]
+pub use elm327_async::{AsyncElm327, AsyncSerialElm327, AsyncTcpElm327};
+
+#[cfg(feature = "std")]
+mod bluetooth;
+#[cfg(feature = "std")]
+pub use bluetooth::{BluetoothSerial, Elm327Bluetooth};
+
+#[cfg(feature = "std")]
+mod tcp;
+#[cfg(feature = "std")]
+pub use tcp::{TcpElm327, TcpTransport, DEFAULT_TCP_ADDRESS};
+
+#[cfg(feature = "embedded")]
+mod embedded;
+#[cfg(feature = "embedded")]
+pub use embedded::EmbeddedElm327;
+
+#[cfg(feature = "web")]
+mod web;
+#[cfg(feature = "web")]
+pub use web::WebElm327;
+
+#[cfg(feature = "std")]
+mod discovery;
+#[cfg(feature = "std")]
+pub use discovery::{
+    discover, discover_bluetooth, discover_serial, discover_wifi, AdapterCandidate, AdapterLink,
+};
+
+#[cfg(feature = "std")]
+mod doip;
+#[cfg(feature = "std")]
+pub use doip::{DoIpDevice, VehicleAnnouncement, DEFAULT_TESTER_ADDRESS, DOIP_PORT};
+
+pub(crate) mod isotp;
+
+#[cfg(feature = "std")]
+pub(crate) mod kline;
+#[cfg(feature = "std")]
+pub use kline::{KLineDevice, KLineInit, KLineTiming, KLineTransport};
+
+#[cfg(feature = "std")]
+mod pipeline;
+#[cfg(feature = "std")]
+pub use pipeline::{PipelinedDevice, RequestPipeline};
+
+mod raw_can;
+pub use raw_can::{CanFilter, CanFrame, RawCanAccess};
+#[cfg(all(feature = "std", target_os = "linux"))]
+mod socketcan;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use socketcan::SocketCanDevice;
+
+#[cfg(feature = "std")]
+mod replay;
+#[cfg(feature = "std")]
+pub use replay::{RecordingDevice, ReplayDevice};
+
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub use shared::SharedObd2;
+
+#[cfg(feature = "std")]
+mod simulator;
+#[cfg(feature = "std")]
+pub use simulator::Obd2Simulator;
+
+#[cfg(feature = "std")]
+mod support;
+#[cfg(feature = "std")]
+pub use support::SupportCheckedDevice;
+
+#[cfg(feature = "std")]
+mod wwh_obd;
+#[cfg(feature = "std")]
+pub use wwh_obd::WwhObdDevice;
+
+#[cfg(test)]
+pub(crate) mod fake;
+
+#[cfg(feature = "test-util")]
+mod mock;
+#[cfg(feature = "test-util")]
+pub use mock::MockObd2Device;
+
+#[cfg(feature = "ble")]
+mod ble;
+#[cfg(feature = "ble")]
+pub use ble::{BleSerial, Elm327Ble};
+
+use crate::Protocol;
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+use core::time::Duration;
+
+type Result<T> = core::result::Result<T, Error>;
 
 /// A lower-level API for using an OBD-II device
 pub trait Obd2BaseDevice: Obd2Reader {
@@ -27,6 +155,95 @@ pub trait Obd2BaseDevice: Obd2Reader {
         self.get_response()
             .map(|o| o.and_then(|resp| String::from_utf8(resp).ok()))
     }
+
+    /// Send an OBD-II command and read the reply into `response`, which is cleared first
+    ///
+    /// Like [cmd](Self::cmd), but the reply goes in a buffer that can be reused for the next
+    /// command. Returns `Ok(false)` if there was no reply. The default implementation copies the
+    /// reply from [cmd](Self::cmd).
+    fn cmd_into(&mut self, cmd: &[u8], response: &mut Vec<u8>) -> Result<bool> {
+        response.clear();
+        match self.cmd(cmd)? {
+            Some(reply) => {
+                response.extend_from_slice(reply.as_bytes());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Send an OBD-II command and get the reply, with the header of each message
+    ///
+    /// Like [cmd](Self::cmd), but each line starts with the header of the message or CAN frame,
+    /// which identifies the ECU that sent it. Devices that cannot show headers return an error.
+    fn cmd_with_headers(&mut self, cmd: &[u8]) -> Result<Option<String>> {
+        Err(Error::Communication(format!(
+            "cmd_with_headers: device cannot show headers for {:02X?}",
+            cmd
+        )))
+    }
+
+    /// Send an OBD-II command to the ECU with the physical request `address` and get the reply
+    ///
+    /// Like [cmd](Self::cmd), but the request is only sent to one ECU instead of being broadcast.
+    /// Devices that cannot address a single ECU return an error.
+    fn cmd_to(&mut self, address: u32, cmd: &[u8]) -> Result<Option<String>> {
+        Err(Error::Communication(format!(
+            "cmd_to: device cannot send {:02X?} to ECU {:X}",
+            cmd, address
+        )))
+    }
+
+    /// Get the protocol the device uses to communicate with the vehicle
+    ///
+    /// Devices that cannot tell return an error.
+    fn protocol(&mut self) -> Result<Protocol> {
+        Err(Error::Communication(
+            "protocol: device cannot report its protocol".to_owned(),
+        ))
+    }
+
+    /// Get the timeouts and retries the device was configured with
+    ///
+    /// [Obd2](crate::Obd2) sends a request again when the device times out, as configured here.
+    fn config(&self) -> DeviceConfig {
+        DeviceConfig::default()
+    }
+
+    /// Measure the voltage of the vehicle's battery in volts
+    ///
+    /// Devices that cannot measure it return an error.
+    fn adapter_voltage(&mut self) -> Result<f32> {
+        Err(Error::Communication(
+            "adapter_voltage: device cannot measure the battery voltage".to_owned(),
+        ))
+    }
+
+    /// Request the SAE J1939 parameter group `pgn` from every ECU and get the reply
+    ///
+    /// Like [cmd](Self::cmd), but a J1939 request message is sent instead of an OBD-II request.
+    /// Devices that cannot use J1939 return an error.
+    fn cmd_j1939(&mut self, pgn: u32) -> Result<Option<String>> {
+        Err(Error::Communication(format!(
+            "cmd_j1939: device cannot request PGN {:X}",
+            pgn
+        )))
+    }
+
+    /// Wait for `duration`, like before sending a request again
+    ///
+    /// The default sleeps the thread.
+    #[cfg(feature = "std")]
+    fn delay(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+
+    /// Wait for `duration`, like before sending a request again
+    ///
+    /// Without the `std` feature there is no thread to sleep, so devices wait with their own
+    /// timer.
+    #[cfg(not(feature = "std"))]
+    fn delay(&mut self, duration: Duration);
 }
 
 /// An API for reading OBD-II response data
@@ -51,26 +268,75 @@ pub trait Obd2Reader {
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     /// An error with Serial
+    #[cfg(feature = "std")]
     #[error("Serial error: `{0:?}`")]
-    Serial(serial_rs::SerialError),
+    Serial(serialport::Error),
 
     /// An I/O error in a low-level [std::io] stream operation
+    #[cfg(feature = "std")]
     #[error("IO error: `{0:?}`")]
     IO(std::io::Error),
 
+    /// An error with Bluetooth Low Energy
+    #[cfg(feature = "ble")]
+    #[error("BLE error: `{0:?}`")]
+    Ble(btleplug::Error),
+
+    /// An error from the serial port of an [EmbeddedElm327]
+    #[cfg(feature = "embedded")]
+    #[error("Serial error: `{0:?}`")]
+    EmbeddedSerial(embedded_hal_nb::serial::ErrorKind),
+
     /// An OBD-II or interface device protocol error
     #[error("Communication error: `{0}`")]
     Communication(String),
+
+    /// The link to the adapter, or the adapter's link to the vehicle, was lost
+    ///
+    /// This includes a closed connection and an adapter reporting that it cannot reach the vehicle
+    /// (like `UNABLE TO CONNECT`). [ManagedDevice] reconnects when it happens.
+    #[error("Disconnected: `{0}`")]
+    Disconnected(String),
 }
 
-impl From<serial_rs::SerialError> for Error {
-    fn from(e: serial_rs::SerialError) -> Self {
+#[cfg(feature = "std")]
+impl From<serialport::Error> for Error {
+    fn from(e: serialport::Error) -> Self {
         Error::Serial(e)
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for Error {
     fn from(e: std::io::Error) -> Self {
         Error::IO(e)
     }
 }
+
+#[cfg(feature = "ble")]
+impl From<btleplug::Error> for Error {
+    fn from(e: btleplug::Error) -> Self {
+        Error::Ble(e)
+    }
+}
+
+/// Errors from the operating system and other libraries are logged with their `Debug` form
+#[cfg(feature = "defmt")]
+impl defmt::Format for Error {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            #[cfg(feature = "std")]
+            Error::Serial(e) => defmt::write!(f, "Serial error: {}", defmt::Debug2Format(e)),
+            #[cfg(feature = "std")]
+            Error::IO(e) => defmt::write!(f, "IO error: {}", defmt::Debug2Format(e)),
+            #[cfg(feature = "ble")]
+            Error::Ble(e) => defmt::write!(f, "BLE error: {}", defmt::Debug2Format(e)),
+            #[cfg(feature = "embedded")]
+            Error::EmbeddedSerial(kind) => {
+                defmt::write!(f, "Serial error: {}", defmt::Debug2Format(kind))
+            }
+            Error::Communication(e) => defmt::write!(f, "Communication error: {=str}", e),
+            Error::Disconnected(e) => defmt::write!(f, "Disconnected: {=str}", e),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use log::debug;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    interface::{pending_error, response_header, strip_tagged_header},
+    EcuResponse, Error, Result,
+};
+
+/// How long to wait for an ECU that responded that its response is pending (P2* in ISO 15765-4)
+const RESPONSE_PENDING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A device that can send a request before the responses to earlier ones have arrived
+///
+/// Adapters like the ELM327 handle one request at a time, but devices that are directly on the
+/// vehicle's network, like [SocketCanDevice](super::SocketCanDevice) and
+/// [DoIpDevice](super::DoIpDevice), can have several outstanding. [RequestPipeline] uses this to
+/// send requests without waiting for each one to finish.
+pub trait PipelinedDevice {
+    /// Send `request` to every ECU, without waiting for any response
+    fn send_request(&mut self, request: &[u8]) -> Result<()>;
+
+    /// Receive the next complete message from any ECU, or `None` if none arrives before
+    /// `deadline`
+    fn receive_response(&mut self, deadline: Instant) -> Result<Option<EcuResponse>>;
+
+    /// How long to wait for the first response to a request
+    fn first_response_timeout(&self) -> Duration;
+
+    /// How long to wait for more ECUs after one has responded
+    fn next_response_timeout(&self) -> Duration;
+}
+
+/// Sends several requests at once to a [PipelinedDevice], matching each response to its request
+///
+/// Instead of waiting for every response to one request before sending the next, up to the
+/// pipeline depth of requests are outstanding at once. Responses are matched to their request by
+/// the mode and parameters they echo, so requests that would get the same echo should not be in
+/// one run. A request is finished when no ECU has responded to it for the device's timeout, or
+/// as soon as the expected number of ECUs have, if it was set.
+///
+/// Negative responses (`7F <mode> <code>`) only echo the mode, so one is only matched when a
+/// single outstanding request with that mode is waiting for the ECU that sent it. Otherwise it
+/// cannot be told which request was rejected, and the response is ignored, so that request
+/// finishes without it. Requests that may be rejected can be sent with a depth of one to get
+/// every negative response.
+///
+/// # Example
+/// ```
+/// use obd2::{device::{PipelinedDevice, RequestPipeline}, EcuResponse, Error};
+/// use std::time::{Duration, Instant};
+///
+/// /// An ECU that answers every outstanding request at once, most recent first
+/// #[derive(Default)]
+/// struct Vehicle {
+///     requests: Vec<Vec<u8>>,
+/// }
+///
+/// impl PipelinedDevice for Vehicle {
+///     fn send_request(&mut self, request: &[u8]) -> Result<(), Error> {
+///         self.requests.push(request.to_vec());
+///         Ok(())
+///     }
+///
+///     fn receive_response(&mut self, _: Instant) -> Result<Option<EcuResponse>, Error> {
+///         Ok(self.requests.pop().map(|request| EcuResponse {
+///             address: 0x7E8,
+///             data: match request.as_slice() {
+///                 [0x01, 0x0C] => vec![0x41, 0x0C, 0x0F, 0xA0],
+///                 [0x01, 0x0D] => vec![0x41, 0x0D, 0x32],
+///                 _ => vec![0x7F, request[0], 0x12],
+///             },
+///         }))
+///     }
+///
+///     fn first_response_timeout(&self) -> Duration {
+///         Duration::from_millis(10)
+///     }
+///
+///     fn next_response_timeout(&self) -> Duration {
+///         Duration::from_millis(10)
+///     }
+/// }
+///
+/// let mut pipeline = RequestPipeline::new(Vehicle::default()).with_expected_responses(1);
+/// let results = pipeline.run([[0x01, 0x0C], [0x01, 0x0D]]).unwrap();
+/// assert_eq!(results[0].as_ref().unwrap()[0].data, [0x0F, 0xA0]);
+/// assert_eq!(results[1].as_ref().unwrap()[0].data, [0x32]);
+/// ```
+pub struct RequestPipeline<T: PipelinedDevice> {
+    device: T,
+    depth: usize,
+    expected_responses: Option<usize>,
+}
+
+/// A request that was sent and is waiting for responses
+struct Outstanding {
+    index: usize,
+    request: Vec<u8>,
+    responses: Vec<EcuResponse>,
+    pending: bool,
+    deadline: Instant,
+}
+
+impl<T: PipelinedDevice> RequestPipeline<T> {
+    /// Create a [`RequestPipeline`] sending requests with `device`, with up to 4 outstanding
+    pub fn new(device: T) -> Self {
+        RequestPipeline {
+            device,
+            depth: 4,
+            expected_responses: None,
+        }
+    }
+
+    /// Set how many requests may be outstanding at once, at least one
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Finish a request as soon as `count` ECUs have responded, instead of waiting for more
+    pub fn with_expected_responses(mut self, count: usize) -> Self {
+        self.expected_responses = Some(count);
+        self
+    }
+
+    /// Get the wrapped device
+    pub fn device(&self) -> &T {
+        &self.device
+    }
+
+    /// Get the wrapped device mutably
+    pub fn device_mut(&mut self) -> &mut T {
+        &mut self.device
+    }
+
+    /// Get back the wrapped device
+    pub fn into_inner(self) -> T {
+        self.device
+    }
+
+    /// Send every request in `requests` and get the responses to each, in the same order
+    ///
+    /// Each result is like the result of
+    /// [obd_request_tagged](crate::Obd2Device::obd_request_tagged) for that request, with the
+    /// echoed request removed. An error sending or receiving stops the whole run.
+    pub fn run<R: AsRef<[u8]>>(
+        &mut self,
+        requests: impl IntoIterator<Item = R>,
+    ) -> Result<Vec<Result<Vec<EcuResponse>>>> {
+        let mut requests = requests
+            .into_iter()
+            .map(|r| r.as_ref().to_vec())
+            .enumerate()
+            .peekable();
+        let mut results: Vec<Option<Result<Vec<EcuResponse>>>> = Vec::new();
+        let mut outstanding: VecDeque<Outstanding> = VecDeque::new();
+        loop {
+            while outstanding.len() < self.depth {
+                let Some((index, request)) = requests.next() else {
+                    break;
+                };
+                self.device.send_request(&request)?;
+                results.push(None);
+                outstanding.push_back(Outstanding {
+                    index,
+                    request,
+                    responses: Vec::new(),
+                    pending: false,
+                    deadline: Instant::now() + self.device.first_response_timeout(),
+                });
+            }
+
+            let now = Instant::now();
+            let (finished, waiting): (VecDeque<_>, VecDeque<_>) =
+                outstanding.into_iter().partition(|r| {
+                    r.deadline <= now
+                        || (!r.pending
+                            && self
+                                .expected_responses
+                                .is_some_and(|count| r.responses.len() >= count))
+                });
+            outstanding = waiting;
+            for request in finished {
+                let index = request.index;
+                results[index] = Some(finish(request));
+            }
+            if outstanding.is_empty() && requests.peek().is_none() {
+                break;
+            }
+            if outstanding.len() < self.depth && requests.peek().is_some() {
+                continue;
+            }
+
+            let deadline = outstanding.iter().map(|r| r.deadline).min().unwrap_or(now);
+            let Some(response) = self.device.receive_response(deadline)? else {
+                continue;
+            };
+            let next_response_timeout = self.device.next_response_timeout();
+            let mut candidates = outstanding.iter_mut().filter(|r| {
+                is_response_to(&r.request, &response.data)
+                    && !r.responses.iter().any(|p| p.address == response.address)
+            });
+            match response.data.as_slice() {
+                // the ECU needs more time, and every request it could be for waits for it
+                [0x7F, _, 0x78, ..] => {
+                    for request in candidates {
+                        request.pending = true;
+                        request.deadline = Instant::now() + RESPONSE_PENDING_TIMEOUT;
+                    }
+                }
+                [0x7F, ..] => match (candidates.next(), candidates.next()) {
+                    (Some(request), None) => {
+                        request.accept(response, next_response_timeout);
+                    }
+                    (None, _) => debug!("run: ignoring unexpected response {:02X?}", response),
+                    _ => debug!(
+                        "run: ignoring ambiguous negative response {:02X?}",
+                        response
+                    ),
+                },
+                _ => match candidates.next() {
+                    Some(request) => request.accept(response, next_response_timeout),
+                    None => debug!("run: ignoring unexpected response {:02X?}", response),
+                },
+            }
+        }
+        Ok(results
+            .into_iter()
+            .map(|r| r.unwrap_or(Err(Error::NoData)))
+            .collect())
+    }
+}
+
+impl Outstanding {
+    /// Add an ECU's final response, and wait for more ECUs
+    fn accept(&mut self, response: EcuResponse, next_response_timeout: Duration) {
+        self.pending = false;
+        self.responses.push(response);
+        self.deadline = Instant::now() + next_response_timeout;
+    }
+}
+
+/// Whether `response` answers `request`, from the mode and parameters it echoes
+///
+/// Negative responses only echo the mode.
+fn is_response_to(request: &[u8], response: &[u8]) -> bool {
+    match response {
+        [0x7F, mode, ..] => request.first() == Some(mode),
+        _ => response.starts_with(&response_header(request)),
+    }
+}
+
+/// The result of a request that has stopped waiting for responses
+fn finish(request: Outstanding) -> Result<Vec<EcuResponse>> {
+    if !request.responses.is_empty() {
+        strip_tagged_header(request.responses, &request.request)
+    } else if request.pending {
+        Err(pending_error(&request.request))
+    } else {
+        Err(Error::NoData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NegativeResponseCode;
+
+    /// An ECU at 0x7E8 that answers the requests in order, supporting only PID 0x0C
+    #[derive(Default)]
+    struct Ecu {
+        requests: VecDeque<Vec<u8>>,
+    }
+
+    impl PipelinedDevice for Ecu {
+        fn send_request(&mut self, request: &[u8]) -> Result<()> {
+            self.requests.push_back(request.to_vec());
+            Ok(())
+        }
+
+        fn receive_response(&mut self, _: Instant) -> Result<Option<EcuResponse>> {
+            let Some(request) = self.requests.pop_front() else {
+                return Ok(None);
+            };
+            Ok(Some(EcuResponse {
+                address: 0x7E8,
+                data: match request.as_slice() {
+                    [0x01, 0x0C] => vec![0x41, 0x0C, 0x0F, 0xA0],
+                    _ => vec![0x7F, request[0], 0x12],
+                },
+            }))
+        }
+
+        fn first_response_timeout(&self) -> Duration {
+            Duration::from_millis(10)
+        }
+
+        fn next_response_timeout(&self) -> Duration {
+            Duration::from_millis(10)
+        }
+    }
+
+    fn run(depth: usize, requests: &[&[u8]]) -> Vec<Result<Vec<EcuResponse>>> {
+        RequestPipeline::new(Ecu::default())
+            .with_depth(depth)
+            .run(requests)
+            .unwrap()
+    }
+
+    #[test]
+    fn negative_response_with_one_request_of_its_mode() {
+        let results = run(2, &[&[0x01, 0x0C], &[0x09, 0x02]]);
+        assert_eq!(results[0].as_ref().unwrap()[0].data, [0x0F, 0xA0]);
+        assert!(matches!(
+            results[1],
+            Err(Error::NegativeResponse {
+                mode: 0x09,
+                nrc: NegativeResponseCode::SubFunctionNotSupported,
+            })
+        ));
+    }
+
+    #[test]
+    fn negative_response_with_several_requests_of_its_mode() {
+        // the rejection of PID 0x0D cannot be told apart from one of PID 0x0E
+        let results = run(3, &[&[0x01, 0x0D], &[0x01, 0x0C], &[0x01, 0x0E]]);
+        assert_eq!(results[1].as_ref().unwrap()[0].data, [0x0F, 0xA0]);
+        assert!(matches!(results[0], Err(Error::NoData)));
+        assert!(matches!(results[2], Err(Error::NoData)));
+
+        let results = run(1, &[&[0x01, 0x0D], &[0x01, 0x0C]]);
+        assert!(matches!(
+            results[0],
+            Err(Error::NegativeResponse { mode: 0x01, .. })
+        ));
+        assert!(results[1].is_ok());
+    }
+}
@@ -0,0 +1,115 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use super::{Obd2BaseDevice, Result};
+use crate::Obd2;
+
+/// A classic CAN frame
+///
+/// Identifiers up to `7FF` are sent as 11-bit identifiers, and larger ones as 29-bit identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CanFrame {
+    /// The CAN identifier
+    pub id: u32,
+    /// The data, up to 8 bytes
+    pub data: Vec<u8>,
+}
+
+/// Which frames to receive with [RawCanAccess::receive_frames]
+///
+/// A frame matches if the bits of its identifier that are set in `mask` are equal to those bits of
+/// `id`.
+///
+/// # Example
+/// ```
+/// use obd2::device::CanFilter;
+///
+/// // the responses from every OBD-II ECU, 7E8 to 7EF
+/// let filter = CanFilter { id: 0x7E8, mask: 0x7F8 };
+/// assert!(filter.matches(0x7E9));
+/// assert!(!filter.matches(0x7DF));
+/// assert!(CanFilter::exact(0x0B4).matches(0x0B4));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CanFilter {
+    /// The identifier bits to match
+    pub id: u32,
+    /// Which bits of the identifier to compare
+    pub mask: u32,
+}
+
+impl CanFilter {
+    /// Match only the identifier `id`
+    pub fn exact(id: u32) -> Self {
+        CanFilter {
+            id,
+            mask: 0x1FFF_FFFF,
+        }
+    }
+
+    /// Whether a frame with the identifier `id` matches
+    pub fn matches(&self, id: u32) -> bool {
+        id & self.mask == self.id & self.mask
+    }
+}
+
+/// Whether a frame with the identifier `id` matches any of `filters`, or there are no filters
+#[cfg(feature = "std")]
+pub(super) fn matches_any(filters: &[CanFilter], id: u32) -> bool {
+    filters.is_empty() || filters.iter().any(|f| f.matches(id))
+}
+
+/// Sending and receiving raw CAN frames
+///
+/// This gives access to the traffic on the bus beyond OBD-II, like the frames the vehicle's
+/// modules broadcast to each other (wheel speeds, steering angle and so on). Their meaning is
+/// specific to the manufacturer. The same device can still be used for OBD-II requests between
+/// calls.
+pub trait RawCanAccess {
+    /// Send `frame` without waiting for any response
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<()>;
+
+    /// Receive the frames on the bus matching any of `filters` (or every frame, if there are no
+    /// filters) for `duration`
+    fn receive_frames(
+        &mut self,
+        filters: &[CanFilter],
+        duration: Duration,
+    ) -> Result<Vec<CanFrame>>;
+}
+
+impl<T: Obd2BaseDevice + RawCanAccess> RawCanAccess for Obd2<T> {
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<()> {
+        self.device.send_frame(frame)
+    }
+
+    fn receive_frames(
+        &mut self,
+        filters: &[CanFilter],
+        duration: Duration,
+    ) -> Result<Vec<CanFrame>> {
+        self.device.receive_frames(filters, duration)
+    }
+}
+
+/// Parse a line printed by an ELM327 monitoring the bus with headers on, like
+/// `7E8 03 41 0D 00 AA AA AA AA` or `18 DA F1 10 03 41 0D 00 AA AA AA AA`
+#[cfg(feature = "std")]
+pub(super) fn parse_monitor_line(line: &str) -> Option<CanFrame> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let (id, data) = match fields.first()?.len() {
+        3 => (u32::from_str_radix(fields[0], 16).ok()?, &fields[1..]),
+        2 if fields.len() >= 4 => (
+            u32::from_str_radix(&fields[..4].concat(), 16).ok()?,
+            &fields[4..],
+        ),
+        _ => return None,
+    };
+    let data = data
+        .iter()
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    (data.len() <= 8).then_some(CanFrame { id, data })
+}
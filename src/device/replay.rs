@@ -0,0 +1,314 @@
+use log::debug;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use crate::{Error, Obd2Device, Protocol, Result};
+
+/// Wraps an [Obd2Device] and logs every request and its response
+///
+/// Each request is written as one line, so that a session with a real vehicle can be played back
+/// later with a [ReplayDevice]. Lines look like `01 0C => 1A F8 | 1A F8`: the bytes of the request
+/// (the mode, then the PID and other parameters, if any), then each ECU's response separated by
+/// `|`. An ECU's response with no data after the header is written as `-`, so that it is not
+/// confused with no ECU responding, which leaves nothing after the `=>`. A request that failed is
+/// written as `01 0C !! <error message>`.
+pub struct RecordingDevice<T: Obd2Device> {
+    device: T,
+    log: Box<dyn Write + Send>,
+}
+
+impl<T: Obd2Device> RecordingDevice<T> {
+    /// Record requests to `device` into a new file at `path`
+    ///
+    /// If the file exists, it is overwritten.
+    pub fn new(device: T, path: impl AsRef<Path>) -> Result<Self> {
+        let file = fs::File::create(path).map_err(crate::device::Error::from)?;
+        Ok(Self::with_writer(device, io::BufWriter::new(file)))
+    }
+
+    /// Record requests to `device` into `log`
+    pub fn with_writer(device: T, log: impl Write + Send + 'static) -> Self {
+        RecordingDevice {
+            device,
+            log: Box::new(log),
+        }
+    }
+
+    /// Stop recording and get back the wrapped device
+    pub fn into_inner(mut self) -> T {
+        let _ = self.log.flush();
+        self.device
+    }
+
+    fn record(&mut self, request: &[u8], result: Result<Vec<Vec<u8>>>) -> Result<Vec<Vec<u8>>> {
+        let request = format_bytes(request);
+        let line = match &result {
+            Ok(responses) => format!(
+                "{} => {}",
+                request,
+                responses
+                    .iter()
+                    .map(|r| format_response(r))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            ),
+            Err(e) => format!("{} !! {}", request, e.to_string().replace('\n', " ")),
+        };
+
+        writeln!(self.log, "{}", line)
+            .and_then(|_| self.log.flush())
+            .map_err(crate::device::Error::from)?;
+        result
+    }
+}
+
+impl<T: Obd2Device> Obd2Device for RecordingDevice<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        let result = self.device.obd_command(mode, pid);
+        self.record(&[mode, pid], result)
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        let result = self.device.obd_mode_command(mode);
+        self.record(&[mode], result)
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let result = self.device.obd_request(request);
+        self.record(request, result)
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        self.device.protocol()
+    }
+
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        self.device.get_adapter_voltage()
+    }
+}
+
+type Recorded = std::result::Result<Vec<Vec<u8>>, String>;
+
+/// Plays back a log written by a [RecordingDevice]
+///
+/// Responses are given back in the order they were recorded for each request, independent of the
+/// order of other requests. Once every recorded response for a request has been used, further
+/// requests for it return [Error::NoRecordedResponse]. Recorded failures are returned as
+/// [Error::Recorded] with the original error message, except that no data and timeouts are
+/// returned as [Error::NoData] and [Error::Timeout].
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::ReplayDevice};
+///
+/// let log = "01 0D => 58\n01 0D => 5A\n01 0C !! no response to command\n";
+/// let mut device = ReplayDevice::from_reader(log.as_bytes()).unwrap();
+///
+/// assert_eq!(device.get_speed().unwrap(), vec![0x58]);
+/// assert_eq!(device.get_speed().unwrap(), vec![0x5A]);
+/// assert!(device.get_rpm().is_err());
+/// ```
+pub struct ReplayDevice {
+    responses: HashMap<Vec<u8>, VecDeque<Recorded>>,
+}
+
+impl ReplayDevice {
+    /// Load a log from the file at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = fs::File::open(path).map_err(crate::device::Error::from)?;
+        Self::from_reader(io::BufReader::new(file))
+    }
+
+    /// Load a log from `reader`
+    pub fn from_reader(reader: impl BufRead) -> Result<Self> {
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line.map_err(crate::device::Error::from)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some((request, response)) = parse_line(&line) else {
+                return Err(Error::InvalidLogLine {
+                    line: n + 1,
+                    text: line,
+                });
+            };
+            responses.entry(request).or_default().push_back(response);
+        }
+        Ok(ReplayDevice { responses })
+    }
+
+    fn replay(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let response = self
+            .responses
+            .get_mut(request)
+            .and_then(|r| r.pop_front())
+            .ok_or_else(|| Error::NoRecordedResponse(request.to_vec()))?;
+        debug!("replay: {:02X?} => {:02X?}", request, response);
+        response.map_err(recorded_error)
+    }
+}
+
+impl Obd2Device for ReplayDevice {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.replay(&[mode, pid])
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.replay(&[mode])
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.replay(request)
+    }
+}
+
+/// Get back a recorded error from its message, where errors other than no data and timeouts are
+/// [Error::Recorded]
+fn recorded_error(message: String) -> Error {
+    [Error::NoData, Error::Timeout]
+        .into_iter()
+        .find(|e| e.to_string() == message)
+        .unwrap_or(Error::Recorded(message))
+}
+
+fn format_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Format one ECU's response, where an empty response is `-`
+fn format_response(response: &[u8]) -> String {
+    if response.is_empty() {
+        EMPTY_RESPONSE.to_owned()
+    } else {
+        format_bytes(response)
+    }
+}
+
+/// How an ECU's response with no data is written
+const EMPTY_RESPONSE: &str = "-";
+
+fn parse_bytes(s: &str) -> Option<Vec<u8>> {
+    s.split_whitespace()
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<(Vec<u8>, Recorded)> {
+    // the space after `=>` may have been trimmed when there are no responses
+    let responses = line
+        .split_once(" => ")
+        .or_else(|| Some((line.strip_suffix(" =>")?, "")));
+    let (request, response) = match responses {
+        Some((request, data)) => {
+            let data = if data.trim().is_empty() {
+                Vec::new()
+            } else {
+                data.split('|')
+                    .map(|r| match r.trim() {
+                        EMPTY_RESPONSE => Some(Vec::new()),
+                        r => parse_bytes(r),
+                    })
+                    .collect::<Option<_>>()?
+            };
+            (request, Ok(data))
+        }
+        None => {
+            let (request, message) = line.split_once(" !! ")?;
+            (request, Err(message.to_owned()))
+        }
+    };
+
+    let request = parse_bytes(request)?;
+    if request.is_empty() {
+        return None;
+    }
+    Some((request, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A log that can still be read after the recording device takes it
+    #[derive(Clone, Default)]
+    struct SharedLog(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedLog {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Answers each request with the next of `responses`
+    struct Scripted(VecDeque<Vec<Vec<u8>>>);
+
+    impl Obd2Device for Scripted {
+        fn obd_command(&mut self, _mode: u8, _pid: u8) -> Result<Vec<Vec<u8>>> {
+            self.0.pop_front().ok_or(Error::NoData)
+        }
+
+        fn obd_mode_command(&mut self, _mode: u8) -> Result<Vec<Vec<u8>>> {
+            self.0.pop_front().ok_or(Error::NoData)
+        }
+    }
+
+    #[test]
+    fn empty_responses_round_trip() {
+        let responses = vec![vec![], vec![vec![]], vec![vec![], vec![0x1A, 0xF8]]];
+        let log = SharedLog::default();
+        let mut device =
+            RecordingDevice::with_writer(Scripted(responses.clone().into()), log.clone());
+        for _ in 0..responses.len() {
+            device.obd_command(0x01, 0x0C).unwrap();
+        }
+
+        let log = log.0.lock().unwrap().clone();
+        assert_eq!(
+            String::from_utf8(log.clone()).unwrap(),
+            "01 0C => \n01 0C => -\n01 0C => - | 1A F8\n"
+        );
+        let mut replay = ReplayDevice::from_reader(log.as_slice()).unwrap();
+        for expected in responses {
+            assert_eq!(replay.obd_command(0x01, 0x0C).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn trimmed_line_without_responses() {
+        let mut replay = ReplayDevice::from_reader("01 0C =>\n".as_bytes()).unwrap();
+        assert!(replay.obd_command(0x01, 0x0C).unwrap().is_empty());
+    }
+
+    #[test]
+    fn recorded_errors() {
+        let log = "01 0C !! No data\n01 0C !! Device error: `cable cut`\n";
+        let mut replay = ReplayDevice::from_reader(log.as_bytes()).unwrap();
+        assert!(matches!(replay.obd_command(0x01, 0x0C), Err(Error::NoData)));
+        assert!(matches!(
+            replay.obd_command(0x01, 0x0C),
+            Err(Error::Recorded(message)) if message == "Device error: `cable cut`"
+        ));
+        assert!(matches!(
+            replay.obd_command(0x01, 0x0C),
+            Err(Error::NoRecordedResponse(request)) if request == [0x01, 0x0C]
+        ));
+        assert!(matches!(
+            ReplayDevice::from_reader("01 0C => 1A\n01 0C ?? \n".as_bytes()),
+            Err(Error::InvalidLogLine { line: 2, .. })
+        ));
+    }
+}
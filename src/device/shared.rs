@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{EcuResponse, Obd2Device, Protocol, Result};
+
+/// Shares an [Obd2Device] between threads
+///
+/// The device is kept behind a mutex, and `&SharedObd2` implements [Obd2Device], so every thread
+/// holding a clone can send requests and use the commands in [commands](crate::commands) without
+/// its own locking. Each request locks the device only while it is sent and its responses are
+/// received, so a logger and a user interface can read values in turns. For a series of requests
+/// that should not be interleaved with other threads', hold the device with [lock](Self::lock).
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Obd2DataRetrieval,
+///     device::{Obd2Simulator, SharedObd2},
+/// };
+/// use std::thread;
+///
+/// let device = SharedObd2::new(Obd2Simulator::new());
+///
+/// let logger = device.clone();
+/// let logging = thread::spawn(move || (&logger).get_rpm().unwrap());
+///
+/// assert_eq!((&device).get_speed().unwrap(), [0]);
+/// assert!(logging.join().unwrap()[0] > 0.);
+/// ```
+pub struct SharedObd2<T> {
+    device: Arc<Mutex<T>>,
+}
+
+impl<T: Obd2Device> SharedObd2<T> {
+    /// Create a [`SharedObd2`] holding `device`
+    pub fn new(device: T) -> Self {
+        SharedObd2 {
+            device: Arc::new(Mutex::new(device)),
+        }
+    }
+
+    /// Lock the device, waiting until no other thread is using it
+    ///
+    /// Other threads' requests wait until the guard is dropped. A thread that panicked while
+    /// holding the device does not stop others from using it.
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.device.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get back the device, or `None` if other clones of this [`SharedObd2`] still exist
+    pub fn into_inner(self) -> Option<T> {
+        Arc::into_inner(self.device)
+            .map(|device| device.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl<T> Clone for SharedObd2<T> {
+    fn clone(&self) -> Self {
+        SharedObd2 {
+            device: Arc::clone(&self.device),
+        }
+    }
+}
+
+impl<T: Obd2Device> Obd2Device for &SharedObd2<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.lock().obd_command(mode, pid)
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.lock().obd_mode_command(mode)
+    }
+
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.lock().obd_multi_command(mode, pids)
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.lock().obd_request(request)
+    }
+
+    fn obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>> {
+        self.lock().obd_command_22(did)
+    }
+
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        self.lock().obd_request_to(address, request)
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        self.lock().obd_request_tagged(request)
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        self.lock().protocol()
+    }
+
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        self.lock().get_adapter_voltage()
+    }
+}
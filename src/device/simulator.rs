@@ -0,0 +1,268 @@
+use std::time;
+
+use crate::{commands::Dtc, EcuResponse, Error, Obd2Device, Protocol, Result};
+
+/// Service 1 PIDs the simulator responds to
+const SUPPORTED_PIDS: [u8; 20] = [
+    0x00, 0x01, 0x03, 0x04, 0x05, 0x06, 0x07, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F, 0x10, 0x11, 0x13, 0x14,
+    0x15, 0x1C, 0x1F, 0x20,
+];
+
+/// CAN address of the simulated engine controller's responses, which is requested at `0x7E0`
+const ENGINE_ADDRESS: u32 = 0x7E8;
+
+/// Length of the simulated drive cycle, which repeats
+const CYCLE: f32 = 60.;
+
+/// An OBD-II device that emulates a vehicle, for examples and testing without an adapter
+///
+/// The simulated vehicle has a single ECU (the engine controller, at CAN address `0x7E8`) which
+/// reports a VIN, the service 1 PIDs it supports, and any DTCs (confirmed, pending, or permanent)
+/// it was created with. If there are confirmed DTCs, freeze frame 0 holds the conditions when the
+/// first one was set, and service 4 clears the confirmed and pending DTCs. Live values follow a
+/// repeating one minute drive cycle: ten seconds of idling, twenty seconds accelerating to
+/// 100 km/h, twenty seconds of cruising, and ten seconds of braking to a stop. The engine warms up
+/// over the first five minutes.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::Obd2Simulator};
+///
+/// let mut device = Obd2Simulator::new().with_vin("1G1JC5444R7252367");
+///
+/// assert_eq!(device.get_vin().unwrap(), "1G1JC5444R7252367");
+/// assert_eq!(device.get_speed().unwrap(), vec![0]);
+/// ```
+pub struct Obd2Simulator {
+    vin: String,
+    dtcs: Vec<Dtc>,
+    pending_dtcs: Vec<Dtc>,
+    permanent_dtcs: Vec<Dtc>,
+    start: time::Instant,
+}
+
+impl Default for Obd2Simulator {
+    fn default() -> Self {
+        Obd2Simulator {
+            vin: "1HGCM82633A004352".to_owned(),
+            dtcs: Vec::new(),
+            pending_dtcs: Vec::new(),
+            permanent_dtcs: Vec::new(),
+            start: time::Instant::now(),
+        }
+    }
+}
+
+impl Obd2Simulator {
+    /// Create a [`Obd2Simulator`] for a vehicle with no DTCs, which starts its engine now
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the VIN reported by the vehicle
+    pub fn with_vin(mut self, vin: impl Into<String>) -> Self {
+        self.vin = vin.into();
+        self
+    }
+
+    /// Set the DTCs stored in the vehicle
+    ///
+    /// If there are any, the malfunction indicator light will be reported as on.
+    pub fn with_dtcs(mut self, dtcs: impl IntoIterator<Item = Dtc>) -> Self {
+        self.dtcs = dtcs.into_iter().collect();
+        self
+    }
+
+    /// Set the pending DTCs, which have not yet been confirmed
+    pub fn with_pending_dtcs(mut self, dtcs: impl IntoIterator<Item = Dtc>) -> Self {
+        self.pending_dtcs = dtcs.into_iter().collect();
+        self
+    }
+
+    /// Set the permanent DTCs, which cannot be cleared
+    pub fn with_permanent_dtcs(mut self, dtcs: impl IntoIterator<Item = Dtc>) -> Self {
+        self.permanent_dtcs = dtcs.into_iter().collect();
+        self
+    }
+
+    /// Restart the engine, so that the drive cycle and warm up begin again
+    pub fn restart(&mut self) {
+        self.start = time::Instant::now();
+    }
+
+    fn elapsed(&self) -> f32 {
+        self.start.elapsed().as_secs_f32()
+    }
+
+    /// Vehicle speed in km/h at the current point of the drive cycle
+    fn speed(&self) -> f32 {
+        match self.elapsed() % CYCLE {
+            t if t < 10. => 0.,
+            t if t < 30. => (t - 10.) * 5.,
+            t if t < 50. => 100.,
+            t => (CYCLE - t) * 10.,
+        }
+    }
+
+    /// Engine speed in RPM, from the speed in a single gear ratio plus idle
+    fn rpm(&self) -> f32 {
+        800. + self.speed() * 18.
+    }
+
+    /// Coolant temperature in ºC
+    fn coolant_temperature(&self) -> f32 {
+        20. + (self.elapsed() / 300.).min(1.) * 70.
+    }
+
+    /// Engine load as a fraction, which is highest while accelerating
+    fn load(&self) -> f32 {
+        match self.elapsed() % CYCLE {
+            t if (10. ..30.).contains(&t) => 0.7,
+            t if (30. ..50.).contains(&t) => 0.35,
+            _ => 0.2,
+        }
+    }
+
+    fn service_1(&self, pid: u8) -> Option<Vec<u8>> {
+        Some(match pid {
+            0x00 | 0x20 => support_bitmap(pid).to_be_bytes().to_vec(),
+            0x01 => {
+                let mil = if self.dtcs.is_empty() { 0 } else { 0x80 };
+                vec![mil | self.dtcs.len().min(0x7f) as u8, 0x07, 0xE5, 0x00]
+            }
+            0x03 => vec![0x02, 0x00],
+            0x04 => vec![(self.load() * 255.) as u8],
+            0x05 => vec![(self.coolant_temperature() + 40.) as u8],
+            0x06 | 0x07 => vec![0x80],
+            0x0B => vec![(20. + self.load() * 80.) as u8],
+            0x0C => ((self.rpm() * 4.) as u16).to_be_bytes().to_vec(),
+            0x0D => vec![self.speed() as u8],
+            0x0E => vec![148], // 10º BTDC
+            0x0F => vec![65],  // 25 ºC
+            0x10 => ((self.rpm() * self.load() * 0.05 * 100.) as u16)
+                .to_be_bytes()
+                .to_vec(),
+            0x11 => vec![(30. + self.load() * 150.) as u8],
+            0x13 => vec![0x03],
+            0x14 | 0x15 => vec![0x5A, 0x80],
+            0x1C => vec![0x01],
+            0x1F => (self.elapsed().min(f32::from(u16::MAX)) as u16)
+                .to_be_bytes()
+                .to_vec(),
+            _ => return None,
+        })
+    }
+
+    /// Service 2 PIDs for freeze frame 0, stored while accelerating hard with a warm engine
+    fn freeze_frame(&self, pid: u8) -> Option<Vec<u8>> {
+        let dtc = self.dtcs.first()?;
+        Some(match pid {
+            0x02 => dtc_to_u16(dtc).to_be_bytes().to_vec(),
+            0x04 => vec![178],
+            0x05 => vec![130],
+            0x06 | 0x07 => vec![0x80],
+            0x0C => 10400u16.to_be_bytes().to_vec(), // 2600 RPM
+            0x0D => vec![45],
+            _ => return None,
+        })
+    }
+
+    fn respond(&self, response: Option<Vec<u8>>) -> Result<Vec<Vec<u8>>> {
+        response.map(|r| vec![r]).ok_or(Error::NoData)
+    }
+}
+
+/// Build the support bitmap for the 32 PIDs following `base`
+fn support_bitmap(base: u8) -> u32 {
+    SUPPORTED_PIDS
+        .iter()
+        .filter(|&&pid| pid > base && u32::from(pid) <= u32::from(base) + 32)
+        .fold(0, |bitmap, &pid| bitmap | (1 << (32 - (pid - base))))
+}
+
+fn dtc_to_u16(dtc: &Dtc) -> u16 {
+    let (category, n) = match dtc {
+        Dtc::Powertrain(n) => (0, n),
+        Dtc::Chassis(n) => (1, n),
+        Dtc::Body(n) => (2, n),
+        Dtc::Network(n) => (3, n),
+    };
+    (category << 14) | (n & 0x3fff)
+}
+
+/// Build the response to a request for DTCs: the count, then two bytes for each DTC
+fn dtc_response(dtcs: &[Dtc]) -> Vec<u8> {
+    let mut data = vec![dtcs.len() as u8];
+    for dtc in dtcs {
+        data.extend(dtc_to_u16(dtc).to_be_bytes());
+    }
+    data
+}
+
+impl Obd2Device for Obd2Simulator {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        let response = match (mode, pid) {
+            (0x01, pid) => self.service_1(pid),
+            (0x09, 0x02) => {
+                let mut vin = vec![0x01];
+                vin.extend(self.vin.bytes());
+                Some(vin)
+            }
+            _ => None,
+        };
+        self.respond(response)
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        let response = match mode {
+            0x03 => Some(dtc_response(&self.dtcs)),
+            0x04 => {
+                self.dtcs.clear();
+                self.pending_dtcs.clear();
+                Some(Vec::new())
+            }
+            0x07 => Some(dtc_response(&self.pending_dtcs)),
+            0x0A => Some(dtc_response(&self.permanent_dtcs)),
+            _ => None,
+        };
+        self.respond(response)
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        match *request {
+            [mode] => self.obd_mode_command(mode),
+            [mode, pid] => self.obd_command(mode, pid),
+            [0x02, 0x02, 0x00] if self.dtcs.is_empty() => self.respond(Some(vec![0x00, 0x00])),
+            [0x02, pid, 0x00] => self.respond(self.freeze_frame(pid)),
+            _ => self.respond(None),
+        }
+    }
+
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        if address == ENGINE_ADDRESS - 8 {
+            Ok(self.obd_request(request)?.remove(0))
+        } else {
+            Err(Error::NoData)
+        }
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        Ok(self
+            .obd_request(request)?
+            .into_iter()
+            .map(|data| EcuResponse {
+                address: ENGINE_ADDRESS,
+                data,
+            })
+            .collect())
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        Ok(Protocol::Can11Bit500)
+    }
+
+    /// The engine is always running, so the battery is at its charging voltage
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        Ok(14.2)
+    }
+}
@@ -0,0 +1,712 @@
+use log::{debug, info, trace};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::CString,
+    io, mem,
+    ops::RangeInclusive,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    process::Command,
+    time::{self, Instant},
+};
+
+use super::{
+    isotp::{self, Progress, Reassembler},
+    CanFilter, CanFrame, DeviceConfig, Error, PipelinedDevice, RawCanAccess, Result,
+};
+use crate::{
+    interface::{
+        pending_error, response_header, single_response, strip_header, strip_tagged_header,
+    },
+    j1939::{self, J1939Device},
+    EcuResponse, Obd2Device, Protocol,
+};
+
+/// CAN identifier for functionally addressed (broadcast) OBD-II requests
+const FUNCTIONAL_REQUEST_ID: u32 = 0x7DF;
+
+/// CAN identifiers used by ECUs to respond, `0x7E8` to `0x7EF`
+const RESPONSE_IDS: RangeInclusive<u32> = 0x7E8..=0x7EF;
+
+/// Offset between an ECU's response identifier and its physical request identifier
+const RESPONSE_ID_OFFSET: u32 = 8;
+
+/// The filter passing the responses of every OBD-II ECU
+const RESPONSE_FILTER: CanFilter = CanFilter {
+    id: 0x7E8,
+    mask: 0x7F8,
+};
+
+/// The filter passing every frame with a 29-bit identifier
+const EXTENDED_FRAMES_FILTER: CanFilter = CanFilter {
+    id: libc::CAN_EFF_FLAG,
+    mask: libc::CAN_EFF_FLAG,
+};
+
+/// CAN identifier of J1939 request messages from this device: priority 6, sent to every ECU
+/// (`FF`) from the source address for off-board diagnostic tools (`F9`)
+const J1939_REQUEST_ID: u32 = 0x18_00_FF_F9 | (j1939::REQUEST_PGN << 8);
+
+/// PGN of J1939 transport protocol connection management messages, which announce messages
+/// longer than 8 bytes
+const J1939_TP_CM_PGN: u32 = 0xEC00;
+
+/// PGN of J1939 transport protocol data transfer messages, which carry 7 bytes of a long message
+const J1939_TP_DT_PGN: u32 = 0xEB00;
+
+/// Control byte of a broadcast announce message (BAM), the transport protocol connection
+/// management message announcing a long message sent to every ECU
+const J1939_BAM: u8 = 0x20;
+
+/// How long ECUs have to respond to a J1939 request (Tr in SAE J1939-21)
+const J1939_RESPONSE_TIMEOUT: time::Duration = time::Duration::from_millis(200);
+
+/// How long to wait between the packets of a long J1939 message (T1 in SAE J1939-21)
+const J1939_PACKET_TIMEOUT: time::Duration = time::Duration::from_millis(750);
+
+/// How long to wait for an ECU that responded that its response is pending (P2* in ISO 15765-4)
+const RESPONSE_PENDING_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+
+/// An OBD-II interface using a Linux SocketCAN network interface directly
+///
+/// This skips the ELM327 entirely and talks ISO 15765-4 (OBD-II over CAN, 11-bit identifiers at
+/// the bus speed the interface is configured with) to the vehicle. The interface must already be
+/// up, for example with `ip link set can0 up type can bitrate 500000`.
+///
+/// Frames are sent and received through a raw CAN socket, with only the frames from the ECUs let
+/// through by the kernel. ISO-TP flow control frames are sent as soon as the first frame of a long
+/// response is received, well within the time ECUs wait for them.
+pub struct SocketCanDevice {
+    interface: String,
+    socket: CanSocket,
+    timeout: time::Duration,
+    /// The messages being received by [PipelinedDevice::receive_response], from each ECU
+    pipelined: HashMap<u32, Reassembler>,
+}
+
+impl SocketCanDevice {
+    /// Open the SocketCAN interface named `interface`, like `"can0"`
+    pub fn new(interface: impl Into<String>) -> Result<Self> {
+        Self::open(interface.into(), false, DeviceConfig::default())
+    }
+
+    /// Open the SocketCAN interface named `interface` with the timeouts in `config`
+    ///
+    /// The command timeout is how long to wait for each frame of the responses, 100 ms by
+    /// default. There is no adapter that could fail to respond, so requests are not retried.
+    pub fn with_config(interface: impl Into<String>, config: DeviceConfig) -> Result<Self> {
+        Self::open(interface.into(), false, config)
+    }
+
+    /// Open the SocketCAN interface named `interface` for SAE J1939 as well as OBD-II
+    ///
+    /// This also receives every frame with a 29-bit identifier, so that it can request J1939
+    /// parameter groups with [J1939Device]. The bus speed of J1939 networks is usually 250
+    /// kbit/s.
+    pub fn new_j1939(interface: impl Into<String>) -> Result<Self> {
+        Self::open(interface.into(), true, DeviceConfig::default())
+    }
+
+    fn open(interface: String, j1939: bool, config: DeviceConfig) -> Result<Self> {
+        info!("Opening SocketCAN interface {}", interface);
+
+        let socket = if j1939 {
+            CanSocket::open(&interface, &[RESPONSE_FILTER, EXTENDED_FRAMES_FILTER])?
+        } else {
+            CanSocket::open(&interface, &[RESPONSE_FILTER])?
+        };
+
+        Ok(SocketCanDevice {
+            interface,
+            socket,
+            timeout: config
+                .command_timeout()
+                .unwrap_or(time::Duration::from_millis(100)),
+            pipelined: HashMap::new(),
+        })
+    }
+
+    /// Send a request to `request_id` and collect each ECU's complete response
+    ///
+    /// The request is sent to all ECUs if `request_id` is [FUNCTIONAL_REQUEST_ID], or else only to
+    /// the ECU with that physical request identifier, and only its responses are kept (see
+    /// [collect_responses]).
+    fn request(&mut self, request_id: u32, data: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        let responders = if request_id == FUNCTIONAL_REQUEST_ID {
+            RESPONSE_IDS
+        } else {
+            let id = request_id + RESPONSE_ID_OFFSET;
+            id..=id
+        };
+
+        // discard any frames left over from a previous request
+        self.socket.drain()?;
+
+        self.send_frame(&isotp::single_frame(request_id, data)?)?;
+        collect_responses(&self.socket, responders, self.timeout, data)
+    }
+
+    /// Like [request](Self::request) to all ECUs, without the ECU addresses
+    fn request_untagged(&mut self, data: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        self.request_untagged_to(FUNCTIONAL_REQUEST_ID, data)
+    }
+
+    /// Like [request](Self::request), without the ECU addresses
+    fn request_untagged_to(&mut self, request_id: u32, data: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        Ok(self
+            .request(request_id, data)?
+            .into_iter()
+            .map(|r| r.data)
+            .collect())
+    }
+}
+
+/// Frames are received with a second socket, so that the kernel only passes the ones matching the
+/// filters
+impl RawCanAccess for SocketCanDevice {
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<()> {
+        trace!("send_frame: sending {:X} {:02X?}", frame.id, frame.data);
+        self.socket.send(frame)
+    }
+
+    fn receive_frames(
+        &mut self,
+        filters: &[CanFilter],
+        duration: time::Duration,
+    ) -> Result<Vec<CanFrame>> {
+        let socket = CanSocket::open(&self.interface, filters)?;
+        let deadline = Instant::now() + duration;
+        let mut frames = Vec::new();
+        while let Some(frame) = socket.receive(deadline)? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+/// A long J1939 message being received with the transport protocol
+struct Transfer {
+    size: usize,
+    data: Vec<u8>,
+}
+
+/// Only available when opened with [new_j1939](SocketCanDevice::new_j1939)
+///
+/// Responses are collected until no ECU has responded for the response timeout, or no packet of a
+/// long message has been received for the packet timeout. Long messages are only received when
+/// they are broadcast (BAM), which is how ECUs respond to requests sent to every ECU.
+impl J1939Device for SocketCanDevice {
+    fn request_pgn(&mut self, pgn: u32) -> crate::Result<Vec<Vec<u8>>> {
+        // discard any frames left over from a previous request
+        self.socket.drain()?;
+
+        let [low, middle, high, _] = pgn.to_le_bytes();
+        self.send_frame(&CanFrame {
+            id: J1939_REQUEST_ID,
+            data: vec![low, middle, high],
+        })?;
+
+        let mut transfers: HashMap<u32, Transfer> = HashMap::new();
+        let mut responses = Vec::new();
+        let mut deadline = Instant::now() + J1939_RESPONSE_TIMEOUT;
+        while let Some(frame) = self.socket.receive(deadline)? {
+            if frame.id <= 0x7FF {
+                continue;
+            }
+
+            let source = frame.id & 0xFF;
+            match (j1939::pgn_from_id(frame.id), frame.data.as_slice()) {
+                (id_pgn, data) if id_pgn == pgn => {
+                    debug!("request_pgn: ECU {:02X} responded {:02X?}", source, data);
+                    responses.push(data.to_vec());
+                }
+                (
+                    J1939_TP_CM_PGN,
+                    &[J1939_BAM, size_low, size_high, _, _, pgn_low, pgn_middle, pgn_high],
+                ) if u32::from_le_bytes([pgn_low, pgn_middle, pgn_high, 0]) == pgn => {
+                    transfers.insert(
+                        source,
+                        Transfer {
+                            size: u16::from_le_bytes([size_low, size_high]).into(),
+                            data: Vec::new(),
+                        },
+                    );
+                }
+                (J1939_TP_DT_PGN, &[_, ref packet @ ..]) => {
+                    let Some(transfer) = transfers.get_mut(&source) else {
+                        continue;
+                    };
+                    transfer.data.extend_from_slice(packet);
+                    if transfer.data.len() >= transfer.size {
+                        let mut data = std::mem::take(&mut transfer.data);
+                        data.truncate(transfer.size);
+                        transfers.remove(&source);
+                        debug!("request_pgn: ECU {:02X} responded {:02X?}", source, data);
+                        responses.push(data);
+                    }
+                }
+                _ => continue,
+            }
+
+            deadline = Instant::now()
+                + if transfers.is_empty() {
+                    J1939_RESPONSE_TIMEOUT
+                } else {
+                    J1939_PACKET_TIMEOUT
+                };
+        }
+
+        if responses.is_empty() {
+            Err(crate::Error::NoData)
+        } else {
+            Ok(responses)
+        }
+    }
+}
+
+/// Requests are sent to all ECUs, and each ECU's multi-frame responses are reassembled as their
+/// frames arrive
+impl PipelinedDevice for SocketCanDevice {
+    fn send_request(&mut self, request: &[u8]) -> crate::Result<()> {
+        Ok(self.send_frame(&isotp::single_frame(FUNCTIONAL_REQUEST_ID, request)?)?)
+    }
+
+    fn receive_response(&mut self, deadline: Instant) -> crate::Result<Option<EcuResponse>> {
+        while let Some(frame) = self.socket.receive(deadline)? {
+            if !RESPONSE_IDS.contains(&frame.id) {
+                continue;
+            }
+            match self
+                .pipelined
+                .entry(frame.id)
+                .or_default()
+                .push(&frame.data)?
+            {
+                Progress::Complete(data) => {
+                    self.pipelined.remove(&frame.id);
+                    return Ok(Some(EcuResponse {
+                        address: frame.id,
+                        data,
+                    }));
+                }
+                Progress::SendFlowControl => {
+                    self.send_frame(&isotp::flow_control(frame.id - RESPONSE_ID_OFFSET))?
+                }
+                Progress::Pending => (),
+            }
+        }
+        Ok(None)
+    }
+
+    fn first_response_timeout(&self) -> time::Duration {
+        self.timeout
+    }
+
+    fn next_response_timeout(&self) -> time::Duration {
+        self.timeout
+    }
+}
+
+impl Obd2Device for SocketCanDevice {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[mode, pid])?;
+        strip_header(responses, &[0x40 | mode, pid])
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[mode])?;
+        strip_header(responses, &[0x40 | mode])
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(request)?;
+        strip_header(responses, &response_header(request))
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> crate::Result<Vec<EcuResponse>> {
+        let responses = self.request(FUNCTIONAL_REQUEST_ID, request)?;
+        strip_tagged_header(responses, request)
+    }
+
+    /// Only the standard physical request identifiers, `0x7E0` to `0x7E7`, are supported
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> crate::Result<Vec<u8>> {
+        if !(0x7E0..0x7E8).contains(&address) {
+            return Err(crate::Error::InvalidArgument {
+                name: "request address (7E0 to 7E7)",
+                value: address,
+            });
+        }
+        let responses = self.request_untagged_to(address, request)?;
+        single_response(strip_header(responses, &response_header(request))?)
+    }
+
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let responses = self.request_untagged(&[&[mode], pids].concat())?;
+        strip_header(responses, &[0x40 | mode])
+    }
+
+    /// ISO 15765-4 with 11-bit identifiers, at the bit rate the interface is configured with (from
+    /// `ip -details link show`)
+    fn protocol(&mut self) -> crate::Result<Protocol> {
+        let output = Command::new("ip")
+            .args(["-details", "link", "show", &self.interface])
+            .output()
+            .map_err(Error::from)?;
+        let details = String::from_utf8_lossy(&output.stdout);
+        let bitrate = details
+            .split_whitespace()
+            .skip_while(|&word| word != "bitrate")
+            .nth(1)
+            .and_then(|rate| rate.parse::<u32>().ok());
+        match bitrate {
+            Some(500_000) => Ok(Protocol::Can11Bit500),
+            Some(250_000) => Ok(Protocol::Can11Bit250),
+            rate => {
+                debug!(
+                    "protocol: {} has no OBD-II bit rate ({:?})",
+                    self.interface, rate
+                );
+                Err(crate::Error::UnsupportedByDevice(
+                    "bit rates other than 250 and 500 kbit/s",
+                ))
+            }
+        }
+    }
+}
+
+/// Collect each ECU's complete response to the request `data`, which was just sent
+///
+/// Only the responses of the ECUs with identifiers in `responders` are kept. Responses are
+/// collected until no frame has been received from them for `timeout`, with multi-frame responses
+/// reassembled as they arrive. While an ECU has responded that its response is pending
+/// (`7F <mode> 78`), the wait is extended until it sends its real response.
+fn collect_responses(
+    socket: &impl FrameLink,
+    responders: RangeInclusive<u32>,
+    timeout: time::Duration,
+    data: &[u8],
+) -> crate::Result<Vec<EcuResponse>> {
+    let mut pending: HashMap<u32, Reassembler> = HashMap::new();
+    let mut waiting: HashSet<u32> = HashSet::new();
+    let mut responses = Vec::new();
+    let mut deadline = Instant::now() + timeout;
+    while let Some(frame) = socket.receive(deadline)? {
+        // frames from other ECUs, like J1939 broadcasts, do not extend the wait
+        if !responders.contains(&frame.id) {
+            continue;
+        }
+
+        match pending.entry(frame.id).or_default().push(&frame.data)? {
+            Progress::Complete(message) => {
+                debug!("request: ECU {:03X} responded {:02X?}", frame.id, message);
+                pending.remove(&frame.id);
+                if matches!(message.as_slice(), [0x7F, _, 0x78, ..]) {
+                    waiting.insert(frame.id);
+                } else {
+                    waiting.remove(&frame.id);
+                    responses.push(EcuResponse {
+                        address: frame.id,
+                        data: message,
+                    });
+                }
+            }
+            Progress::SendFlowControl => {
+                socket.send(&isotp::flow_control(frame.id - RESPONSE_ID_OFFSET))?
+            }
+            Progress::Pending => (),
+        }
+
+        deadline = Instant::now()
+            + if waiting.is_empty() {
+                timeout
+            } else {
+                RESPONSE_PENDING_TIMEOUT
+            };
+    }
+
+    if !waiting.is_empty() && responses.is_empty() {
+        Err(pending_error(data))
+    } else if responses.is_empty() {
+        Err(crate::Error::NoData)
+    } else {
+        Ok(responses)
+    }
+}
+
+/// Sends and receives CAN frames, so that responses can be collected from something other than a
+/// socket in tests
+trait FrameLink {
+    fn send(&self, frame: &CanFrame) -> Result<()>;
+
+    /// Receive the next data frame, or `None` if there is none before `deadline`
+    fn receive(&self, deadline: Instant) -> Result<Option<CanFrame>>;
+}
+
+impl FrameLink for CanSocket {
+    fn send(&self, frame: &CanFrame) -> Result<()> {
+        trace!("send_frame: sending {:X} {:02X?}", frame.id, frame.data);
+        CanSocket::send(self, frame)
+    }
+
+    fn receive(&self, deadline: Instant) -> Result<Option<CanFrame>> {
+        CanSocket::receive(self, deadline)
+    }
+}
+
+/// A raw CAN socket (`CAN_RAW`), bound to one interface
+struct CanSocket(OwnedFd);
+
+#[allow(unsafe_code)]
+impl CanSocket {
+    /// Open a socket on `interface` receiving the frames matching any of `filters`, or every frame
+    /// if there are none
+    fn open(interface: &str, filters: &[CanFilter]) -> Result<Self> {
+        let name = CString::new(interface).map_err(|_| {
+            Error::Communication(format!("open: invalid interface name {:?}", interface))
+        })?;
+        // SAFETY: `name` is a null-terminated string
+        let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+        if index == 0 {
+            return Err(os_error());
+        }
+
+        // SAFETY: the arguments are plain integers
+        let fd = unsafe {
+            libc::socket(
+                libc::PF_CAN,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::CAN_RAW,
+            )
+        };
+        if fd < 0 {
+            return Err(os_error());
+        }
+        // SAFETY: `fd` is a socket that was just opened, and is not owned by anything else
+        let socket = CanSocket(unsafe { OwnedFd::from_raw_fd(fd) });
+
+        if !filters.is_empty() {
+            let filters: Vec<libc::can_filter> = filters.iter().map(kernel_filter).collect();
+            // SAFETY: the pointer and length are those of `filters`, which outlives the call
+            let result = unsafe {
+                libc::setsockopt(
+                    fd,
+                    libc::SOL_CAN_RAW,
+                    libc::CAN_RAW_FILTER,
+                    filters.as_ptr().cast(),
+                    mem::size_of_val(filters.as_slice()) as libc::socklen_t,
+                )
+            };
+            if result < 0 {
+                return Err(os_error());
+            }
+        }
+
+        // SAFETY: `sockaddr_can` is plain data, for which all zeros is valid
+        let mut address: libc::sockaddr_can = unsafe { mem::zeroed() };
+        address.can_family = libc::AF_CAN as libc::sa_family_t;
+        address.can_ifindex = index as libc::c_int;
+        // SAFETY: the pointer and length are those of `address`, which outlives the call
+        let result = unsafe {
+            libc::bind(
+                fd,
+                (&address as *const libc::sockaddr_can).cast(),
+                mem::size_of::<libc::sockaddr_can>() as libc::socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(os_error());
+        }
+        Ok(socket)
+    }
+
+    fn send(&self, frame: &CanFrame) -> Result<()> {
+        if frame.data.len() > libc::CAN_MAX_DLEN {
+            return Err(Error::Communication(format!(
+                "send: frame {:02X?} has more than 8 bytes",
+                frame.data
+            )));
+        }
+        // SAFETY: `can_frame` is plain data, for which all zeros is valid
+        let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+        raw.can_id = if frame.id > 0x7FF {
+            frame.id | libc::CAN_EFF_FLAG
+        } else {
+            frame.id
+        };
+        raw.can_dlc = frame.data.len() as u8;
+        raw.data[..frame.data.len()].copy_from_slice(&frame.data);
+
+        // SAFETY: the pointer and length are those of `raw`, which outlives the call
+        let written = unsafe {
+            libc::write(
+                self.0.as_raw_fd(),
+                (&raw as *const libc::can_frame).cast(),
+                libc::CAN_MTU,
+            )
+        };
+        match written {
+            n if n < 0 => Err(os_error()),
+            n if n as usize != libc::CAN_MTU => Err(Error::Communication(format!(
+                "send: only {} bytes of the frame were written",
+                n
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Receive the next data frame, or `None` if there is none before `deadline`
+    fn receive(&self, deadline: Instant) -> Result<Option<CanFrame>> {
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let mut poll = libc::pollfd {
+                fd: self.0.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // rounded up, so that the wait does not end just before the deadline
+            let millis = timeout
+                .as_micros()
+                .div_ceil(1000)
+                .min(libc::c_int::MAX as u128);
+            // SAFETY: `poll` is a valid pollfd, and the count is 1
+            match unsafe { libc::poll(&mut poll, 1, millis as libc::c_int) } {
+                0 => return Ok(None),
+                n if n < 0 => match io::Error::last_os_error().kind() {
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(os_error()),
+                },
+                _ => (),
+            }
+
+            // SAFETY: `can_frame` is plain data, for which all zeros is valid
+            let mut raw: libc::can_frame = unsafe { mem::zeroed() };
+            // SAFETY: the pointer and length are those of `raw`, which outlives the call
+            let read = unsafe {
+                libc::read(
+                    self.0.as_raw_fd(),
+                    (&mut raw as *mut libc::can_frame).cast(),
+                    libc::CAN_MTU,
+                )
+            };
+            if read < 0 {
+                return Err(os_error());
+            }
+            if read as usize != libc::CAN_MTU
+                || raw.can_id & (libc::CAN_ERR_FLAG | libc::CAN_RTR_FLAG) != 0
+            {
+                continue;
+            }
+
+            let id = if raw.can_id & libc::CAN_EFF_FLAG != 0 {
+                raw.can_id & libc::CAN_EFF_MASK
+            } else {
+                raw.can_id & libc::CAN_SFF_MASK
+            };
+            let len = usize::from(raw.can_dlc).min(libc::CAN_MAX_DLEN);
+            let frame = CanFrame {
+                id,
+                data: raw.data[..len].to_vec(),
+            };
+            trace!("receive: received {:X} {:02X?}", frame.id, frame.data);
+            return Ok(Some(frame));
+        }
+    }
+
+    /// Discard the frames that have been received but not read
+    fn drain(&self) -> Result<()> {
+        while self.receive(Instant::now())?.is_some() {}
+        Ok(())
+    }
+}
+
+/// Convert a filter for the kernel, where the identifier length is part of what is matched
+///
+/// Filters with identifiers up to `7FF` only match 11-bit identifiers, and others only 29-bit
+/// identifiers. [EXTENDED_FRAMES_FILTER] already has the flag for 29-bit identifiers set.
+fn kernel_filter(filter: &CanFilter) -> libc::can_filter {
+    let extended = filter.id & libc::CAN_EFF_FLAG != 0 || filter.id > 0x7FF;
+    let (id, mask) = if extended {
+        (libc::CAN_EFF_FLAG, libc::CAN_EFF_MASK)
+    } else {
+        (0, libc::CAN_SFF_MASK)
+    };
+    libc::can_filter {
+        can_id: filter.id & mask | id,
+        can_mask: filter.mask & mask | libc::CAN_EFF_FLAG,
+    }
+}
+
+/// Get the error for the failed system call, where a missing or down interface is a lost
+/// connection
+fn os_error() -> Error {
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ENETDOWN | libc::ENODEV | libc::ENXIO) => Error::Disconnected(error.to_string()),
+        _ => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernel_filter_standard_identifiers() {
+        let filter = kernel_filter(&RESPONSE_FILTER);
+        assert_eq!(filter.can_id, 0x7E8);
+        assert_eq!(filter.can_mask, 0x7F8 | libc::CAN_EFF_FLAG);
+
+        let filter = kernel_filter(&CanFilter::exact(0x0B4));
+        assert_eq!(filter.can_id, 0x0B4);
+        assert_eq!(filter.can_mask, 0x7FF | libc::CAN_EFF_FLAG);
+    }
+
+    #[test]
+    fn kernel_filter_extended_identifiers() {
+        let filter = kernel_filter(&CanFilter::exact(0x18DAF110));
+        assert_eq!(filter.can_id, 0x18DAF110 | libc::CAN_EFF_FLAG);
+        assert_eq!(filter.can_mask, 0x1FFF_FFFF | libc::CAN_EFF_FLAG);
+
+        let filter = kernel_filter(&EXTENDED_FRAMES_FILTER);
+        assert_eq!(filter.can_id, libc::CAN_EFF_FLAG);
+        assert_eq!(filter.can_mask, libc::CAN_EFF_FLAG);
+    }
+
+    /// A bus where an ECU broadcasts a J1939 frame every 10 ms and nothing answers requests
+    struct BusyBus;
+
+    impl FrameLink for BusyBus {
+        fn send(&self, _frame: &CanFrame) -> Result<()> {
+            Ok(())
+        }
+
+        fn receive(&self, deadline: Instant) -> Result<Option<CanFrame>> {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep((deadline - now).min(time::Duration::from_millis(10)));
+            Ok(Some(CanFrame {
+                id: 0x18FEF100,
+                data: vec![0xFF; 8],
+            }))
+        }
+    }
+
+    #[test]
+    fn unrelated_frames_do_not_extend_wait() {
+        let start = Instant::now();
+        let result = collect_responses(
+            &BusyBus,
+            RESPONSE_IDS,
+            time::Duration::from_millis(100),
+            &[0x01, 0x0C],
+        );
+        assert!(matches!(result, Err(crate::Error::NoData)));
+        assert!(start.elapsed() < time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn missing_interface() {
+        assert!(SocketCanDevice::new("obd2test0").is_err());
+    }
+}
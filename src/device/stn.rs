@@ -0,0 +1,89 @@
+use super::{at::encode_hex, CanFilter, Obd2BaseDevice, Result};
+use crate::Obd2;
+
+/// The extended commands of STN chips, like the STN1110 and STN2120 in OBDLink adapters
+///
+/// STN chips understand the ELM327's AT commands, and add their own `ST` commands. When the
+/// adapter is an STN chip, these methods use them, and otherwise they fall back to the closest
+/// ELM327 behavior, so they can be used with either.
+///
+/// Monitoring with [RawCanAccess](super::RawCanAccess) also uses the STN's hardware filters and
+/// its `STM` monitoring when it can, which lose fewer frames on a busy bus than `ATMA`, as the STN
+/// has a much larger buffer.
+pub trait StnExtensions {
+    /// Get the identifier of the STN firmware (`STI`), like `STN1110 v4.0.1`, or `None` if the
+    /// adapter is not an STN chip
+    fn stn_identifier(&mut self) -> Result<Option<String>>;
+
+    /// Whether the adapter is an STN chip
+    fn is_stn(&mut self) -> Result<bool> {
+        Ok(self.stn_identifier()?.is_some())
+    }
+
+    /// Send an OBD-II command and get the reply, telling the adapter how many ECUs will respond
+    ///
+    /// Normally the adapter waits for its timeout after the last response, in case more ECUs
+    /// respond. Knowing how many will, it stops waiting as soon as they have, which makes polling
+    /// much faster. On STN chips this uses `STPX`, and otherwise the count is added to the end of
+    /// the request, which ELM327 v1.3 and later support.
+    fn cmd_with_response_count(&mut self, cmd: &[u8], responses: u8) -> Result<Option<String>>;
+
+    /// Only receive CAN frames matching any of `filters`, or every frame if there are none
+    ///
+    /// STN chips have a list of pass filters (`STFAP`). The ELM327 has a single filter
+    /// (`ATCF` and `ATCM`), so it is set to the narrowest filter passing every frame that any of
+    /// `filters` would, which can let some other frames through.
+    fn set_pass_filters(&mut self, filters: &[CanFilter]) -> Result<()>;
+}
+
+impl<T: Obd2BaseDevice + StnExtensions> StnExtensions for Obd2<T> {
+    fn stn_identifier(&mut self) -> Result<Option<String>> {
+        self.device.stn_identifier()
+    }
+
+    fn cmd_with_response_count(&mut self, cmd: &[u8], responses: u8) -> Result<Option<String>> {
+        self.device.cmd_with_response_count(cmd, responses)
+    }
+
+    fn set_pass_filters(&mut self, filters: &[CanFilter]) -> Result<()> {
+        self.device.set_pass_filters(filters)
+    }
+}
+
+/// The commands replacing the STN's pass filters with `filters`
+pub(super) fn stn_filter_commands(filters: &[CanFilter]) -> Vec<String> {
+    std::iter::once("STFCP".to_owned())
+        .chain(filters.iter().map(|f| {
+            if f.id <= 0x7FF && f.mask <= 0x7FF {
+                format!("STFAP{:03X},{:03X}", f.id, f.mask)
+            } else {
+                format!("STFAP{:08X},{:08X}", f.id, f.mask)
+            }
+        }))
+        .collect()
+}
+
+/// The STN command sending `cmd` and waiting for `responses` responses
+pub(super) fn stpx_command(cmd: &[u8], responses: u8) -> String {
+    format!("STPX D:{}, R:{}", encode_hex(cmd), responses)
+}
+
+/// The ELM327 commands setting its single receive filter to pass every frame matching `filters`
+///
+/// The filter's mask keeps only the bits that every filter compares and that are the same in
+/// every filter's identifier.
+pub(super) fn elm327_filter_commands(filters: &[CanFilter]) -> Vec<String> {
+    let Some(first) = filters.first() else {
+        // the defaults, which pass every frame
+        return vec!["ATCRA".to_owned()];
+    };
+    let mask = filters
+        .iter()
+        .fold(first.mask, |mask, f| mask & f.mask & !(f.id ^ first.id));
+    let id = first.id & mask;
+    if filters.iter().all(|f| f.id <= 0x7FF && f.mask <= 0x7FF) {
+        vec![format!("ATCF{:03X}", id), format!("ATCM{:03X}", mask)]
+    } else {
+        vec![format!("ATCF{:08X}", id), format!("ATCM{:08X}", mask)]
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    commands::{Obd2DataRetrieval, PidSupport},
+    EcuResponse, Error, Obd2Device, Protocol, Result,
+};
+
+/// Wraps an [Obd2Device] and rejects requests for service 1 PIDs that the vehicle does not support
+///
+/// Requesting an unsupported PID usually means waiting for the adapter to time out. Instead, this
+/// finds which PIDs each ECU supports when it is created, and afterwards returns
+/// [Error::NotSupported] right away for PIDs that no ECU supports. Other requests are passed
+/// through unchanged.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::{Obd2Simulator, SupportCheckedDevice}, Error};
+///
+/// let mut device = SupportCheckedDevice::new(Obd2Simulator::new()).unwrap();
+///
+/// assert!(device.get_rpm().is_ok());
+/// assert!(matches!(device.get_fuel_pressure(), Err(Error::NotSupported(0x01, 0x0A))));
+/// ```
+pub struct SupportCheckedDevice<T: Obd2Device> {
+    device: T,
+    support: Vec<PidSupport>,
+}
+
+impl<T: Obd2Device> SupportCheckedDevice<T> {
+    /// Find the PIDs supported by `device`, and wrap it
+    pub fn new(mut device: T) -> Result<Self> {
+        let support = device.get_pid_support()?;
+        Ok(SupportCheckedDevice { device, support })
+    }
+
+    /// Get the service 1 PIDs supported by each ECU
+    pub fn support(&self) -> &[PidSupport] {
+        &self.support
+    }
+
+    /// Get back the wrapped device
+    pub fn into_inner(self) -> T {
+        self.device
+    }
+
+    fn check(&self, mode: u8, pid: u8) -> Result<()> {
+        if mode == 0x01 && !self.support.iter().any(|ecu| ecu.supports(pid)) {
+            Err(Error::NotSupported(mode, pid))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T: Obd2Device> Obd2Device for SupportCheckedDevice<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.check(mode, pid)?;
+        self.device.obd_command(mode, pid)
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.device.obd_mode_command(mode)
+    }
+
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        for &pid in pids {
+            self.check(mode, pid)?;
+        }
+        self.device.obd_multi_command(mode, pids)
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        if let [mode, pid] = *request {
+            self.check(mode, pid)?;
+        }
+        self.device.obd_request(request)
+    }
+
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        if let [mode, pid] = *request {
+            self.check(mode, pid)?;
+        }
+        self.device.obd_request_to(address, request)
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        if let [mode, pid] = *request {
+            self.check(mode, pid)?;
+        }
+        self.device.obd_request_tagged(request)
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        self.device.protocol()
+    }
+
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        self.device.get_adapter_voltage()
+    }
+}
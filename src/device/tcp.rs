@@ -0,0 +1,207 @@
+use log::{info, warn};
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time,
+};
+
+use super::{DeviceConfig, Elm327, Elm327Transport, Error, Result};
+
+/// An ELM327 OBD-II adapter connected over Wi-Fi
+///
+/// Wi-Fi adapters act as an access point and accept a single TCP connection, over which the
+/// ELM327 protocol is sent unchanged.
+pub type TcpElm327 = Elm327<TcpTransport>;
+
+/// The address used by most Wi-Fi ELM327 adapters
+pub const DEFAULT_TCP_ADDRESS: &str = "192.168.0.10:35000";
+
+/// A TCP connection to a Wi-Fi adapter
+///
+/// Adapters often drop the connection when they lose power or Wi-Fi signal. When the connection is
+/// found to be closed while reading or writing, it is reopened once before an error is returned,
+/// and the [Elm327] initializes the adapter again before its next request.
+pub struct TcpTransport {
+    stream: TcpStream,
+    address: SocketAddr,
+    connect_timeout: time::Duration,
+    read_timeout: time::Duration,
+    reconnected: bool,
+}
+
+impl TcpTransport {
+    /// Connect to an adapter at `address`
+    ///
+    /// `connect_timeout` limits how long opening the connection may take, and `read_timeout` is
+    /// how long a single read waits for data before returning.
+    pub fn connect(
+        address: impl ToSocketAddrs,
+        connect_timeout: time::Duration,
+        read_timeout: time::Duration,
+    ) -> Result<Self> {
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            Error::Communication("TcpTransport::connect: no address to connect to".to_owned())
+        })?;
+        Ok(TcpTransport {
+            stream: open_stream(address, connect_timeout, read_timeout)?,
+            address,
+            connect_timeout,
+            read_timeout,
+            reconnected: false,
+        })
+    }
+
+    /// Get the address of the adapter
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Get the time limit for opening the connection
+    pub fn connect_timeout(&self) -> time::Duration {
+        self.connect_timeout
+    }
+
+    /// Get how long a single read waits for data
+    pub fn read_timeout(&self) -> time::Duration {
+        self.read_timeout
+    }
+
+    fn reconnect(&mut self) -> io::Result<()> {
+        warn!("Connection to {} dropped, reconnecting", self.address);
+        self.stream = open_stream(self.address, self.connect_timeout, self.read_timeout)?;
+        self.reconnected = true;
+        Ok(())
+    }
+}
+
+fn open_stream(
+    address: SocketAddr,
+    connect_timeout: time::Duration,
+    read_timeout: time::Duration,
+) -> io::Result<TcpStream> {
+    info!("Connecting to {}", address);
+    let stream = TcpStream::connect_timeout(&address, connect_timeout)?;
+    stream.set_read_timeout(Some(read_timeout))?;
+    stream.set_write_timeout(Some(connect_timeout))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+fn is_dropped(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+impl Read for TcpTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            // a read of zero bytes means the adapter closed the connection
+            Ok(0) if !buf.is_empty() => {
+                self.reconnect()?;
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            Err(e) if is_dropped(&e) => {
+                self.reconnect()?;
+                Err(io::ErrorKind::TimedOut.into())
+            }
+            r => r,
+        }
+    }
+}
+
+impl Write for TcpTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.stream.write(buf) {
+            Err(e) if is_dropped(&e) => {
+                self.reconnect()?;
+                self.stream.write(buf)
+            }
+            r => r,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl Elm327Transport for TcpTransport {
+    /// Set when a dropped connection is reopened while reading or writing
+    fn take_reconnected(&mut self) -> bool {
+        core::mem::take(&mut self.reconnected)
+    }
+}
+
+impl Elm327<TcpTransport> {
+    /// Connect to an ELM327 adapter over Wi-Fi at `address`
+    ///
+    /// Most adapters use [DEFAULT_TCP_ADDRESS]. The connection must be made within five seconds,
+    /// and reads wait up to 100 ms for data. Use
+    /// [connect_with_timeouts](Self::connect_with_timeouts) to change these.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self> {
+        Self::connect_with_timeouts(
+            address,
+            time::Duration::from_secs(5),
+            time::Duration::from_millis(100),
+        )
+    }
+
+    /// Connect to an ELM327 adapter over Wi-Fi at `address` with the given timeouts
+    ///
+    /// See [TcpTransport::connect] for the meaning of the timeouts.
+    pub fn connect_with_timeouts(
+        address: impl ToSocketAddrs,
+        connect_timeout: time::Duration,
+        read_timeout: time::Duration,
+    ) -> Result<Self> {
+        Elm327::with_transport(
+            TcpTransport::connect(address, connect_timeout, read_timeout)?,
+            38400,
+        )
+    }
+
+    /// Connect to an ELM327 adapter over Wi-Fi at `address` with the timeouts and retries in
+    /// `config`
+    ///
+    /// The connect timeout limits how long opening the connection may take, five seconds by
+    /// default. Reads wait up to 100 ms for data. See [connect](Self::connect).
+    pub fn connect_with_config(address: impl ToSocketAddrs, config: DeviceConfig) -> Result<Self> {
+        let connect_timeout = config
+            .connect_timeout()
+            .unwrap_or(time::Duration::from_secs(5));
+        let transport =
+            TcpTransport::connect(address, connect_timeout, time::Duration::from_millis(100))?;
+        Elm327::with_transport_and_config(transport, 38400, None, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn reconnects_when_dropped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut transport = TcpTransport::connect(
+            listener.local_addr().unwrap(),
+            time::Duration::from_secs(1),
+            time::Duration::from_millis(100),
+        )
+        .unwrap();
+        drop(listener.accept().unwrap());
+        assert!(!transport.take_reconnected());
+
+        let mut buf = [0; 8];
+        let error = transport.read(&mut buf).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(transport.take_reconnected());
+        assert!(!transport.take_reconnected());
+    }
+}
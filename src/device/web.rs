@@ -0,0 +1,630 @@
+use alloc::{borrow::ToOwned, collections::VecDeque, format, rc::Rc, string::String, vec::Vec};
+use core::{cell::RefCell, time::Duration};
+use js_sys::{Array, DataView, Date, Function, Object, Promise, Reflect, Uint8Array};
+use log::{debug, info, trace};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use super::{
+    at::{
+        check_elm327_protocol, encode_hex, parse_protocol_number, parse_voltage,
+        select_protocol_command, DEFAULT_COMMAND_TIMEOUT,
+    },
+    DeviceConfig, Error, Result,
+};
+use crate::{
+    commands::{
+        pid::{self, StandardPid},
+        DecodeObd2ValuesMode, Dtc,
+    },
+    interface::{
+        decode_response, pending_error, remove_pending, response_header, strip_header,
+        PENDING_RETRIES, PENDING_RETRY_DELAY,
+    },
+    obd2_device::join_pid_responses,
+    Protocol,
+};
+
+/// GATT services used by common BLE adapters, in order of preference
+const UART_SERVICES: [u16; 2] = [0xFFF0, 0xFFE0];
+
+/// Largest write that fits in a single packet with the default MTU
+const MAX_WRITE_LEN: usize = 20;
+
+/// How often the notifications of a Bluetooth adapter are checked while waiting for a response
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// An ELM327 adapter used from a web page, over Web Serial or Web Bluetooth
+///
+/// This is for in-browser diagnostic tools built for `wasm32-unknown-unknown` with
+/// `wasm-bindgen`. Browsers only allow waiting for the adapter asynchronously, so instead of
+/// implementing [Obd2Device](crate::Obd2Device), this has `async` versions of its requests, and
+/// of [get_snapshot](crate::commands::Obd2DataRetrieval::get_snapshot) and
+/// [get_dtcs](crate::commands::Obd2DataRetrieval::get_dtcs). The responses are decoded with the
+/// rest of the crate, like [StandardPid::decode] and [Dtc]. Like
+/// [EmbeddedElm327](super::EmbeddedElm327), each request is broadcast to every ECU.
+///
+/// Browsers only show the port or device picker in response to a click, so
+/// [request_serial](Self::request_serial) and [request_bluetooth](Self::request_bluetooth) must
+/// be called from an event handler. Chrome and Edge support Web Serial and Web Bluetooth; other
+/// browsers do not yet.
+///
+/// The `web` feature does not need `std`, so it is used without the default features.
+///
+/// # Example
+/// ```no_run
+/// use obd2::{commands::pid::StandardPid, device::WebElm327};
+///
+/// // called from a button's click handler, through wasm_bindgen_futures::spawn_local
+/// async fn show_rpm() -> Result<f32, obd2::Error> {
+///     let mut device = WebElm327::request_serial().await?;
+///     let snapshot = device.get_snapshot(&[StandardPid::EngineSpeed]).await?;
+///     Ok(snapshot[&StandardPid::EngineSpeed][0])
+/// }
+/// ```
+pub struct WebElm327 {
+    link: Link,
+    buffer: VecDeque<u8>,
+    protocol: Option<Protocol>,
+    config: DeviceConfig,
+}
+
+/// The browser API bytes are sent and received through
+enum Link {
+    Serial {
+        port: JsValue,
+        reader: JsValue,
+        writer: JsValue,
+        /// A read that timed out, to be awaited before reading again so its data is not lost
+        pending: Option<Promise>,
+    },
+    Bluetooth {
+        device: JsValue,
+        characteristic: JsValue,
+        write_method: &'static str,
+        received: Rc<RefCell<VecDeque<u8>>>,
+        _listener: Closure<dyn FnMut(JsValue)>,
+    },
+}
+
+impl WebElm327 {
+    /// Ask the user to pick a serial port, and open the ELM327 connected to it
+    ///
+    /// This must be called in response to a user action, like a click.
+    pub async fn request_serial() -> Result<Self> {
+        let serial = get(&get(&js_sys::global(), "navigator")?, "serial")?;
+        if serial.is_undefined() {
+            return Err(Error::Communication(
+                "request_serial: Web Serial is not supported by this browser".to_owned(),
+            ));
+        }
+        let port = resolve(call(&serial, "requestPort", &[])?).await?;
+        Self::open_serial(port).await
+    }
+
+    /// Open the ELM327 connected to the Web Serial `SerialPort` `port`, like one from
+    /// `navigator.serial.getPorts()`
+    pub async fn open_serial(port: JsValue) -> Result<Self> {
+        Self::open_serial_with_config(port, None, DeviceConfig::default()).await
+    }
+
+    /// Open the ELM327 connected to `port`, using `protocol` if it is given, and with the timeouts
+    /// and retries in `config`
+    ///
+    /// See [open_serial](Self::open_serial).
+    pub async fn open_serial_with_config(
+        port: JsValue,
+        protocol: Option<Protocol>,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let options = Object::new();
+        Reflect::set(&options, &"baudRate".into(), &38400.into()).map_err(js_error)?;
+        resolve(call(&port, "open", &[options.into()])?).await?;
+        let reader = call(&get(&port, "readable")?, "getReader", &[])?;
+        let writer = call(&get(&port, "writable")?, "getWriter", &[])?;
+        let link = Link::Serial {
+            port,
+            reader,
+            writer,
+            pending: None,
+        };
+        Self::with_link(link, protocol, config).await
+    }
+
+    /// Ask the user to pick a Bluetooth Low Energy adapter, and open it
+    ///
+    /// Adapters are found by their GATT UART service, 0xFFF0 or 0xFFE0. This must be called in
+    /// response to a user action, like a click.
+    pub async fn request_bluetooth() -> Result<Self> {
+        let bluetooth = get(&get(&js_sys::global(), "navigator")?, "bluetooth")?;
+        if bluetooth.is_undefined() {
+            return Err(Error::Communication(
+                "request_bluetooth: Web Bluetooth is not supported by this browser".to_owned(),
+            ));
+        }
+        let services: Array = UART_SERVICES.iter().map(|&s| JsValue::from(s)).collect();
+        let options = Object::new();
+        Reflect::set(&options, &"acceptAllDevices".into(), &true.into()).map_err(js_error)?;
+        Reflect::set(&options, &"optionalServices".into(), &services).map_err(js_error)?;
+        let device = resolve(call(&bluetooth, "requestDevice", &[options.into()])?).await?;
+        Self::open_bluetooth(device).await
+    }
+
+    /// Open the Web Bluetooth `BluetoothDevice` `device`, an adapter with a GATT UART service
+    pub async fn open_bluetooth(device: JsValue) -> Result<Self> {
+        Self::open_bluetooth_with_config(device, None, DeviceConfig::default()).await
+    }
+
+    /// Open the Bluetooth adapter `device`, using `protocol` if it is given, and with the timeouts
+    /// and retries in `config`
+    ///
+    /// See [open_bluetooth](Self::open_bluetooth).
+    pub async fn open_bluetooth_with_config(
+        device: JsValue,
+        protocol: Option<Protocol>,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        let server = resolve(call(&get(&device, "gatt")?, "connect", &[])?).await?;
+        let (notify, write) = find_uart_characteristics(&server).await?;
+
+        let received = Rc::new(RefCell::new(VecDeque::new()));
+        let queue = Rc::clone(&received);
+        let listener = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+            let value = Reflect::get(&event, &"target".into())
+                .and_then(|target| Reflect::get(&target, &"value".into()));
+            if let Ok(view) = value.and_then(|value| value.dyn_into::<DataView>()) {
+                let bytes = Uint8Array::new_with_byte_offset_and_length(
+                    &view.buffer(),
+                    view.byte_offset() as u32,
+                    view.byte_length() as u32,
+                );
+                trace!("WebElm327: notification {:?}", bytes.to_vec());
+                queue.borrow_mut().extend(bytes.to_vec());
+            }
+        });
+        call(
+            &notify,
+            "addEventListener",
+            &[
+                "characteristicvaluechanged".into(),
+                listener.as_ref().clone(),
+            ],
+        )?;
+        resolve(call(&notify, "startNotifications", &[])?).await?;
+
+        let properties = get(&write, "properties")?;
+        let write_method = if get(&properties, "writeWithoutResponse")?.is_truthy() {
+            "writeValueWithoutResponse"
+        } else {
+            "writeValueWithResponse"
+        };
+        let link = Link::Bluetooth {
+            device,
+            characteristic: write,
+            write_method,
+            received,
+            _listener: listener,
+        };
+        Self::with_link(link, protocol, config).await
+    }
+
+    async fn with_link(
+        link: Link,
+        protocol: Option<Protocol>,
+        config: DeviceConfig,
+    ) -> Result<Self> {
+        check_elm327_protocol(protocol)?;
+        let mut device = WebElm327 {
+            link,
+            buffer: VecDeque::new(),
+            protocol,
+            config,
+        };
+        device.reset().await?;
+        Ok(device)
+    }
+
+    /// Close the serial port or disconnect from the Bluetooth adapter, so that other pages can
+    /// use it
+    pub async fn close(self) -> Result<()> {
+        match self.link {
+            Link::Serial {
+                port,
+                reader,
+                writer,
+                ..
+            } => {
+                // cancelling the reader ends any read that timed out
+                resolve(call(&reader, "cancel", &[])?).await?;
+                call(&reader, "releaseLock", &[])?;
+                call(&writer, "releaseLock", &[])?;
+                resolve(call(&port, "close", &[])?).await?;
+            }
+            Link::Bluetooth { device, .. } => {
+                call(&get(&device, "gatt")?, "disconnect", &[])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Always use `protocol` to communicate with the vehicle, or find it automatically if `None`
+    ///
+    /// See [Elm327::set_protocol](super::Elm327::set_protocol).
+    pub async fn set_protocol(&mut self, protocol: Option<Protocol>) -> Result<()> {
+        check_elm327_protocol(protocol)?;
+        self.protocol = protocol;
+        self.reset_protocol().await
+    }
+
+    /// Ask the adapter which protocol it is using (`ATDPN`)
+    pub async fn protocol(&mut self) -> Result<Protocol> {
+        parse_protocol_number(self.serial_cmd("ATDPN").await?)
+    }
+
+    /// Read the voltage of the adapter's supply pin (`ATRV`), which is the vehicle's battery
+    /// voltage
+    pub async fn adapter_voltage(&mut self) -> Result<f32> {
+        parse_voltage(self.serial_cmd("ATRV").await?)
+    }
+
+    /// Reset the device and the OBD-II interface
+    pub async fn reset(&mut self) -> Result<()> {
+        self.write_bytes(b" \r\n").await?;
+        self.get_response().await?;
+        self.buffer.clear();
+
+        info!("Performing IC reset");
+        self.send_serial_str("ATZ").await?;
+        debug!("reset: got response {:?}", self.get_response().await?);
+        sleep(Duration::from_millis(500)).await;
+
+        self.reset_protocol().await
+    }
+
+    async fn reset_protocol(&mut self) -> Result<()> {
+        info!("Performing protocol reset");
+        let at = select_protocol_command(self.protocol);
+        debug!("reset: {} got {:?}", at, self.serial_cmd(&at).await?);
+        let at = self.config.adaptive_timing().elm327_command();
+        debug!("reset: {} got {:?}", at, self.serial_cmd(at).await?);
+        debug!(
+            "reset: got OBD response {:?}",
+            self.serial_cmd(&encode_hex(&[0x01, 0x00])).await?
+        );
+        Ok(())
+    }
+
+    /// Send command `mode` for `pid`, and get the data from each ECU that responded
+    ///
+    /// See [Obd2Device::obd_command](crate::Obd2Device::obd_command).
+    pub async fn obd_command(&mut self, mode: u8, pid: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let result = self.command(&[mode, pid]).await?;
+        strip_header(result, &[0x40 | mode, pid])
+    }
+
+    /// Send command `mode`, which has no PID, and get the data from each ECU that responded
+    ///
+    /// See [Obd2Device::obd_mode_command](crate::Obd2Device::obd_mode_command).
+    pub async fn obd_mode_command(&mut self, mode: u8) -> crate::Result<Vec<Vec<u8>>> {
+        let result = self.command(&[mode]).await?;
+        strip_header(result, &[0x40 | mode])
+    }
+
+    /// Send command `mode` for up to six `pids` at once
+    ///
+    /// See [Obd2Device::obd_multi_command](crate::Obd2Device::obd_multi_command). Only the CAN
+    /// protocols accept several PIDs in one request, so on other protocols each PID is requested
+    /// separately.
+    pub async fn obd_multi_command(
+        &mut self,
+        mode: u8,
+        pids: &[u8],
+    ) -> crate::Result<Vec<Vec<u8>>> {
+        if pids.len() <= 1 || self.protocol().await?.is_can() {
+            let result = self.command(&[&[mode], pids].concat()).await?;
+            return strip_header(result, &[0x40 | mode]);
+        }
+        let mut responses: Vec<Vec<u8>> = Vec::new();
+        let mut declined = None;
+        for &pid in pids {
+            match self.obd_command(mode, pid).await {
+                Ok(data) => join_pid_responses(&mut responses, pid, data),
+                Err(e) if e.is_declined() => declined = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        match declined {
+            Some(e) if responses.is_empty() => Err(e),
+            _ => Ok(responses),
+        }
+    }
+
+    /// Send the raw request `request`, and get the data from each ECU that responded, after the
+    /// echoed mode and parameters
+    ///
+    /// See [Obd2Device::obd_request](crate::Obd2Device::obd_request).
+    pub async fn obd_request(&mut self, request: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        let result = self.command(request).await?;
+        strip_header(result, &response_header(request))
+    }
+
+    /// Get the current values of several PIDs, requesting up to six at once
+    ///
+    /// See [get_snapshot](crate::commands::Obd2DataRetrieval::get_snapshot).
+    pub async fn get_snapshot(&mut self, pids: &[StandardPid]) -> crate::Result<pid::Snapshot> {
+        let mut snapshot = pid::Snapshot::new();
+        for chunk in pids.chunks(pid::MAX_PIDS_PER_REQUEST) {
+            let numbers: Vec<u8> = chunk.iter().map(|pid| pid.pid()).collect();
+            let responses = match self.obd_multi_command(0x01, &numbers).await {
+                Ok(responses) => responses,
+                Err(crate::Error::NoData) => continue,
+                Err(e) if e.is_declined() && numbers.len() > 1 => {
+                    let mut responses = Vec::new();
+                    for pid in numbers {
+                        match self.obd_multi_command(0x01, &[pid]).await {
+                            Ok(pid_responses) => responses.extend(pid_responses),
+                            Err(e) if e.is_declined() => (),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                    responses
+                }
+                Err(e) => return Err(e),
+            };
+            for response in responses {
+                pid::decode_snapshot(&response, &mut snapshot)?;
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Get the stored DTCs of each ECU
+    ///
+    /// See [get_dtcs](crate::commands::Obd2DataRetrieval::get_dtcs).
+    pub async fn get_dtcs(&mut self) -> crate::Result<Vec<Vec<Dtc>>> {
+        Vec::<Dtc>::decode_obd2_val_mode(self.obd_mode_command(0x03).await?)
+    }
+
+    /// Send `command` and get the response, sending it again while the adapter times out or an
+    /// ECU asks for more time
+    async fn command(&mut self, command: &[u8]) -> crate::Result<Vec<Vec<u8>>> {
+        for _ in 0..=PENDING_RETRIES {
+            let response = self.cmd_with_retries(command).await?;
+            let data = decode_response(response)?;
+            debug!("Sent OBD command {:?} and got data {:?}", command, data);
+            match remove_pending(data) {
+                Some(data) => return Ok(data),
+                None => sleep(PENDING_RETRY_DELAY).await,
+            }
+        }
+        Err(pending_error(command))
+    }
+
+    async fn cmd_with_retries(&mut self, command: &[u8]) -> crate::Result<String> {
+        for attempt in 0..=self.config.retries() {
+            if attempt > 0 {
+                let delay = self.config.retry_delay(attempt - 1);
+                debug!("Request timed out, retrying in {:?}", delay);
+                sleep(delay).await;
+            }
+            if let Some(response) = self.serial_cmd(&encode_hex(command)).await? {
+                return Ok(response);
+            }
+        }
+        Err(crate::Error::Timeout)
+    }
+
+    async fn serial_cmd(&mut self, cmd: &str) -> Result<Option<String>> {
+        self.send_serial_str(cmd).await?;
+        Ok(self
+            .get_response()
+            .await?
+            .and_then(|resp| String::from_utf8(resp).ok()))
+    }
+
+    /// Send a raw string, without encoding into ASCII hex, and check that it is echoed
+    async fn send_serial_str(&mut self, data: &str) -> Result<()> {
+        trace!("send_serial_str: sending {:?}", data);
+
+        self.write_bytes(&[data.as_bytes(), b"\r\n"].concat())
+            .await?;
+        let line = self.get_until(b'\n', false).await?;
+        if line.as_deref() == Some(data.as_bytes()) {
+            Ok(())
+        } else {
+            Err(Error::Communication(format!(
+                "send_serial_str: got {:?} instead of echoed command ({:?})",
+                line, data
+            )))
+        }
+    }
+
+    /// Read data until the ELM327's prompt character is printed
+    async fn get_response(&mut self) -> Result<Option<Vec<u8>>> {
+        self.get_until(b'>', true).await
+    }
+
+    async fn get_until(&mut self, end_byte: u8, allow_empty: bool) -> Result<Option<Vec<u8>>> {
+        let timeout = self
+            .config
+            .command_timeout()
+            .unwrap_or(DEFAULT_COMMAND_TIMEOUT);
+        let deadline = Date::now() + timeout.as_millis() as f64;
+        let mut line = Vec::new();
+        loop {
+            while let Some(b) = self.buffer.pop_front() {
+                match b {
+                    b'\0' | b'\n' => (),
+                    b if b == end_byte || (b == b'\r' && end_byte == b'\n') => {
+                        if allow_empty || !line.is_empty() {
+                            trace!("get_until: got {:?}", String::from_utf8_lossy(&line));
+                            return Ok(Some(line));
+                        }
+                    }
+                    b'\r' => line.push(b'\n'),
+                    b => line.push(b),
+                }
+            }
+
+            let remaining = deadline - Date::now();
+            let received = if remaining > 0. {
+                self.read(Duration::from_millis(remaining as u64)).await?
+            } else {
+                None
+            };
+            match received {
+                Some(bytes) => self.buffer.extend(bytes),
+                None => {
+                    // incomplete line read, keep it for next time
+                    for b in line.into_iter().rev() {
+                        self.buffer.push_front(b);
+                    }
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for bytes from the adapter
+    async fn read(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>> {
+        match &mut self.link {
+            Link::Serial {
+                reader, pending, ..
+            } => {
+                let read = match pending.take() {
+                    Some(read) => read,
+                    None => call(reader, "read", &[])?.unchecked_into(),
+                };
+                let race = Promise::race(&Array::of2(&read, &timer(timeout)));
+                let result = JsFuture::from(race).await.map_err(js_error)?;
+                if result.is_undefined() {
+                    *pending = Some(read);
+                    return Ok(None);
+                }
+                if get(&result, "done")?.is_truthy() {
+                    return Err(Error::Disconnected(
+                        "WebElm327::read: serial port closed".to_owned(),
+                    ));
+                }
+                let bytes: Uint8Array = get(&result, "value")?.unchecked_into();
+                Ok(Some(bytes.to_vec()))
+            }
+            Link::Bluetooth { received, .. } => {
+                let deadline = Date::now() + timeout.as_millis() as f64;
+                loop {
+                    let bytes: Vec<u8> = received.borrow_mut().drain(..).collect();
+                    if !bytes.is_empty() {
+                        return Ok(Some(bytes));
+                    }
+                    if Date::now() >= deadline {
+                        return Ok(None);
+                    }
+                    sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    async fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
+        match &self.link {
+            Link::Serial { writer, .. } => {
+                let bytes = Uint8Array::from(data);
+                resolve(call(writer, "write", &[bytes.into()])?).await?;
+            }
+            Link::Bluetooth {
+                characteristic,
+                write_method,
+                ..
+            } => {
+                for chunk in data.chunks(MAX_WRITE_LEN) {
+                    let bytes = Uint8Array::from(chunk);
+                    resolve(call(characteristic, write_method, &[bytes.into()])?).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Find the notify and write characteristics of the adapter's UART service
+async fn find_uart_characteristics(server: &JsValue) -> Result<(JsValue, JsValue)> {
+    for &uuid in &UART_SERVICES {
+        let Ok(service) = resolve(call(server, "getPrimaryService", &[uuid.into()])?).await else {
+            continue;
+        };
+        let characteristics: Array = resolve(call(&service, "getCharacteristics", &[])?)
+            .await?
+            .unchecked_into();
+        let with_property = |names: &[&str]| {
+            characteristics.iter().find(|c| {
+                get(c, "properties")
+                    .map(|p| {
+                        names
+                            .iter()
+                            .any(|n| get(&p, n).is_ok_and(|v| v.is_truthy()))
+                    })
+                    .unwrap_or(false)
+            })
+        };
+        if let (Some(notify), Some(write)) = (
+            with_property(&["notify"]),
+            with_property(&["write", "writeWithoutResponse"]),
+        ) {
+            info!("Using BLE service {:04X}", uuid);
+            return Ok((notify, write));
+        }
+    }
+    Err(Error::Communication(
+        "find_uart_characteristics: no UART service found".to_owned(),
+    ))
+}
+
+/// Get the property `key` of `target`
+fn get(target: &JsValue, key: &str) -> Result<JsValue> {
+    Reflect::get(target, &key.into()).map_err(js_error)
+}
+
+/// Call the method `name` of `target` with `args`
+fn call(target: &JsValue, name: &str, args: &[JsValue]) -> Result<JsValue> {
+    let method: Function = get(target, name)?
+        .dyn_into()
+        .map_err(|_| Error::Communication(format!("{} is not a function", name)))?;
+    method
+        .apply(target, &args.iter().collect())
+        .map_err(js_error)
+}
+
+/// Wait for `value` if it is a promise, and get what it resolves to
+async fn resolve(value: JsValue) -> Result<JsValue> {
+    match value.dyn_into::<Promise>() {
+        Ok(promise) => JsFuture::from(promise).await.map_err(js_error),
+        Err(value) => Ok(value),
+    }
+}
+
+/// A promise that resolves to `undefined` after `duration`
+fn timer(duration: Duration) -> Promise {
+    Promise::new(&mut |resolve, _| {
+        let global = js_sys::global();
+        if let Ok(set_timeout) = get(&global, "setTimeout").and_then(|f| {
+            f.dyn_into::<Function>()
+                .map_err(|_| Error::Communication("setTimeout is not a function".to_owned()))
+        }) {
+            let millis = JsValue::from(duration.as_millis() as f64);
+            let _ = set_timeout.call2(&global, &resolve, &millis);
+        }
+    })
+}
+
+async fn sleep(duration: Duration) {
+    let _ = JsFuture::from(timer(duration)).await;
+}
+
+fn js_error(e: JsValue) -> Error {
+    let message = e
+        .dyn_ref::<js_sys::Error>()
+        .map(|e| String::from(e.message()))
+        .or_else(|| e.as_string())
+        .unwrap_or_else(|| format!("{:?}", e));
+    Error::Communication(format!("JavaScript error: {}", message))
+}
@@ -0,0 +1,196 @@
+use log::{debug, info};
+
+use crate::{obd2_device::join_pid_responses, EcuResponse, Error, Obd2Device, Protocol, Result};
+
+/// Wraps an [Obd2Device] and sends WWH-OBD (ISO 27145) requests to vehicles that do not answer the
+/// classic OBD-II services
+///
+/// Newer heavy-duty vehicles, and some passenger vehicles, only report through WWH-OBD, which uses
+/// the UDS services with standardized data identifiers instead of the classic services. When it is
+/// created, this checks whether the vehicle answers service 1. If it does not but answers WWH-OBD,
+/// requests are translated, so the getters in
+/// [Obd2DataRetrieval](crate::commands::Obd2DataRetrieval) work unchanged:
+///
+/// - Service 1 PIDs are read as data identifiers `F400` to `F4FF` (service 0x22)
+/// - DTCs (services 3 and 7) are read with the WWH-OBD DTC report (service 0x19, `42`), and
+///   permanent DTCs (service 0xA) with its permanent DTC report (service 0x19, `55`)
+/// - Clearing DTCs (service 4) clears the emissions related DTCs (service 0x14)
+///
+/// Other requests are passed through unchanged.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Obd2DataRetrieval,
+///     device::{MockObd2Device, WwhObdDevice},
+/// };
+///
+/// let mut device = MockObd2Device::new();
+/// device.set_request_response(&[0x22, 0xF4, 0x00], [vec![0xBE, 0x1F, 0xA8, 0x13]]);
+/// device.set_request_response(&[0x22, 0xF4, 0x0C], [vec![0x0F, 0xA0]]);
+/// device.set_request_response(
+///     &[0x19, 0x42, 0x33, 0x08, 0xFF],
+///     // functional group, status and severity availability, format, then a DTC record
+///     [vec![0x33, 0xFF, 0xE0, 0x04, 0x20, 0x03, 0x01, 0x00, 0x08]],
+/// );
+///
+/// let mut device = WwhObdDevice::new(device).unwrap();
+/// assert!(device.is_wwh_obd());
+/// assert_eq!(device.get_rpm().unwrap(), [1000.]);
+/// assert_eq!(device.get_dtcs().unwrap()[0][0].to_string(), "P0301");
+/// ```
+pub struct WwhObdDevice<T: Obd2Device> {
+    device: T,
+    wwh_obd: bool,
+}
+
+impl<T: Obd2Device> WwhObdDevice<T> {
+    /// Find whether `device` is connected to a vehicle using WWH-OBD, and wrap it
+    ///
+    /// If the vehicle answers neither, the error from the classic request is returned.
+    pub fn new(mut device: T) -> Result<Self> {
+        let wwh_obd = match device.obd_command(0x01, 0x00) {
+            Ok(_) => false,
+            Err(classic) => {
+                debug!("WwhObdDevice::new: service 1 failed: {}", classic);
+                device
+                    .obd_request(&[0x22, 0xF4, 0x00])
+                    .map_err(|_| classic)?;
+                info!("Vehicle uses WWH-OBD");
+                true
+            }
+        };
+        Ok(WwhObdDevice { device, wwh_obd })
+    }
+
+    /// Whether the vehicle uses WWH-OBD, so requests are translated
+    pub fn is_wwh_obd(&self) -> bool {
+        self.wwh_obd
+    }
+
+    /// Get back the wrapped device
+    pub fn into_inner(self) -> T {
+        self.device
+    }
+
+    /// Get the WWH-OBD request replacing `request`, if the vehicle uses WWH-OBD and there is one
+    fn translate(&self, request: &[u8]) -> Option<Vec<u8>> {
+        if !self.wwh_obd {
+            return None;
+        }
+        match *request {
+            [0x01, pid] => Some(vec![0x22, 0xF4, pid]),
+            [0x03] => Some(vec![0x19, 0x42, 0x33, 0x08, 0xFF]),
+            [0x04] => Some(vec![0x14, 0xFF, 0xFF, 0x33]),
+            [0x07] => Some(vec![0x19, 0x42, 0x33, 0x04, 0xFF]),
+            [0x0A] => Some(vec![0x19, 0x55, 0x33]),
+            _ => None,
+        }
+    }
+
+    /// Send `request`, translated if needed
+    fn request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        match self.translate(request) {
+            Some(wwh) => self
+                .device
+                .obd_request(&wwh)?
+                .into_iter()
+                .map(|r| classic_response(request, r))
+                .collect(),
+            None => self.device.obd_request(request),
+        }
+    }
+}
+
+/// Convert the response to the WWH-OBD translation of `request` to the response to `request`
+///
+/// The data of PIDs is the same, but DTC reports are rewritten into the CAN format of the classic
+/// services: the number of DTCs, then two bytes for each. The failure type byte of each DTC is
+/// dropped.
+fn classic_response(request: &[u8], response: Vec<u8>) -> Result<Vec<u8>> {
+    // where the DTC records start, the length of each record, and where in it the DTC is
+    let (start, size, dtc) = match *request {
+        [0x03] | [0x07] => (4, 5, 1),
+        [0x0A] => (3, 4, 0),
+        _ => return Ok(response),
+    };
+    let records = response.get(start..).ok_or(Error::IncorrectResponseLength(
+        "WWH-OBD DTC report",
+        start,
+        response.len(),
+    ))?;
+    let dtcs: Vec<&[u8]> = records
+        .chunks_exact(size)
+        .map(|r| &r[dtc..dtc + 2])
+        .take(usize::from(u8::MAX))
+        .collect();
+    let mut classic = vec![dtcs.len() as u8];
+    classic.extend(dtcs.concat());
+    Ok(classic)
+}
+
+impl<T: Obd2Device> Obd2Device for WwhObdDevice<T> {
+    fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        if self.translate(&[mode, pid]).is_some() {
+            self.request(&[mode, pid])
+        } else {
+            self.device.obd_command(mode, pid)
+        }
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        if self.translate(&[mode]).is_some() {
+            self.request(&[mode])
+        } else {
+            self.device.obd_mode_command(mode)
+        }
+    }
+
+    /// With WWH-OBD, each PID is requested separately
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        if !self.wwh_obd || mode != 0x01 {
+            return self.device.obd_multi_command(mode, pids);
+        }
+        let mut responses: Vec<Vec<u8>> = Vec::new();
+        for &pid in pids {
+            join_pid_responses(&mut responses, pid, self.request(&[mode, pid])?);
+        }
+        Ok(responses)
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.request(request)
+    }
+
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        match self.translate(request) {
+            Some(wwh) => classic_response(request, self.device.obd_request_to(address, &wwh)?),
+            None => self.device.obd_request_to(address, request),
+        }
+    }
+
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        match self.translate(request) {
+            Some(wwh) => self
+                .device
+                .obd_request_tagged(&wwh)?
+                .into_iter()
+                .map(|r| {
+                    Ok(EcuResponse {
+                        address: r.address,
+                        data: classic_response(request, r.data)?,
+                    })
+                })
+                .collect(),
+            None => self.device.obd_request_tagged(request),
+        }
+    }
+
+    fn protocol(&mut self) -> Result<Protocol> {
+        self.device.protocol()
+    }
+
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        self.device.get_adapter_voltage()
+    }
+}
@@ -1,7 +1,32 @@
-pub type Result<T> = std::result::Result<T, Error>;
+use alloc::{string::String, vec::Vec};
+
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// An error with OBD-II communication
+///
+/// The variants separate problems with the link to the vehicle (like an unplugged adapter, which
+/// is a [Device](Self::Device) error) from the vehicle declining a request (like
+/// [NotSupported](Self::NotSupported), [NoData](Self::NoData) or
+/// [NegativeResponse](Self::NegativeResponse)), so callers can decide whether to retry, skip the
+/// PID, or reconnect.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Obd2DataRetrieval, device::Obd2Simulator, Error};
+///
+/// let mut device = Obd2Simulator::new();
+///
+/// // the simulated vehicle does not report the fuel pressure
+/// match device.get_fuel_pressure() {
+///     Err(Error::NoData | Error::NotSupported(..)) => println!("not available"),
+///     Err(e) => panic!("{}", e),
+///     Ok(pressure) => println!("{:?}", pressure),
+/// }
+/// # assert!(matches!(device.get_fuel_pressure(), Err(Error::NoData)));
+/// ```
 #[derive(thiserror::Error, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// An error occurred in the [Odb2BaseDevice](crate::device::Obd2BaseDevice)
     #[error("Device error: `{0:?}`")]
@@ -11,28 +36,350 @@ pub enum Error {
     #[error("Incorrect length (`{0}`): expected `{1}`, got `{2}`")]
     IncorrectResponseLength(&'static str, usize, usize),
 
+    /// The vehicle does not support the request (the mode and PID)
+    #[error("Not supported: mode `{0:02X}`, PID `{1:02X}`")]
+    NotSupported(u8, u8),
+
+    /// No ECU responded to the request, which usually means the vehicle does not support it
+    #[error("No data")]
+    NoData,
+
+    /// An ECU rejected the request with a negative response (`7F <mode> <code>`)
+    #[error("Negative response to mode `{mode:02X}`: {nrc}")]
+    NegativeResponse {
+        /// The mode of the rejected request
+        mode: u8,
+        /// The reason the request was rejected
+        nrc: NegativeResponseCode,
+    },
+
+    /// The adapter did not finish responding in time, after any retries from the device's
+    /// [DeviceConfig](crate::device::DeviceConfig)
+    #[error("Timed out waiting for a response")]
+    Timeout,
+
+    /// A frame from the vehicle failed its checksum, on protocols where frames have one
+    #[error("Invalid checksum")]
+    InvalidChecksum,
+
+    /// A different number of ECUs responded than the request expects
+    #[error("Unexpected ECU count: expected `{expected}`, got `{got}`")]
+    UnexpectedEcuCount {
+        /// The number of ECUs that should have responded
+        expected: usize,
+        /// The number of ECUs that responded
+        got: usize,
+    },
+
+    /// A response could not be decoded into the value for its PID
+    #[error("Could not decode response to mode `{mode:02X}`, PID `{pid:02X}`: `{bytes:02X?}`")]
+    DecodeError {
+        /// The mode of the request
+        mode: u8,
+        /// The PID of the request
+        pid: u8,
+        /// The data an ECU sent, after the echoed mode and PID
+        bytes: Vec<u8>,
+    },
+
+    /// The operation was stopped with a [CancellationToken](crate::device::CancellationToken)
+    #[error("Cancelled")]
+    Cancelled,
+
+    /// The device cannot do what was asked, like report the addresses of the ECUs that responded
+    #[error("Device does not support {0}")]
+    UnsupportedByDevice(&'static str),
+
+    /// An argument is outside the values the request accepts, like a fuel trim bank other than 1
+    /// or 2
+    #[error("Invalid {name}: `{value}`")]
+    InvalidArgument {
+        /// What the argument is
+        name: &'static str,
+        /// The value that was passed
+        value: u32,
+    },
+
+    /// A response did not start with the bytes it should echo from the request
+    #[error("Expected response starting with `{expected:02X?}`, got `{got:02X?}`")]
+    HeaderMismatch {
+        /// The echoed bytes, like the mode with `0x40` added and the PID
+        expected: Vec<u8>,
+        /// The response
+        got: Vec<u8>,
+    },
+
+    /// A response has fewer items than it should, like fewer DTCs than the count it starts with
+    #[error("Missing items: expected `{expected}`, got `{got}`")]
+    MissingItems {
+        /// The number of items the response should have
+        expected: usize,
+        /// The number of whole items in the response
+        got: usize,
+    },
+
+    /// A response to a request for several PIDs has a PID that is not a
+    /// [StandardPid](crate::commands::pid::StandardPid), so the length of its data is not known
+    #[error("Unknown PID `{0:02X}`")]
+    UnknownPid(u8),
+
+    /// A DTC is not a letter (P, C, B or U) followed by four hexadecimal digits, like `P0301`
+    #[error("Invalid DTC `{0}`")]
+    InvalidDtc(String),
+
+    /// A VIN is not 17 characters long, or has characters that are not allowed in VINs
+    #[error("Invalid VIN `{0}`")]
+    InvalidVin(String),
+
+    /// A line of the adapter's response could not be read, like a line without a header when
+    /// headers are on, or the lines of a long response out of order
+    #[error("Malformed response line `{0}`")]
+    MalformedLine(String),
+
+    /// A record could not be written, like a sample to a log file or an MQTT broker
+    #[error("Could not write {destination}: `{message}`")]
+    Output {
+        /// What was being written, like `JSON record`
+        destination: &'static str,
+        /// The error from the library doing the writing
+        message: String,
+    },
+
+    /// A [MockObd2Device](crate::device::MockObd2Device) or
+    /// [ReplayDevice](crate::device::ReplayDevice) has no more responses for a request
+    #[error("No response for request `{0:02X?}`")]
+    NoRecordedResponse(Vec<u8>),
+
+    /// A line of a replay log could not be read
+    #[error("Invalid replay log line {line}: `{text}`")]
+    InvalidLogLine {
+        /// The number of the line, starting from 1
+        line: usize,
+        /// The line
+        text: String,
+    },
+
+    /// A request failed when it was recorded, with the original error message
+    #[error("Recorded error: `{0}`")]
+    Recorded(String),
+
     /// Another error occurred
     #[error("Other OBD2 error: `{0}`")]
     Other(String),
 }
 
-#[derive(Debug)]
+/// The reason an ECU gave for rejecting a request, from ISO 14229-1
+///
+/// OBD-II only uses a few of these, but vehicles respond with the codes from the diagnostic
+/// services they share with manufacturer tools, so all of the common ones are included.
+///
+/// # Example
+/// ```
+/// use obd2::NegativeResponseCode;
+///
+/// let nrc = NegativeResponseCode::from(0x22);
+/// assert_eq!(nrc, NegativeResponseCode::ConditionsNotCorrect);
+/// assert_eq!(u8::from(nrc), 0x22);
+/// assert_eq!(nrc.to_string(), "conditions not correct (22)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum NegativeResponseCode {
+    /// The request was rejected without a more specific reason (0x10)
+    GeneralReject,
+    /// The ECU does not support the mode (0x11)
+    ServiceNotSupported,
+    /// The ECU does not support the parameter of the request, like a PID (0x12)
+    SubFunctionNotSupported,
+    /// The request was the wrong length or badly formed (0x13)
+    IncorrectMessageLength,
+    /// The response would be too long for the protocol (0x14)
+    ResponseTooLong,
+    /// The ECU is busy, and the request should be sent again later (0x21)
+    BusyRepeatRequest,
+    /// The vehicle is not in a state where the request can be carried out, like the engine
+    /// running during a test that needs it off (0x22)
+    ConditionsNotCorrect,
+    /// The request was sent in the wrong order with other requests (0x24)
+    RequestSequenceError,
+    /// A parameter of the request is outside the supported range (0x31)
+    RequestOutOfRange,
+    /// The request needs security access that has not been granted (0x33)
+    SecurityAccessDenied,
+    /// The key sent for security access was wrong (0x35)
+    InvalidKey,
+    /// Too many wrong keys were sent for security access (0x36)
+    ExceededNumberOfAttempts,
+    /// Security access was requested again too soon after a failure (0x37)
+    RequiredTimeDelayNotExpired,
+    /// The request was received, but the ECU needs more time before responding (0x78)
+    ///
+    /// This is handled while waiting for the response, so it is only returned if the ECU never
+    /// sends its real response.
+    ResponsePending,
+    /// The parameter of the request is not supported in the current diagnostic session (0x7E)
+    SubFunctionNotSupportedInActiveSession,
+    /// The mode is not supported in the current diagnostic session (0x7F)
+    ServiceNotSupportedInActiveSession,
+    /// Another code, including manufacturer specific codes
+    Other(u8),
+}
+
+impl From<u8> for NegativeResponseCode {
+    fn from(val: u8) -> Self {
+        match val {
+            0x10 => Self::GeneralReject,
+            0x11 => Self::ServiceNotSupported,
+            0x12 => Self::SubFunctionNotSupported,
+            0x13 => Self::IncorrectMessageLength,
+            0x14 => Self::ResponseTooLong,
+            0x21 => Self::BusyRepeatRequest,
+            0x22 => Self::ConditionsNotCorrect,
+            0x24 => Self::RequestSequenceError,
+            0x31 => Self::RequestOutOfRange,
+            0x33 => Self::SecurityAccessDenied,
+            0x35 => Self::InvalidKey,
+            0x36 => Self::ExceededNumberOfAttempts,
+            0x37 => Self::RequiredTimeDelayNotExpired,
+            0x78 => Self::ResponsePending,
+            0x7E => Self::SubFunctionNotSupportedInActiveSession,
+            0x7F => Self::ServiceNotSupportedInActiveSession,
+            _ => Self::Other(val),
+        }
+    }
+}
+
+impl From<NegativeResponseCode> for u8 {
+    fn from(nrc: NegativeResponseCode) -> Self {
+        use NegativeResponseCode::*;
+        match nrc {
+            GeneralReject => 0x10,
+            ServiceNotSupported => 0x11,
+            SubFunctionNotSupported => 0x12,
+            IncorrectMessageLength => 0x13,
+            ResponseTooLong => 0x14,
+            BusyRepeatRequest => 0x21,
+            ConditionsNotCorrect => 0x22,
+            RequestSequenceError => 0x24,
+            RequestOutOfRange => 0x31,
+            SecurityAccessDenied => 0x33,
+            InvalidKey => 0x35,
+            ExceededNumberOfAttempts => 0x36,
+            RequiredTimeDelayNotExpired => 0x37,
+            ResponsePending => 0x78,
+            SubFunctionNotSupportedInActiveSession => 0x7E,
+            ServiceNotSupportedInActiveSession => 0x7F,
+            Other(val) => val,
+        }
+    }
+}
+
+impl core::fmt::Display for NegativeResponseCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use NegativeResponseCode::*;
+        let name = match self {
+            GeneralReject => "general reject",
+            ServiceNotSupported => "service not supported",
+            SubFunctionNotSupported => "sub-function not supported",
+            IncorrectMessageLength => "incorrect message length or format",
+            ResponseTooLong => "response too long",
+            BusyRepeatRequest => "busy, repeat request",
+            ConditionsNotCorrect => "conditions not correct",
+            RequestSequenceError => "request sequence error",
+            RequestOutOfRange => "request out of range",
+            SecurityAccessDenied => "security access denied",
+            InvalidKey => "invalid key",
+            ExceededNumberOfAttempts => "exceeded number of attempts",
+            RequiredTimeDelayNotExpired => "required time delay not expired",
+            ResponsePending => "response pending",
+            SubFunctionNotSupportedInActiveSession => {
+                "sub-function not supported in active session"
+            }
+            ServiceNotSupportedInActiveSession => "service not supported in active session",
+            Other(_) => "unknown code",
+        };
+        write!(f, "{} ({:02X})", name, u8::from(*self))
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[error(transparent)]
 pub struct DeviceError(crate::device::Error);
 
-impl From<super::device::Error> for Error {
-    fn from(e: super::device::Error) -> Self {
-        Error::Device(DeviceError(e))
+impl Error {
+    /// Whether the link to the adapter or the vehicle was lost, so reconnecting may help
+    ///
+    /// This is true for closed connections, unplugged adapters, and adapters that cannot reach
+    /// the vehicle, but not when the vehicle declines a request or the adapter does not support
+    /// something.
+    pub fn is_connection_lost(&self) -> bool {
+        use crate::device::Error as E;
+        match self {
+            Error::Device(DeviceError(e)) => match e {
+                #[cfg(feature = "std")]
+                E::Serial(e) => !matches!(e.kind(), serialport::ErrorKind::InvalidInput),
+                #[cfg(feature = "std")]
+                E::IO(e) => is_dropped(e),
+                #[cfg(feature = "ble")]
+                E::Ble(_) => true,
+                #[cfg(feature = "embedded")]
+                E::EmbeddedSerial(_) => false,
+                E::Communication(_) => false,
+                E::Disconnected(_) => true,
+            },
+            _ => false,
+        }
+    }
+
+    /// Whether the vehicle declined the request, because no ECU supports it or an ECU rejected
+    /// it
+    ///
+    /// Sending the same request again is expected to be declined again, so callers reading many
+    /// values can leave this one out.
+    pub fn is_declined(&self) -> bool {
+        matches!(
+            self,
+            Error::NoData | Error::NotSupported(..) | Error::NegativeResponse { .. }
+        )
     }
 }
 
-impl From<std::num::ParseIntError> for Error {
-    fn from(e: std::num::ParseIntError) -> Self {
-        Error::Other(format!("invalid data recieved: {:?}", e))
+/// Whether an I/O error means the connection or the device is gone
+#[cfg(feature = "std")]
+fn is_dropped(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        e.kind(),
+        ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::NotConnected
+            | ErrorKind::UnexpectedEof
+            | ErrorKind::NotFound
+    ) || matches!(
+        // EIO, ENXIO and ENODEV, which reads of an unplugged serial adapter fail with
+        e.raw_os_error(),
+        Some(5 | 6 | 19) if cfg!(unix)
+    )
+}
+
+impl From<super::device::Error> for Error {
+    fn from(e: super::device::Error) -> Self {
+        Error::Device(DeviceError(e))
     }
 }
 
-impl From<std::string::FromUtf8Error> for Error {
-    fn from(e: std::string::FromUtf8Error) -> Self {
-        Error::Other(format!("invalid string recieved: {:?}", e))
+/// Errors are serialized as their message, since the underlying device errors (like I/O errors)
+/// cannot be serialized
+#[cfg(feature = "serde")]
+impl serde::Serialize for Error {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
     }
 }
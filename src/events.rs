@@ -0,0 +1,293 @@
+//! Detecting driving events
+//!
+//! Fleet and insurance telematics score drivers on events like braking hard or speeding. An
+//! [EventDetector] watches the [samples](crate::poller::Sample) of an
+//! [Obd2Poller](crate::poller::Obd2Poller) and reports each event once when its threshold is
+//! crossed, and again only after the value has gone back below it.
+//!
+//! # Example
+//! ```no_run
+//! use obd2::{
+//!     commands::pid::StandardPid, device::Obd2Simulator, events::EventDetector,
+//!     poller::Obd2Poller,
+//! };
+//! use std::time::Duration;
+//!
+//! let poller = Obd2Poller::new(Obd2Simulator::new())
+//!     .poll(StandardPid::VehicleSpeed, Duration::from_millis(200))
+//!     .poll(StandardPid::EngineSpeed, Duration::from_millis(200))
+//!     .poll(StandardPid::EngineCoolantTemperature, Duration::from_secs(5))
+//!     .start();
+//!
+//! let mut detector = EventDetector::new();
+//! for sample in poller.samples().iter().flatten() {
+//!     if let Some(event) = detector.add_sample(&sample) {
+//!         println!("{:?}", event);
+//!     }
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::{commands::pid::StandardPid, poller::Sample};
+
+/// An event detected by an [EventDetector]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DrivingEvent {
+    /// The vehicle sped up faster than the threshold, at `rate` km/h per second
+    HarshAcceleration {
+        /// How fast the vehicle sped up, in km/h per second
+        rate: f32,
+    },
+    /// The vehicle slowed down faster than the threshold, at `rate` km/h per second
+    HarshBraking {
+        /// How fast the vehicle slowed down, in km/h per second
+        rate: f32,
+    },
+    /// The engine speed went over the threshold
+    OverRev {
+        /// The engine speed in rpm
+        rpm: f32,
+    },
+    /// The vehicle speed went over the threshold
+    Overspeed {
+        /// The vehicle speed in km/h
+        speed: f32,
+    },
+    /// The vehicle has been stopped with the engine running for longer than the threshold
+    LongIdle {
+        /// How long the vehicle has been idling
+        duration: Duration,
+    },
+    /// The coolant temperature went over the threshold
+    CoolantOverheat {
+        /// The coolant temperature in ºC
+        temperature: f32,
+    },
+}
+
+/// The limits of an [EventDetector]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EventThresholds {
+    /// The acceleration for [HarshAcceleration](DrivingEvent::HarshAcceleration), in km/h per
+    /// second
+    pub harsh_acceleration: f32,
+
+    /// The deceleration for [HarshBraking](DrivingEvent::HarshBraking), in km/h per second
+    pub harsh_braking: f32,
+
+    /// The shortest time the speed is compared over, so that the speed's 1 km/h steps do not look
+    /// like harsh events between samples close together
+    pub acceleration_window: Duration,
+
+    /// The engine speed for [OverRev](DrivingEvent::OverRev), in rpm
+    pub max_rpm: f32,
+
+    /// The vehicle speed for [Overspeed](DrivingEvent::Overspeed), in km/h
+    pub max_speed: f32,
+
+    /// The idle time for [LongIdle](DrivingEvent::LongIdle)
+    pub max_idle: Duration,
+
+    /// The coolant temperature for [CoolantOverheat](DrivingEvent::CoolantOverheat), in ºC
+    pub max_coolant_temperature: f32,
+}
+
+impl Default for EventThresholds {
+    /// Harsh events at about 0.35 g (12 km/h per second) accelerating and 0.4 g (14 km/h per
+    /// second) braking, over-rev at 4500 rpm, overspeed at 130 km/h, long idle after 5 minutes,
+    /// and overheating at 110 ºC
+    fn default() -> Self {
+        EventThresholds {
+            harsh_acceleration: 12.,
+            harsh_braking: 14.,
+            acceleration_window: Duration::from_secs(1),
+            max_rpm: 4500.,
+            max_speed: 130.,
+            max_idle: Duration::from_secs(300),
+            max_coolant_temperature: 110.,
+        }
+    }
+}
+
+impl EventThresholds {
+    /// Set the acceleration for harsh acceleration, in km/h per second
+    pub fn with_harsh_acceleration(mut self, rate: f32) -> Self {
+        self.harsh_acceleration = rate;
+        self
+    }
+
+    /// Set the deceleration for harsh braking, in km/h per second
+    pub fn with_harsh_braking(mut self, rate: f32) -> Self {
+        self.harsh_braking = rate;
+        self
+    }
+
+    /// Set the shortest time the speed is compared over for harsh events
+    pub fn with_acceleration_window(mut self, window: Duration) -> Self {
+        self.acceleration_window = window;
+        self
+    }
+
+    /// Set the engine speed for over-rev, in rpm
+    pub fn with_max_rpm(mut self, rpm: f32) -> Self {
+        self.max_rpm = rpm;
+        self
+    }
+
+    /// Set the vehicle speed for overspeed, in km/h
+    pub fn with_max_speed(mut self, speed: f32) -> Self {
+        self.max_speed = speed;
+        self
+    }
+
+    /// Set the idle time for long idle
+    pub fn with_max_idle(mut self, duration: Duration) -> Self {
+        self.max_idle = duration;
+        self
+    }
+
+    /// Set the coolant temperature for overheating, in ºC
+    pub fn with_max_coolant_temperature(mut self, temperature: f32) -> Self {
+        self.max_coolant_temperature = temperature;
+        self
+    }
+}
+
+/// Detects [DrivingEvent]s from the vehicle speed, engine speed and coolant temperature
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::pid::StandardPid,
+///     events::{DrivingEvent, EventDetector},
+/// };
+/// use std::time::{Duration, Instant};
+///
+/// let mut detector = EventDetector::new();
+/// let start = Instant::now();
+///
+/// detector.update(StandardPid::VehicleSpeed, 60., start);
+/// // 20 km/h slower a second later
+/// let event = detector.update(StandardPid::VehicleSpeed, 40., start + Duration::from_secs(1));
+/// assert_eq!(event, Some(DrivingEvent::HarshBraking { rate: 20. }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EventDetector {
+    thresholds: EventThresholds,
+    reference_speed: Option<(f32, Instant)>,
+    idle_since: Option<Instant>,
+    engine_stopped: bool,
+    harsh: bool,
+    over_rev: bool,
+    overspeed: bool,
+    long_idle: bool,
+    overheat: bool,
+}
+
+impl EventDetector {
+    /// Create an [`EventDetector`] with the default thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the thresholds events are detected at
+    pub fn with_thresholds(mut self, thresholds: EventThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Get the thresholds events are detected at
+    pub fn thresholds(&self) -> &EventThresholds {
+        &self.thresholds
+    }
+
+    /// Check a sample from a poller for an event
+    ///
+    /// Only the first ECU's value is used.
+    pub fn add_sample(&mut self, sample: &Sample) -> Option<DrivingEvent> {
+        let &value = sample.values.first()?;
+        self.update(sample.pid, value, sample.time)
+    }
+
+    /// Check a new `value` of `pid`, read at `time`, for an event
+    ///
+    /// The vehicle speed, engine speed and coolant temperature are used, and other PIDs are
+    /// ignored. Without the engine speed, the engine is taken to be running whenever the vehicle
+    /// is stopped.
+    pub fn update(&mut self, pid: StandardPid, value: f32, time: Instant) -> Option<DrivingEvent> {
+        match pid {
+            StandardPid::VehicleSpeed => self.update_speed(value, time),
+            StandardPid::EngineSpeed => {
+                self.engine_stopped = value == 0.;
+                let event = DrivingEvent::OverRev { rpm: value };
+                edge(&mut self.over_rev, value > self.thresholds.max_rpm, event)
+            }
+            StandardPid::EngineCoolantTemperature => {
+                let over = value > self.thresholds.max_coolant_temperature;
+                let event = DrivingEvent::CoolantOverheat { temperature: value };
+                edge(&mut self.overheat, over, event)
+            }
+            _ => None,
+        }
+    }
+
+    fn update_speed(&mut self, speed: f32, time: Instant) -> Option<DrivingEvent> {
+        let thresholds = self.thresholds;
+
+        let harsh = match self.reference_speed {
+            Some((reference, since)) => {
+                let elapsed = time.saturating_duration_since(since);
+                if elapsed < thresholds.acceleration_window {
+                    None
+                } else {
+                    self.reference_speed = Some((speed, time));
+                    let rate = (speed - reference) / elapsed.as_secs_f32();
+                    if rate > thresholds.harsh_acceleration {
+                        Some(DrivingEvent::HarshAcceleration { rate })
+                    } else if -rate > thresholds.harsh_braking {
+                        Some(DrivingEvent::HarshBraking { rate: -rate })
+                    } else {
+                        self.harsh = false;
+                        None
+                    }
+                }
+            }
+            None => {
+                self.reference_speed = Some((speed, time));
+                None
+            }
+        };
+        if let Some(event) = harsh {
+            if let Some(event) = edge(&mut self.harsh, true, event) {
+                return Some(event);
+            }
+        }
+
+        if speed > 0. || self.engine_stopped {
+            self.idle_since = None;
+            self.long_idle = false;
+        } else {
+            let since = *self.idle_since.get_or_insert(time);
+            let duration = time.saturating_duration_since(since);
+            let event = DrivingEvent::LongIdle { duration };
+            if let Some(event) = edge(&mut self.long_idle, duration > thresholds.max_idle, event) {
+                return Some(event);
+            }
+        }
+
+        let event = DrivingEvent::Overspeed { speed };
+        edge(&mut self.overspeed, speed > thresholds.max_speed, event)
+    }
+}
+
+/// Get `event` if `over` became true, remembering it in `state`
+fn edge(state: &mut bool, over: bool, event: DrivingEvent) -> Option<DrivingEvent> {
+    let crossed = over && !*state;
+    *state = over;
+    crossed.then_some(event)
+}
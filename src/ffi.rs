@@ -0,0 +1,511 @@
+//! A C API, for using the crate from C and C++
+//!
+//! With the `cdylib` feature, these functions are exported with C linkage, and the crate can be
+//! built as a shared library with
+//!
+//! ```text
+//! cargo rustc --release --lib --features cdylib --crate-type cdylib
+//! ```
+//!
+//! which produces `libobd2.so` (or `obd2.dll`, or `libobd2.dylib`) in `target/release`. Their
+//! declarations are in `include/obd2.h`, which is generated with
+//! [cbindgen](https://github.com/mozilla/cbindgen) from this module by running `cbindgen --output
+//! include/obd2.h` in the crate's root.
+//!
+//! A device is opened into an opaque `Obd2Device`, which is passed to the other functions and
+//! freed with `obd2_close`. Every function that can fail returns an `Obd2Status`, with its
+//! results written through pointers:
+//!
+//! ```c
+//! #include <stdio.h>
+//! #include "obd2.h"
+//!
+//! int main(void) {
+//!     Obd2Device *device;
+//!     Obd2Status status = obd2_open_serial("/dev/ttyUSB0", &device);
+//!     if (status != OBD2_STATUS_OK) {
+//!         fprintf(stderr, "could not open device: %s\n", obd2_status_message(status));
+//!         return 1;
+//!     }
+//!
+//!     float rpm;
+//!     if (obd2_read_pid(device, 0x0C, &rpm) == OBD2_STATUS_OK) {
+//!         printf("%.0f rpm\n", rpm);
+//!     }
+//!
+//!     Obd2Dtc dtcs[16];
+//!     size_t count;
+//!     if (obd2_read_dtcs(device, dtcs, 16, &count) == OBD2_STATUS_OK) {
+//!         for (size_t i = 0; i < count && i < 16; i++) {
+//!             printf("%s\n", dtcs[i].code);
+//!         }
+//!     }
+//!
+//!     obd2_close(device);
+//!     return 0;
+//! }
+//! ```
+//!
+//! A panic in the library does not unwind into the caller: the function returns
+//! `OBD2_STATUS_PANIC` instead, and the open functions leave the device null.
+//!
+//! The devices are not thread-safe: each may only be used by one thread at a time. A device that
+//! is being polled with `obd2_poll_start` belongs to the poller until `obd2_poll_stop` gives it
+//! back.
+#![allow(unsafe_code)]
+
+use std::{
+    ffi::{c_char, c_void, CStr},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    sync::mpsc::{self, RecvTimeoutError, TryRecvError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    commands::{pid::StandardPid, Obd2DataRetrieval},
+    device::{Elm327, Obd2Simulator, TcpElm327},
+    Error, Obd2, Obd2Device as _, Obd2DeviceDyn,
+};
+
+/// An open device
+pub struct Obd2Device(Box<dyn Obd2DeviceDyn + Send>);
+
+/// A device being polled on a background thread
+pub struct Obd2Poller {
+    stop: mpsc::Sender<()>,
+    /// Gives back the device, or `None` if polling panicked
+    thread: JoinHandle<Option<Box<dyn Obd2DeviceDyn + Send>>>,
+}
+
+/// The result of a call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Obd2Status {
+    /// The call succeeded
+    Ok = 0,
+    /// A pointer was null, a string was not valid UTF-8, or a PID is not known
+    InvalidArgument = 1,
+    /// No ECU responded, which usually means the vehicle does not support the request
+    NoData = 2,
+    /// The adapter did not respond in time
+    Timeout = 3,
+    /// The vehicle rejected the request or does not support it
+    NotSupported = 4,
+    /// Communicating with the adapter failed
+    Device = 5,
+    /// Another error occurred
+    Other = 6,
+    /// The library panicked, and the device it was using is lost
+    Panic = 7,
+}
+
+impl From<Error> for Obd2Status {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::NoData => Obd2Status::NoData,
+            Error::Timeout => Obd2Status::Timeout,
+            Error::NotSupported(..)
+            | Error::NegativeResponse { .. }
+            | Error::UnsupportedByDevice(_) => Obd2Status::NotSupported,
+            Error::InvalidArgument { .. } => Obd2Status::InvalidArgument,
+            Error::Device(_) => Obd2Status::Device,
+            _ => Obd2Status::Other,
+        }
+    }
+}
+
+/// A diagnostic trouble code, like `P0301`, as a NUL-terminated string
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Obd2Dtc {
+    /// The code, followed by NUL bytes
+    pub code: [c_char; 8],
+}
+
+/// Called with each value polled, on the poller's thread, with the `user_data` given to
+/// `obd2_poll_start`
+pub type Obd2SampleCallback = Option<extern "C" fn(pid: u8, value: f32, user_data: *mut c_void)>;
+
+/// The `user_data` of a [Obd2SampleCallback], which C code is responsible for sharing safely
+struct UserData(*mut c_void);
+
+// SAFETY: the caller of `obd2_poll_start` promises that `user_data` may be used from the poller's
+// thread
+unsafe impl Send for UserData {}
+
+/// Get a description of `status`, as a static NUL-terminated string
+#[no_mangle]
+pub extern "C" fn obd2_status_message(status: Obd2Status) -> *const c_char {
+    let message: &'static [u8] = match status {
+        Obd2Status::Ok => b"ok\0",
+        Obd2Status::InvalidArgument => b"invalid argument\0",
+        Obd2Status::NoData => b"no data\0",
+        Obd2Status::Timeout => b"timed out waiting for a response\0",
+        Obd2Status::NotSupported => b"not supported by the vehicle\0",
+        Obd2Status::Device => b"device error\0",
+        Obd2Status::Other => b"other error\0",
+        Obd2Status::Panic => b"panicked\0",
+    };
+    message.as_ptr().cast()
+}
+
+/// Open an ELM327 adapter connected to the serial port at `path`, like `/dev/ttyUSB0` or `COM3`
+///
+/// # Safety
+/// `path` must be a NUL-terminated string, and `device` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_open_serial(
+    path: *const c_char,
+    device: *mut *mut Obd2Device,
+) -> Obd2Status {
+    let Some(path) = str_arg(path) else {
+        return Obd2Status::InvalidArgument;
+    };
+    open(device, || Ok(Obd2::new(Elm327::new(path)?).boxed()))
+}
+
+/// Open an ELM327 adapter connected over Wi-Fi at `address`, like `192.168.0.10:35000`
+///
+/// # Safety
+/// `address` must be a NUL-terminated string, and `device` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_open_tcp(
+    address: *const c_char,
+    device: *mut *mut Obd2Device,
+) -> Obd2Status {
+    let Some(address) = str_arg(address) else {
+        return Obd2Status::InvalidArgument;
+    };
+    open(device, || {
+        Ok(Obd2::new(TcpElm327::connect(address)?).boxed())
+    })
+}
+
+/// Open a simulated vehicle, for testing without an adapter
+///
+/// # Safety
+/// `device` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_open_simulator(device: *mut *mut Obd2Device) -> Obd2Status {
+    open(device, || Ok(Obd2Simulator::new().boxed()))
+}
+
+/// Close `device`, which must not be used afterwards
+///
+/// Passing a null pointer does nothing.
+///
+/// # Safety
+/// `device` must be null or a device that was opened and not yet closed or being polled.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_close(device: *mut Obd2Device) {
+    if !device.is_null() {
+        catch(|| {
+            drop(Box::from_raw(device));
+            Obd2Status::Ok
+        });
+    }
+}
+
+/// Read the value of service 1 `pid` from the first ECU that responds, in the units of SAE J1979
+///
+/// Only PIDs that hold a single number, like 0x0C for the engine speed, can be read.
+///
+/// # Safety
+/// `device` must be an open device, and `value` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_read_pid(
+    device: *mut Obd2Device,
+    pid: u8,
+    value: *mut f32,
+) -> Obd2Status {
+    catch(|| {
+        let (Some(device), false) = (device.as_mut(), value.is_null()) else {
+            return Obd2Status::InvalidArgument;
+        };
+        let Some(pid) = StandardPid::from_pid(pid).filter(|pid| pid.unit().is_some()) else {
+            return Obd2Status::InvalidArgument;
+        };
+        match device.0.get_snapshot(&[pid]) {
+            Ok(snapshot) => match snapshot.get(&pid).and_then(|values| values.first()) {
+                Some(&read) => {
+                    *value = read;
+                    Obd2Status::Ok
+                }
+                None => Obd2Status::NoData,
+            },
+            Err(e) => e.into(),
+        }
+    })
+}
+
+/// Read the stored DTCs of every ECU into `dtcs`, which has room for `capacity` codes
+///
+/// The number of DTCs is written to `count`, even if it is more than `capacity`, in which case
+/// only the first `capacity` are written.
+///
+/// # Safety
+/// `device` must be an open device, `dtcs` must be valid for writing `capacity` codes (or null if
+/// `capacity` is 0), and `count` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_read_dtcs(
+    device: *mut Obd2Device,
+    dtcs: *mut Obd2Dtc,
+    capacity: usize,
+    count: *mut usize,
+) -> Obd2Status {
+    catch(|| {
+        let Some(device) = device.as_mut() else {
+            return Obd2Status::InvalidArgument;
+        };
+        if count.is_null() || (dtcs.is_null() && capacity > 0) {
+            return Obd2Status::InvalidArgument;
+        }
+        let read: Vec<_> = match device.0.get_dtcs() {
+            Ok(read) => read.into_iter().flatten().collect(),
+            Err(e) => return e.into(),
+        };
+        *count = read.len();
+        for (i, dtc) in read.iter().take(capacity).enumerate() {
+            let mut code = [0; 8];
+            for (c, byte) in code.iter_mut().zip(dtc.to_string().bytes()) {
+                *c = byte as c_char;
+            }
+            *dtcs.add(i) = Obd2Dtc { code };
+        }
+        Obd2Status::Ok
+    })
+}
+
+/// Clear the DTCs and freeze frames of every ECU, and turn off the "check engine" light
+///
+/// Vehicles usually only accept this with the ignition on and the engine off.
+///
+/// # Safety
+/// `device` must be an open device.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_clear_dtcs(device: *mut Obd2Device) -> Obd2Status {
+    catch(|| {
+        let Some(device) = device.as_mut() else {
+            return Obd2Status::InvalidArgument;
+        };
+        match device.0.obd_mode_command(0x04) {
+            Ok(_) => Obd2Status::Ok,
+            Err(e) => e.into(),
+        }
+    })
+}
+
+/// Poll `pids` every `interval_ms` milliseconds on a new thread, calling `callback` with each
+/// value
+///
+/// On success, the poller is written to `poller`, and `device` belongs to it until
+/// `obd2_poll_stop` gives it back. Requests that fail are skipped.
+///
+/// # Safety
+/// `device` must be an open device, `pids` must be valid for reading `count` PIDs, `poller` must
+/// be valid for writes, and `user_data` must be safe to use from the poller's thread.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_poll_start(
+    device: *mut Obd2Device,
+    pids: *const u8,
+    count: usize,
+    interval_ms: u32,
+    callback: Obd2SampleCallback,
+    user_data: *mut c_void,
+    poller: *mut *mut Obd2Poller,
+) -> Obd2Status {
+    catch(|| {
+        let (false, false, false, Some(callback)) =
+            (device.is_null(), pids.is_null(), poller.is_null(), callback)
+        else {
+            return Obd2Status::InvalidArgument;
+        };
+        let Some(pids) = std::slice::from_raw_parts(pids, count)
+            .iter()
+            .map(|&pid| StandardPid::from_pid(pid))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Obd2Status::InvalidArgument;
+        };
+
+        let device = Box::from_raw(device).0;
+        let interval = Duration::from_millis(interval_ms.into());
+        let handle = pids
+            .into_iter()
+            .fold(crate::poller::Obd2Poller::new(device), |poller, pid| {
+                poller.poll(pid, interval)
+            })
+            .start();
+
+        let (stop, stopped) = mpsc::channel();
+        let user_data = UserData(user_data);
+        // a panic must not unwind into the caller of `obd2_poll_stop`
+        let thread = thread::spawn(move || {
+            let user_data = user_data;
+            panic::catch_unwind(AssertUnwindSafe(|| loop {
+                if !matches!(stopped.try_recv(), Err(TryRecvError::Empty)) {
+                    break handle.stop();
+                }
+                match handle.samples().recv_timeout(Duration::from_millis(50)) {
+                    Ok(Ok(sample)) => {
+                        if let Some(&value) = sample.values.first() {
+                            callback(sample.pid.pid(), value, user_data.0);
+                        }
+                    }
+                    Ok(Err(_)) | Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break handle.stop(),
+                }
+            }))
+            .ok()
+        });
+        *poller = Box::into_raw(Box::new(Obd2Poller { stop, thread }));
+        Obd2Status::Ok
+    })
+}
+
+/// Stop `poller` and write its device back to `device`, once the current request and callback
+/// have finished
+///
+/// No more callbacks are made after this returns, and the poller is freed. If polling panicked,
+/// the device is lost: null is written to `device` and the status is `OBD2_STATUS_PANIC`.
+///
+/// # Safety
+/// `poller` must be a poller that was started and not yet stopped, and `device` must be valid
+/// for writes.
+#[no_mangle]
+pub unsafe extern "C" fn obd2_poll_stop(
+    poller: *mut Obd2Poller,
+    device: *mut *mut Obd2Device,
+) -> Obd2Status {
+    catch(|| {
+        if poller.is_null() || device.is_null() {
+            return Obd2Status::InvalidArgument;
+        }
+        let poller = Box::from_raw(poller);
+        let _ = poller.stop.send(());
+        match poller.thread.join() {
+            Ok(Some(stopped)) => {
+                *device = Box::into_raw(Box::new(Obd2Device(stopped)));
+                Obd2Status::Ok
+            }
+            Ok(None) | Err(_) => {
+                *device = ptr::null_mut();
+                Obd2Status::Panic
+            }
+        }
+    })
+}
+
+/// Open a device with `open`, writing it to `device`
+unsafe fn open(
+    device: *mut *mut Obd2Device,
+    open: impl FnOnce() -> crate::Result<Box<dyn Obd2DeviceDyn + Send>>,
+) -> Obd2Status {
+    if device.is_null() {
+        return Obd2Status::InvalidArgument;
+    }
+    // left null if opening fails or panics
+    *device = ptr::null_mut();
+    catch(|| match open() {
+        Ok(opened) => {
+            *device = Box::into_raw(Box::new(Obd2Device(opened)));
+            Obd2Status::Ok
+        }
+        Err(e) => e.into(),
+    })
+}
+
+/// Run the body of an exported function, getting [Obd2Status::Panic] if it panics, so that the
+/// panic does not unwind into the caller
+fn catch(body: impl FnOnce() -> Obd2Status) -> Obd2Status {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(Obd2Status::Panic)
+}
+
+/// Borrow a NUL-terminated UTF-8 string argument, or get `None` if it is null or not UTF-8
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Result;
+
+    /// A device that panics on every request
+    struct Panicking;
+
+    impl crate::Obd2Device for Panicking {
+        fn obd_command(&mut self, _mode: u8, _pid: u8) -> Result<Vec<Vec<u8>>> {
+            panic!("device failed");
+        }
+
+        fn obd_mode_command(&mut self, _mode: u8) -> Result<Vec<Vec<u8>>> {
+            panic!("device failed");
+        }
+    }
+
+    extern "C" fn ignore(_pid: u8, _value: f32, _user_data: *mut c_void) {}
+
+    unsafe fn poll(device: Obd2Device) -> (Obd2Status, *mut Obd2Device) {
+        let device = Box::into_raw(Box::new(device));
+        let mut poller = ptr::null_mut();
+        let status = obd2_poll_start(
+            device,
+            [0x0C].as_ptr(),
+            1,
+            10,
+            Some(ignore),
+            ptr::null_mut(),
+            &mut poller,
+        );
+        assert_eq!(status, Obd2Status::Ok);
+        thread::sleep(Duration::from_millis(100));
+        let mut stopped = ptr::null_mut();
+        (obd2_poll_stop(poller, &mut stopped), stopped)
+    }
+
+    #[test]
+    fn poll_stop_gives_back_device() {
+        unsafe {
+            let (status, device) = poll(Obd2Device(Obd2Simulator::new().boxed()));
+            assert_eq!(status, Obd2Status::Ok);
+            assert!(!device.is_null());
+            obd2_close(device);
+        }
+    }
+
+    #[test]
+    fn requests_report_panic() {
+        unsafe {
+            let device = Box::into_raw(Box::new(Obd2Device(Panicking.boxed())));
+            let mut value = 0.0;
+            assert_eq!(obd2_read_pid(device, 0x0C, &mut value), Obd2Status::Panic);
+            assert_eq!(obd2_clear_dtcs(device), Obd2Status::Panic);
+            obd2_close(device);
+        }
+    }
+
+    #[test]
+    fn open_reports_panic() {
+        unsafe {
+            let mut device = ptr::NonNull::dangling().as_ptr();
+            let status = open(&mut device, || panic!("open failed"));
+            assert_eq!(status, Obd2Status::Panic);
+            assert!(device.is_null());
+        }
+    }
+
+    #[test]
+    fn poll_stop_reports_panic() {
+        unsafe {
+            let (status, device) = poll(Obd2Device(Panicking.boxed()));
+            assert_eq!(status, Obd2Status::Panic);
+            assert!(device.is_null());
+        }
+    }
+}
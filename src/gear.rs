@@ -0,0 +1,300 @@
+//! Estimating the gear
+//!
+//! Vehicles do not report the gear over OBD-II, but in each gear the engine turns a fixed number
+//! of times for each turn of the wheels, so the gear can be found from the engine speed divided by
+//! the vehicle speed. A [GearEstimator] knows that ratio for each gear, either computed from the
+//! gearbox, final drive and tires of a [Drivetrain], or learned while driving.
+//!
+//! The estimate is only meaningful for manual gearboxes and automatics with a locked torque
+//! converter. While the clutch is slipping, or a CVT is changing its ratio, no gear matches and
+//! the estimate is `None`.
+//!
+//! # Example
+//! ```
+//! use obd2::gear::{Drivetrain, GearEstimator};
+//!
+//! // a five speed gearbox with a 4.06 final drive, on 205/55R16 tires
+//! let drivetrain = Drivetrain::new(
+//!     vec![3.58, 2.02, 1.35, 1.03, 0.81],
+//!     4.06,
+//!     Drivetrain::tire_circumference(205., 55., 16.),
+//! );
+//! let estimator = GearEstimator::new(&drivetrain);
+//!
+//! assert_eq!(estimator.estimate(2420., 50.), Some(3));
+//! // the clutch is in
+//! assert_eq!(estimator.estimate(900., 50.), None);
+//! ```
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use crate::{commands::pid::StandardPid, poller::Sample};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// The parts of a vehicle that set how fast the engine turns at each speed
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Drivetrain {
+    /// The ratio of each gear of the gearbox, starting with first
+    pub gear_ratios: Vec<f32>,
+
+    /// The ratio of the final drive (the differential)
+    pub final_drive: f32,
+
+    /// The distance the driven wheels travel in one turn, in m
+    pub tire_circumference: f32,
+}
+
+impl Drivetrain {
+    /// Create a [`Drivetrain`] from the ratio of each gear starting with first, the final drive
+    /// ratio and the tire circumference in m
+    pub fn new(gear_ratios: Vec<f32>, final_drive: f32, tire_circumference: f32) -> Self {
+        Drivetrain {
+            gear_ratios,
+            final_drive,
+            tire_circumference,
+        }
+    }
+
+    /// Compute the circumference in m of a tire from its size, like 205/55R16 for a tire 205 mm
+    /// wide, with sidewalls 55% as tall as it is wide, on a 16" rim
+    pub fn tire_circumference(width: f32, aspect_ratio: f32, rim_diameter: f32) -> f32 {
+        let diameter = rim_diameter * 0.0254 + 2. * width / 1000. * aspect_ratio / 100.;
+        diameter * core::f32::consts::PI
+    }
+
+    /// Get the engine speed in rpm for each km/h in each gear, starting with first
+    pub fn rpm_per_kmh(&self) -> Vec<f32> {
+        let wheel_rpm_per_kmh = 1000. / 60. / self.tire_circumference;
+        self.gear_ratios
+            .iter()
+            .map(|ratio| wheel_rpm_per_kmh * ratio * self.final_drive)
+            .collect()
+    }
+}
+
+/// Estimates the gear from the engine speed and vehicle speed
+///
+/// Each gear is known by its engine speed in rpm for each km/h. These come from a [Drivetrain],
+/// or are learned by [learning](Self::learning): the ratios seen while driving are gathered, and
+/// once there are enough they are grouped into the given number of gears. Learning needs a drive
+/// that spends some time in every gear, and gives no estimate until it is done. The learned
+/// [ratios](Self::ratios) can be saved and given to [from_ratios](Self::from_ratios) next time.
+///
+/// # Example
+/// ```
+/// use obd2::gear::GearEstimator;
+///
+/// let mut estimator = GearEstimator::learning(3).with_learning_samples(30);
+/// // driving for a while in each gear, at 100, 60 and 40 rpm per km/h
+/// for speed in 20..30 {
+///     let speed = speed as f32;
+///     for ratio in [100., 60., 40.] {
+///         estimator.update(ratio * speed, speed);
+///     }
+/// }
+///
+/// assert!(!estimator.is_learning());
+/// let ratios = estimator.ratios();
+/// assert_eq!(ratios.len(), 3);
+/// assert!((ratios[0] - 100.).abs() < 0.01 && (ratios[2] - 40.).abs() < 0.01);
+/// assert_eq!(estimator.estimate(2400., 40.), Some(2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GearEstimator {
+    ratios: Vec<f32>,
+    tolerance: f32,
+    min_speed: f32,
+    learning: Option<Learning>,
+    #[cfg(feature = "std")]
+    rpm: Option<(f32, Instant)>,
+    #[cfg(feature = "std")]
+    speed: Option<(f32, Instant)>,
+    #[cfg(feature = "std")]
+    gear: Option<u8>,
+}
+
+/// The ratios gathered while a [GearEstimator] is learning
+#[derive(Debug, Clone)]
+struct Learning {
+    gears: usize,
+    needed: usize,
+    samples: Vec<f32>,
+}
+
+impl GearEstimator {
+    /// Create a [`GearEstimator`] for the gears of `drivetrain`
+    pub fn new(drivetrain: &Drivetrain) -> Self {
+        Self::from_ratios(drivetrain.rpm_per_kmh())
+    }
+
+    /// Create a [`GearEstimator`] from the engine speed in rpm for each km/h in each gear,
+    /// starting with first, as measured or returned by [ratios](Self::ratios)
+    pub fn from_ratios(rpm_per_kmh: Vec<f32>) -> Self {
+        GearEstimator {
+            ratios: rpm_per_kmh,
+            tolerance: 0.1,
+            min_speed: 10.,
+            learning: None,
+            #[cfg(feature = "std")]
+            rpm: None,
+            #[cfg(feature = "std")]
+            speed: None,
+            #[cfg(feature = "std")]
+            gear: None,
+        }
+    }
+
+    /// Create a [`GearEstimator`] that learns the ratios of a vehicle with `gears` gears from
+    /// the values given to [update](Self::update), by default from 500 of them
+    pub fn learning(gears: usize) -> Self {
+        GearEstimator {
+            learning: Some(Learning {
+                gears,
+                needed: 500,
+                samples: Vec::new(),
+            }),
+            ..Self::from_ratios(Vec::new())
+        }
+    }
+
+    /// Set how many values to learn the ratios from
+    pub fn with_learning_samples(mut self, samples: usize) -> Self {
+        if let Some(learning) = &mut self.learning {
+            learning.needed = samples;
+        }
+        self
+    }
+
+    /// Set how far the ratio can be from the nearest gear's, as a fraction of it, for that gear to
+    /// be estimated, by default 0.1
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the speed in km/h below which no gear is estimated or learned, by default 10 km/h
+    ///
+    /// At low speeds the vehicle speed's steps of 1 km/h make the ratio inaccurate, and the clutch
+    /// is often slipping.
+    pub fn with_min_speed(mut self, speed: f32) -> Self {
+        self.min_speed = speed;
+        self
+    }
+
+    /// Whether the ratios are still being learned
+    pub fn is_learning(&self) -> bool {
+        self.learning.is_some()
+    }
+
+    /// Get the engine speed in rpm for each km/h in each gear, starting with first
+    ///
+    /// This is empty while learning.
+    pub fn ratios(&self) -> &[f32] {
+        &self.ratios
+    }
+
+    /// Estimate the gear, starting from 1, at `rpm` and `speed` in km/h
+    ///
+    /// This is `None` while learning, below the minimum speed, or if no gear's ratio is within the
+    /// tolerance, like while the clutch is in.
+    pub fn estimate(&self, rpm: f32, speed: f32) -> Option<u8> {
+        if speed < self.min_speed || rpm <= 0. {
+            return None;
+        }
+        let ratio = rpm / speed;
+        let (gear, error) = self
+            .ratios
+            .iter()
+            .map(|&r| if ratio > r { ratio / r } else { r / ratio })
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        (error - 1. <= self.tolerance).then(|| gear as u8 + 1)
+    }
+
+    /// Learn from `rpm` and `speed` in km/h if still learning, and estimate the gear
+    pub fn update(&mut self, rpm: f32, speed: f32) -> Option<u8> {
+        if let Some(learning) = &mut self.learning {
+            if speed >= self.min_speed && rpm > 0. {
+                learning.samples.push(rpm / speed);
+            }
+            if learning.samples.len() >= learning.needed {
+                self.ratios = cluster(&mut learning.samples, learning.gears);
+                self.learning = None;
+            }
+        }
+        self.estimate(rpm, speed)
+    }
+
+    /// Use the value of an engine speed or vehicle speed sample, and get the estimated gear
+    ///
+    /// The estimate is updated once both speeds have been read in the same snapshot, so both
+    /// PIDs should be polled at the same interval. Samples of other PIDs are ignored.
+    #[cfg(feature = "std")]
+    pub fn add_sample(&mut self, sample: &Sample) -> Option<u8> {
+        let Some(&value) = sample.values.first() else {
+            return self.gear;
+        };
+        match sample.pid {
+            StandardPid::EngineSpeed => self.rpm = Some((value, sample.time)),
+            StandardPid::VehicleSpeed => self.speed = Some((value, sample.time)),
+            _ => return self.gear,
+        }
+        if let (Some((rpm, rpm_time)), Some((speed, speed_time))) = (self.rpm, self.speed) {
+            if rpm_time == speed_time {
+                self.gear = self.update(rpm, speed);
+            }
+        }
+        self.gear
+    }
+}
+
+/// Group `ratios` into `gears` clusters, returning their means from the highest
+///
+/// The clusters are found in km/h per rpm, where the gears of most gearboxes are spread about
+/// evenly, starting evenly spaced between the 5th and 95th percentiles so that the odd ratio seen
+/// while changing gear does not stretch them.
+fn cluster(ratios: &mut [f32], gears: usize) -> Vec<f32> {
+    if ratios.is_empty() || gears == 0 {
+        return Vec::new();
+    }
+    for ratio in ratios.iter_mut() {
+        *ratio = 1. / *ratio;
+    }
+    ratios.sort_by(f32::total_cmp);
+    let low = ratios[ratios.len() / 20];
+    let high = ratios[ratios.len() * 19 / 20];
+    let mut centers: Vec<f32> = (0..gears)
+        .map(|i| low + (high - low) * (i as f32 + 0.5) / gears as f32)
+        .collect();
+
+    for _ in 0..50 {
+        let mut sums = alloc::vec![(0., 0); gears];
+        for &ratio in ratios.iter() {
+            let nearest = (0..gears)
+                .min_by(|&a, &b| {
+                    let distance = |i: usize| (ratio - centers[i]) * (ratio - centers[i]);
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap_or(0);
+            sums[nearest].0 += ratio;
+            sums[nearest].1 += 1;
+        }
+        let mut moved = false;
+        for (center, (sum, count)) in centers.iter_mut().zip(sums) {
+            if count > 0 && *center != sum / count as f32 {
+                *center = sum / count as f32;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    centers.sort_by(f32::total_cmp);
+    centers.into_iter().map(|center| 1. / center).collect()
+}
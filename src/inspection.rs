@@ -0,0 +1,327 @@
+//! Checking whether a vehicle will pass an emissions inspection
+//!
+//! OBD-II inspections, like US state I/M (inspection and maintenance) programs and California's
+//! Smog Check, plug into the vehicle and fail it if the check engine light is on, and turn it away
+//! as not ready if too many readiness monitors have not completed since the DTCs were cleared.
+//! [check_inspection_readiness] makes the same checks ahead of time, with [InspectionRules] for
+//! the jurisdiction, so that a repaired vehicle is only taken in once it will pass.
+//!
+//! # Example
+//! ```
+//! use obd2::{
+//!     commands::Dtc,
+//!     device::Obd2Simulator,
+//!     inspection::{
+//!         check_inspection_readiness, InspectionIssue, InspectionResult, InspectionRules,
+//!     },
+//! };
+//!
+//! let mut device = Obd2Simulator::new();
+//! let result = check_inspection_readiness(&mut device, &InspectionRules::default())?;
+//! assert_eq!(result, InspectionResult::Pass);
+//!
+//! let mut device = Obd2Simulator::new().with_dtcs([Dtc::Powertrain(0x0301)]);
+//! let result = check_inspection_readiness(&mut device, &InspectionRules::default())?;
+//! assert_eq!(result, InspectionResult::Fail(vec![InspectionIssue::MilOn]));
+//! # Ok::<(), obd2::Error>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::{fmt, time::Duration};
+
+use crate::{
+    commands::{Dtc, Monitor, Obd2DataRetrieval},
+    report::optional,
+    Obd2Device, Result,
+};
+
+/// The checks made by an inspection
+///
+/// The default follows the US EPA's guidance for model year 2001 and newer vehicles: the check
+/// engine light must be off, and at most one non-continuous monitor may be incomplete. The
+/// continuous monitors (misfire, fuel system and comprehensive components) are never counted, as
+/// they run all the time.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::Monitor, inspection::InspectionRules};
+///
+/// // a state that exempts the evaporative system and allows two incomplete monitors
+/// let rules = InspectionRules::default()
+///     .with_max_incomplete_monitors(2)
+///     .with_exempt_monitor(Monitor::EvaporativeSystem);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct InspectionRules {
+    /// How many monitors may be incomplete for the vehicle to be ready
+    pub max_incomplete_monitors: usize,
+
+    /// Monitors that are not counted when incomplete
+    pub exempt_monitors: Vec<Monitor>,
+
+    /// Whether permanent DTCs fail the vehicle, even with the check engine light off
+    pub fail_on_permanent_dtcs: bool,
+
+    /// The distance in km that must have been driven since the DTCs were cleared
+    pub min_distance_since_cleared: Option<u16>,
+
+    /// The time the engine must have run since the DTCs were cleared
+    pub min_time_since_cleared: Option<Duration>,
+}
+
+impl Default for InspectionRules {
+    fn default() -> Self {
+        InspectionRules {
+            max_incomplete_monitors: 1,
+            exempt_monitors: Vec::new(),
+            fail_on_permanent_dtcs: false,
+            min_distance_since_cleared: None,
+            min_time_since_cleared: None,
+        }
+    }
+}
+
+impl InspectionRules {
+    /// The rules of California's Smog Check for model year 2000 and newer gasoline vehicles,
+    /// which also fails vehicles with permanent DTCs
+    pub fn california() -> Self {
+        Self::default().with_permanent_dtcs_failing(true)
+    }
+
+    /// Allow `count` monitors to be incomplete
+    pub fn with_max_incomplete_monitors(mut self, count: usize) -> Self {
+        self.max_incomplete_monitors = count;
+        self
+    }
+
+    /// Do not count `monitor` when it is incomplete
+    pub fn with_exempt_monitor(mut self, monitor: Monitor) -> Self {
+        self.exempt_monitors.push(monitor);
+        self
+    }
+
+    /// Set whether permanent DTCs fail the vehicle
+    pub fn with_permanent_dtcs_failing(mut self, fail: bool) -> Self {
+        self.fail_on_permanent_dtcs = fail;
+        self
+    }
+
+    /// Require `distance` km to have been driven since the DTCs were cleared
+    pub fn with_min_distance_since_cleared(mut self, distance: u16) -> Self {
+        self.min_distance_since_cleared = Some(distance);
+        self
+    }
+
+    /// Require the engine to have run for `time` since the DTCs were cleared
+    pub fn with_min_time_since_cleared(mut self, time: Duration) -> Self {
+        self.min_time_since_cleared = Some(time);
+        self
+    }
+}
+
+/// Whether the vehicle would pass, from [check_inspection_readiness]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InspectionResult {
+    /// The vehicle would pass
+    Pass,
+
+    /// The vehicle would be turned away until it has been driven more, for these reasons
+    NotReady(Vec<InspectionIssue>),
+
+    /// The vehicle would fail, for these reasons and any that also make it not ready
+    Fail(Vec<InspectionIssue>),
+}
+
+impl InspectionResult {
+    /// Get every reason the vehicle would not pass
+    pub fn issues(&self) -> &[InspectionIssue] {
+        match self {
+            InspectionResult::Pass => &[],
+            InspectionResult::NotReady(issues) | InspectionResult::Fail(issues) => issues,
+        }
+    }
+}
+
+/// A reason a vehicle would not pass an inspection
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum InspectionIssue {
+    /// The check engine light is on
+    MilOn,
+
+    /// There are permanent DTCs, which the ECU clears only once the fault's monitor passes
+    PermanentDtcs(Vec<Dtc>),
+
+    /// More monitors than allowed have not completed
+    IncompleteMonitors(Vec<Monitor>),
+
+    /// Too little distance in km has been driven since the DTCs were cleared
+    DistanceSinceCleared {
+        /// The distance driven
+        distance: u16,
+        /// The distance required
+        required: u16,
+    },
+
+    /// The engine has run too little time since the DTCs were cleared
+    TimeSinceCleared {
+        /// The time the engine has run
+        time: Duration,
+        /// The time required
+        required: Duration,
+    },
+}
+
+impl InspectionIssue {
+    /// Whether this fails the vehicle, rather than only making it not ready yet
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            InspectionIssue::MilOn | InspectionIssue::PermanentDtcs(_)
+        )
+    }
+}
+
+impl fmt::Display for InspectionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InspectionIssue::MilOn => f.write_str("the check engine light is on"),
+            InspectionIssue::PermanentDtcs(dtcs) => {
+                f.write_str("permanent DTCs are stored:")?;
+                for (i, dtc) in dtcs.iter().enumerate() {
+                    let separator = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", separator, dtc)?;
+                }
+                Ok(())
+            }
+            InspectionIssue::IncompleteMonitors(monitors) => {
+                f.write_str("monitors are incomplete:")?;
+                for (i, monitor) in monitors.iter().enumerate() {
+                    let separator = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", separator, monitor.name())?;
+                }
+                Ok(())
+            }
+            InspectionIssue::DistanceSinceCleared { distance, required } => write!(
+                f,
+                "only {} km driven since the DTCs were cleared, {} km needed",
+                distance, required
+            ),
+            InspectionIssue::TimeSinceCleared { time, required } => write!(
+                f,
+                "the engine has only run {} minutes since the DTCs were cleared, {} minutes needed",
+                time.as_secs() / 60,
+                required.as_secs() / 60
+            ),
+        }
+    }
+}
+
+/// Check whether the vehicle would pass an inspection with `rules`
+///
+/// The readiness monitors and check engine light of every ECU are checked, and the permanent
+/// DTCs and distance or time since the DTCs were cleared if `rules` needs them. A vehicle that
+/// does not report the distance or time is given the benefit of the doubt, as inspections only
+/// use what it reports.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Monitor,
+///     device::MockObd2Device,
+///     inspection::{
+///         check_inspection_readiness, InspectionIssue, InspectionResult, InspectionRules,
+///     },
+/// };
+///
+/// // the catalyst, evaporative system and oxygen sensor monitors have not completed
+/// let mut device =
+///     MockObd2Device::new().with_response(0x01, 0x01, [vec![0x00, 0x07, 0x65, 0x25]]);
+///
+/// let result = check_inspection_readiness(&mut device, &InspectionRules::default())?;
+/// assert_eq!(
+///     result,
+///     InspectionResult::NotReady(vec![InspectionIssue::IncompleteMonitors(vec![
+///         Monitor::Catalyst,
+///         Monitor::EvaporativeSystem,
+///         Monitor::OxygenSensor,
+///     ])])
+/// );
+/// assert_eq!(
+///     result.issues()[0].to_string(),
+///     "monitors are incomplete: Catalyst, Evaporative system, Oxygen sensor"
+/// );
+/// # Ok::<(), obd2::Error>(())
+/// ```
+pub fn check_inspection_readiness<T: Obd2Device>(
+    device: &mut T,
+    rules: &InspectionRules,
+) -> Result<InspectionResult> {
+    let status = device.get_dtc_info()?;
+    let mut issues = Vec::new();
+
+    if status.iter().any(|ecu| ecu.malfunction_indicator_light) {
+        issues.push(InspectionIssue::MilOn);
+    }
+
+    if rules.fail_on_permanent_dtcs {
+        if let Some(dtcs) = optional(device.get_permanent_dtcs())? {
+            let mut dtcs: Vec<Dtc> = dtcs.into_iter().flatten().map(|dtc| dtc.0).collect();
+            dtcs.sort();
+            dtcs.dedup();
+            if !dtcs.is_empty() {
+                issues.push(InspectionIssue::PermanentDtcs(dtcs));
+            }
+        }
+    }
+
+    let mut incomplete = Vec::new();
+    for monitor in status.iter().flat_map(|ecu| &ecu.readiness.monitors) {
+        let counted = !CONTINUOUS_MONITORS.contains(&monitor.monitor)
+            && !rules.exempt_monitors.contains(&monitor.monitor);
+        if counted
+            && monitor.available
+            && !monitor.complete
+            && !incomplete.contains(&monitor.monitor)
+        {
+            incomplete.push(monitor.monitor);
+        }
+    }
+    if incomplete.len() > rules.max_incomplete_monitors {
+        issues.push(InspectionIssue::IncompleteMonitors(incomplete));
+    }
+
+    if let Some(required) = rules.min_distance_since_cleared {
+        let distance = optional(device.get_distance_since_codes_cleared())?;
+        if let Some(&distance) = distance.as_ref().and_then(|d| d.iter().max()) {
+            if distance < required {
+                issues.push(InspectionIssue::DistanceSinceCleared { distance, required });
+            }
+        }
+    }
+
+    if let Some(required) = rules.min_time_since_cleared {
+        let time = optional(device.get_time_since_dtcs_cleared())?;
+        if let Some(&time) = time.as_ref().and_then(|t| t.iter().max()) {
+            if time < required {
+                issues.push(InspectionIssue::TimeSinceCleared { time, required });
+            }
+        }
+    }
+
+    Ok(if issues.is_empty() {
+        InspectionResult::Pass
+    } else if issues.iter().any(InspectionIssue::is_failure) {
+        InspectionResult::Fail(issues)
+    } else {
+        InspectionResult::NotReady(issues)
+    })
+}
+
+/// Monitors that run all the time, which inspections do not count
+const CONTINUOUS_MONITORS: [Monitor; 3] =
+    [Monitor::Misfire, Monitor::FuelSystem, Monitor::Components];
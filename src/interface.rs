@@ -1,13 +1,29 @@
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::time::Duration;
 use log::{debug, trace};
 
-use super::{device::Obd2BaseDevice, Error, Obd2Device, Result};
+use super::{
+    device,
+    device::{
+        isotp::{Progress, Reassembler},
+        Obd2BaseDevice,
+    },
+    obd2_device::request_each_pid,
+    EcuResponse, Error, NegativeResponseCode, Obd2Device, Protocol, Result,
+};
+
+/// How many times a request is sent again when the ECUs only respond that it is pending
+pub(crate) const PENDING_RETRIES: usize = 3;
+
+/// How long to wait before sending a pending request again
+pub(crate) const PENDING_RETRY_DELAY: Duration = Duration::from_millis(200);
 
 /// An OBD-II interface
 ///
 /// Wraps an implementer of [Obd2BaseDevice] to allow for higher-level usage of the OBD-II
 /// interface.
 pub struct Obd2<T: Obd2BaseDevice> {
-    device: T,
+    pub(crate) device: T,
 }
 
 impl<T: Obd2BaseDevice> Obd2<T> {
@@ -15,111 +31,866 @@ impl<T: Obd2BaseDevice> Obd2<T> {
     pub fn new(device: T) -> Self {
         Self { device }
     }
+
+    /// Get the wrapped device, for features specific to it
+    pub fn device(&self) -> &T {
+        &self.device
+    }
+
+    /// Get the wrapped device mutably, for features specific to it, like
+    /// [Elm327::at_command](crate::device::Elm327::at_command)
+    pub fn device_mut(&mut self) -> &mut T {
+        &mut self.device
+    }
+
+    /// Send service `mode` with `pid`, and decode each ECU's response into `responses`
+    ///
+    /// Like [obd_command](Obd2Device::obd_command), but the response is read and decoded into
+    /// buffers kept in `responses`, which are reused by the next request. Once they have grown to
+    /// fit the responses, polling a PID this way does not allocate for the response, although
+    /// sending the request can, depending on the device.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use obd2::{device::Elm327, Obd2, ResponseBuffer};
+    ///
+    /// let mut device = Obd2::new(Elm327::new("/dev/ttyUSB0")?);
+    /// let mut responses = ResponseBuffer::new();
+    /// loop {
+    ///     device.obd_command_into(0x01, 0x0C, &mut responses)?;
+    ///     if let Some(&[a, b]) = responses.first() {
+    ///         println!("{} rpm", f32::from(u16::from_be_bytes([a, b])) / 4.);
+    ///     }
+    /// }
+    /// # Ok::<(), obd2::Error>(())
+    /// ```
+    pub fn obd_command_into(
+        &mut self,
+        mode: u8,
+        pid: u8,
+        responses: &mut ResponseBuffer,
+    ) -> Result<()> {
+        self.command_into(&[mode, pid], responses)?;
+        responses.responses.strip_header(&[0x40 | mode, pid])
+    }
+}
+
+impl<T: Obd2BaseDevice + Default> Default for Obd2<T> {
+    fn default() -> Self {
+        Obd2::new(T::default())
+    }
 }
 
 impl<T: Obd2BaseDevice> Obd2Device for Obd2<T> {
     fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
         let result = self.command(&[mode, pid])?;
+        strip_header(result, &[0x40 | mode, pid])
+    }
+
+    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        let result = self.command(core::slice::from_ref(&mode))?;
+        strip_header(result, &[0x40 | mode])
+    }
+
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let result = self.command(request)?;
+        strip_header(result, &response_header(request))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(request = ?format_args!("{:02X?}", request)),
+            err
+        )
+    )]
+    fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        let response = self.with_retries(|device| device.cmd_with_headers(request))?;
+        let data = decode_tagged_response(response)?;
+        debug!("Sent OBD command {:?} and got data {:?}", request, data);
+        strip_tagged_header(data, request)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                address = ?format_args!("{:X}", address),
+                request = ?format_args!("{:02X?}", request),
+            ),
+            err
+        )
+    )]
+    fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        let response = self.with_retries(|device| device.cmd_to(address, request))?;
+        let data = decode_response(response)?;
+        debug!(
+            "Sent OBD command {:?} to {:X} and got data {:?}",
+            request, address, data
+        );
+        let data = remove_pending(data).ok_or_else(|| pending_error(request))?;
+        single_response(strip_header(data, &response_header(request))?)
+    }
+
+    /// Only the CAN protocols accept several PIDs in one request, so on other protocols each PID
+    /// is requested separately
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        if pids.len() > 1 && !self.protocol()?.is_can() {
+            return request_each_pid(self, mode, pids);
+        }
+        let result = self.command(&[&[mode], pids].concat())?;
+        strip_header(result, &[0x40 | mode])
+    }
 
-        for response in result.iter() {
-            if response.first() != Some(&(0x40 | mode)) {
-                todo!()
+    fn protocol(&mut self) -> Result<Protocol> {
+        Ok(self.device.protocol()?)
+    }
+
+    /// If the device cannot measure the voltage, the control module voltage is read instead
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        match self.device.adapter_voltage() {
+            Ok(voltage) => Ok(voltage),
+            Err(e) => {
+                debug!("get_adapter_voltage: falling back to PID 42: {}", e);
+                let responses = self.obd_command_len::<2>(0x01, 0x42)?;
+                let voltage = responses.first().ok_or(Error::NoData)?;
+                Ok(f32::from(u16::from_be_bytes(*voltage)) / 1000.)
             }
-            if response.get(1) != Some(&pid) {
-                todo!()
+        }
+    }
+}
+
+impl<T: Obd2BaseDevice> Obd2<T> {
+    /// Send `command` and decode the response
+    ///
+    /// If every ECU that responded only said the response is pending, the command is sent again
+    /// after a delay, since the adapter stops listening once it prints its prompt.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(request = ?format_args!("{:02X?}", command)),
+            err
+        )
+    )]
+    fn command(&mut self, command: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let mut buffer = ResponseBuffer::new();
+        self.command_into(command, &mut buffer)?;
+        Ok(buffer.iter().map(<[u8]>::to_vec).collect())
+    }
+
+    /// Send `command` and decode the response into `buffer`, like [command](Self::command)
+    fn command_into(&mut self, command: &[u8], buffer: &mut ResponseBuffer) -> Result<()> {
+        let ResponseBuffer { text, responses } = buffer;
+        for _ in 0..=PENDING_RETRIES {
+            self.with_retries(|device| Ok(device.cmd_into(command, text)?.then_some(())))?;
+            let response = core::str::from_utf8(text)
+                .map_err(|_| Error::MalformedLine(String::from_utf8_lossy(text).into_owned()))?;
+
+            trace!(
+                "Sent OBD command {:?} and got response {:?}",
+                command,
+                response
+            );
+
+            decode_response_into(response, responses)?;
+            debug!(
+                "Sent OBD command {:?} and got data {:?}",
+                command, responses
+            );
+            if responses.remove_pending() {
+                return Ok(());
             }
+            self.device.delay(PENDING_RETRY_DELAY);
         }
+        Err(pending_error(command))
+    }
 
-        Ok(result.iter().map(|l| l.split_at(2).1.to_vec()).collect())
+    /// Run `send`, which sends a request and gets the response, again while the device times out
+    ///
+    /// The number of retries and the wait between them are from the device's
+    /// [config](Obd2BaseDevice::config). Returns [Error::Timeout] if every attempt timed out.
+    pub(crate) fn with_retries<R>(
+        &mut self,
+        mut send: impl FnMut(&mut T) -> core::result::Result<Option<R>, crate::device::Error>,
+    ) -> Result<R> {
+        let config = self.device.config();
+        for attempt in 0..=config.retries() {
+            if attempt > 0 {
+                let delay = config.retry_delay(attempt - 1);
+                debug!("Request timed out, retrying in {:?}", delay);
+                self.device.delay(delay);
+            }
+            if let Some(response) = send(&mut self.device)? {
+                return Ok(response);
+            }
+        }
+        Err(Error::Timeout)
     }
+}
 
-    fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
-        let result = self.command(std::slice::from_ref(&mode))?;
+/// Check that each ECU's response starts with `header`, and remove it
+///
+/// The header is the request's mode (with `0x40` added) and PID, if any, echoed by the vehicle. An
+/// ECU that rejects the request sends `7F`, the mode and a reason instead, which is returned as
+/// [Error::NegativeResponse].
+pub(crate) fn strip_header(mut responses: Vec<Vec<u8>>, header: &[u8]) -> Result<Vec<Vec<u8>>> {
+    for response in responses.iter_mut() {
+        strip_response_header(response, header)?;
+    }
+    Ok(responses)
+}
+
+/// Check that one response starts with `header`, and remove it in place
+fn strip_response_header(response: &mut Vec<u8>, header: &[u8]) -> Result<()> {
+    if response.starts_with(header) {
+        response.drain(..header.len());
+        Ok(())
+    } else {
+        Err(header_error(response, header))
+    }
+}
+
+/// The error for a response that does not start with `header`
+fn header_error(response: &[u8], header: &[u8]) -> Error {
+    if let [0x7F, mode, nrc, ..] = *response {
+        Error::NegativeResponse {
+            mode,
+            nrc: nrc.into(),
+        }
+    } else {
+        Error::HeaderMismatch {
+            expected: header.to_vec(),
+            got: response.to_vec(),
+        }
+    }
+}
+
+/// Check and remove the echoed request from each ECU's response to `request`, keeping the ECU
+/// addresses
+///
+/// Responses that are pending are skipped, since the real response follows them.
+pub(crate) fn strip_tagged_header(
+    mut responses: Vec<EcuResponse>,
+    request: &[u8],
+) -> Result<Vec<EcuResponse>> {
+    let count = responses.len();
+    responses.retain(|r| !is_pending(&r.data));
+    if responses.is_empty() && count > 0 {
+        return Err(pending_error(request));
+    }
+    let header = response_header(request);
+    for response in responses.iter_mut() {
+        strip_response_header(&mut response.data, &header)?;
+    }
+    Ok(responses)
+}
+
+/// Get the only response, from a request sent to a single ECU
+pub(crate) fn single_response(responses: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+    let count = responses.len();
+    let [response] =
+        <[Vec<u8>; 1]>::try_from(responses).map_err(|_| Error::UnexpectedEcuCount {
+            expected: 1,
+            got: count,
+        })?;
+    Ok(response)
+}
+
+/// Whether `response` is a "response pending" negative response (`7F <mode> 78`)
+fn is_pending(response: &[u8]) -> bool {
+    matches!(response, [0x7F, _, 0x78, ..])
+}
+
+/// Remove "response pending" negative responses
+///
+/// An ECU that needs more time to respond sends these before its real response. If nothing else
+/// was received, `None` is returned so the request can be tried again.
+pub(crate) fn remove_pending(mut responses: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let count = responses.len();
+    responses.retain(|r| !is_pending(r));
+    if responses.is_empty() && count > 0 {
+        debug!("remove_pending: every response is pending");
+        None
+    } else {
+        Some(responses)
+    }
+}
+
+/// The error for a `request` that was still pending after every retry
+pub(crate) fn pending_error(request: &[u8]) -> Error {
+    Error::NegativeResponse {
+        mode: request.first().copied().unwrap_or_default(),
+        nrc: NegativeResponseCode::ResponsePending,
+    }
+}
+
+/// Get the header a vehicle echoes in its response to `request`
+///
+/// This is the start of the request with `0x40` added to the mode. The OBD-II services echo their
+/// parameters, like the PID and frame number, except that service 8 only echoes the test ID and
+/// not the data bytes sent with it. The UDS services (0x10 and up) only echo the service and its
+/// sub-function or identifier, not the data sent with them, like the key for security access.
+pub(crate) fn response_header(request: &[u8]) -> Vec<u8> {
+    let echoed = match request.first() {
+        // the sub-function, or the test ID of service 8
+        Some(0x08 | 0x10 | 0x11 | 0x19 | 0x27 | 0x28 | 0x3E | 0x85 | 0x87) => 2,
+        Some(0x01..=0x0F) => request.len(),
+        // the data identifier
+        Some(0x22 | 0x2E | 0x2F) => 3,
+        // the sub-function and routine identifier
+        Some(0x31) => 4,
+        // only the service, like clearing DTCs (0x14)
+        _ => 1,
+    };
+    let mut header = request[..echoed.min(request.len())].to_vec();
+    if let Some(mode) = header.first_mut() {
+        *mode |= 0x40;
+    }
+    header
+}
+
+/// Decode an ELM327-style response into the bytes sent by each ECU
+///
+/// The response is lines of hex bytes separated by spaces, one line for each ECU. A response that
+/// spans several CAN frames starts with a line giving its length in bytes (like "014"), followed
+/// by a line for each frame prefixed with its index (like "0: AB CD ..."), which wraps around
+/// after "F:". The lines are joined and the padding at the end of the last frame is removed. If no
+/// ECU responds, the adapter prints "NO DATA", which is returned as [Error::NoData]. If a message
+/// fails its checksum, the adapter prints "DATA ERROR", which is returned as
+/// [Error::InvalidChecksum]. If the adapter cannot reach the vehicle, like "UNABLE TO CONNECT",
+/// the error is [Disconnected](device::Error::Disconnected).
+pub(crate) fn decode_response(response: String) -> Result<Vec<Vec<u8>>> {
+    let mut responses = Responses::default();
+    decode_response_into(&response, &mut responses)?;
+    Ok(responses.iter().map(<[u8]>::to_vec).collect())
+}
+
+/// Decode an ELM327-style response into `responses`, like [decode_response]
+fn decode_response_into(response: &str, responses: &mut Responses) -> Result<()> {
+    responses.clear();
+    if response.lines().any(|l| l.trim() == "NO DATA") {
+        return Err(Error::NoData);
+    }
+    if response.contains("DATA ERROR") {
+        return Err(Error::InvalidChecksum);
+    }
+    link_error(response)?;
+
+    let mut multiline: Option<Multiline> = None;
+    for line in response.lines().map(str::trim) {
+        if line.is_empty() || line == "SEARCHING..." {
+            continue;
+        }
 
-        for response in result.iter() {
-            if response.first() != Some(&(0x40 | mode)) {
-                todo!()
+        if let Some((index, data)) = line.split_once(':') {
+            let message = multiline.get_or_insert_with(|| Multiline::new(responses.data.len()));
+            let index = u8::from_str_radix(index.trim(), 16)
+                .map_err(|_| Error::MalformedLine(line.to_owned()))?;
+            if index != message.next_index {
+                debug!("decode_response: expected line {:X}", message.next_index);
+                return Err(Error::MalformedLine(line.to_owned()));
+            }
+            message.next_index = (message.next_index + 1) % 0x10;
+            parse_bytes_into(data, &mut responses.data)?;
+            if message
+                .len
+                .is_some_and(|len| responses.data.len() - message.start >= len)
+            {
+                responses.finish(message);
+                multiline = None;
+            }
+        } else if !line.contains(' ') && line.len() == 3 {
+            // the length of a multiline response
+            if let Some(message) = &multiline {
+                responses.finish(message);
+            }
+            multiline = Some(Multiline {
+                len: Some(
+                    usize::from_str_radix(line, 16)
+                        .map_err(|_| Error::MalformedLine(line.to_owned()))?,
+                ),
+                ..Multiline::new(responses.data.len())
+            });
+        } else {
+            let start = responses.data.len();
+            parse_bytes_into(line, &mut responses.data)?;
+            if let Some(message) = &mut multiline {
+                // another ECU's response in the middle of a multiline one goes before it
+                let len = responses.data.len() - start;
+                responses.data[message.start..].rotate_right(len);
+                message.start += len;
+                responses.ends.push(message.start);
+            } else {
+                responses.ends.push(responses.data.len());
             }
         }
+    }
+    if let Some(message) = &multiline {
+        responses.finish(message);
+    }
 
-        Ok(result.iter().map(|l| l.split_at(1).1.to_vec()).collect())
+    if responses.ends.is_empty() {
+        Err(Error::NoData)
+    } else {
+        Ok(())
     }
 }
 
-impl<T: Obd2BaseDevice> Obd2<T> {
-    fn command(&mut self, command: &[u8]) -> Result<Vec<Vec<u8>>> {
-        let response = self
-            .device
-            .cmd(command)?
-            .ok_or(Error::Other("no response to command".to_owned()))?;
-
-        trace!(
-            "Sent OBD command {:?} and got response {:?}",
-            command,
+/// A response split over several lines, which is being joined by [decode_response]
+struct Multiline {
+    /// The length of the response, if the adapter sent it
+    len: Option<usize>,
+    next_index: u8,
+    /// Where the response starts in [Responses::data]
+    start: usize,
+}
+
+impl Multiline {
+    fn new(start: usize) -> Self {
+        Multiline {
+            len: None,
+            next_index: 0,
+            start,
+        }
+    }
+}
+
+/// Each ECU's response, one after the other in a single buffer
+#[derive(Clone, Default)]
+struct Responses {
+    data: Vec<u8>,
+    /// Where each response ends in `data`
+    ends: Vec<usize>,
+}
+
+impl Responses {
+    fn clear(&mut self) {
+        self.data.clear();
+        self.ends.clear();
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        let mut start = 0;
+        self.ends.iter().map(move |&end| {
+            let response = &self.data[start..end];
+            start = end;
             response
-        );
+        })
+    }
 
-        let data = if response.contains("0:") {
-            vec![self.parse_command_multiline(response)?]
+    /// End the multiline response at the end of the data, without the padding after its length
+    fn finish(&mut self, message: &Multiline) {
+        if let Some(len) = message.len {
+            self.data.truncate(message.start + len);
+        }
+        self.ends.push(self.data.len());
+    }
+
+    /// Keep the responses that `keep` returns a length for, without that many bytes at their start
+    fn retain_trimmed(
+        &mut self,
+        mut keep: impl FnMut(&[u8]) -> Result<Option<usize>>,
+    ) -> Result<()> {
+        let (mut start, mut len, mut kept) = (0, 0, 0);
+        for index in 0..self.ends.len() {
+            let end = self.ends[index];
+            if let Some(trim) = keep(&self.data[start..end])? {
+                self.data.copy_within(start + trim..end, len);
+                len += end - start - trim;
+                self.ends[kept] = len;
+                kept += 1;
+            }
+            start = end;
+        }
+        self.data.truncate(len);
+        self.ends.truncate(kept);
+        Ok(())
+    }
+
+    /// Remove "response pending" negative responses, like [remove_pending], returning `false` if
+    /// every response was pending
+    fn remove_pending(&mut self) -> bool {
+        let count = self.ends.len();
+        // never fails, since `keep` does not
+        let _ = self.retain_trimmed(|r| Ok((!is_pending(r)).then_some(0)));
+        if self.ends.is_empty() && count > 0 {
+            debug!("remove_pending: every response is pending");
+            false
         } else {
-            self.parse_command(response)?
-        };
+            true
+        }
+    }
 
-        debug!("Sent OBD command {:?} and got data {:?}", command, data);
+    /// Check that each response starts with `header`, and remove it, like [strip_header]
+    fn strip_header(&mut self, header: &[u8]) -> Result<()> {
+        self.retain_trimmed(|response| {
+            if response.starts_with(header) {
+                Ok(Some(header.len()))
+            } else {
+                Err(header_error(response, header))
+            }
+        })
+    }
+}
 
-        let result = data
-            .iter()
-            .map(|l| {
-                l.iter()
-                    .map(|s| u8::from_str_radix(s, 16).map_err(|e| e.into()))
-                    .collect()
-            })
-            .collect();
-
-        result
-    }
-
-    fn parse_command(&mut self, response: String) -> Result<Vec<Vec<String>>> {
-        let result: Vec<_> = response
-            .split('\n')
-            .filter_map(|l| {
-                let res: Vec<_> = l
-                    .split(' ')
-                    .filter_map(|s| {
-                        if !s.is_empty() {
-                            Some(s.to_owned())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                if !res.is_empty() {
-                    Some(res)
-                } else {
-                    None
+impl core::fmt::Debug for Responses {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Buffers for the responses to a request, which are reused by the next one
+///
+/// Filled by [Obd2::obd_command_into], after which it holds the data of each ECU's response, in
+/// the order they responded.
+#[derive(Clone, Default)]
+pub struct ResponseBuffer {
+    /// The text printed by the adapter
+    text: Vec<u8>,
+    responses: Responses,
+}
+
+impl ResponseBuffer {
+    /// Create empty buffers, which grow to fit the responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of ECUs that responded
+    pub fn len(&self) -> usize {
+        self.responses.ends.len()
+    }
+
+    /// Whether there are no responses
+    pub fn is_empty(&self) -> bool {
+        self.responses.ends.is_empty()
+    }
+
+    /// Get the response of the `index`th ECU to respond
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        self.iter().nth(index)
+    }
+
+    /// Get the first response, which is the only one when one ECU responds
+    pub fn first(&self) -> Option<&[u8]> {
+        self.get(0)
+    }
+
+    /// Iterate over each ECU's response
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.responses.iter()
+    }
+}
+
+impl core::fmt::Debug for ResponseBuffer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.responses.fmt(f)
+    }
+}
+
+/// The start of the lines an ELM327 prints when it cannot reach the vehicle
+const LINK_ERRORS: [&str; 4] = ["UNABLE TO CONNECT", "BUS ERROR", "CAN ERROR", "LV RESET"];
+
+/// Check a response for a line saying that the adapter cannot reach the vehicle
+///
+/// `BUS INIT: ...ERROR` is a failed K-line or J1850 initialization, and `LV RESET` means the
+/// adapter reset itself because the voltage dropped, as when the vehicle is turned off.
+fn link_error(response: &str) -> Result<()> {
+    match response.lines().map(str::trim).find(|l| {
+        LINK_ERRORS.iter().any(|e| l.starts_with(e))
+            || (l.starts_with("BUS INIT") && l.ends_with("ERROR"))
+    }) {
+        Some(line) => Err(device::Error::Disconnected(line.to_owned()).into()),
+        None => Ok(()),
+    }
+}
+
+/// Parse hex bytes separated by spaces onto the end of `bytes`
+///
+/// A token that is not a hex byte is an [Error::MalformedLine] with the whole line.
+fn parse_bytes_into(line: &str, bytes: &mut Vec<u8>) -> Result<()> {
+    for s in line.split_whitespace() {
+        let byte =
+            u8::from_str_radix(s, 16).map_err(|_| Error::MalformedLine(line.trim().to_owned()))?;
+        bytes.push(byte);
+    }
+    Ok(())
+}
+
+/// Decode an ELM327-style response with headers into each ECU's address and message
+///
+/// On CAN, each line is a frame: the identifier (three hex digits, or four bytes starting with
+/// `18 DA` for 29-bit identifiers) followed by the ISO-TP framing, which is reassembled. On other
+/// protocols, each line is a message with a three byte header ending in the sender's address, and
+/// a checksum (or a CRC on J1850) at the end, which is checked. Messages from the same ECU are
+/// joined.
+pub(crate) fn decode_tagged_response(response: String) -> Result<Vec<EcuResponse>> {
+    if response.lines().any(|l| l.trim() == "NO DATA") {
+        return Err(Error::NoData);
+    }
+    if response.contains("DATA ERROR") {
+        return Err(Error::InvalidChecksum);
+    }
+    link_error(&response)?;
+
+    let mut frames: Vec<(u32, Reassembler)> = Vec::new();
+    let mut responses: Vec<EcuResponse> = Vec::new();
+    // reused for every line, since the frames are copied out of it
+    let mut bytes = Vec::new();
+    for line in response.lines() {
+        let mut tokens = line.split_whitespace().peekable();
+        let Some(first) = tokens.peek() else {
+            continue;
+        };
+        let standard_id = match first.len() {
+            3 => tokens
+                .next()
+                .map(|id| u32::from_str_radix(id, 16))
+                .transpose()
+                .map_err(|_| Error::MalformedLine(line.to_owned()))?,
+            _ => None,
+        };
+        bytes.clear();
+        for token in tokens {
+            let byte = u8::from_str_radix(token, 16)
+                .map_err(|_| Error::MalformedLine(line.trim().to_owned()))?;
+            bytes.push(byte);
+        }
+
+        let (address, frame) = match (standard_id, bytes.as_slice()) {
+            (Some(id), frame) => (id, frame),
+            (None, [0x18, 0xDA | 0xDB, target, source, frame @ ..]) => (
+                u32::from_be_bytes([0x18, bytes[1], *target, *source]),
+                frame,
+            ),
+            (None, [_, _, source, message @ .., sum]) => {
+                let body = &bytes[..bytes.len() - 1];
+                if *sum != checksum(body) && *sum != j1850_crc(body) {
+                    return Err(Error::InvalidChecksum);
                 }
-            })
-            .collect();
+                let address = u32::from(*source);
+                match responses.iter_mut().find(|r| r.address == address) {
+                    Some(response) => response.data.extend_from_slice(message),
+                    None => responses.push(EcuResponse {
+                        address,
+                        data: message.to_vec(),
+                    }),
+                }
+                continue;
+            }
+            (None, _) => return Err(Error::MalformedLine(line.to_owned())),
+        };
 
-        if !result.is_empty() {
-            Ok(result)
-        } else {
-            Err(Error::Other("parse_command: found no responses".to_owned()))
+        let index = match frames.iter().position(|(id, _)| *id == address) {
+            Some(index) => index,
+            None => {
+                frames.push((address, Reassembler::default()));
+                frames.len() - 1
+            }
+        };
+        if let Progress::Complete(data) = frames[index].1.push(frame)? {
+            responses.push(EcuResponse { address, data });
         }
     }
 
-    fn parse_command_multiline(&mut self, response: String) -> Result<Vec<String>> {
-        let mut n_idx = 0;
-        Ok(response
-            .split('\n')
-            .filter_map(|l| l.split_once(':'))
-            .flat_map(|(idx, data)| {
-                if u8::from_str_radix(idx, 16) != Ok(n_idx) {
-                    todo!()
-                }
-                n_idx = (n_idx + 1) % 0x10;
-                data.split_whitespace().map(|s| s.to_owned())
+    if responses.is_empty() {
+        Err(Error::NoData)
+    } else {
+        Ok(responses)
+    }
+}
+
+/// The checksum of K-line messages: the sum of the bytes, modulo 256
+pub(crate) fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+}
+
+/// The CRC of SAE J1850 messages
+pub(crate) fn j1850_crc(bytes: &[u8]) -> u8 {
+    let crc = bytes.iter().fold(0xFF, |crc, &b| {
+        (0..8).fold(crc ^ b, |crc: u8, _| {
+            if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x1D
+            } else {
+                crc << 1
+            }
+        })
+    });
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commands::{mode08, Obd2DataRetrieval},
+        device::fake::FakeDevice,
+        uds::UdsClient,
+    };
+
+    #[test]
+    fn response_header_echoes_service_8_test_id_only() {
+        assert_eq!(response_header(&[0x08, 0x01, 0, 0, 0, 0, 0]), [0x48, 0x01]);
+        assert_eq!(response_header(&[0x01, 0x0C]), [0x41, 0x0C]);
+        assert_eq!(response_header(&[0x02, 0x0C, 0x00]), [0x42, 0x0C, 0x00]);
+    }
+
+    #[test]
+    fn response_header_echoes_uds_identifiers_only() {
+        assert_eq!(response_header(&[0x10, 0x03]), [0x50, 0x03]);
+        assert_eq!(response_header(&[0x14, 0xFF, 0xFF, 0xFF]), [0x54]);
+        assert_eq!(response_header(&[0x19, 0x02, 0x08]), [0x59, 0x02]);
+        assert_eq!(response_header(&[0x22, 0xF1, 0x90]), [0x62, 0xF1, 0x90]);
+        assert_eq!(response_header(&[0x27, 0x02, 0xAB, 0xCD]), [0x67, 0x02]);
+        assert_eq!(
+            response_header(&[0x2E, 0xF1, 0x98, 0x01]),
+            [0x6E, 0xF1, 0x98]
+        );
+        assert_eq!(
+            response_header(&[0x31, 0x01, 0xFF, 0x00, 0x01]),
+            [0x71, 0x01, 0xFF, 0x00]
+        );
+        assert_eq!(response_header(&[0x3E, 0x00]), [0x7E, 0x00]);
+    }
+
+    #[test]
+    fn malformed_byte_keeps_line() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x01, 0x0C], "41 0C 1A G8")
+            .into_obd2();
+        assert!(matches!(
+            device.get_rpm(),
+            Err(Error::MalformedLine(line)) if line == "41 0C 1A G8"
+        ));
+    }
+
+    #[test]
+    fn security_access_through_obd2() {
+        let device = FakeDevice::new()
+            .with_response(&[0x27, 0x01], "67 01 12 34")
+            .with_response(&[0x27, 0x02, 0xED, 0xCB], "67 02")
+            .into_obd2();
+        let mut ecu = UdsClient::new(device, 0x7E0);
+        ecu.security_access(0x01, |seed| seed.iter().map(|b| !b).collect())
+            .unwrap();
+
+        let device = ecu.into_inner().device;
+        assert_eq!(
+            device.requests,
+            [vec![0x27, 0x01], vec![0x27, 0x02, 0xED, 0xCB]]
+        );
+    }
+
+    #[test]
+    fn security_access_invalid_key() {
+        let device = FakeDevice::new()
+            .with_response(&[0x27, 0x01], "67 01 12 34")
+            .with_response(&[0x27, 0x02, 0x00], "7F 27 35")
+            .into_obd2();
+        let mut ecu = UdsClient::new(device, 0x7E0);
+        assert!(matches!(
+            ecu.security_access(0x01, |_| vec![0x00]),
+            Err(Error::NegativeResponse {
+                mode: 0x27,
+                nrc: NegativeResponseCode::InvalidKey,
             })
-            .collect())
+        ));
+    }
+
+    #[test]
+    fn request_control_on_can() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x08, 0x01, 0, 0, 0, 0, 0], "48 01")
+            .into_obd2();
+        let responses = device.request_control(mode08::EVAP_LEAK_TEST).unwrap();
+        assert!(matches!(
+            responses[..],
+            [mode08::ControlResponse::EvapSystemSealed]
+        ));
+    }
+
+    #[test]
+    fn request_control_with_data_bytes() {
+        let mut device = FakeDevice::new()
+            .with_response(
+                &[0x08, 0x05, 0, 0, 0, 0, 0],
+                "48 05 00 00 00 00 00\n48 05 12 34",
+            )
+            .into_obd2();
+        let responses = device.request_control(0x05).unwrap();
+        use mode08::ControlResponse::Other;
+        let [Other { test_id, data }, Other { data: other, .. }] = &responses[..] else {
+            panic!("unexpected responses {:?}", responses);
+        };
+        assert_eq!(*test_id, 0x05);
+        assert_eq!(data, &[0; 5]);
+        assert_eq!(other, &[0x12, 0x34]);
+    }
+
+    #[test]
+    fn request_control_rejected() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x08, 0x01, 0, 0, 0, 0, 0], "7F 08 22")
+            .into_obd2();
+        assert!(matches!(
+            device.request_control(mode08::EVAP_LEAK_TEST),
+            Err(Error::NegativeResponse { mode: 0x08, .. })
+        ));
+    }
+
+    #[test]
+    fn response_for_another_pid() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x01, 0x0C], "41 0D 00")
+            .into_obd2();
+        match device.obd_command(0x01, 0x0C) {
+            Err(Error::HeaderMismatch { expected, got }) => {
+                assert_eq!(expected, [0x41, 0x0C]);
+                assert_eq!(got, [0x41, 0x0D, 0x00]);
+            }
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn multiline_response_around_another_ecu() {
+        let response = [
+            "014",
+            "0: 49 02 01 31 44 34",
+            "49 02 01 00",
+            "1: 47 50 30 30 52 35 35",
+            "2: 42 31 32 33 34 35 36",
+        ];
+        let responses = decode_response(response.join("\n")).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0], [0x49, 0x02, 0x01, 0x00]);
+        assert_eq!(responses[1].len(), 0x14);
+        assert_eq!(responses[1][..4], [0x49, 0x02, 0x01, 0x31]);
+    }
+
+    #[test]
+    fn command_into_reuses_buffer() {
+        let mut device = FakeDevice::new()
+            .with_response(&[0x01, 0x0C], "7F 01 78\n41 0C 1A F8\n41 0C 00 00")
+            .with_response(&[0x01, 0x0D], "41 0D 32")
+            .into_obd2();
+        let mut responses = ResponseBuffer::new();
+        device.obd_command_into(0x01, 0x0C, &mut responses).unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses.first(), Some(&[0x1A, 0xF8][..]));
+        assert_eq!(responses.get(1), Some(&[0x00, 0x00][..]));
+
+        device.obd_command_into(0x01, 0x0D, &mut responses).unwrap();
+        assert_eq!(responses.iter().collect::<Vec<_>>(), [[0x32]]);
+        assert!(matches!(
+            device.obd_command_into(0x01, 0x0E, &mut responses),
+            Err(Error::NoData)
+        ));
     }
 }
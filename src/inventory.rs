@@ -0,0 +1,182 @@
+//! Finding the ECUs on the vehicle and what they are
+//!
+//! [enumerate_ecus] broadcasts a request that every emissions-related ECU answers, then asks each
+//! ECU that responded for its name (service 9 PID 0x0A) and the standard UDS identification DIDs,
+//! like its part number and software version. Most ECUs only report some of these.
+//!
+//! # Example
+//! ```
+//! use obd2::{device::Obd2Simulator, inventory::enumerate_ecus};
+//!
+//! let mut device = Obd2Simulator::new();
+//! let inventory = enumerate_ecus(&mut device)?;
+//!
+//! let [engine] = &inventory.ecus[..] else { panic!() };
+//! assert_eq!(engine.address, 0x7E8);
+//! assert!(engine.name.is_none());
+//! # Ok::<(), obd2::Error>(())
+//! ```
+
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::{
+    commands::mode09::{decode_ecu_names, EcuName},
+    report::optional,
+    Obd2Device, Result,
+};
+
+/// The ECUs that responded, from [enumerate_ecus]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EcuInventory {
+    /// Each ECU, in the order they responded
+    pub ecus: Vec<EcuInfo>,
+}
+
+impl EcuInventory {
+    /// Get the ECU that responds from `address`
+    pub fn ecu(&self, address: u32) -> Option<&EcuInfo> {
+        self.ecus.iter().find(|ecu| ecu.address == address)
+    }
+}
+
+/// What one ECU reported about itself
+///
+/// Each field is `None` if the ECU does not report it.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EcuInfo {
+    /// The address the ECU responds from (see [EcuResponse](crate::EcuResponse))
+    pub address: u32,
+
+    /// The ECU's name, like `ECM-EngineControl`
+    pub name: Option<EcuName>,
+
+    /// The vehicle manufacturer's spare part number (DID 0xF187)
+    pub part_number: Option<String>,
+
+    /// The supplier that made the ECU (DID 0xF18A)
+    pub supplier: Option<String>,
+
+    /// The ECU's serial number (DID 0xF18C)
+    pub serial_number: Option<String>,
+
+    /// The supplier's hardware version (DID 0xF193)
+    pub hardware_version: Option<String>,
+
+    /// The supplier's software version (DID 0xF195)
+    pub software_version: Option<String>,
+}
+
+/// Find the ECUs on the vehicle, and read what each reports about itself
+///
+/// The ECUs are found from the addresses of the responses to service 1 PID 0x00, so this needs a
+/// device that reports them (see [obd_request_tagged](Obd2Device::obd_request_tagged)). Each is
+/// then sent its own requests, to its physical request address on CAN (like `0x7E0` for the ECU
+/// responding from `0x7E8`). Information an ECU does not report is left out, but the enumeration
+/// stops with an error if the connection is lost.
+///
+/// # Example
+/// ```
+/// use obd2::{device::MockObd2Device, inventory::enumerate_ecus};
+///
+/// let mut name = vec![0x01];
+/// name.extend(b"ECM\0-EngineControl\0\0");
+/// // the engine and transmission controllers, where only the engine reports its name and
+/// // software version
+/// let mut device = MockObd2Device::new()
+///     .with_response(0x01, 0x00, [vec![0xBE, 0x1F, 0xA8, 0x13], vec![0x80, 0x00, 0x00, 0x00]])
+///     .with_response(0x09, 0x0A, [name]);
+/// device.set_request_response(&[0x22, 0xF1, 0x95], [b"SW 1.04   ".to_vec()]);
+///
+/// let inventory = enumerate_ecus(&mut device)?;
+///
+/// let engine = inventory.ecu(0x7E8).unwrap();
+/// assert_eq!(engine.name.as_ref().unwrap().acronym, "ECM");
+/// assert_eq!(engine.software_version.as_deref(), Some("SW 1.04"));
+/// assert_eq!(engine.part_number, None);
+///
+/// let transmission = inventory.ecu(0x7E9).unwrap();
+/// assert!(transmission.name.is_none());
+/// # Ok::<(), obd2::Error>(())
+/// ```
+pub fn enumerate_ecus<T: Obd2Device>(device: &mut T) -> Result<EcuInventory> {
+    let mut addresses: Vec<u32> = Vec::new();
+    for response in device.obd_request_tagged(&[0x01, 0x00])? {
+        if !addresses.contains(&response.address) {
+            addresses.push(response.address);
+        }
+    }
+
+    let mut inventory = EcuInventory::default();
+    for address in addresses {
+        let name = query(device, address, &[0x09, 0x0A])?
+            .and_then(|data| decode_ecu_names(vec![data]).ok())
+            .and_then(|mut names| names.pop());
+        inventory.ecus.push(EcuInfo {
+            address,
+            name,
+            part_number: read_identifier(device, address, 0xF187)?,
+            supplier: read_identifier(device, address, 0xF18A)?,
+            serial_number: read_identifier(device, address, 0xF18C)?,
+            hardware_version: read_identifier(device, address, 0xF193)?,
+            software_version: read_identifier(device, address, 0xF195)?,
+        });
+    }
+    Ok(inventory)
+}
+
+/// Read an identification DID from the ECU at `address`, as text without the padding
+fn read_identifier<T: Obd2Device>(
+    device: &mut T,
+    address: u32,
+    did: u16,
+) -> Result<Option<String>> {
+    let [high, low] = did.to_be_bytes();
+    let Some(data) = query(device, address, &[0x22, high, low])? else {
+        return Ok(None);
+    };
+    // padded with spaces, null bytes or 0xFF, depending on the manufacturer
+    let text: String = data
+        .into_iter()
+        .map(char::from)
+        .filter(|c| c.is_ascii_graphic() || *c == ' ')
+        .collect();
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| text.into()))
+}
+
+/// Send `request` to only the ECU that responds from `address`, or `None` if it does not support
+/// the request
+///
+/// Where the physical request address is not known, the request is broadcast and only that ECU's
+/// response is kept.
+fn query<T: Obd2Device>(device: &mut T, address: u32, request: &[u8]) -> Result<Option<Vec<u8>>> {
+    match request_address(address) {
+        Some(request_address) => optional(device.obd_request_to(request_address, request)),
+        None => Ok(
+            optional(device.obd_request_tagged(request))?.and_then(|responses| {
+                responses
+                    .into_iter()
+                    .find(|response| response.address == address)
+                    .map(|response| response.data)
+            }),
+        ),
+    }
+}
+
+/// The physical request address of the ECU responding from CAN identifier `address`
+///
+/// ECUs responding from `0x7E8` to `0x7EF` are sent requests at 8 lower, and 29-bit identifiers
+/// like `0x18DAF110` have their addresses swapped, like `0x18DA10F1`.
+fn request_address(address: u32) -> Option<u32> {
+    match address {
+        0x7E8..=0x7EF => Some(address - 8),
+        _ if address >> 16 == 0x18DA => {
+            Some(0x18DA_0000 | (address & 0xFF) << 8 | (address >> 8) & 0xFF)
+        }
+        _ => None,
+    }
+}
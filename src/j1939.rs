@@ -0,0 +1,244 @@
+//! SAE J1939 for heavy-duty vehicles
+//!
+//! Trucks, buses, and agricultural and construction machinery mostly report through SAE J1939
+//! instead of the OBD-II services. Data is sent in parameter groups, identified by a parameter
+//! group number (PGN), and each value in a group is a suspect parameter, identified by a suspect
+//! parameter number (SPN). Many groups are broadcast regularly, and any of them can be requested.
+//!
+//! A [J1939Device] requests parameter groups. It is implemented for [Obd2], for
+//! adapters like the ELM327 that can switch to J1939, and for `SocketCanDevice` opened with
+//! `SocketCanDevice::new_j1939` on Linux. [J1939DataRetrieval] decodes the groups, and custom
+//! groups can be decoded by implementing [Pgn].
+//!
+//! # Example
+//! ```
+//! use obd2::j1939::{Dm1, Pgn};
+//!
+//! // amber warning lamp on, SPN 110 (coolant temperature) with FMI 0 (too high), seen 3 times
+//! let dm1 = Dm1::decode(&[0x04, 0xFF, 0x6E, 0x00, 0x00, 0x03, 0xFF, 0xFF]).unwrap();
+//! assert!(dm1.lamps.amber_warning);
+//! assert!(!dm1.lamps.malfunction_indicator);
+//! assert_eq!(dm1.dtcs[0].spn, 110);
+//! assert_eq!(dm1.dtcs[0].fmi, 0);
+//! assert_eq!(dm1.dtcs[0].occurrence_count, 3);
+//! ```
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{device::Obd2BaseDevice, interface::decode_response, Error, Obd2, Result};
+
+/// PGN of request messages, which ask ECUs to send a parameter group
+#[cfg(feature = "std")]
+pub(crate) const REQUEST_PGN: u32 = 0xEA00;
+
+/// Get the PGN of the message with the 29-bit CAN identifier `id`
+///
+/// For PDU1 groups (PDU format below 240) the low byte of the PGN is the destination address,
+/// which is not part of the PGN.
+#[cfg(feature = "std")]
+pub(crate) fn pgn_from_id(id: u32) -> u32 {
+    let pgn = (id >> 8) & 0x3_FFFF;
+    if (pgn >> 8) & 0xFF < 0xF0 {
+        pgn & 0x3_FF00
+    } else {
+        pgn
+    }
+}
+
+/// A device that can request J1939 parameter groups
+pub trait J1939Device {
+    /// Request the parameter group `pgn` from every ECU, and get the data from each ECU that
+    /// responded
+    fn request_pgn(&mut self, pgn: u32) -> Result<Vec<Vec<u8>>>;
+}
+
+/// The adapter switches to its J1939 protocol for each request, and back to automatically
+/// selecting the OBD-II protocol afterwards
+impl<T: Obd2BaseDevice> J1939Device for Obd2<T> {
+    fn request_pgn(&mut self, pgn: u32) -> Result<Vec<Vec<u8>>> {
+        let response = self.with_retries(|device| device.cmd_j1939(pgn))?;
+        decode_response(response)
+    }
+}
+
+/// A J1939 parameter group, which can be requested with [J1939DataRetrieval::query]
+pub trait Pgn {
+    /// The parameter group number
+    const PGN: u32;
+
+    /// The decoded value
+    type Output;
+
+    /// Decode the data of the parameter group
+    fn decode(data: &[u8]) -> Result<Self::Output>;
+}
+
+/// Read a parameter of `len` bytes (1, 2 or 4) starting at byte `start` of a parameter group
+///
+/// J1939 parameters are little-endian. Returns `None` if the data is too short, or if the
+/// parameter is "not available" or an error indicator (a most significant byte above `0xFA`).
+pub fn read_parameter(data: &[u8], start: usize, len: usize) -> Option<u32> {
+    let bytes = data.get(start..start + len)?;
+    if *bytes.last()? > 0xFA {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |acc, &b| (acc << 8) | u32::from(b)),
+    )
+}
+
+/// The warning lamps an ECU is requesting, from [Dm1] or [Dm2]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct LampStatus {
+    /// The malfunction indicator lamp, for emissions related faults
+    pub malfunction_indicator: bool,
+    /// The red stop lamp, for faults serious enough to stop the vehicle
+    pub red_stop: bool,
+    /// The amber warning lamp, for faults that do not need the vehicle to stop
+    pub amber_warning: bool,
+    /// The protect lamp, for faults outside the electronic systems (like low coolant)
+    pub protect: bool,
+}
+
+/// A J1939 DTC, which is a suspect parameter and how it failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct J1939Dtc {
+    /// The suspect parameter number of the failed parameter or component
+    pub spn: u32,
+    /// The failure mode identifier, like 0 for data above the normal range or 5 for an open
+    /// circuit
+    pub fmi: u8,
+    /// How many times the fault has gone from inactive to active, up to 126
+    pub occurrence_count: u8,
+}
+
+/// The lamps and DTCs from a diagnostic message, [Dm1] or [Dm2]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct DiagnosticMessage {
+    /// The warning lamps the ECU is requesting
+    pub lamps: LampStatus,
+    /// The DTCs
+    pub dtcs: Vec<J1939Dtc>,
+}
+
+fn decode_diagnostic_message(data: &[u8], name: &'static str) -> Result<DiagnosticMessage> {
+    let [lamps, _, records @ ..] = data else {
+        return Err(Error::IncorrectResponseLength(name, 2, data.len()));
+    };
+    let lamp = |shift: u8| (*lamps >> shift) & 0x03 == 0x01;
+    Ok(DiagnosticMessage {
+        lamps: LampStatus {
+            malfunction_indicator: lamp(6),
+            red_stop: lamp(4),
+            amber_warning: lamp(2),
+            protect: lamp(0),
+        },
+        dtcs: records
+            .chunks_exact(4)
+            .map(|r| J1939Dtc {
+                spn: u32::from(r[0]) | u32::from(r[1]) << 8 | u32::from(r[2] & 0xE0) << 11,
+                fmi: r[2] & 0x1F,
+                occurrence_count: r[3] & 0x7F,
+            })
+            // a message without DTCs has one of all zeros, and single frames are padded with 0xFF
+            .filter(|dtc| dtc.spn != 0 && dtc.spn != 0x7_FFFF)
+            .collect(),
+    })
+}
+
+/// DM1, the active DTCs (PGN 65226)
+///
+/// ECUs broadcast this every second, and it can also be requested.
+pub struct Dm1;
+
+impl Pgn for Dm1 {
+    const PGN: u32 = 0xFECA;
+    type Output = DiagnosticMessage;
+
+    fn decode(data: &[u8]) -> Result<DiagnosticMessage> {
+        decode_diagnostic_message(data, "DM1")
+    }
+}
+
+/// DM2, the previously active DTCs (PGN 65227)
+pub struct Dm2;
+
+impl Pgn for Dm2 {
+    const PGN: u32 = 0xFECB;
+    type Output = DiagnosticMessage;
+
+    fn decode(data: &[u8]) -> Result<DiagnosticMessage> {
+        decode_diagnostic_message(data, "DM2")
+    }
+}
+
+/// Total engine hours (SPN 247, PGN 65253), or `None` if not available
+pub struct EngineHours;
+
+impl Pgn for EngineHours {
+    const PGN: u32 = 0xFEE5;
+    type Output = Option<Duration>;
+
+    fn decode(data: &[u8]) -> Result<Option<Duration>> {
+        // 0.05 hours per bit
+        Ok(read_parameter(data, 0, 4).map(|v| Duration::from_secs(u64::from(v) * 180)))
+    }
+}
+
+/// Engine fuel rate in L/h (SPN 183, PGN 65266), or `None` if not available
+pub struct FuelRate;
+
+impl Pgn for FuelRate {
+    const PGN: u32 = 0xFEF2;
+    type Output = Option<f32>;
+
+    fn decode(data: &[u8]) -> Result<Option<f32>> {
+        Ok(read_parameter(data, 0, 2).map(|v| v as f32 * 0.05))
+    }
+}
+
+/// High-level J1939 data retrieval
+///
+/// Each getter returns one value for each ECU that responded. Automatically implemented for
+/// implementers of [J1939Device].
+pub trait J1939DataRetrieval: J1939Device {
+    /// Request and decode the parameter group `P`
+    fn query<P: Pgn>(&mut self) -> Result<Vec<P::Output>> {
+        self.request_pgn(P::PGN)?
+            .iter()
+            .map(|data| P::decode(data))
+            .collect()
+    }
+
+    /// Get the warning lamps and active DTCs (DM1)
+    fn get_active_dtcs(&mut self) -> Result<Vec<DiagnosticMessage>> {
+        self.query::<Dm1>()
+    }
+
+    /// Get the warning lamps and previously active DTCs (DM2)
+    fn get_previously_active_dtcs(&mut self) -> Result<Vec<DiagnosticMessage>> {
+        self.query::<Dm2>()
+    }
+
+    /// Get the total time the engine has run
+    fn get_engine_hours(&mut self) -> Result<Vec<Option<Duration>>> {
+        self.query::<EngineHours>()
+    }
+
+    /// Get the rate of fuel flow into the engine in L/h
+    fn get_fuel_rate(&mut self) -> Result<Vec<Option<f32>>> {
+        self.query::<FuelRate>()
+    }
+}
+
+impl<T: J1939Device> J1939DataRetrieval for T {}
@@ -1,33 +1,136 @@
 //! Crate for communicating with OBD-II (on-board diagnostics) interfaces on cars
 //!
-//! Currently only the ELM327 is supported (many cheap USB to OBD-II devices you can buy online are
-//! compatible with the ELM327). The high-level data retrieval functions can be found in
-//! [commands::Obd2DataRetrieval].
+//! The ELM327 is supported over serial, Bluetooth and Wi-Fi (many cheap USB to OBD-II devices you
+//! can buy online are compatible with the ELM327), as are CAN interfaces on Linux through
+//! SocketCAN, K-line interfaces and Diagnostics over IP; see [device]. The high-level data
+//! retrieval functions can be found in
+//! [commands::Obd2DataRetrieval], and [poller] reads PIDs repeatedly in the background. [uds] sends
+//! Unified Diagnostic Services requests to a single ECU, and [j1939] reads heavy-duty vehicles
+//! that use SAE J1939. [report] reads everything about a vehicle's emissions faults at once,
+//! [inspection] checks whether it is ready for an emissions inspection, [inventory] lists the ECUs
+//! on the bus and [capabilities] finds which PIDs each of them supports.
 //!
 //! # Usage
-//! ```
+//! ```no_run
 //! use obd2::{commands::Obd2DataRetrieval, device::Elm327, Obd2};
 //!
 //! fn main() -> Result<(), obd2::Error> {
-//!     let mut device = Obd2::<Elm327>::default();
+//!     let mut device = Obd2::new(Elm327::new("/dev/ttyUSB0")?);
 //!     println!("VIN: {}", device.get_vin()?);
 //!     Ok(())
 //! }
 //! ```
+//!
+//! # Features
+//! - `std` (on by default): the devices that communicate through the operating system, like
+//!   [device::Elm327], and [poller], [logger] and [uds]. Without it the crate is `no_std`, but
+//!   still needs an allocator
+//! - `arrow`: `logger::ParquetLogger`, writing polled values to Parquet files through Arrow
+//!   record batches
+//! - `async`: `AsyncObd2Device` and `commands::AsyncObd2DataRetrieval`, with tokio-based
+//!   serial and Wi-Fi ELM327 devices
+//! - `ble`: ELM327 adapters connected over Bluetooth Low Energy
+//! - `cdylib`: `ffi`, a C API for building the crate as a shared library, declared in
+//!   `include/obd2.h`
+//! - `cli`: the `obd2` command-line tool, with `scan`, `dtc`, `watch` and `log` commands
+//! - `dtc-db`: descriptions of the generic DTCs, from `commands::Dtc::description`
+//! - `defmt`: `defmt::Format` for the errors, DTCs, freeze frames and responses, for logging on
+//!   embedded targets
+//! - `embedded`: `device::EmbeddedElm327`, for an ELM327 connected to a microcontroller's serial
+//!   port through the `embedded-hal` traits, which works without `std`
+//! - `heapless`: `Obd2Device::obd_command_heapless`, returning the responses in fixed-capacity
+//!   vectors
+//! - `json`: `logger::JsonLinesWriter`, streaming records as JSON Lines
+//! - `mqtt`: `mqtt::MqttPublisher`, publishing polled values and DTCs to an MQTT broker, with
+//!   Home Assistant discovery
+//! - `msgpack`: `logger::MessagePackWriter`, streaming records as MessagePack
+//! - `serde`: `Serialize` and `Deserialize` for the decoded values, so they can be logged or sent
+//!   over the network (types holding `&'static str` names, and `Error`, are only `Serialize`)
+//! - `test-util`: a mock device for testing code that uses this crate
+//! - `tracing`: `tracing` spans around each request (at DEBUG), the ELM327's raw reads and writes
+//!   (at TRACE) and its protocol selection (at INFO), so that with `tracing-log` the `log`
+//!   messages are grouped by request
+//! - `tui`: the `dashboard` command of the `obd2` command-line tool, with live gauges, the DTCs
+//!   and the state of the connection
+//! - `uom`: getters returning typed quantities from the [uom](https://docs.rs/uom) crate, in
+//!   `commands::units`
+//! - `web`: `device::WebElm327`, for in-browser tools built for `wasm32-unknown-unknown`, talking
+//!   to an ELM327 through Web Serial or Web Bluetooth. With `--no-default-features`, the commands
+//!   and decoding compile for `wasm32-unknown-unknown` on their own
+//! - `websocket`: `websocket::WebSocketServer`, streaming polled values and DTCs to browsers as
+//!   JSON over WebSocket
 
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(
+    not(any(feature = "cdylib", all(feature = "std", target_os = "linux"))),
+    forbid(unsafe_code)
+)]
+#![cfg_attr(
+    any(feature = "cdylib", all(feature = "std", target_os = "linux")),
+    deny(unsafe_code)
+)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
+pub mod calc;
+
+pub mod capabilities;
+
 pub mod commands;
 
 pub mod device;
 
+#[cfg(feature = "std")]
+pub mod events;
+
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+
+pub mod gear;
+
+pub mod inspection;
+
+pub mod inventory;
+
 mod error;
-pub use error::Error;
 use error::Result;
+pub use error::{Error, NegativeResponseCode};
 
 mod interface;
-pub use interface::Obd2;
+pub use interface::{Obd2, ResponseBuffer};
 
 mod obd2_device;
-pub use obd2_device::Obd2Device;
+#[cfg(feature = "async")]
+pub use obd2_device::AsyncObd2Device;
+#[cfg(feature = "heapless")]
+pub use obd2_device::HeaplessResponses;
+pub use obd2_device::{EcuResponse, Obd2Device, Obd2DeviceDyn};
+
+mod protocol;
+pub use protocol::Protocol;
+
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+#[cfg(feature = "std")]
+pub mod poller;
+
+pub mod report;
+
+#[cfg(feature = "std")]
+pub mod uds;
+
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+pub mod j1939;
+
+#[cfg(feature = "std")]
+pub mod logger;
+
+pub mod trip;
+
+pub mod units;
+
+pub mod vin;
@@ -0,0 +1,328 @@
+use std::{
+    borrow::Cow,
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::Receiver,
+    time::Duration,
+};
+
+use super::{column_name, Clock, RotatingFile, Row, Rows};
+use crate::{commands::pid::StandardPid, poller::Sample, units::UnitSystem, Result};
+
+/// The layout of the files written by a [CsvLogger]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum CsvFormat {
+    /// The time as seconds since the Unix epoch, to the millisecond, followed by a column for
+    /// each PID named with its name and unit, like `Engine speed (rpm)`
+    #[default]
+    Plain,
+    /// The layout of the logs of the Torque Pro app, so that spreadsheets and tools made for them
+    /// can read the files
+    ///
+    /// The columns start with the GPS time, the device time and the GPS position, speed and
+    /// accelerometer readings, which are all `-` except the device time, written in UTC like
+    /// `16-Oct-2026 10:55:33.123`. Each PID follows, named like Torque names it, like
+    /// `Engine RPM(rpm)` and `Speed (OBD)(km/h)`, and missing values are `-`.
+    TorquePro,
+}
+
+/// Writes polled values to CSV files
+///
+/// Each row has the time, followed by a column for each PID, laid out as set by the
+/// [CsvFormat]. See the [module](super) documentation for how samples become rows and files are
+/// rotated.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::pid::StandardPid, device::Obd2Simulator, logger::CsvLogger, poller::Obd2Poller,
+/// };
+/// use std::time::Duration;
+///
+/// let pids = [StandardPid::EngineSpeed, StandardPid::VehicleSpeed];
+/// let poller = Obd2Poller::new(Obd2Simulator::new())
+///     .poll(pids[0], Duration::from_millis(10))
+///     .poll(pids[1], Duration::from_millis(10))
+///     .start();
+///
+/// // a new file every hour; `run` would log until the poller stops
+/// let path = std::env::temp_dir().join("obd2-csv-logger-example.csv");
+/// let mut logger = CsvLogger::new(&path, pids).with_max_duration(Duration::from_secs(3600));
+/// for sample in poller.samples().iter().take(4) {
+///     logger.add_sample(&sample?)?;
+/// }
+/// drop(logger);
+///
+/// let csv = std::fs::read_to_string(&path).unwrap();
+/// let mut lines = csv.lines();
+/// assert_eq!(lines.next(), Some("time,Engine speed (rpm),Vehicle speed (km/h)"));
+/// assert_eq!(lines.count(), 2);
+/// # Ok::<(), obd2::Error>(())
+/// ```
+pub struct CsvLogger {
+    rows: Rows,
+    file: RotatingFile,
+    clock: Clock,
+    units: UnitSystem,
+    format: CsvFormat,
+}
+
+impl CsvLogger {
+    /// Create a logger writing a column for each of `columns` to `path`
+    ///
+    /// The file is created when the first row is written, overwriting any file at `path`.
+    pub fn new(path: impl AsRef<Path>, columns: impl IntoIterator<Item = StandardPid>) -> Self {
+        CsvLogger {
+            rows: Rows::new(columns.into_iter().collect()),
+            file: RotatingFile::new(path.as_ref().to_path_buf()),
+            clock: Clock::new(),
+            units: UnitSystem::Metric,
+            format: CsvFormat::Plain,
+        }
+    }
+
+    /// Start a new file once the current one has `bytes` bytes
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.file.max_size = Some(bytes);
+        self
+    }
+
+    /// Start a new file once the current one has rows from `duration`
+    pub fn with_max_duration(mut self, duration: Duration) -> Self {
+        self.file.max_duration = Some(duration);
+        self
+    }
+
+    /// Write the values in `units` instead of metric units
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Write the files in `format`, instead of [CsvFormat::Plain]
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{
+    ///     commands::pid::StandardPid,
+    ///     device::Obd2Simulator,
+    ///     logger::{CsvFormat, CsvLogger},
+    ///     poller::Obd2Poller,
+    /// };
+    /// use std::time::Duration;
+    ///
+    /// let pids = [StandardPid::EngineSpeed, StandardPid::EngineCoolantTemperature];
+    /// let poller = Obd2Poller::new(Obd2Simulator::new())
+    ///     .poll(pids[0], Duration::from_millis(10))
+    ///     .start();
+    ///
+    /// let path = std::env::temp_dir().join("obd2-torque-logger-example.csv");
+    /// let mut logger = CsvLogger::new(&path, pids).with_format(CsvFormat::TorquePro);
+    /// logger.add_sample(&poller.samples().recv().unwrap()?)?;
+    /// drop(logger);
+    ///
+    /// let csv = std::fs::read_to_string(&path).unwrap();
+    /// let mut lines = csv.lines();
+    /// let header = lines.next().unwrap();
+    /// assert!(header.starts_with("GPS Time, Device Time, Longitude, Latitude,"));
+    /// assert!(header.ends_with(",Engine RPM(rpm),Engine Coolant Temperature(°C)"));
+    ///
+    /// // the coolant temperature was not polled
+    /// let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+    /// assert_eq!(row[0], "-");
+    /// assert_eq!(row[1].len(), "16-Oct-2026 10:55:33.123".len());
+    /// assert_eq!(row[13], "-");
+    /// # Ok::<(), obd2::Error>(())
+    /// ```
+    pub fn with_format(mut self, format: CsvFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Get the path of the file being written
+    pub fn path(&self) -> PathBuf {
+        self.file.path()
+    }
+
+    /// Add a sample, writing the previous row if the sample was read at a different time
+    ///
+    /// Samples of PIDs without a column are ignored.
+    pub fn add_sample(&mut self, sample: &Sample) -> Result<()> {
+        match self.rows.add(sample) {
+            Some(row) => self.write_row(&row),
+            None => Ok(()),
+        }
+    }
+
+    /// Add every sample from a poller's channel until the poller stops
+    ///
+    /// Failed requests sent on the channel are skipped, so only errors writing the file stop
+    /// logging.
+    pub fn run(&mut self, samples: &Receiver<Result<Sample>>) -> Result<()> {
+        for sample in samples.iter().flatten() {
+            self.add_sample(&sample)?;
+        }
+        self.flush()
+    }
+
+    /// Write the row being filled and flush the file
+    ///
+    /// Samples added afterwards with the same time start a new row.
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(row) = self.rows.finish() {
+            self.write_row(&row)?;
+        }
+        self.file.flush()
+    }
+
+    fn write_row(&mut self, row: &Row) -> Result<()> {
+        let time = self.clock.unix_time(row.time);
+        let (mut line, missing) = match self.format {
+            CsvFormat::Plain => (format!("{:.3}", time), ""),
+            CsvFormat::TorquePro => (format!("-,{},-,-,-,-,-,-,-,-,-,-", torque_time(time)), "-"),
+        };
+        for (&pid, value) in self.rows.columns.iter().zip(&row.values) {
+            line.push(',');
+            match value {
+                Some(value) => {
+                    let _ = write!(line, "{}", self.units.convert_pid(pid, *value));
+                }
+                None => line.push_str(missing),
+            }
+        }
+        line.push('\n');
+        let header = || header(&self.rows.columns, self.units, self.format);
+        self.file.write(row.time, header, line.as_bytes())
+    }
+}
+
+/// The first line of each file, with the name and unit of each column
+fn header(columns: &[StandardPid], units: UnitSystem, format: CsvFormat) -> String {
+    let mut header = String::from(match format {
+        CsvFormat::Plain => "time",
+        CsvFormat::TorquePro => TORQUE_HEADER,
+    });
+    for &pid in columns {
+        let name = match format {
+            CsvFormat::Plain => column_name(pid, units),
+            CsvFormat::TorquePro => torque_column_name(pid, units),
+        };
+        header.push(',');
+        header.push_str(&escape(&name));
+    }
+    header.push('\n');
+    header
+}
+
+/// The columns before the PIDs in a Torque Pro log
+const TORQUE_HEADER: &str =
+    "GPS Time, Device Time, Longitude, Latitude,GPS Speed (Meters/second), \
+    Horizontal Dilution of Precision, Altitude, Bearing, G(x), G(y), G(z), G(calibrated)";
+
+/// Name the column of `pid` like Torque Pro does, like `Engine RPM(rpm)`
+fn torque_column_name(pid: StandardPid, units: UnitSystem) -> String {
+    use StandardPid as P;
+    let name = match pid {
+        P::EngineLoad => "Engine Load",
+        P::EngineCoolantTemperature => "Engine Coolant Temperature",
+        P::ShortTermFuelTrim1 => "Fuel Trim Bank 1 Short Term",
+        P::LongTermFuelTrim1 => "Fuel Trim Bank 1 Long Term",
+        P::ShortTermFuelTrim2 => "Fuel Trim Bank 2 Short Term",
+        P::LongTermFuelTrim2 => "Fuel Trim Bank 2 Long Term",
+        P::FuelPressure => "Fuel pressure",
+        P::IntakeManifoldPressure => "Intake Manifold Pressure",
+        P::EngineSpeed => "Engine RPM",
+        P::VehicleSpeed => "Speed (OBD)",
+        P::TimingAdvance => "Timing Advance",
+        P::IntakeAirTemperature => "Intake Air Temperature",
+        P::MafAirFlowRate => "Mass Air Flow Rate",
+        P::ThrottlePosition => "Throttle Position(Manifold)",
+        P::RunTimeSinceStart => "Run time since engine start",
+        P::DistanceWithMil => "Distance travelled with MIL/CEL lit",
+        P::FuelRailPressure => "Fuel Rail Pressure",
+        P::CommandedEgr => "EGR Commanded",
+        P::EgrError => "EGR Error",
+        P::FuelTankLevel => "Fuel Level (From Engine ECU)",
+        P::DistanceSinceCodesCleared => "Distance travelled since codes cleared",
+        P::BarometricPressure => "Barometric pressure (from vehicle)",
+        P::CatalystTemperature1Sensor1 => "Catalyst Temperature (Bank 1 Sensor 1)",
+        P::CatalystTemperature2Sensor1 => "Catalyst Temperature (Bank 2 Sensor 1)",
+        P::CatalystTemperature1Sensor2 => "Catalyst Temperature (Bank 1 Sensor 2)",
+        P::CatalystTemperature2Sensor2 => "Catalyst Temperature (Bank 2 Sensor 2)",
+        P::ControlModuleVoltage => "Voltage (Control Module)",
+        P::AbsoluteLoad => "Engine Load(Absolute)",
+        P::CommandedEquivalenceRatio => "Commanded Equivalence Ratio(lambda)",
+        P::RelativeThrottlePosition => "Relative Throttle Position",
+        P::AmbientAirTemperature => "Ambient air temp",
+        P::AcceleratorPedalPositionD => "Accelerator PedalPosition D",
+        P::AcceleratorPedalPositionE => "Accelerator PedalPosition E",
+        P::AcceleratorPedalPositionF => "Accelerator PedalPosition F",
+        P::EngineOilTemperature => "Engine Oil Temperature",
+        P::EngineFuelRate => "Fuel flow rate/hour",
+        pid => pid.name(),
+    };
+    let unit = match units.pid_unit(pid) {
+        Some("ºC") => "°C",
+        Some("ºF") => "°F",
+        Some("º before TDC") | Some("º") => "°",
+        Some("L/h") => "l/hr",
+        Some("gal/h") => "gal/hr",
+        Some(unit) => unit,
+        None => return name.to_string(),
+    };
+    format!("{}({})", name, unit)
+}
+
+/// Format the time like Torque Pro's device time, like `16-Oct-2026 10:55:33.123`, in UTC
+fn torque_time(unix_time: f64) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let millis = (unix_time * 1000.).round() as i64;
+    let (days, millis) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+
+    // the civil date from the days since 1970-01-01, from Howard Hinnant's `civil_from_days`
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:02}-{}-{} {:02}:{:02}:{:02}.{:03}",
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+impl Drop for CsvLogger {
+    /// Write the last row and flush the file, ignoring errors; call [flush](Self::flush) first
+    /// to handle them
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Quote `field` if it contains a comma, quote or line break
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
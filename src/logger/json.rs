@@ -0,0 +1,63 @@
+use std::io::Write;
+
+use crate::{Error, Result};
+
+/// Writes records as JSON Lines (NDJSON), one JSON object per line
+///
+/// Any `Serialize` type can be written, like [LiveData](crate::commands::LiveData) or
+/// [DrivingEvent](crate::events::DrivingEvent), to any writer, like a file, a socket or the
+/// standard output of a process piping into another one. Each record is flushed once it is
+/// written, so readers see it right away.
+///
+/// # Example
+/// ```
+/// use obd2::{events::DrivingEvent, logger::JsonLinesWriter};
+///
+/// let mut writer = JsonLinesWriter::new(Vec::new());
+/// writer.write(&DrivingEvent::Overspeed { speed: 135. }).unwrap();
+/// writer.write(&DrivingEvent::OverRev { rpm: 4800. }).unwrap();
+///
+/// let output = String::from_utf8(writer.into_inner()).unwrap();
+/// assert_eq!(
+///     output,
+///     "{\"Overspeed\":{\"speed\":135.0}}\n{\"OverRev\":{\"rpm\":4800.0}}\n"
+/// );
+/// ```
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Write records to `writer`
+    pub fn new(writer: W) -> Self {
+        JsonLinesWriter { writer }
+    }
+
+    /// Write `record` as one line
+    pub fn write<T: serde::Serialize + ?Sized>(&mut self, record: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, record).map_err(|e| Error::Output {
+            destination: "JSON record",
+            message: e.to_string(),
+        })?;
+        self.writer
+            .write_all(b"\n")
+            .and_then(|_| self.writer.flush())
+            .map_err(crate::device::Error::from)?;
+        Ok(())
+    }
+
+    /// Get the writer
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Get the writer mutably
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Get back the writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
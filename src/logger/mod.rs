@@ -0,0 +1,209 @@
+//! Logging polled values to files
+//!
+//! The loggers take the [samples](crate::poller::Sample) of an
+//! [Obd2Poller](crate::poller::Obd2Poller) and write them as rows, one for each time the PIDs
+//! were read, with a column for each PID. The samples of PIDs that were read together have the
+//! same time, so they end up in the same row, and columns that were not read at that time are
+//! left empty.
+//!
+//! A [CsvLogger] can split long recordings across files by size or time. The first file is written
+//! at the given path, and the following ones have a number before the extension, like
+//! `drive.1.csv`, `drive.2.csv`, and so on. Rows are buffered, and written out when the logger is
+//! dropped.
+//!
+//! With the `arrow` feature, a [ParquetLogger] writes the rows to a Parquet file for analysis
+//! with pandas or Polars. With the `json` and `msgpack` features, whole records like
+//! [LiveData](crate::commands::LiveData) and [DrivingEvent](crate::events::DrivingEvent) can
+//! instead be streamed to any writer as JSON Lines or MessagePack, to pipe them to another process
+//! or upload them.
+
+mod csv;
+pub use csv::{CsvFormat, CsvLogger};
+
+#[cfg(feature = "arrow")]
+mod parquet;
+#[cfg(feature = "arrow")]
+pub use parquet::ParquetLogger;
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::JsonLinesWriter;
+
+#[cfg(feature = "msgpack")]
+mod msgpack;
+#[cfg(feature = "msgpack")]
+pub use msgpack::MessagePackWriter;
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{commands::pid::StandardPid, poller::Sample, units::UnitSystem, Result};
+
+/// The values of the columns read at the same time
+struct Row {
+    time: Instant,
+    values: Vec<Option<f32>>,
+}
+
+/// Gathers samples with the same time into rows
+struct Rows {
+    columns: Vec<StandardPid>,
+    pending: Option<Row>,
+}
+
+impl Rows {
+    fn new(columns: Vec<StandardPid>) -> Self {
+        Rows {
+            columns,
+            pending: None,
+        }
+    }
+
+    /// Add the first ECU's value of `sample` to its column, and get the previous row if the sample
+    /// starts a new one
+    fn add(&mut self, sample: &Sample) -> Option<Row> {
+        let column = self.columns.iter().position(|&pid| pid == sample.pid)?;
+        let &value = sample.values.first()?;
+        let finished = match &self.pending {
+            Some(row) if row.time != sample.time => self.pending.take(),
+            _ => None,
+        };
+        let row = self.pending.get_or_insert_with(|| Row {
+            time: sample.time,
+            values: vec![None; self.columns.len()],
+        });
+        row.values[column] = Some(value);
+        finished
+    }
+
+    /// Get the row that is still being filled
+    fn finish(&mut self) -> Option<Row> {
+        self.pending.take()
+    }
+}
+
+/// Name the column of `pid` with its name and unit, like `Engine speed (rpm)`
+fn column_name(pid: StandardPid, units: UnitSystem) -> String {
+    match units.pid_unit(pid) {
+        Some(unit) => format!("{} ({})", pid.name(), unit),
+        None => pid.name().to_string(),
+    }
+}
+
+/// Converts the [Instant]s of samples to the time of day
+pub(crate) struct Clock {
+    instant: Instant,
+    system: SystemTime,
+}
+
+impl Clock {
+    pub(crate) fn new() -> Self {
+        Clock {
+            instant: Instant::now(),
+            system: SystemTime::now(),
+        }
+    }
+
+    /// Get the seconds from the Unix epoch to `time`
+    pub(crate) fn unix_time(&self, time: Instant) -> f64 {
+        let time = match time.checked_duration_since(self.instant) {
+            Some(after) => self.system + after,
+            None => self.system - self.instant.duration_since(time),
+        };
+        time.duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64()
+    }
+}
+
+/// A file that is replaced by a new, numbered one once it is too big or too old
+struct RotatingFile {
+    path: PathBuf,
+    max_size: Option<u64>,
+    max_duration: Option<Duration>,
+    number: u32,
+    file: Option<BufWriter<File>>,
+    opened: Instant,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn new(path: PathBuf) -> Self {
+        RotatingFile {
+            path,
+            max_size: None,
+            max_duration: None,
+            number: 0,
+            file: None,
+            opened: Instant::now(),
+            written: 0,
+        }
+    }
+
+    /// Get the path of the current file, or of the first file if none is open yet
+    fn path(&self) -> PathBuf {
+        numbered_path(&self.path, self.number)
+    }
+
+    /// Write `bytes` of a row at `time`, starting a new file with `header` if there is no file
+    /// open or the current one is full
+    fn write(
+        &mut self,
+        time: Instant,
+        header: impl FnOnce() -> String,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let full = self.max_size.is_some_and(|max| self.written >= max)
+            || self
+                .max_duration
+                .is_some_and(|max| time.saturating_duration_since(self.opened) >= max);
+        if self.file.is_some() && full {
+            self.flush()?;
+            self.file = None;
+            self.number += 1;
+        }
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => {
+                let file = File::create(self.path()).map_err(crate::device::Error::from)?;
+                self.opened = time;
+                self.written = 0;
+                let file = self.file.insert(BufWriter::new(file));
+                let header = header();
+                file.write_all(header.as_bytes())
+                    .map_err(crate::device::Error::from)?;
+                self.written += header.len() as u64;
+                file
+            }
+        };
+        file.write_all(bytes).map_err(crate::device::Error::from)?;
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush().map_err(crate::device::Error::from)?;
+        }
+        Ok(())
+    }
+}
+
+/// Put `number` before the extension of `path`, unless it is 0
+fn numbered_path(path: &Path, number: u32) -> PathBuf {
+    if number == 0 {
+        return path.to_path_buf();
+    }
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", number));
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
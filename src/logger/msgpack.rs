@@ -0,0 +1,57 @@
+use std::io::Write;
+
+use crate::{Error, Result};
+
+/// Writes records as a stream of MessagePack values
+///
+/// Like `JsonLinesWriter`, but in the more compact MessagePack format, for uploading over slow
+/// or metered links. Structs are written as maps with their field names, so readers do not need to
+/// know the order of the fields. MessagePack values are self-delimiting, so they are written one
+/// after another, and read back by decoding values until the end of the stream.
+///
+/// # Example
+/// ```
+/// use obd2::{events::DrivingEvent, logger::MessagePackWriter};
+///
+/// let mut writer = MessagePackWriter::new(Vec::new());
+/// writer.write(&DrivingEvent::OverRev { rpm: 4800. }).unwrap();
+///
+/// let bytes = writer.into_inner();
+/// let event: DrivingEvent = rmp_serde::from_slice(&bytes).unwrap();
+/// assert_eq!(event, DrivingEvent::OverRev { rpm: 4800. });
+/// ```
+pub struct MessagePackWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> MessagePackWriter<W> {
+    /// Write records to `writer`
+    pub fn new(writer: W) -> Self {
+        MessagePackWriter { writer }
+    }
+
+    /// Write `record` as one MessagePack value
+    pub fn write<T: serde::Serialize + ?Sized>(&mut self, record: &T) -> Result<()> {
+        rmp_serde::encode::write_named(&mut self.writer, record).map_err(|e| Error::Output {
+            destination: "MessagePack record",
+            message: e.to_string(),
+        })?;
+        self.writer.flush().map_err(crate::device::Error::from)?;
+        Ok(())
+    }
+
+    /// Get the writer
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Get the writer mutably
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Get back the writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
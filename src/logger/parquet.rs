@@ -0,0 +1,216 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{mpsc::Receiver, Arc},
+};
+
+use arrow_array::{
+    builder::{ArrayBuilder, Float32Builder, TimestampMillisecondBuilder},
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use parquet::arrow::ArrowWriter;
+
+use super::{column_name, Clock, Row, Rows};
+use crate::{commands::pid::StandardPid, poller::Sample, units::UnitSystem, Error, Result};
+
+/// Writes polled values to a Parquet file, for loading into pandas, Polars or Spark
+///
+/// Rows are gathered into Arrow record batches of [batch_size](Self::with_batch_size) rows, and
+/// each batch is written to the file as it fills. The first column, `time`, is a UTC timestamp
+/// in milliseconds, followed by a nullable `f32` column for each PID, named like the columns of a
+/// [CsvLogger](super::CsvLogger). See the [module](super) documentation for how samples become
+/// rows.
+///
+/// A Parquet file cannot be read until its footer is written, so call [close](Self::close) when
+/// done; dropping the logger also closes it, but ignores errors.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::pid::StandardPid, device::Obd2Simulator, logger::ParquetLogger,
+///     poller::Obd2Poller,
+/// };
+/// use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+/// use std::{fs::File, time::Duration};
+///
+/// let pids = [StandardPid::EngineSpeed, StandardPid::VehicleSpeed];
+/// let poller = Obd2Poller::new(Obd2Simulator::new())
+///     .poll(pids[0], Duration::from_millis(10))
+///     .poll(pids[1], Duration::from_millis(10))
+///     .start();
+///
+/// let path = std::env::temp_dir().join("obd2-parquet-logger-example.parquet");
+/// let mut logger = ParquetLogger::new(&path, pids);
+/// for sample in poller.samples().iter().take(4) {
+///     logger.add_sample(&sample?)?;
+/// }
+/// logger.close()?;
+///
+/// let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+/// assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+/// assert_eq!(reader.schema().field(1).name(), "Engine speed (rpm)");
+/// # Ok::<(), obd2::Error>(())
+/// ```
+pub struct ParquetLogger {
+    rows: Rows,
+    path: PathBuf,
+    writer: Option<ArrowWriter<File>>,
+    clock: Clock,
+    units: UnitSystem,
+    batch_size: usize,
+    times: TimestampMillisecondBuilder,
+    values: Vec<Float32Builder>,
+}
+
+impl ParquetLogger {
+    /// Create a logger writing a column for each of `columns` to `path`
+    ///
+    /// The file is created when the first batch is written, overwriting any file at `path`.
+    pub fn new(path: impl AsRef<Path>, columns: impl IntoIterator<Item = StandardPid>) -> Self {
+        let columns: Vec<StandardPid> = columns.into_iter().collect();
+        ParquetLogger {
+            values: columns.iter().map(|_| Float32Builder::new()).collect(),
+            rows: Rows::new(columns),
+            path: path.as_ref().to_path_buf(),
+            writer: None,
+            clock: Clock::new(),
+            units: UnitSystem::Metric,
+            batch_size: 1024,
+            times: TimestampMillisecondBuilder::new().with_timezone("UTC"),
+        }
+    }
+
+    /// Write a batch every `rows` rows, by default 1024
+    pub fn with_batch_size(mut self, rows: usize) -> Self {
+        self.batch_size = rows.max(1);
+        self
+    }
+
+    /// Write the values in `units` instead of metric units
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Get the path of the file being written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Add a sample, adding the previous row to the batch if the sample was read at a different
+    /// time
+    ///
+    /// Samples of PIDs without a column are ignored.
+    pub fn add_sample(&mut self, sample: &Sample) -> Result<()> {
+        match self.rows.add(sample) {
+            Some(row) => self.add_row(&row),
+            None => Ok(()),
+        }
+    }
+
+    /// Add every sample from a poller's channel until the poller stops, then close the file
+    ///
+    /// Failed requests sent on the channel are skipped, so only errors writing the file stop
+    /// logging.
+    pub fn run(mut self, samples: &Receiver<Result<Sample>>) -> Result<()> {
+        for sample in samples.iter().flatten() {
+            self.add_sample(&sample)?;
+        }
+        self.close()
+    }
+
+    /// Write the rows so far to the file, as a row group of their own
+    ///
+    /// Small row groups make the file bigger and slower to read, so this is best left to
+    /// [close](Self::close) unless the rows must be saved now.
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(row) = self.rows.finish() {
+            self.add_row(&row)?;
+        }
+        self.write_batch()?;
+        if let Some(writer) = &mut self.writer {
+            writer.flush().map_err(parquet_error)?;
+        }
+        Ok(())
+    }
+
+    /// Write the remaining rows and the file's footer
+    pub fn close(mut self) -> Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(row) = self.rows.finish() {
+            self.add_row(&row)?;
+        }
+        self.write_batch()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close().map_err(parquet_error)?;
+        }
+        Ok(())
+    }
+
+    fn add_row(&mut self, row: &Row) -> Result<()> {
+        let millis = (self.clock.unix_time(row.time) * 1000.).round() as i64;
+        self.times.append_value(millis);
+        for ((&pid, value), builder) in self
+            .rows
+            .columns
+            .iter()
+            .zip(&row.values)
+            .zip(&mut self.values)
+        {
+            builder.append_option(value.map(|value| self.units.convert_pid(pid, value)));
+        }
+        if self.times.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn write_batch(&mut self) -> Result<()> {
+        if self.times.is_empty() {
+            return Ok(());
+        }
+        let mut fields = vec![Field::new(
+            "time",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        )];
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(self.times.finish())];
+        for (&pid, builder) in self.rows.columns.iter().zip(&mut self.values) {
+            let name = column_name(pid, self.units);
+            fields.push(Field::new(name, DataType::Float32, true));
+            columns.push(Arc::new(builder.finish()));
+        }
+        let batch =
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(parquet_error)?;
+
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let file = File::create(&self.path).map_err(crate::device::Error::from)?;
+                let writer =
+                    ArrowWriter::try_new(file, batch.schema(), None).map_err(parquet_error)?;
+                self.writer.insert(writer)
+            }
+        };
+        writer.write(&batch).map_err(parquet_error)
+    }
+}
+
+impl Drop for ParquetLogger {
+    /// Write the remaining rows and close the file, ignoring errors; call [close](Self::close)
+    /// to handle them
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+fn parquet_error(e: impl std::fmt::Display) -> Error {
+    Error::Output {
+        destination: "Parquet file",
+        message: e.to_string(),
+    }
+}
@@ -0,0 +1,295 @@
+//! Publishing values over MQTT
+//!
+//! An [MqttPublisher] sends polled values and changes to the DTCs to an MQTT broker, so that a
+//! computer in the vehicle can feed a home dashboard. Each PID is published to
+//! `<prefix>/<service>_<pid>_<name>`, like `obd2/01_0c_engine_speed`, as a bare number, and the
+//! DTCs to `<prefix>/dtcs` as JSON like `{"count":1,"codes":["P0301"]}`. With
+//! [publish_discovery](MqttPublisher::publish_discovery), Home Assistant finds the sensors by
+//! itself, with their names and units.
+//!
+//! # Example
+//! ```no_run
+//! use obd2::{
+//!     commands::pid::StandardPid,
+//!     device::Obd2Simulator,
+//!     mqtt::{MqttOptions, MqttPublisher},
+//!     poller::Obd2Poller,
+//! };
+//! use std::time::Duration;
+//!
+//! let pids = [StandardPid::EngineSpeed, StandardPid::VehicleSpeed];
+//! let poller = Obd2Poller::new(Obd2Simulator::new())
+//!     .poll(pids[0], Duration::from_secs(1))
+//!     .poll(pids[1], Duration::from_secs(1))
+//!     .start();
+//!
+//! let mut publisher = MqttPublisher::new(MqttOptions::new("my-car", "homeassistant.local", 1883));
+//! publisher.publish_discovery(&pids)?;
+//! publisher.run(poller.samples())?;
+//! # Ok::<(), obd2::Error>(())
+//! ```
+
+use log::warn;
+use serde_json::json;
+use std::{sync::mpsc::Receiver, thread, time::Duration};
+
+use rumqttc::Client;
+pub use rumqttc::{MqttOptions, QoS};
+
+use crate::{
+    commands::{pid::StandardPid, Dtc},
+    poller::Sample,
+    units::UnitSystem,
+    Error, Result,
+};
+
+/// Publishes values and DTCs to an MQTT broker
+///
+/// Values are published as they come, without being retained, and the DTCs and discovery
+/// messages are retained so that new subscribers see them.
+pub struct MqttPublisher {
+    client: Client,
+    device_id: String,
+    device_name: String,
+    prefix: String,
+    discovery_prefix: String,
+    units: UnitSystem,
+    qos: QoS,
+    dtcs: Option<Vec<Dtc>>,
+}
+
+impl MqttPublisher {
+    /// Connect to the broker in `options`, using its client ID to identify the vehicle
+    ///
+    /// The connection is kept up on a new thread, reconnecting after errors, until the publisher
+    /// is dropped.
+    pub fn new(options: MqttOptions) -> Self {
+        let device_id = options.client_id();
+        let (client, mut connection) = Client::new(options, 64);
+        thread::spawn(move || {
+            for event in connection.iter() {
+                if let Err(e) = event {
+                    warn!("MQTT connection error: {}", e);
+                    thread::sleep(Duration::from_secs(1));
+                }
+            }
+        });
+        Self::from_client(client, device_id)
+    }
+
+    /// Publish with `client`, identifying the vehicle by `device_id`
+    ///
+    /// The client's `Connection` must be polled elsewhere for the messages to be sent.
+    pub fn from_client(client: Client, device_id: impl Into<String>) -> Self {
+        let device_id = device_id.into();
+        MqttPublisher {
+            client,
+            device_name: device_id.clone(),
+            device_id,
+            prefix: "obd2".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+            units: UnitSystem::Metric,
+            qos: QoS::AtMostOnce,
+            dtcs: None,
+        }
+    }
+
+    /// Set the prefix of the topics values are published to, by default `obd2`
+    pub fn with_topic_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the prefix of Home Assistant's discovery topics, by default `homeassistant`
+    pub fn with_discovery_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.discovery_prefix = prefix.into();
+        self
+    }
+
+    /// Set the name of the vehicle shown in Home Assistant, by default its device ID
+    pub fn with_device_name(mut self, name: impl Into<String>) -> Self {
+        self.device_name = name.into();
+        self
+    }
+
+    /// Publish the values in `units` instead of metric units
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Set the quality of service messages are published with, by default at most once
+    pub fn with_qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Get the topic the values of `pid` are published to
+    pub fn topic(&self, pid: StandardPid) -> String {
+        format!("{}/{}", self.prefix, pid_slug(pid))
+    }
+
+    /// Publish `value` of `pid`, converted to the publisher's units
+    pub fn publish_value(&mut self, pid: StandardPid, value: f32) -> Result<()> {
+        let value = self.units.convert_pid(pid, value);
+        self.publish(self.topic(pid), false, value.to_string())
+    }
+
+    /// Publish the first ECU's value of `sample`
+    pub fn publish_sample(&mut self, sample: &Sample) -> Result<()> {
+        match sample.values.first() {
+            Some(&value) => self.publish_value(sample.pid, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Publish every sample from a poller's channel until the poller stops
+    ///
+    /// Failed requests sent on the channel are skipped.
+    pub fn run(&mut self, samples: &Receiver<Result<Sample>>) -> Result<()> {
+        for sample in samples.iter().flatten() {
+            self.publish_sample(&sample)?;
+        }
+        Ok(())
+    }
+
+    /// Publish `dtcs` if they are different from the last ones published, and get whether they
+    /// were
+    ///
+    /// The DTCs of every ECU, as read with
+    /// [get_dtcs](crate::commands::Obd2DataRetrieval::get_dtcs), can be passed every time they
+    /// are read, and only changes are sent.
+    pub fn publish_dtcs(&mut self, dtcs: &[Dtc]) -> Result<bool> {
+        let mut dtcs = dtcs.to_vec();
+        dtcs.sort();
+        dtcs.dedup();
+        if self.dtcs.as_ref() == Some(&dtcs) {
+            return Ok(false);
+        }
+        let codes: Vec<String> = dtcs.iter().map(|dtc| dtc.to_string()).collect();
+        let payload = json!({ "count": codes.len(), "codes": codes });
+        self.publish(self.dtcs_topic(), true, payload.to_string())?;
+        self.dtcs = Some(dtcs);
+        Ok(true)
+    }
+
+    /// Publish Home Assistant discovery messages for a sensor for each of `pids`, and one for
+    /// the number of DTCs, with the list of DTCs as its attributes
+    ///
+    /// PIDs that do not hold a single number are skipped.
+    pub fn publish_discovery(&mut self, pids: &[StandardPid]) -> Result<()> {
+        let device = json!({
+            "identifiers": [self.device_id],
+            "name": self.device_name,
+        });
+        for &pid in pids {
+            let Some(unit) = self.units.pid_unit(pid) else {
+                continue;
+            };
+            let unit = unit.replace('º', "°");
+            let mut config = json!({
+                "name": pid.name(),
+                "unique_id": format!("{}_{}", self.device_id, pid_slug(pid)),
+                "state_topic": self.topic(pid),
+                "state_class": "measurement",
+                "device": device,
+            });
+            if let Some(class) = device_class(&unit) {
+                config["device_class"] = class.into();
+            }
+            if unit != "count" {
+                config["unit_of_measurement"] = unit.into();
+            }
+            self.publish(
+                self.discovery_topic(&pid_slug(pid)),
+                true,
+                config.to_string(),
+            )?;
+        }
+
+        let config = json!({
+            "name": "Diagnostic trouble codes",
+            "unique_id": format!("{}_dtcs", self.device_id),
+            "state_topic": self.dtcs_topic(),
+            "value_template": "{{ value_json.count }}",
+            "json_attributes_topic": self.dtcs_topic(),
+            "icon": "mdi:engine-off",
+            "device": device,
+        });
+        self.publish(self.discovery_topic("dtcs"), true, config.to_string())
+    }
+
+    fn dtcs_topic(&self) -> String {
+        format!("{}/dtcs", self.prefix)
+    }
+
+    fn discovery_topic(&self, object_id: &str) -> String {
+        format!(
+            "{}/sensor/{}/{}/config",
+            self.discovery_prefix,
+            slug(&self.device_id),
+            object_id
+        )
+    }
+
+    fn publish(&mut self, topic: String, retain: bool, payload: String) -> Result<()> {
+        self.client
+            .publish(topic, self.qos, retain, payload)
+            .map_err(|e| Error::Output {
+                destination: "MQTT message",
+                message: e.to_string(),
+            })
+    }
+}
+
+/// Turn `name` into a topic level, like `engine_speed`
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('_') {
+            slug.push('_');
+        }
+    }
+    slug.trim_end_matches('_').to_string()
+}
+
+/// Turn `pid` into a topic level, like `01_0c_engine_speed`
+///
+/// The service and PID come first, since some PIDs share a name.
+fn pid_slug(pid: StandardPid) -> String {
+    format!("01_{:02x}_{}", pid.pid(), slug(pid.name()))
+}
+
+/// The Home Assistant device class of sensors in `unit`
+fn device_class(unit: &str) -> Option<&'static str> {
+    match unit {
+        "°C" | "°F" => Some("temperature"),
+        "km/h" | "mph" => Some("speed"),
+        "kPa" | "Pa" | "psi" => Some("pressure"),
+        "km" | "mi" => Some("distance"),
+        "s" | "min" => Some("duration"),
+        "V" => Some("voltage"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn topics_unique() {
+        let (client, _connection) = Client::new(MqttOptions::new("car", "localhost", 1883), 1);
+        let publisher = MqttPublisher::from_client(client, "car");
+        let pids: Vec<_> = (0..=0xFF).filter_map(StandardPid::from_pid).collect();
+        let topics: HashSet<_> = pids.iter().map(|&pid| publisher.topic(pid)).collect();
+        assert_eq!(topics.len(), pids.len());
+        assert_eq!(
+            publisher.topic(StandardPid::EngineSpeed),
+            "obd2/01_0c_engine_speed"
+        );
+    }
+}
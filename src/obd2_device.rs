@@ -1,4 +1,158 @@
-use crate::{Error, Result};
+use crate::{Error, Protocol, Result};
+use alloc::{boxed::Box, vec::Vec};
+
+/// The response from one ECU, with the address it was sent from
+///
+/// On CAN, the address is the identifier the ECU responds with: by convention `0x7E8` is the
+/// engine controller and `0x7E9` the transmission controller, or `0x18DAF110` and up with 29-bit
+/// identifiers. On other protocols it is the ECU's one byte source address, like `0x10` for the
+/// engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EcuResponse {
+    /// The address of the ECU that responded
+    pub address: u32,
+
+    /// The response data, with the echoed request bytes removed
+    pub data: Vec<u8>,
+}
+
+/// The responses of up to `ECUS` ECUs, of up to `LEN` bytes each, stored without allocating
+///
+/// Returned by [Obd2Device::obd_command_heapless].
+#[cfg(feature = "heapless")]
+pub type HeaplessResponses<const ECUS: usize, const LEN: usize> =
+    heapless::Vec<heapless::Vec<u8, LEN>, ECUS>;
+
+/// Request each of `pids` separately, joining the responses like
+/// [obd_multi_command](Obd2Device::obd_multi_command)
+///
+/// This is for protocols other than CAN, where a request has only one PID. ECUs respond in no
+/// fixed order, so each ECU's responses are joined by its address when the device reports it (see
+/// [obd_request_tagged](Obd2Device::obd_request_tagged)), or else by their order. PIDs the
+/// vehicle declines are left out, and if it declines every PID, the error for the last one is
+/// returned.
+pub(crate) fn request_each_pid<T: Obd2Device + ?Sized>(
+    device: &mut T,
+    mode: u8,
+    pids: &[u8],
+) -> Result<Vec<Vec<u8>>> {
+    let mut joined = JoinedResponses::default();
+    let mut tagged = true;
+    for &pid in pids {
+        let mut result = Err(Error::UnsupportedByDevice("reporting ECU addresses"));
+        if tagged {
+            result = device.obd_request_tagged(&[mode, pid]).map(by_address);
+            tagged = !matches!(result, Err(Error::UnsupportedByDevice(_)));
+        }
+        if !tagged {
+            result = device.obd_command(mode, pid).map(by_order);
+        }
+        joined.add(pid, result)?;
+    }
+    joined.finish()
+}
+
+/// Like [request_each_pid], for an [AsyncObd2Device]
+#[cfg(feature = "async")]
+pub(crate) async fn request_each_pid_async<T: AsyncObd2Device + ?Sized>(
+    device: &mut T,
+    mode: u8,
+    pids: &[u8],
+) -> Result<Vec<Vec<u8>>> {
+    let mut joined = JoinedResponses::default();
+    let mut tagged = true;
+    for &pid in pids {
+        let mut result = Err(Error::UnsupportedByDevice("reporting ECU addresses"));
+        if tagged {
+            result = device
+                .obd_request_tagged(&[mode, pid])
+                .await
+                .map(by_address);
+            tagged = !matches!(result, Err(Error::UnsupportedByDevice(_)));
+        }
+        if !tagged {
+            result = device.obd_command(mode, pid).await.map(by_order);
+        }
+        joined.add(pid, result)?;
+    }
+    joined.finish()
+}
+
+/// Each ECU's response, with the ECU's address if the device reports it
+type Responses = Vec<(Option<u32>, Vec<u8>)>;
+
+fn by_address(responses: Vec<EcuResponse>) -> Responses {
+    responses
+        .into_iter()
+        .map(|response| (Some(response.address), response.data))
+        .collect()
+}
+
+fn by_order(responses: Vec<Vec<u8>>) -> Responses {
+    responses.into_iter().map(|data| (None, data)).collect()
+}
+
+/// The responses to the requests for each PID of [request_each_pid], joined for each ECU
+#[derive(Default)]
+struct JoinedResponses {
+    responses: Responses,
+    /// The error for the last PID the vehicle declined
+    declined: Option<Error>,
+}
+
+impl JoinedResponses {
+    /// Add each ECU's response to `pid` after the PID in its joined response, matching the ECUs
+    /// by address, or by their order if the address is not known
+    fn add(&mut self, pid: u8, result: Result<Responses>) -> Result<()> {
+        let data = match result {
+            Ok(data) => data,
+            Err(e) if e.is_declined() => {
+                self.declined = Some(e);
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        for (i, (address, data)) in data.into_iter().enumerate() {
+            let position = match address {
+                Some(_) => self.responses.iter().position(|(a, _)| *a == address),
+                None => (i < self.responses.len()).then_some(i),
+            };
+            let (_, response) = match position {
+                Some(position) => &mut self.responses[position],
+                None => {
+                    self.responses.push((address, Vec::new()));
+                    self.responses.last_mut().unwrap()
+                }
+            };
+            response.push(pid);
+            response.extend(data);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Vec<Vec<u8>>> {
+        match self.declined {
+            Some(e) if self.responses.is_empty() => Err(e),
+            _ => Ok(self.responses.into_iter().map(|(_, data)| data).collect()),
+        }
+    }
+}
+
+/// Add each ECU's response to `pid` to its response in `responses`, after the PID
+///
+/// The responses are matched by their order, for devices that cannot see where they come from.
+#[cfg(any(feature = "std", feature = "web"))]
+pub(crate) fn join_pid_responses(responses: &mut Vec<Vec<u8>>, pid: u8, data: Vec<Vec<u8>>) {
+    for (i, data) in data.into_iter().enumerate() {
+        if i == responses.len() {
+            responses.push(Vec::new());
+        }
+        responses[i].push(pid);
+        responses[i].extend(data);
+    }
+}
 
 /// A higher-level API for using an OBD-II device
 pub trait Obd2Device {
@@ -16,6 +170,107 @@ pub trait Obd2Device {
     /// the mode the vehicle recieved---is validated and removed.
     fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>>;
 
+    /// Send an OBD-II request made of any number of bytes and get responses
+    ///
+    /// The first byte of `request` is the mode, and the rest are its parameters, like the PID and
+    /// frame number for a freeze frame request. The responses are a list with one element for each
+    /// ECU that responds, with the echoed request bytes (the mode with `0x40` added, then the
+    /// parameters the service echoes) validated and removed. UDS services only echo their
+    /// sub-function or identifier, not the data sent with it.
+    ///
+    /// The default implementation only supports requests of one or two bytes, which it sends with
+    /// [obd_mode_command](Self::obd_mode_command) or [obd_command](Self::obd_command).
+    fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        match *request {
+            [mode] => self.obd_mode_command(mode),
+            [mode, pid] => self.obd_command(mode, pid),
+            _ => Err(Error::UnsupportedByDevice("requests longer than two bytes")),
+        }
+    }
+
+    /// Read a manufacturer-specific data identifier (DID) with service 0x22 and get responses
+    ///
+    /// The responses are a list with one element for each ECU that supports `did`, with the
+    /// echoed service and identifier validated and removed. See
+    /// [ManufacturerPid](crate::commands::pid::ManufacturerPid) for decoding the data.
+    fn obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>> {
+        let [high, low] = did.to_be_bytes();
+        self.obd_request(&[0x22, high, low])
+    }
+
+    /// Send an OBD-II request made of any number of bytes and get each ECU's response with its
+    /// address
+    ///
+    /// Like [obd_request](Self::obd_request), but each response is tagged with the address of the
+    /// ECU that sent it, so that values and DTCs can be attributed to (for example) the engine or
+    /// transmission controller on vehicles with several ECUs.
+    ///
+    /// The default implementation returns an error, for devices that cannot see where responses
+    /// come from.
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{device::Obd2Simulator, Obd2Device};
+    ///
+    /// let mut device = Obd2Simulator::new();
+    ///
+    /// for response in device.obd_request_tagged(&[0x01, 0x0D]).unwrap() {
+    ///     assert_eq!(response.address, 0x7E8); // the engine controller
+    ///     assert_eq!(response.data, [0]); // stopped
+    /// }
+    /// ```
+    fn obd_request_tagged(&mut self, _request: &[u8]) -> Result<Vec<EcuResponse>> {
+        Err(Error::UnsupportedByDevice("reporting ECU addresses"))
+    }
+
+    /// Send an OBD-II request to one ECU and get its response
+    ///
+    /// Requests are normally broadcast to every ECU. This sends `request` to the ECU with the
+    /// physical request `address` instead, so only that ECU responds. On CAN, the engine
+    /// controller is usually `0x7E0` and the transmission controller `0x7E1`, which respond from
+    /// `0x7E8` and `0x7E9` (see [EcuResponse]). 29-bit identifiers like `0x18DA10F1` are also
+    /// accepted. The echoed request bytes are validated and removed.
+    ///
+    /// The default implementation returns an error, for devices that cannot address a single ECU.
+    fn obd_request_to(&mut self, _address: u32, _request: &[u8]) -> Result<Vec<u8>> {
+        Err(Error::UnsupportedByDevice("sending requests to one ECU"))
+    }
+
+    /// Send an OBD-II command with mode and PID to one ECU and get its response
+    ///
+    /// See [obd_request_to](Self::obd_request_to).
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{device::Obd2Simulator, Obd2Device};
+    ///
+    /// let mut device = Obd2Simulator::new();
+    ///
+    /// // the engine controller responds
+    /// assert_eq!(device.obd_command_to(0x7E0, 0x01, 0x0D).unwrap(), [0]);
+    /// // but there is no transmission controller
+    /// assert!(device.obd_command_to(0x7E1, 0x01, 0x0D).is_err());
+    /// ```
+    fn obd_command_to(&mut self, address: u32, mode: u8, pid: u8) -> Result<Vec<u8>> {
+        self.obd_request_to(address, &[mode, pid])
+    }
+
+    /// Send an OBD-II command with mode and several PIDs and get responses
+    ///
+    /// ISO 15765-4 (CAN) vehicles accept up to six PIDs in one request, which is much faster than
+    /// requesting them one at a time. The responses are a list with one element for each ECU that
+    /// responds, with the mode byte validated and removed, leaving each PID the ECU supports
+    /// followed by its data.
+    ///
+    /// The default implementation requests each PID separately with
+    /// [obd_request_tagged](Self::obd_request_tagged), or [obd_command](Self::obd_command) if the
+    /// device does not report addresses, and joins each ECU's responses in the same format. PIDs
+    /// the vehicle declines (see [Error::is_declined]) are left out, unless it declines all of
+    /// them.
+    fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        request_each_pid(self, mode, pids)
+    }
+
     /// Send command and get list of OBD-II responses as an array
     ///
     /// Like [obd_command](Self::obd_command), but each ECU's response (after removing the first
@@ -52,8 +307,348 @@ pub trait Obd2Device {
     ) -> Result<[[u8; RESPONSE_LENGTH]; RESPONSE_COUNT]> {
         let result = self.obd_command_len::<RESPONSE_LENGTH>(mode, pid)?;
         let count = result.len();
-        result
-            .try_into()
-            .map_err(|_| Error::IncorrectResponseLength("count", RESPONSE_COUNT, count))
+        result.try_into().map_err(|_| Error::UnexpectedEcuCount {
+            expected: RESPONSE_COUNT,
+            got: count,
+        })
+    }
+
+    /// Send command and get the responses in fixed-capacity `heapless` vectors
+    ///
+    /// Like [obd_command](Self::obd_command), but each ECU's response is copied into a vector of up
+    /// to `LEN` bytes, in a list of up to `ECUS` responses. On embedded targets these can be kept
+    /// in static memory or passed through `heapless` queues without allocating. If a response is
+    /// longer, or more ECUs respond, an error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{device::MockObd2Device, Obd2Device};
+    ///
+    /// let mut device = MockObd2Device::new().with_response(0x01, 0x0C, [vec![0x0F, 0xA0]]);
+    ///
+    /// let responses = device.obd_command_heapless::<2, 4>(0x01, 0x0C).unwrap();
+    /// assert_eq!(responses[0], [0x0F, 0xA0]);
+    /// ```
+    #[cfg(feature = "heapless")]
+    fn obd_command_heapless<const ECUS: usize, const LEN: usize>(
+        &mut self,
+        mode: u8,
+        pid: u8,
+    ) -> Result<HeaplessResponses<ECUS, LEN>> {
+        let responses = self.obd_command(mode, pid)?;
+        let mut fixed = HeaplessResponses::new();
+        for response in &responses {
+            let data = heapless::Vec::from_slice(response)
+                .map_err(|_| Error::IncorrectResponseLength("length", LEN, response.len()))?;
+            fixed.push(data).map_err(|_| Error::UnexpectedEcuCount {
+                expected: ECUS,
+                got: responses.len(),
+            })?;
+        }
+        Ok(fixed)
+    }
+
+    /// Get the protocol used to communicate with the vehicle
+    ///
+    /// The default implementation returns an error, for devices that cannot tell which protocol
+    /// is used.
+    fn protocol(&mut self) -> Result<Protocol> {
+        Err(Error::UnsupportedByDevice("reporting its protocol"))
+    }
+
+    /// Get the voltage of the vehicle's battery in volts, as measured by the adapter
+    ///
+    /// Adapters measure the supply pin of the OBD-II port, which works with the engine and
+    /// ignition off, so this can check that the adapter is plugged in and monitor the 12 V battery
+    /// of a parked vehicle. With the engine running it shows the charging voltage instead.
+    ///
+    /// The default implementation reads the control module voltage (service 1 PID 42) from the
+    /// first ECU that responds, for devices that cannot measure the voltage themselves.
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::{device::Obd2Simulator, Obd2Device};
+    ///
+    /// let mut device = Obd2Simulator::new();
+    /// // the simulated engine is running, so the alternator is charging the battery
+    /// assert!(device.get_adapter_voltage().unwrap() > 13.);
+    /// ```
+    fn get_adapter_voltage(&mut self) -> Result<f32> {
+        let responses = self.obd_command_len::<2>(0x01, 0x42)?;
+        let voltage = responses.first().ok_or(Error::NoData)?;
+        Ok(f32::from(u16::from_be_bytes(*voltage)) / 1000.)
+    }
+
+    /// Put the device in a [Box], to hold it as a `dyn` [Obd2DeviceDyn]
+    fn boxed<'a>(self) -> Box<dyn Obd2DeviceDyn + Send + 'a>
+    where
+        Self: Sized + Send + 'a,
+    {
+        Box::new(self)
+    }
+}
+
+/// An object-safe version of [Obd2Device], for holding any device as a trait object
+///
+/// [Obd2Device] has methods with const generics, so `dyn Obd2Device` is not possible. Every
+/// [Obd2Device] implements this trait, and `Box<dyn Obd2DeviceDyn>` and `&mut dyn Obd2DeviceDyn`
+/// implement [Obd2Device], so an application can choose between a serial, Bluetooth or TCP adapter
+/// at runtime, keep it in its state, and still use every command. The methods have a `dyn_` prefix
+/// so that calls to [Obd2Device] methods stay unambiguous with both traits imported.
+///
+/// # Example
+/// ```
+/// use obd2::{
+///     commands::Obd2DataRetrieval,
+///     device::{MockObd2Device, Obd2Simulator},
+///     Obd2Device, Obd2DeviceDyn,
+/// };
+///
+/// let simulated = true;
+/// let mut device: Box<dyn Obd2DeviceDyn + Send> = if simulated {
+///     Obd2Simulator::new().boxed()
+/// } else {
+///     MockObd2Device::new().boxed()
+/// };
+/// assert!(device.get_rpm().unwrap()[0] > 0.);
+/// ```
+pub trait Obd2DeviceDyn {
+    /// See [Obd2Device::obd_command]
+    fn dyn_obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>>;
+
+    /// See [Obd2Device::obd_mode_command]
+    fn dyn_obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>>;
+
+    /// See [Obd2Device::obd_request]
+    fn dyn_obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>>;
+
+    /// See [Obd2Device::obd_command_22]
+    fn dyn_obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>>;
+
+    /// See [Obd2Device::obd_request_tagged]
+    fn dyn_obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>>;
+
+    /// See [Obd2Device::obd_request_to]
+    fn dyn_obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>>;
+
+    /// See [Obd2Device::obd_command_to]
+    fn dyn_obd_command_to(&mut self, address: u32, mode: u8, pid: u8) -> Result<Vec<u8>>;
+
+    /// See [Obd2Device::obd_multi_command]
+    fn dyn_obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>>;
+
+    /// See [Obd2Device::protocol]
+    fn dyn_protocol(&mut self) -> Result<Protocol>;
+
+    /// See [Obd2Device::get_adapter_voltage]
+    fn dyn_get_adapter_voltage(&mut self) -> Result<f32>;
+}
+
+impl<T: Obd2Device + ?Sized> Obd2DeviceDyn for T {
+    fn dyn_obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+        self.obd_command(mode, pid)
+    }
+
+    fn dyn_obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+        self.obd_mode_command(mode)
+    }
+
+    fn dyn_obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.obd_request(request)
+    }
+
+    fn dyn_obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>> {
+        self.obd_command_22(did)
+    }
+
+    fn dyn_obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+        self.obd_request_tagged(request)
+    }
+
+    fn dyn_obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+        self.obd_request_to(address, request)
+    }
+
+    fn dyn_obd_command_to(&mut self, address: u32, mode: u8, pid: u8) -> Result<Vec<u8>> {
+        self.obd_command_to(address, mode, pid)
+    }
+
+    fn dyn_obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+        self.obd_multi_command(mode, pids)
+    }
+
+    fn dyn_protocol(&mut self) -> Result<Protocol> {
+        self.protocol()
+    }
+
+    fn dyn_get_adapter_voltage(&mut self) -> Result<f32> {
+        self.get_adapter_voltage()
+    }
+}
+
+/// Forward every method of [Obd2Device] to an [Obd2DeviceDyn] behind a pointer
+macro_rules! impl_obd2_device_for_pointer {
+    ($($pointer:ty),*) => {$(
+        impl<D: Obd2DeviceDyn + ?Sized> Obd2Device for $pointer {
+            fn obd_command(&mut self, mode: u8, pid: u8) -> Result<Vec<Vec<u8>>> {
+                (**self).dyn_obd_command(mode, pid)
+            }
+
+            fn obd_mode_command(&mut self, mode: u8) -> Result<Vec<Vec<u8>>> {
+                (**self).dyn_obd_mode_command(mode)
+            }
+
+            fn obd_request(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+                (**self).dyn_obd_request(request)
+            }
+
+            fn obd_command_22(&mut self, did: u16) -> Result<Vec<Vec<u8>>> {
+                (**self).dyn_obd_command_22(did)
+            }
+
+            fn obd_request_tagged(&mut self, request: &[u8]) -> Result<Vec<EcuResponse>> {
+                (**self).dyn_obd_request_tagged(request)
+            }
+
+            fn obd_request_to(&mut self, address: u32, request: &[u8]) -> Result<Vec<u8>> {
+                (**self).dyn_obd_request_to(address, request)
+            }
+
+            fn obd_command_to(&mut self, address: u32, mode: u8, pid: u8) -> Result<Vec<u8>> {
+                (**self).dyn_obd_command_to(address, mode, pid)
+            }
+
+            fn obd_multi_command(&mut self, mode: u8, pids: &[u8]) -> Result<Vec<Vec<u8>>> {
+                (**self).dyn_obd_multi_command(mode, pids)
+            }
+
+            fn protocol(&mut self) -> Result<Protocol> {
+                (**self).dyn_protocol()
+            }
+
+            fn get_adapter_voltage(&mut self) -> Result<f32> {
+                (**self).dyn_get_adapter_voltage()
+            }
+        }
+    )*};
+}
+
+impl_obd2_device_for_pointer!(Box<D>, &mut D);
+
+/// A higher-level API for using an OBD-II device asynchronously
+///
+/// The asynchronous version of [Obd2Device], for applications that should not block a thread
+/// while waiting for the vehicle to respond.
+#[cfg(feature = "async")]
+pub trait AsyncObd2Device: Send {
+    /// Send an OBD-II command with mode and PID and get responses
+    ///
+    /// See [Obd2Device::obd_command].
+    fn obd_command(
+        &mut self,
+        mode: u8,
+        pid: u8,
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<u8>>>> + Send;
+
+    /// Send an OBD-II command with only mode and get responses
+    ///
+    /// See [Obd2Device::obd_mode_command].
+    fn obd_mode_command(
+        &mut self,
+        mode: u8,
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<u8>>>> + Send;
+
+    /// Send an OBD-II command with mode and several PIDs and get responses
+    ///
+    /// See [Obd2Device::obd_multi_command].
+    fn obd_multi_command(
+        &mut self,
+        mode: u8,
+        pids: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<u8>>>> + Send {
+        request_each_pid_async(self, mode, pids)
+    }
+
+    /// Send an OBD-II request made of any number of bytes and get responses
+    ///
+    /// See [Obd2Device::obd_request].
+    fn obd_request(
+        &mut self,
+        request: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<u8>>>> + Send {
+        async move {
+            match *request {
+                [mode] => self.obd_mode_command(mode).await,
+                [mode, pid] => self.obd_command(mode, pid).await,
+                _ => Err(Error::UnsupportedByDevice("requests longer than two bytes")),
+            }
+        }
+    }
+
+    /// Read a manufacturer-specific data identifier (DID) with service 0x22 and get responses
+    ///
+    /// See [Obd2Device::obd_command_22].
+    fn obd_command_22(
+        &mut self,
+        did: u16,
+    ) -> impl std::future::Future<Output = Result<Vec<Vec<u8>>>> + Send {
+        async move {
+            let [high, low] = did.to_be_bytes();
+            self.obd_request(&[0x22, high, low]).await
+        }
+    }
+
+    /// Send an OBD-II request to one ECU and get its response
+    ///
+    /// See [Obd2Device::obd_request_to].
+    fn obd_request_to(
+        &mut self,
+        _address: u32,
+        _request: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<u8>>> + Send {
+        async move { Err(Error::UnsupportedByDevice("sending requests to one ECU")) }
+    }
+
+    /// Send an OBD-II command with mode and PID to one ECU and get its response
+    ///
+    /// See [Obd2Device::obd_command_to].
+    fn obd_command_to(
+        &mut self,
+        address: u32,
+        mode: u8,
+        pid: u8,
+    ) -> impl std::future::Future<Output = Result<Vec<u8>>> + Send {
+        async move { self.obd_request_to(address, &[mode, pid]).await }
+    }
+
+    /// Send an OBD-II request and get each ECU's response with its address
+    ///
+    /// See [Obd2Device::obd_request_tagged].
+    fn obd_request_tagged(
+        &mut self,
+        _request: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<EcuResponse>>> + Send {
+        async move { Err(Error::UnsupportedByDevice("reporting ECU addresses")) }
+    }
+
+    /// Get the protocol used to communicate with the vehicle
+    ///
+    /// See [Obd2Device::protocol].
+    fn protocol(&mut self) -> impl std::future::Future<Output = Result<Protocol>> + Send {
+        async move { Err(Error::UnsupportedByDevice("reporting its protocol")) }
+    }
+
+    /// Get the voltage of the vehicle's battery in volts, as measured by the adapter
+    ///
+    /// See [Obd2Device::get_adapter_voltage].
+    fn get_adapter_voltage(&mut self) -> impl std::future::Future<Output = Result<f32>> + Send {
+        async move {
+            let responses = self.obd_command(0x01, 0x42).await?;
+            let response = responses.first().ok_or(Error::NoData)?;
+            let voltage: [u8; 2] = response.as_slice().try_into().map_err(|_| {
+                Error::IncorrectResponseLength("control module voltage", 2, response.len())
+            })?;
+            Ok(f32::from(u16::from_be_bytes(voltage)) / 1000.)
+        }
     }
 }
@@ -0,0 +1,373 @@
+//! Polling PIDs in the background
+//!
+//! Dashboards and loggers usually read some PIDs often (like the engine speed) and others rarely
+//! (like the coolant temperature). An [Obd2Poller] takes ownership of a device and reads each PID
+//! at its own interval on a separate thread, sending the values back over a channel, or keeping
+//! only the latest value of each PID in an [Obd2Cache] that can be read at any time. The cache can
+//! also keep an estimate of the gear, with a [GearEstimator].
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    commands::{pid::StandardPid, Obd2DataRetrieval},
+    gear::GearEstimator,
+    Obd2Device, Result,
+};
+
+/// A value read by an [Obd2Poller]
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Sample {
+    /// The PID that was read
+    pub pid: StandardPid,
+
+    /// The decoded value from each ECU that responded
+    pub values: Vec<f32>,
+
+    /// When the value was received
+    pub time: Instant,
+}
+
+/// Configures which PIDs to poll, and how often
+///
+/// PIDs that are due at the same time are requested together with
+/// [get_snapshot](crate::commands::Obd2DataRetrieval::get_snapshot), so only PIDs that hold a
+/// single number produce samples. A PID's next read is scheduled from when it was last read, so a
+/// slow vehicle lowers the rate rather than building up a backlog.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::pid::StandardPid, device::Obd2Simulator, poller::Obd2Poller};
+/// use std::time::Duration;
+///
+/// let poller = Obd2Poller::new(Obd2Simulator::new())
+///     .poll(StandardPid::EngineSpeed, Duration::from_millis(100))
+///     .poll(StandardPid::EngineCoolantTemperature, Duration::from_secs(5))
+///     .start();
+///
+/// let sample = poller.samples().recv().unwrap().unwrap();
+/// assert!(matches!(
+///     sample.pid,
+///     StandardPid::EngineSpeed | StandardPid::EngineCoolantTemperature
+/// ));
+///
+/// // stop polling and get the device back
+/// let device = poller.stop();
+/// ```
+pub struct Obd2Poller<T: Obd2Device> {
+    device: T,
+    schedule: Vec<(StandardPid, Duration)>,
+    gear: Option<GearEstimator>,
+}
+
+impl<T: Obd2Device + Send + 'static> Obd2Poller<T> {
+    /// Create a poller for `device`, with no PIDs to poll yet
+    pub fn new(device: T) -> Self {
+        Obd2Poller {
+            device,
+            schedule: Vec::new(),
+            gear: None,
+        }
+    }
+
+    /// Poll `pid` every `interval`
+    pub fn poll(mut self, pid: StandardPid, interval: Duration) -> Self {
+        self.schedule.push((pid, interval));
+        self
+    }
+
+    /// Estimate the gear with `estimator`, for [Obd2Cache::get_estimated_gear]
+    ///
+    /// The engine speed and vehicle speed must be polled, at the same interval so that they are
+    /// read together. A learning estimator learns from the values as they are polled.
+    pub fn with_gear_estimator(mut self, estimator: GearEstimator) -> Self {
+        self.gear = Some(estimator);
+        self
+    }
+
+    /// Start polling on a new thread
+    pub fn start(self) -> PollerHandle<T> {
+        let (sender, samples) = mpsc::channel();
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            run(self.device, self.schedule, stopped, |sample| {
+                sender.send(sample).is_ok()
+            })
+        });
+        PollerHandle {
+            thread,
+            samples,
+            stop,
+        }
+    }
+
+    /// Start polling on a new thread, keeping the latest value of each PID in an [Obd2Cache]
+    pub fn start_cache(self) -> Obd2Cache<T> {
+        let store = Arc::new(Store {
+            start: Instant::now(),
+            slots: self
+                .schedule
+                .iter()
+                .map(|&(pid, _)| (pid, AtomicU64::new(0)))
+                .collect(),
+            errors: AtomicU32::new(0),
+            gear: AtomicU8::new(0),
+        });
+        let (stop, stopped) = mpsc::channel();
+        let writer = Arc::clone(&store);
+        let mut gear = self.gear;
+        let thread = thread::spawn(move || {
+            run(self.device, self.schedule, stopped, |sample| {
+                if let (Some(estimator), Ok(sample)) = (&mut gear, &sample) {
+                    let estimate = estimator.add_sample(sample).unwrap_or(0);
+                    writer.gear.store(estimate, Ordering::Relaxed);
+                }
+                writer.store(sample);
+                true
+            })
+        });
+        Obd2Cache {
+            reader: CacheReader { store },
+            thread,
+            stop,
+        }
+    }
+}
+
+/// A running [Obd2Poller]
+///
+/// Polling stops when this is dropped, or when [stop](Self::stop) is called to get the device
+/// back.
+pub struct PollerHandle<T> {
+    thread: JoinHandle<T>,
+    samples: Receiver<Result<Sample>>,
+    stop: Sender<()>,
+}
+
+impl<T> PollerHandle<T> {
+    /// Get the channel that samples are sent to
+    ///
+    /// If a request fails, its error is sent instead, and polling continues.
+    pub fn samples(&self) -> &Receiver<Result<Sample>> {
+        &self.samples
+    }
+
+    /// Stop polling, and get back the device once the current request has finished
+    pub fn stop(self) -> T {
+        let _ = self.stop.send(());
+        self.thread
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}
+
+/// The latest value of a PID in an [Obd2Cache]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct CachedValue {
+    /// The decoded value from the first ECU that responded
+    pub value: f32,
+
+    /// When the value was received, to the millisecond
+    pub time: Instant,
+}
+
+impl CachedValue {
+    /// How long ago the value was received
+    pub fn age(&self) -> Duration {
+        self.time.elapsed()
+    }
+}
+
+/// A running [Obd2Poller] that keeps the latest value of each PID
+///
+/// Reading a value never blocks, even while a request is in progress, so it can be done from a
+/// GUI's render loop. Each value is stored in a single atomic, with no locks between the polling
+/// thread and the readers. Use [reader](Self::reader) to read from other threads.
+///
+/// Polling stops when this is dropped, or when [stop](Self::stop) is called to get the device
+/// back.
+///
+/// # Example
+/// ```
+/// use obd2::{commands::pid::StandardPid, device::Obd2Simulator, poller::Obd2Poller};
+/// use std::time::Duration;
+///
+/// let cache = Obd2Poller::new(Obd2Simulator::new())
+///     .poll(StandardPid::EngineSpeed, Duration::from_millis(10))
+///     .start_cache();
+///
+/// // in a render loop, draw whatever value is there
+/// while cache.latest(StandardPid::EngineSpeed).is_none() {
+///     std::thread::sleep(Duration::from_millis(1));
+/// }
+/// let rpm = cache.latest(StandardPid::EngineSpeed).unwrap();
+/// assert!(rpm.value > 0. && rpm.age() < Duration::from_secs(1));
+///
+/// // PIDs that are not polled have no value
+/// assert_eq!(cache.latest(StandardPid::VehicleSpeed), None);
+/// let device = cache.stop();
+/// ```
+pub struct Obd2Cache<T> {
+    reader: CacheReader,
+    thread: JoinHandle<T>,
+    stop: Sender<()>,
+}
+
+impl<T> Obd2Cache<T> {
+    /// Get the latest value of `pid`, or `None` if it is not polled or has not been read yet
+    pub fn latest(&self, pid: StandardPid) -> Option<CachedValue> {
+        self.reader.latest(pid)
+    }
+
+    /// Get how many requests have failed
+    pub fn errors(&self) -> u32 {
+        self.reader.errors()
+    }
+
+    /// Get the gear estimated from the latest engine speed and vehicle speed, or `None` without a
+    /// [GearEstimator] or when no gear matches
+    pub fn get_estimated_gear(&self) -> Option<u8> {
+        self.reader.get_estimated_gear()
+    }
+
+    /// Get a handle for reading the values from other threads
+    pub fn reader(&self) -> CacheReader {
+        self.reader.clone()
+    }
+
+    /// Stop polling, and get back the device once the current request has finished
+    pub fn stop(self) -> T {
+        let _ = self.stop.send(());
+        self.thread
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}
+
+/// Reads the values of an [Obd2Cache] from any thread
+///
+/// Readers keep working after the cache is stopped, returning the last values that were read.
+#[derive(Clone)]
+pub struct CacheReader {
+    store: Arc<Store>,
+}
+
+impl CacheReader {
+    /// Get the latest value of `pid`, or `None` if it is not polled or has not been read yet
+    pub fn latest(&self, pid: StandardPid) -> Option<CachedValue> {
+        let (_, slot) = self.store.slots.iter().find(|(p, _)| *p == pid)?;
+        match slot.load(Ordering::Acquire) {
+            0 => None,
+            packed => Some(CachedValue {
+                value: f32::from_bits((packed >> 32) as u32),
+                time: self.store.start + Duration::from_millis((packed as u32 - 1).into()),
+            }),
+        }
+    }
+
+    /// Get how many requests have failed
+    pub fn errors(&self) -> u32 {
+        self.store.errors.load(Ordering::Relaxed)
+    }
+
+    /// Get the gear estimated from the latest engine speed and vehicle speed, or `None` without a
+    /// [GearEstimator] or when no gear matches
+    pub fn get_estimated_gear(&self) -> Option<u8> {
+        match self.store.gear.load(Ordering::Relaxed) {
+            0 => None,
+            gear => Some(gear),
+        }
+    }
+}
+
+/// The values shared between the polling thread and the readers
+///
+/// Each slot holds the bits of the value in its upper half, and in the lower half one more than
+/// the milliseconds from `start` to when it was received, so that 0 means no value yet. The gear
+/// is 0 when it is not known.
+struct Store {
+    start: Instant,
+    slots: Vec<(StandardPid, AtomicU64)>,
+    errors: AtomicU32,
+    gear: AtomicU8,
+}
+
+impl Store {
+    fn store(&self, sample: Result<Sample>) {
+        let sample = match sample {
+            Ok(sample) => sample,
+            Err(_) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        };
+        let Some(&value) = sample.values.first() else {
+            return;
+        };
+        let millis = sample.time.duration_since(self.start).as_millis();
+        let time = u32::try_from(millis).unwrap_or(u32::MAX - 1) + 1;
+        let packed = u64::from(value.to_bits()) << 32 | u64::from(time);
+        for (_, slot) in self.slots.iter().filter(|(pid, _)| *pid == sample.pid) {
+            slot.store(packed, Ordering::Release);
+        }
+    }
+}
+
+/// Poll the PIDs in `schedule` until `stopped` receives or `publish` returns false
+fn run<T: Obd2Device>(
+    mut device: T,
+    schedule: Vec<(StandardPid, Duration)>,
+    stopped: Receiver<()>,
+    mut publish: impl FnMut(Result<Sample>) -> bool,
+) -> T {
+    let mut due = vec![Instant::now(); schedule.len()];
+    loop {
+        let now = Instant::now();
+        let pids: Vec<StandardPid> = schedule
+            .iter()
+            .zip(&due)
+            .filter(|(_, &due)| due <= now)
+            .map(|(&(pid, _), _)| pid)
+            .collect();
+
+        if !pids.is_empty() {
+            let result = device.get_snapshot(&pids);
+            let time = Instant::now();
+            for ((_, interval), due) in schedule.iter().zip(&mut due) {
+                if *due <= now {
+                    *due = time + *interval;
+                }
+            }
+
+            let sent = match result {
+                Ok(mut snapshot) => pids
+                    .iter()
+                    .filter_map(|pid| snapshot.remove_entry(pid))
+                    .all(|(pid, values)| publish(Ok(Sample { pid, values, time }))),
+                Err(e) => publish(Err(e)),
+            };
+            if !sent {
+                // the handle was dropped
+                break;
+            }
+        }
+
+        let stop = match due.iter().min() {
+            Some(next) => stopped.recv_timeout(next.saturating_duration_since(Instant::now())),
+            None => stopped.recv().map_err(RecvTimeoutError::from),
+        };
+        if !matches!(stop, Err(RecvTimeoutError::Timeout)) {
+            break;
+        }
+    }
+    device
+}
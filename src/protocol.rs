@@ -0,0 +1,112 @@
+use core::fmt;
+
+/// A protocol used to communicate with the vehicle
+///
+/// The number the ELM327 uses for each protocol, with `ATSP` to select it and `ATDPN` to report
+/// it, is given in brackets.
+///
+/// # Example
+/// ```
+/// use obd2::{device::Obd2Simulator, Obd2Device, Protocol};
+///
+/// let mut device = Obd2Simulator::new();
+/// let protocol = device.protocol().unwrap();
+/// assert_eq!(protocol, Protocol::Can11Bit500);
+/// assert_eq!(protocol.to_string(), "ISO 15765-4 CAN (11 bit ID, 500 kbaud)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Protocol {
+    /// SAE J1850 PWM at 41.6 kbaud, used by older Ford vehicles (1)
+    J1850Pwm,
+    /// SAE J1850 VPW at 10.4 kbaud, used by older GM vehicles (2)
+    J1850Vpw,
+    /// ISO 9141-2, a K-line protocol used by older European and Asian vehicles (3)
+    Iso9141_2,
+    /// ISO 14230-4 (KWP2000) on the K-line, started with a slow (5 baud) init (4)
+    Kwp2000SlowInit,
+    /// ISO 14230-4 (KWP2000) on the K-line, started with a fast init (5)
+    Kwp2000FastInit,
+    /// ISO 15765-4 CAN with 11-bit identifiers at 500 kbit/s, used by most vehicles since 2008 (6)
+    Can11Bit500,
+    /// ISO 15765-4 CAN with 29-bit identifiers at 500 kbit/s (7)
+    Can29Bit500,
+    /// ISO 15765-4 CAN with 11-bit identifiers at 250 kbit/s (8)
+    Can11Bit250,
+    /// ISO 15765-4 CAN with 29-bit identifiers at 250 kbit/s (9)
+    Can29Bit250,
+    /// SAE J1939 CAN with 29-bit identifiers at 250 kbit/s, used by heavy-duty vehicles (A)
+    J1939,
+    /// Diagnostics over IP (ISO 13400), which the ELM327 does not support
+    DoIp,
+    /// Another protocol with its ELM327 number, like the user defined CAN protocols (B and C)
+    Other(u8),
+}
+
+impl Protocol {
+    /// Get the protocol with the ELM327 protocol `number`, or `None` for 0 (automatic)
+    #[cfg(any(feature = "std", feature = "embedded", feature = "web"))]
+    pub(crate) fn from_elm327(number: u8) -> Option<Self> {
+        Some(match number {
+            0 => return None,
+            1 => Self::J1850Pwm,
+            2 => Self::J1850Vpw,
+            3 => Self::Iso9141_2,
+            4 => Self::Kwp2000SlowInit,
+            5 => Self::Kwp2000FastInit,
+            6 => Self::Can11Bit500,
+            7 => Self::Can29Bit500,
+            8 => Self::Can11Bit250,
+            9 => Self::Can29Bit250,
+            0xA => Self::J1939,
+            number => Self::Other(number),
+        })
+    }
+
+    /// Get the ELM327 number of the protocol, if the ELM327 supports it
+    #[cfg(any(feature = "std", feature = "embedded", feature = "web"))]
+    pub(crate) fn elm327_number(self) -> Option<u8> {
+        match self {
+            Self::J1850Pwm => Some(1),
+            Self::J1850Vpw => Some(2),
+            Self::Iso9141_2 => Some(3),
+            Self::Kwp2000SlowInit => Some(4),
+            Self::Kwp2000FastInit => Some(5),
+            Self::Can11Bit500 => Some(6),
+            Self::Can29Bit500 => Some(7),
+            Self::Can11Bit250 => Some(8),
+            Self::Can29Bit250 => Some(9),
+            Self::J1939 => Some(0xA),
+            Self::DoIp => None,
+            Self::Other(number) => Some(number),
+        }
+    }
+
+    /// Whether the protocol is one of the ISO 15765-4 CAN protocols
+    pub fn is_can(self) -> bool {
+        matches!(
+            self,
+            Self::Can11Bit500 | Self::Can29Bit500 | Self::Can11Bit250 | Self::Can29Bit250
+        )
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::J1850Pwm => write!(f, "SAE J1850 PWM (41.6 kbaud)"),
+            Self::J1850Vpw => write!(f, "SAE J1850 VPW (10.4 kbaud)"),
+            Self::Iso9141_2 => write!(f, "ISO 9141-2 (5 baud init, 10.4 kbaud)"),
+            Self::Kwp2000SlowInit => write!(f, "ISO 14230-4 KWP (5 baud init, 10.4 kbaud)"),
+            Self::Kwp2000FastInit => write!(f, "ISO 14230-4 KWP (fast init, 10.4 kbaud)"),
+            Self::Can11Bit500 => write!(f, "ISO 15765-4 CAN (11 bit ID, 500 kbaud)"),
+            Self::Can29Bit500 => write!(f, "ISO 15765-4 CAN (29 bit ID, 500 kbaud)"),
+            Self::Can11Bit250 => write!(f, "ISO 15765-4 CAN (11 bit ID, 250 kbaud)"),
+            Self::Can29Bit250 => write!(f, "ISO 15765-4 CAN (29 bit ID, 250 kbaud)"),
+            Self::J1939 => write!(f, "SAE J1939 CAN (29 bit ID, 250 kbaud)"),
+            Self::DoIp => write!(f, "ISO 13400 DoIP"),
+            Self::Other(number) => write!(f, "protocol {:X}", number),
+        }
+    }
+}
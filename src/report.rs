@@ -0,0 +1,423 @@
+//! Full-vehicle diagnostic reports
+//!
+//! Before buying a used vehicle, or when it comes into a shop, a scan tool is used to read
+//! everything the ECUs report about emissions faults. [generate_report] reads the VIN, software
+//! calibrations, readiness monitors, DTCs, freeze frames and on-board monitor test results into a
+//! [DiagnosticReport], which can be saved with `serde` or rendered as Markdown or HTML.
+//!
+//! Sections the vehicle does not support are left empty rather than failing the report, so older
+//! vehicles still get one.
+//!
+//! # Example
+//! ```
+//! use obd2::{commands::Dtc, device::Obd2Simulator, report::generate_report};
+//!
+//! let mut device = Obd2Simulator::new().with_dtcs([Dtc::Powertrain(0x0301)]);
+//!
+//! let report = generate_report(&mut device)?;
+//! assert_eq!(report.vin.as_deref(), Some("1HGCM82633A004352"));
+//! assert!(report.mil_on());
+//! assert_eq!(report.dtcs, [Dtc::Powertrain(0x0301)]);
+//! assert_eq!(report.freeze_frames[0].dtc, Some(Dtc::Powertrain(0x0301)));
+//!
+//! let markdown = report.to_markdown();
+//! assert!(markdown.contains("| VIN | 1HGCM82633A004352 |"));
+//! assert!(markdown.contains("- P0301"));
+//! # Ok::<(), obd2::Error>(())
+//! ```
+
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{
+    commands::{
+        mode06::{self, MonitorTestResult},
+        Dtc, DtcsInfo, FreezeFrame, Obd2DataRetrieval, PermanentDtc,
+    },
+    Obd2Device, Result,
+};
+
+/// Everything read by [generate_report]
+///
+/// Values from several ECUs are listed together, in the order the ECUs responded.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct DiagnosticReport {
+    /// The VIN, or `None` if no ECU reported it
+    pub vin: Option<String>,
+
+    /// The calibration IDs of the ECUs' software
+    pub calibration_ids: Vec<String>,
+
+    /// The calibration verification numbers, checksums of the ECUs' software, in the order of
+    /// the calibration IDs
+    pub cvns: Vec<u32>,
+
+    /// The check engine light, DTC count and readiness monitors of each ECU
+    pub status: Vec<DtcsInfo>,
+
+    /// The distance driven since the DTCs were cleared in km
+    ///
+    /// A short distance means the DTCs were cleared recently, maybe to hide a fault.
+    pub distance_since_codes_cleared: Option<u16>,
+
+    /// The distance driven with the check engine light on in km
+    pub distance_with_mil: Option<u16>,
+
+    /// The stored DTCs
+    pub dtcs: Vec<Dtc>,
+
+    /// The pending DTCs, which have not been confirmed
+    pub pending_dtcs: Vec<Dtc>,
+
+    /// The permanent DTCs, which only the ECU can clear
+    pub permanent_dtcs: Vec<PermanentDtc>,
+
+    /// The freeze frames stored with the DTCs
+    pub freeze_frames: Vec<FreezeFrame>,
+
+    /// The latest result of each service 6 on-board monitor test
+    pub monitor_tests: Vec<MonitorTestResult>,
+}
+
+/// Read everything about the vehicle's emissions faults into a report
+///
+/// This sends a few dozen requests, so it takes several seconds with an ELM327. Only a lost
+/// connection, or no response to the monitor status (PID 0x01, which every vehicle supports),
+/// stops the report; other sections are left empty when the vehicle does not answer.
+pub fn generate_report<T: Obd2Device>(device: &mut T) -> Result<DiagnosticReport> {
+    let status = device.get_dtc_info()?;
+    let vin = optional(device.get_vin())?;
+    let calibration_ids = optional(device.get_calibration_ids())?.unwrap_or_default();
+    let cvns = optional(device.get_cvns())?.unwrap_or_default();
+    let distance_since_codes_cleared = optional(device.get_distance_since_codes_cleared())?;
+    let distance_with_mil = optional(device.get_distance_with_mil())?;
+    let dtcs = optional(device.get_dtcs())?.unwrap_or_default();
+    let pending_dtcs = optional(device.get_pending_dtcs())?.unwrap_or_default();
+    let permanent_dtcs = optional(device.get_permanent_dtcs())?.unwrap_or_default();
+    let freeze_frames = optional(device.get_freeze_frame(0))?.unwrap_or_default();
+
+    let mut monitor_tests = Vec::new();
+    for mid in supported_mids(device)? {
+        if let Some(results) = optional(device.get_monitor_test_results(mid))? {
+            monitor_tests.extend(results.into_iter().flatten());
+        }
+    }
+
+    Ok(DiagnosticReport {
+        vin,
+        calibration_ids: calibration_ids.into_iter().flatten().collect(),
+        cvns: cvns.into_iter().flatten().collect(),
+        status,
+        distance_since_codes_cleared: distance_since_codes_cleared.and_then(first),
+        distance_with_mil: distance_with_mil.and_then(first),
+        dtcs: dtcs.into_iter().flatten().collect(),
+        pending_dtcs: pending_dtcs.into_iter().flatten().collect(),
+        permanent_dtcs: permanent_dtcs.into_iter().flatten().collect(),
+        freeze_frames: freeze_frames
+            .into_iter()
+            .filter(|frame| frame.dtc.is_some())
+            .collect(),
+        monitor_tests,
+    })
+}
+
+/// Get `None` for a section the vehicle does not report, and stop if the connection was lost
+pub(crate) fn optional<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.is_connection_lost() => Err(e),
+        Err(_) => Ok(None),
+    }
+}
+
+fn first(values: Vec<u16>) -> Option<u16> {
+    values.first().copied()
+}
+
+/// Find the service 6 monitors any ECU supports, from the bitmaps at OBDMIDs 0x00, 0x20, etc.
+fn supported_mids<T: Obd2Device>(device: &mut T) -> Result<Vec<u8>> {
+    let mut mids = Vec::new();
+    let mut base = 0x00u8;
+    while let Some(responses) = optional(device.obd_command(0x06, base))? {
+        let bitmap = responses
+            .iter()
+            .filter_map(|response| Some(u32::from_be_bytes(response.get(..4)?.try_into().ok()?)))
+            .fold(0, |all, bitmap| all | bitmap);
+        mids.extend(
+            (1..32)
+                .filter(|bit| bitmap & (1 << (32 - bit)) != 0)
+                .map(|bit| base + bit as u8),
+        );
+        if base == 0xE0 || bitmap & 1 == 0 {
+            break;
+        }
+        base += 0x20;
+    }
+    Ok(mids)
+}
+
+impl DiagnosticReport {
+    /// Whether any ECU has the check engine light on
+    pub fn mil_on(&self) -> bool {
+        self.status
+            .iter()
+            .any(|ecu| ecu.malfunction_indicator_light)
+    }
+
+    /// Whether every available readiness monitor of every ECU has completed
+    pub fn monitors_complete(&self) -> bool {
+        self.status.iter().all(|ecu| ecu.readiness.all_complete())
+    }
+
+    /// Render the report as a Markdown document, with a table for each section
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for block in self.blocks() {
+            match block {
+                Block::Heading(level, text) => {
+                    let _ = writeln!(out, "{} {}\n", "#".repeat(level), text);
+                }
+                Block::Table(header, rows) => {
+                    let row = |cells: &[String]| {
+                        let cells: Vec<String> =
+                            cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+                        format!("| {} |\n", cells.join(" | "))
+                    };
+                    out.push_str(&row(&header));
+                    out.push_str(&row(&vec!["---".to_owned(); header.len()]));
+                    for cells in &rows {
+                        out.push_str(&row(cells));
+                    }
+                    out.push('\n');
+                }
+                Block::List(items) => {
+                    for item in items {
+                        let _ = writeln!(out, "- {}", item);
+                    }
+                    out.push('\n');
+                }
+                Block::Paragraph(text) => {
+                    let _ = writeln!(out, "{}\n", text);
+                }
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    /// Render the report as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Diagnostic report</title>\n<style>\n\
+             body { font-family: sans-serif; max-width: 60em; margin: auto; }\n\
+             table { border-collapse: collapse; }\n\
+             th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        for block in self.blocks() {
+            match block {
+                Block::Heading(level, text) => {
+                    let _ = writeln!(out, "<h{0}>{1}</h{0}>", level, escape(&text));
+                }
+                Block::Table(header, rows) => {
+                    out.push_str("<table>\n<tr>");
+                    for cell in &header {
+                        let _ = write!(out, "<th>{}</th>", escape(cell));
+                    }
+                    out.push_str("</tr>\n");
+                    for cells in &rows {
+                        out.push_str("<tr>");
+                        for cell in cells {
+                            let _ = write!(out, "<td>{}</td>", escape(cell));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    for item in items {
+                        let _ = writeln!(out, "<li>{}</li>", escape(&item));
+                    }
+                    out.push_str("</ul>\n");
+                }
+                Block::Paragraph(text) => {
+                    let _ = writeln!(out, "<p>{}</p>", escape(&text));
+                }
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Lay out the report, for each format to render
+    fn blocks(&self) -> Vec<Block> {
+        let mut blocks = vec![Block::Heading(1, "Diagnostic report".to_owned())];
+
+        let or_unknown = |value: Option<String>| value.unwrap_or_else(|| "Unknown".to_owned());
+        let mut summary = vec![
+            ["VIN".to_owned(), or_unknown(self.vin.clone())],
+            [
+                "Check engine light".to_owned(),
+                if self.mil_on() { "On" } else { "Off" }.to_owned(),
+            ],
+            [
+                "Readiness monitors".to_owned(),
+                if self.monitors_complete() {
+                    "Complete"
+                } else {
+                    "Incomplete"
+                }
+                .to_owned(),
+            ],
+            [
+                "Distance since DTCs cleared".to_owned(),
+                or_unknown(
+                    self.distance_since_codes_cleared
+                        .map(|d| format!("{} km", d)),
+                ),
+            ],
+            [
+                "Distance with check engine light on".to_owned(),
+                or_unknown(self.distance_with_mil.map(|d| format!("{} km", d))),
+            ],
+        ];
+        for (i, id) in self.calibration_ids.iter().enumerate() {
+            let cvn = match self.cvns.get(i) {
+                Some(cvn) => format!("{} (CVN {:08X})", id, cvn),
+                None => id.clone(),
+            };
+            summary.push(["Calibration ID".to_owned(), cvn]);
+        }
+        blocks.push(Block::Table(
+            vec!["Item".to_owned(), "Value".to_owned()],
+            summary.into_iter().map(Vec::from).collect(),
+        ));
+
+        blocks.push(Block::Heading(2, "Readiness monitors".to_owned()));
+        let mut monitors = Vec::new();
+        for (ecu, info) in self.status.iter().enumerate() {
+            for status in info.readiness.monitors.iter().filter(|m| m.available) {
+                let state = if status.complete {
+                    "Complete"
+                } else {
+                    "Incomplete"
+                };
+                monitors.push(vec![
+                    (ecu + 1).to_string(),
+                    status.monitor.name().to_owned(),
+                    state.to_owned(),
+                ]);
+            }
+        }
+        blocks.push(table_or_none(&["ECU", "Monitor", "Status"], monitors));
+
+        blocks.push(Block::Heading(2, "DTCs".to_owned()));
+        let permanent: Vec<Dtc> = self.permanent_dtcs.iter().map(|dtc| dtc.0).collect();
+        for (title, dtcs) in [
+            ("Stored", &self.dtcs),
+            ("Pending", &self.pending_dtcs),
+            ("Permanent", &permanent),
+        ] {
+            blocks.push(Block::Heading(3, title.to_owned()));
+            blocks.push(if dtcs.is_empty() {
+                Block::Paragraph("None".to_owned())
+            } else {
+                // with the alternate flag, codes in the database are followed by their description
+                Block::List(dtcs.iter().map(|dtc| format!("{:#}", dtc)).collect())
+            });
+        }
+
+        blocks.push(Block::Heading(2, "Freeze frames".to_owned()));
+        let frames = self
+            .freeze_frames
+            .iter()
+            .map(|frame| {
+                let cell = |value: Option<String>| value.unwrap_or_else(|| "-".to_owned());
+                let trim = |trim: Option<f32>| cell(trim.map(|t| format!("{:.1} %", t)));
+                vec![
+                    cell(frame.dtc.map(|dtc| dtc.to_string())),
+                    cell(frame.rpm.map(|rpm| format!("{:.0} rpm", rpm))),
+                    cell(frame.speed.map(|speed| format!("{} km/h", speed))),
+                    cell(
+                        frame
+                            .engine_load
+                            .map(|load| format!("{:.0} %", f32::from(load) / 2.55)),
+                    ),
+                    cell(
+                        frame
+                            .engine_coolant_temperature
+                            .map(|temperature| format!("{} ºC", temperature)),
+                    ),
+                    trim(frame.short_term_fuel_trim_1),
+                    trim(frame.long_term_fuel_trim_1),
+                ]
+            })
+            .collect();
+        blocks.push(table_or_none(
+            &["DTC", "RPM", "Speed", "Load", "Coolant", "STFT 1", "LTFT 1"],
+            frames,
+        ));
+
+        blocks.push(Block::Heading(2, "On-board monitor tests".to_owned()));
+        let tests = self
+            .monitor_tests
+            .iter()
+            .map(|test| {
+                let monitor = match mode06::mid_name(test.mid) {
+                    Some(name) => name.to_owned(),
+                    None => format!("OBDMID {:02X}", test.mid),
+                };
+                let value = |value: f32| format!("{} {}", value, test.unit).trim_end().to_owned();
+                vec![
+                    monitor,
+                    format!("{:02X}", test.id),
+                    value(test.value),
+                    value(test.min),
+                    value(test.max),
+                    if test.passed { "Pass" } else { "Fail" }.to_owned(),
+                ]
+            })
+            .collect();
+        blocks.push(table_or_none(
+            &["Monitor", "Test", "Value", "Min", "Max", "Result"],
+            tests,
+        ));
+
+        blocks
+    }
+}
+
+/// A piece of a rendered report
+enum Block {
+    Heading(usize, String),
+    Table(Vec<String>, Vec<Vec<String>>),
+    List(Vec<String>),
+    Paragraph(String),
+}
+
+/// A table with `header`, or a note that the vehicle reported nothing if there are no rows
+fn table_or_none(header: &[&str], rows: Vec<Vec<String>>) -> Block {
+    if rows.is_empty() {
+        Block::Paragraph("Not reported".to_owned())
+    } else {
+        Block::Table(header.iter().map(|&h| h.to_owned()).collect(), rows)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
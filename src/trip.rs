@@ -0,0 +1,213 @@
+//! A trip computer
+//!
+//! A [Trip] adds up the distance, fuel and time of a drive from the speed and fuel rate as they
+//! are read, like the trip computer in the instrument cluster. It can be fed the
+//! [samples](crate::poller::Sample) of an [Obd2Poller](crate::poller::Obd2Poller), or the values
+//! from any other loop, and its [TripSummary] can be saved or sent with `serde`.
+
+use core::time::Duration;
+
+use crate::calc::{self, FuelProperties};
+#[cfg(feature = "std")]
+use crate::{commands::pid::StandardPid, poller::Sample};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// The totals of a [Trip]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TripSummary {
+    /// How long the trip has been running
+    pub duration: Duration,
+
+    /// The distance driven in km
+    pub distance: f32,
+
+    /// The fuel used in L, over the time the fuel rate was known
+    pub fuel_used: f32,
+
+    /// The highest speed in km/h
+    pub max_speed: f32,
+
+    /// How long the vehicle was stopped
+    pub idle_time: Duration,
+}
+
+impl TripSummary {
+    /// The average speed in km/h, including the time stopped, or `None` before any time has
+    /// passed
+    pub fn average_speed(&self) -> Option<f32> {
+        let hours = self.duration.as_secs_f32() / 3600.;
+        (hours > 0.).then(|| self.distance / hours)
+    }
+
+    /// The average fuel consumption in L/100km, or `None` before the vehicle has moved
+    pub fn liters_per_100km(&self) -> Option<f32> {
+        (self.distance > 0.).then(|| self.fuel_used / self.distance * 100.)
+    }
+
+    /// The average fuel consumption in miles per US gallon, or `None` before the vehicle has
+    /// moved
+    pub fn mpg(&self) -> Option<f32> {
+        self.liters_per_100km().map(calc::mpg)
+    }
+}
+
+/// Adds up the distance, fuel and time of a trip
+///
+/// The latest speed and fuel rate are set as they are read, and [advance](Self::advance) adds the
+/// time since the previous values, as if they stayed the same in between. With `std`,
+/// [add_sample](Self::add_sample) does both from a poller's samples. Nothing is added while the
+/// trip is stopped.
+///
+/// # Example
+/// ```
+/// use obd2::trip::Trip;
+/// use std::time::Duration;
+///
+/// let mut trip = Trip::new();
+/// trip.start();
+///
+/// // five minutes stopped, then half an hour at 80 km/h using 5 L/h
+/// trip.set_speed(0.);
+/// trip.advance(Duration::from_secs(300));
+/// trip.set_speed(80.);
+/// trip.set_fuel_rate(5.);
+/// trip.advance(Duration::from_secs(1800));
+///
+/// let summary = trip.summary();
+/// assert_eq!(summary.distance, 40.);
+/// assert_eq!(summary.fuel_used, 2.5);
+/// assert_eq!(summary.max_speed, 80.);
+/// assert_eq!(summary.idle_time, Duration::from_secs(300));
+/// assert_eq!(summary.duration, Duration::from_secs(2100));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trip {
+    fuel: FuelProperties,
+    running: bool,
+    speed: Option<f32>,
+    fuel_rate: Option<f32>,
+    maf_fuel_rate: Option<f32>,
+    #[cfg(feature = "std")]
+    last_sample: Option<Instant>,
+    summary: TripSummary,
+}
+
+impl Default for Trip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Trip {
+    /// Create a stopped [`Trip`] of a gasoline vehicle
+    pub fn new() -> Self {
+        Trip {
+            fuel: FuelProperties::GASOLINE,
+            running: false,
+            speed: None,
+            fuel_rate: None,
+            maf_fuel_rate: None,
+            #[cfg(feature = "std")]
+            last_sample: None,
+            summary: TripSummary::default(),
+        }
+    }
+
+    /// Set the fuel the vehicle uses, to estimate the fuel rate from the mass air flow rate
+    pub fn with_fuel(mut self, fuel: FuelProperties) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Start adding up time, distance and fuel
+    pub fn start(&mut self) {
+        self.running = true;
+    }
+
+    /// Stop adding up, keeping the totals
+    pub fn stop(&mut self) {
+        self.running = false;
+        #[cfg(feature = "std")]
+        {
+            self.last_sample = None;
+        }
+    }
+
+    /// Whether the trip is adding up
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Clear the totals, without starting or stopping the trip
+    pub fn reset(&mut self) {
+        self.summary = TripSummary::default();
+    }
+
+    /// Get the totals so far
+    pub fn summary(&self) -> &TripSummary {
+        &self.summary
+    }
+
+    /// Set the speed in km/h
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = Some(speed);
+        if self.running {
+            self.summary.max_speed = self.summary.max_speed.max(speed);
+        }
+    }
+
+    /// Set the fuel rate in L/h, from the engine fuel rate (PID 0x5E)
+    pub fn set_fuel_rate(&mut self, fuel_rate: f32) {
+        self.fuel_rate = Some(fuel_rate);
+    }
+
+    /// Set the mass air flow rate in g/s (PID 0x10), which the fuel rate is estimated from if it
+    /// is not set
+    pub fn set_maf(&mut self, maf: f32) {
+        self.maf_fuel_rate = Some(self.fuel.fuel_rate_from_maf(maf));
+    }
+
+    /// Add `elapsed` time at the current speed and fuel rate
+    pub fn advance(&mut self, elapsed: Duration) {
+        if !self.running {
+            return;
+        }
+        let hours = elapsed.as_secs_f32() / 3600.;
+        self.summary.duration += elapsed;
+        if let Some(speed) = self.speed {
+            self.summary.distance += speed * hours;
+            if speed == 0. {
+                self.summary.idle_time += elapsed;
+            }
+        }
+        if let Some(fuel_rate) = self.fuel_rate.or(self.maf_fuel_rate) {
+            self.summary.fuel_used += fuel_rate * hours;
+        }
+    }
+
+    /// Add the time since the previous sample, and use the value of a vehicle speed, engine fuel
+    /// rate or mass air flow rate sample
+    ///
+    /// Samples of other PIDs only add the time.
+    #[cfg(feature = "std")]
+    pub fn add_sample(&mut self, sample: &Sample) {
+        if let Some(last) = self.last_sample {
+            self.advance(sample.time.saturating_duration_since(last));
+        }
+        if self.running {
+            self.last_sample = Some(sample.time);
+        }
+        let Some(&value) = sample.values.first() else {
+            return;
+        };
+        match sample.pid {
+            StandardPid::VehicleSpeed => self.set_speed(value),
+            StandardPid::EngineFuelRate => self.set_fuel_rate(value),
+            StandardPid::MafAirFlowRate => self.set_maf(value),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Unified Diagnostic Services (UDS, ISO 14229)
+//!
+//! Modern vehicles report much more through UDS than through the OBD-II services: DTCs with their
+//! full status, manufacturer data identifiers, and services for workshops like clearing DTCs
+//! from one ECU. UDS requests go to a single ECU, so a [UdsClient] sends each request to one
+//! physical address with [obd_request_to](crate::Obd2Device::obd_request_to), over whichever
+//! device it wraps.
+//!
+//! Rejected requests are returned as [Error::NegativeResponse], and ECUs that need more time are
+//! waited for by the device.
+//!
+//! # Example
+//! ```
+//! use obd2::{
+//!     device::MockObd2Device,
+//!     uds::{DtcStatus, Session, UdsClient},
+//! };
+//!
+//! let mut device = MockObd2Device::new();
+//! device.set_request_response(&[0x10, 0x03], [vec![0x00, 0x32, 0x01, 0xF4]]);
+//! device.set_request_response(
+//!     &[0x19, 0x02, 0x08],
+//!     [vec![0xFF, 0x03, 0x01, 0x00, 0x2F]],
+//! );
+//!
+//! let mut ecu = UdsClient::new(device, 0x7E0);
+//! ecu.diagnostic_session_control(Session::Extended).unwrap();
+//!
+//! let dtcs = ecu.read_dtc_information(DtcStatus::CONFIRMED).unwrap();
+//! assert_eq!(dtcs[0].dtc().to_string(), "P0301");
+//! assert!(dtcs[0].status.contains(DtcStatus::TEST_FAILED));
+//! ```
+
+use std::time::{Duration, Instant};
+
+use crate::{commands::Dtc, Error, Obd2Device, Result};
+
+/// How long after the last request to send a tester present message, to stay within the ECU's
+/// session timeout (S3, 5 seconds)
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A diagnostic session, which decides which services an ECU allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Session {
+    /// The session ECUs start in, which allows reading data (0x01)
+    Default,
+    /// For reprogramming the ECU (0x02)
+    Programming,
+    /// For services that change how the vehicle behaves, like actuator tests (0x03)
+    Extended,
+    /// For safety systems like airbags (0x04)
+    SafetySystem,
+    /// A manufacturer specific session
+    Other(u8),
+}
+
+impl From<Session> for u8 {
+    fn from(session: Session) -> Self {
+        match session {
+            Session::Default => 0x01,
+            Session::Programming => 0x02,
+            Session::Extended => 0x03,
+            Session::SafetySystem => 0x04,
+            Session::Other(val) => val,
+        }
+    }
+}
+
+/// Response timing the ECU uses in a session, from
+/// [diagnostic_session_control](UdsClient::diagnostic_session_control)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct SessionTiming {
+    /// The longest time the ECU takes to start responding (P2)
+    pub response_time: Duration,
+
+    /// The longest time the ECU takes to respond after saying the response is pending (P2*)
+    pub extended_response_time: Duration,
+}
+
+/// The status byte of a UDS DTC
+///
+/// The associated constants are the status bits, which can be combined with `|` to make the mask
+/// for [read_dtc_information](UdsClient::read_dtc_information).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DtcStatus(pub u8);
+
+impl DtcStatus {
+    /// The most recent test failed
+    pub const TEST_FAILED: Self = Self(0x01);
+    /// A test failed during the current operation cycle
+    pub const TEST_FAILED_THIS_OPERATION_CYCLE: Self = Self(0x02);
+    /// A test failed during the current or last completed operation cycle
+    pub const PENDING: Self = Self(0x04);
+    /// The fault has been confirmed, and is stored until it is cleared
+    pub const CONFIRMED: Self = Self(0x08);
+    /// The test has not completed since DTCs were last cleared
+    pub const TEST_NOT_COMPLETED_SINCE_CLEAR: Self = Self(0x10);
+    /// A test failed since DTCs were last cleared
+    pub const TEST_FAILED_SINCE_CLEAR: Self = Self(0x20);
+    /// The test has not completed during the current operation cycle
+    pub const TEST_NOT_COMPLETED_THIS_OPERATION_CYCLE: Self = Self(0x40);
+    /// The fault is turning on a warning indicator, like the MIL
+    pub const WARNING_INDICATOR_REQUESTED: Self = Self(0x80);
+    /// Every status bit, to read every stored DTC
+    pub const ALL: Self = Self(0xFF);
+
+    /// Whether every bit set in `other` is also set in this status
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for DtcStatus {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// A DTC read with UDS, which has three bytes and a status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct UdsDtc {
+    /// The DTC: the two bytes of the SAE J2012 code, then the failure type byte
+    pub code: u32,
+
+    /// The status of the DTC
+    pub status: DtcStatus,
+}
+
+impl UdsDtc {
+    /// Get the SAE J2012 code, like P0301, from the first two bytes
+    pub fn dtc(&self) -> Dtc {
+        Dtc::from((self.code >> 8) as u16)
+    }
+
+    /// Get the failure type byte, which says how the component failed (like a short to ground)
+    pub fn failure_type(&self) -> u8 {
+        self.code as u8
+    }
+}
+
+/// Sends UDS requests to one ECU
+///
+/// See the [module](self) documentation.
+pub struct UdsClient<T: Obd2Device> {
+    device: T,
+    address: u32,
+    last_request: Instant,
+}
+
+impl<T: Obd2Device> UdsClient<T> {
+    /// Wrap `device` to send requests to the ECU with the physical request `address`, like
+    /// `0x7E0` for the engine controller
+    pub fn new(device: T, address: u32) -> Self {
+        UdsClient {
+            device,
+            address,
+            last_request: Instant::now(),
+        }
+    }
+
+    /// Get the physical request address of the ECU
+    pub fn address(&self) -> u32 {
+        self.address
+    }
+
+    /// Get back the wrapped device
+    pub fn into_inner(self) -> T {
+        self.device
+    }
+
+    /// Send a raw UDS request and get the response, with the echoed service and its sub-function
+    /// or identifier removed
+    pub fn request(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+        self.last_request = Instant::now();
+        self.device.obd_request_to(self.address, request)
+    }
+
+    /// Switch to a diagnostic session (service 0x10)
+    ///
+    /// The ECU returns to the default session if it does not receive a request for a few
+    /// seconds, so call [keep_alive](Self::keep_alive) regularly while in another session.
+    pub fn diagnostic_session_control(&mut self, session: Session) -> Result<SessionTiming> {
+        match *self.request(&[0x10, session.into()])? {
+            [p2_high, p2_low, p2_star_high, p2_star_low, ..] => Ok(SessionTiming {
+                response_time: Duration::from_millis(u16::from_be_bytes([p2_high, p2_low]).into()),
+                extended_response_time: Duration::from_millis(
+                    u64::from(u16::from_be_bytes([p2_star_high, p2_star_low])) * 10,
+                ),
+            }),
+            ref response => Err(Error::IncorrectResponseLength(
+                "session timing",
+                4,
+                response.len(),
+            )),
+        }
+    }
+
+    /// Read the data identifier `did` (service 0x22)
+    pub fn read_data_by_identifier(&mut self, did: u16) -> Result<Vec<u8>> {
+        let [high, low] = did.to_be_bytes();
+        self.request(&[0x22, high, low])
+    }
+
+    /// Read the DTCs whose status has any of the bits in `status_mask` set (service 0x19, report
+    /// DTCs by status mask)
+    pub fn read_dtc_information(&mut self, status_mask: DtcStatus) -> Result<Vec<UdsDtc>> {
+        let response = self.request(&[0x19, 0x02, status_mask.0])?;
+        // the first byte is the status bits the ECU supports
+        let records = response.get(1..).unwrap_or_default();
+        if records.len() % 4 != 0 {
+            return Err(Error::IncorrectResponseLength(
+                "DTC records",
+                records.len() / 4 * 4 + 4,
+                records.len(),
+            ));
+        }
+        Ok(records
+            .chunks_exact(4)
+            .map(|r| UdsDtc {
+                code: u32::from_be_bytes([0, r[0], r[1], r[2]]),
+                status: DtcStatus(r[3]),
+            })
+            .collect())
+    }
+
+    /// Clear the DTCs in `group` (service 0x14), where `0xFFFFFF` clears every DTC
+    pub fn clear_diagnostic_information(&mut self, group: u32) -> Result<()> {
+        let [_, high, middle, low] = group.to_be_bytes();
+        self.request(&[0x14, high, middle, low])?;
+        Ok(())
+    }
+
+    /// Tell the ECU a tester is still connected (service 0x3E), so it stays in the current
+    /// session
+    pub fn tester_present(&mut self) -> Result<()> {
+        self.request(&[0x3E, 0x00])?;
+        Ok(())
+    }
+
+    /// Send [tester_present](Self::tester_present) if no request has been sent recently
+    ///
+    /// Call this regularly (at least every second or two) while in a non-default session.
+    pub fn keep_alive(&mut self) -> Result<()> {
+        if self.last_request.elapsed() >= KEEP_ALIVE_INTERVAL {
+            self.tester_present()?;
+        }
+        Ok(())
+    }
+
+    /// Unlock security access `level` (service 0x27), computing the key from the seed with `key`
+    ///
+    /// `level` is the odd number used to request the seed; the key is sent with `level + 1`. The
+    /// algorithm for the key is specific to the manufacturer. If the ECU is already unlocked, it
+    /// sends a seed of zeros and `key` is not called.
+    pub fn security_access(&mut self, level: u8, key: impl FnOnce(&[u8]) -> Vec<u8>) -> Result<()> {
+        let seed = self.request(&[0x27, level])?;
+        if seed.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+        let mut request = vec![0x27, level + 1];
+        request.extend(key(&seed));
+        self.request(&request)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! Converting values to imperial units
+//!
+//! The getters, [StandardPid] and the [calc] functions give values in the metric units SAE J1979
+//! defines, like km/h, ºC and kPa. A [UnitSystem] converts them for display, so that apps showing
+//! mph, ºF and psi do not each repeat the conversions. With the `uom` feature, `commands::units`
+//! returns typed quantities instead.
+//!
+//! # Example
+//! ```
+//! use obd2::{
+//!     commands::{pid::StandardPid, Obd2DataRetrieval},
+//!     device::MockObd2Device,
+//!     units::UnitSystem,
+//! };
+//!
+//! let mut device = MockObd2Device::new()
+//!     .with_response(0x01, 0x0D, [vec![100]])
+//!     .with_response(0x01, 0x05, [vec![130]]);
+//! let units = UnitSystem::UsCustomary;
+//!
+//! let speed = units.speed(device.get_speed().unwrap()[0].into());
+//! assert_eq!(format!("{:.0} {}", speed, units.speed_unit()), "62 mph");
+//!
+//! // every value of a snapshot, with the unit of each PID
+//! let pids = [StandardPid::EngineCoolantTemperature];
+//! for (pid, values) in device.get_snapshot(&pids).unwrap() {
+//!     let value = units.convert_pid(pid, values[0]);
+//!     assert_eq!(format!("{} {}", value, units.pid_unit(pid).unwrap()), "194 ºF");
+//! }
+//! ```
+
+use crate::{calc, commands::pid::StandardPid};
+
+/// The units values are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum UnitSystem {
+    /// The metric units the values are decoded in, so nothing is converted
+    #[default]
+    Metric,
+    /// The units used in the United States: mph, miles, ºF, psi, pounds, lb·ft and US gallons
+    UsCustomary,
+    /// The units used in the United Kingdom: like [UsCustomary](Self::UsCustomary), but with
+    /// imperial gallons
+    Imperial,
+}
+
+impl UnitSystem {
+    /// Convert `value` from the metric `unit` of a [StandardPid], like `"km/h"`, and get the unit
+    /// it is converted to
+    ///
+    /// Units that are the same in every system, like rpm, % and V, are returned unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use obd2::units::UnitSystem;
+    ///
+    /// assert_eq!(UnitSystem::UsCustomary.convert(100., "ºC"), (212., "ºF"));
+    /// assert_eq!(UnitSystem::UsCustomary.convert(50., "%"), (50., "%"));
+    /// assert_eq!(UnitSystem::Metric.convert(100., "ºC"), (100., "ºC"));
+    /// ```
+    pub fn convert(self, value: f32, unit: &str) -> (f32, &str) {
+        let gallons_per_liter = match self {
+            Self::Metric => return (value, unit),
+            Self::UsCustomary => 1. / 3.785_411_8,
+            Self::Imperial => 1. / 4.546_09,
+        };
+        match unit {
+            "km/h" => (value / KM_PER_MILE, "mph"),
+            "km" => (value / KM_PER_MILE, "mi"),
+            "ºC" => (value * 1.8 + 32., "ºF"),
+            "kPa" => (value / KPA_PER_PSI, "psi"),
+            "L" => (value * gallons_per_liter, "gal"),
+            "L/h" => (value * gallons_per_liter, "gal/h"),
+            "g/s" => (value * 60. / GRAMS_PER_POUND, "lb/min"),
+            "kg/h" => (value * 1000. / GRAMS_PER_POUND, "lb/h"),
+            "N·m" => (value / NM_PER_LB_FT, "lb·ft"),
+            _ => (value, unit),
+        }
+    }
+
+    /// Convert the decoded value of `pid`
+    pub fn convert_pid(self, pid: StandardPid, value: f32) -> f32 {
+        match pid.unit() {
+            Some(unit) => self.convert(value, unit).0,
+            None => value,
+        }
+    }
+
+    /// Get the unit [convert_pid](Self::convert_pid) gives values of `pid` in, or `None` if the
+    /// PID does not hold a single number
+    pub fn pid_unit(self, pid: StandardPid) -> Option<&'static str> {
+        pid.unit().map(|unit| self.convert(0., unit).1)
+    }
+
+    /// Convert a speed in km/h
+    pub fn speed(self, speed: f32) -> f32 {
+        self.convert(speed, "km/h").0
+    }
+
+    /// The unit of [speed](Self::speed)
+    pub fn speed_unit(self) -> &'static str {
+        self.convert(0., "km/h").1
+    }
+
+    /// Convert a distance in km
+    pub fn distance(self, distance: f32) -> f32 {
+        self.convert(distance, "km").0
+    }
+
+    /// The unit of [distance](Self::distance)
+    pub fn distance_unit(self) -> &'static str {
+        self.convert(0., "km").1
+    }
+
+    /// Convert a temperature in ºC
+    pub fn temperature(self, temperature: f32) -> f32 {
+        self.convert(temperature, "ºC").0
+    }
+
+    /// The unit of [temperature](Self::temperature)
+    pub fn temperature_unit(self) -> &'static str {
+        self.convert(0., "ºC").1
+    }
+
+    /// Convert a pressure in kPa
+    pub fn pressure(self, pressure: f32) -> f32 {
+        self.convert(pressure, "kPa").0
+    }
+
+    /// The unit of [pressure](Self::pressure)
+    pub fn pressure_unit(self) -> &'static str {
+        self.convert(0., "kPa").1
+    }
+
+    /// Convert a volume of fuel in L
+    pub fn volume(self, volume: f32) -> f32 {
+        self.convert(volume, "L").0
+    }
+
+    /// The unit of [volume](Self::volume)
+    pub fn volume_unit(self) -> &'static str {
+        self.convert(0., "L").1
+    }
+
+    /// Convert a fuel rate in L/h
+    pub fn fuel_rate(self, fuel_rate: f32) -> f32 {
+        self.convert(fuel_rate, "L/h").0
+    }
+
+    /// The unit of [fuel_rate](Self::fuel_rate)
+    pub fn fuel_rate_unit(self) -> &'static str {
+        self.convert(0., "L/h").1
+    }
+
+    /// Convert a fuel consumption in L/100km to miles per gallon, of the system's gallons
+    pub fn fuel_economy(self, liters_per_100km: f32) -> f32 {
+        match self {
+            Self::Metric => liters_per_100km,
+            Self::UsCustomary => calc::mpg(liters_per_100km),
+            Self::Imperial => calc::mpg_imperial(liters_per_100km),
+        }
+    }
+
+    /// The unit of [fuel_economy](Self::fuel_economy)
+    pub fn fuel_economy_unit(self) -> &'static str {
+        match self {
+            Self::Metric => "L/100km",
+            Self::UsCustomary | Self::Imperial => "mpg",
+        }
+    }
+}
+
+const KM_PER_MILE: f32 = 1.609_344;
+const KPA_PER_PSI: f32 = 6.894_757;
+const GRAMS_PER_POUND: f32 = 453.592_37;
+const NM_PER_LB_FT: f32 = 1.355_818;
@@ -0,0 +1,303 @@
+//! Decoding vehicle identification numbers
+//!
+//! A VIN, as read with [get_vin](crate::commands::Obd2DataRetrieval::get_vin), is 17 characters
+//! defined by ISO 3779. The first three are the world manufacturer identifier (WMI), which tells
+//! the country and manufacturer. The next six describe the vehicle, ending in a check digit in
+//! North America (and often elsewhere), and the last eight identify the vehicle itself, starting
+//! with its model year and the plant that built it.
+//!
+//! # Example
+//! ```
+//! use obd2::vin::VinInfo;
+//!
+//! let info = VinInfo::decode("1HGCM82633A004352").unwrap();
+//! assert_eq!(info.wmi, "1HG");
+//! assert_eq!(info.manufacturer, Some("Honda"));
+//! assert_eq!(info.country, Some("United States"));
+//! assert!(info.check_digit_valid);
+//! assert_eq!(info.model_year, Some(2003));
+//! assert_eq!(info.plant_code, 'A');
+//! assert_eq!(info.serial_number, "004352");
+//! ```
+
+use alloc::string::String;
+
+use crate::{Error, Result};
+
+/// The information encoded in a VIN
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct VinInfo {
+    /// The whole VIN, in upper case
+    pub vin: String,
+
+    /// The world manufacturer identifier, the first three characters
+    pub wmi: String,
+
+    /// The manufacturer, if the WMI is a well-known one
+    pub manufacturer: Option<&'static str>,
+
+    /// The country the manufacturer's WMI was assigned in, which is usually where the vehicle was
+    /// built
+    pub country: Option<&'static str>,
+
+    /// The continent the WMI was assigned in, like `"Europe"`
+    pub region: Option<&'static str>,
+
+    /// The vehicle descriptor section, characters 4 to 8, which each manufacturer uses for the
+    /// model, body and engine
+    pub vds: String,
+
+    /// The check digit, character 9
+    pub check_digit: char,
+
+    /// Whether the check digit matches the rest of the VIN
+    ///
+    /// The check digit is required in North America. Elsewhere manufacturers may use the character
+    /// for something else, so a mismatch does not always mean the VIN was read wrong.
+    pub check_digit_valid: bool,
+
+    /// The model year, from character 10
+    ///
+    /// The year codes repeat every 30 years. As in North America, a number as character 7 is taken
+    /// to mean a year from 1980 to 2009, and a letter a year from 2010 to 2039. This is `None` if
+    /// character 10 is not a year code.
+    pub model_year: Option<u16>,
+
+    /// The code of the plant that built the vehicle, character 11
+    pub plant_code: char,
+
+    /// The production sequence number, the last six characters
+    pub serial_number: String,
+}
+
+impl VinInfo {
+    /// Decode `vin`
+    ///
+    /// Surrounding whitespace and padding NUL characters are ignored, and lower case letters are
+    /// accepted. The VIN must be 17 characters, without the letters `I`, `O` and `Q`. A VIN whose
+    /// check digit does not match is still decoded; see
+    /// [check_digit_valid](Self::check_digit_valid).
+    pub fn decode(vin: &str) -> Result<Self> {
+        let vin = vin
+            .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+            .to_ascii_uppercase();
+        if vin.len() != 17 {
+            return Err(Error::InvalidVin(vin));
+        }
+        if vin.chars().any(|c| transliterate(c).is_none()) {
+            return Err(Error::InvalidVin(vin));
+        }
+
+        // only ASCII characters have a value
+        let char_at = |i: usize| char::from(vin.as_bytes()[i]);
+        let wmi = &vin[..3];
+        Ok(VinInfo {
+            wmi: wmi.into(),
+            manufacturer: MANUFACTURERS
+                .iter()
+                .find(|(prefix, _)| wmi.starts_with(prefix))
+                .map(|&(_, name)| name),
+            country: country(char_at(0), char_at(1)),
+            region: region(char_at(0)),
+            vds: vin[3..8].into(),
+            check_digit: char_at(8),
+            check_digit_valid: check_digit(&vin) == Some(char_at(8)),
+            model_year: model_year(char_at(9), char_at(6).is_ascii_digit()),
+            plant_code: char_at(10),
+            serial_number: vin[11..].into(),
+            vin,
+        })
+    }
+}
+
+/// Compute the check digit (character 9) that `vin` should have, or `None` if it is not a valid
+/// VIN
+///
+/// Each character is given a value and weighted by its position, and the check digit is the sum
+/// modulo 11, with 10 written as `X`.
+///
+/// # Example
+/// ```
+/// use obd2::vin::check_digit;
+///
+/// assert_eq!(check_digit("1M8GDM9AXKP042788"), Some('X'));
+/// assert_eq!(check_digit("1M8GDM9A"), None);
+/// ```
+pub fn check_digit(vin: &str) -> Option<char> {
+    if vin.len() != 17 {
+        return None;
+    }
+    let mut sum = 0;
+    for (c, weight) in vin.chars().zip(WEIGHTS) {
+        sum += transliterate(c.to_ascii_uppercase())? * weight;
+    }
+    char::from_digit(sum % 11, 10).or(Some('X'))
+}
+
+/// The weight of each character of a VIN in its check digit
+const WEIGHTS: [u32; 17] = [8, 7, 6, 5, 4, 3, 2, 10, 0, 9, 8, 7, 6, 5, 4, 3, 2];
+
+/// The value of a character of a VIN in its check digit, or `None` if it cannot be in a VIN
+fn transliterate(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => c.to_digit(10),
+        'A'..='H' => Some(c as u32 - 'A' as u32 + 1),
+        'J'..='N' => Some(c as u32 - 'J' as u32 + 1),
+        'P' => Some(7),
+        'R'..='Z' => Some(c as u32 - 'R' as u32 + 9),
+        _ => None,
+    }
+}
+
+/// The model year codes, in order from 1980 (and 2010)
+const YEAR_CODES: &str = "ABCDEFGHJKLMNPRSTVWXY123456789";
+
+fn model_year(code: char, before_2010: bool) -> Option<u16> {
+    let offset = YEAR_CODES.chars().position(|c| c == code)?;
+    let start = if before_2010 { 1980 } else { 2010 };
+    Some(start + offset as u16)
+}
+
+fn region(first: char) -> Option<&'static str> {
+    match first {
+        'A'..='H' => Some("Africa"),
+        'J'..='R' => Some("Asia"),
+        'S'..='Z' => Some("Europe"),
+        '1'..='5' => Some("North America"),
+        '6' | '7' => Some("Oceania"),
+        '8' | '9' | '0' => Some("South America"),
+        _ => None,
+    }
+}
+
+/// The position of the second character of a WMI in the order used to assign ranges of them,
+/// `A` to `Z` then `1` to `9` and `0`
+fn wmi_order(c: char) -> Option<u8> {
+    match c {
+        'A'..='Z' => Some(c as u8 - b'A'),
+        '1'..='9' => Some(c as u8 - b'1' + 26),
+        '0' => Some(35),
+        _ => None,
+    }
+}
+
+/// The countries that were assigned ranges of WMIs, by the first character and the range of the
+/// second
+const COUNTRIES: [(char, char, char, &str); 38] = [
+    ('1', 'A', '0', "United States"),
+    ('2', 'A', '0', "Canada"),
+    ('3', 'A', 'W', "Mexico"),
+    ('4', 'A', '0', "United States"),
+    ('5', 'A', '0', "United States"),
+    ('6', 'A', 'W', "Australia"),
+    ('7', 'A', 'E', "New Zealand"),
+    ('8', 'A', 'E', "Argentina"),
+    ('8', 'F', 'K', "Chile"),
+    ('9', 'A', 'E', "Brazil"),
+    ('9', '3', '9', "Brazil"),
+    ('A', 'A', 'H', "South Africa"),
+    ('J', 'A', '0', "Japan"),
+    ('K', 'L', 'R', "South Korea"),
+    ('L', 'A', '0', "China"),
+    ('M', 'A', 'E', "India"),
+    ('M', 'F', 'K', "Indonesia"),
+    ('M', 'L', 'R', "Thailand"),
+    ('N', 'L', 'R', "Turkey"),
+    ('P', 'L', 'R', "Malaysia"),
+    ('R', 'F', 'K', "Taiwan"),
+    ('S', 'A', 'M', "United Kingdom"),
+    ('S', 'U', 'Z', "Poland"),
+    ('T', 'A', 'H', "Switzerland"),
+    ('T', 'J', 'P', "Czech Republic"),
+    ('T', 'R', 'V', "Hungary"),
+    ('T', 'W', '1', "Portugal"),
+    ('U', '5', '7', "Slovakia"),
+    ('V', 'A', 'E', "Austria"),
+    ('V', 'F', 'R', "France"),
+    ('V', 'S', 'W', "Spain"),
+    ('W', 'A', '0', "Germany"),
+    ('X', 'L', 'R', "Netherlands"),
+    ('X', '3', '0', "Russia"),
+    ('Y', 'A', 'E', "Belgium"),
+    ('Y', 'F', 'K', "Finland"),
+    ('Y', 'S', 'W', "Sweden"),
+    ('Z', 'A', 'R', "Italy"),
+];
+
+fn country(first: char, second: char) -> Option<&'static str> {
+    let second = wmi_order(second)?;
+    COUNTRIES
+        .iter()
+        .find(|&&(c, start, end, _)| {
+            c == first
+                && wmi_order(start)
+                    .zip(wmi_order(end))
+                    .is_some_and(|(start, end)| (start..=end).contains(&second))
+        })
+        .map(|&(.., name)| name)
+}
+
+/// Well-known WMIs, or the first two characters of manufacturers that use many
+const MANUFACTURERS: [(&str, &str); 59] = [
+    ("1C3", "Chrysler"),
+    ("1C4", "Jeep"),
+    ("1C6", "Ram"),
+    ("1FA", "Ford"),
+    ("1FM", "Ford"),
+    ("1FT", "Ford"),
+    ("1G1", "Chevrolet"),
+    ("1GC", "Chevrolet"),
+    ("1GN", "Chevrolet"),
+    ("1G6", "Cadillac"),
+    ("1GT", "GMC"),
+    ("1HG", "Honda"),
+    ("1J4", "Jeep"),
+    ("1LN", "Lincoln"),
+    ("1N4", "Nissan"),
+    ("1VW", "Volkswagen"),
+    ("2C3", "Chrysler"),
+    ("2FM", "Ford"),
+    ("2G1", "Chevrolet"),
+    ("2HG", "Honda"),
+    ("2T1", "Toyota"),
+    ("3FA", "Ford"),
+    ("3G1", "Chevrolet"),
+    ("3N1", "Nissan"),
+    ("3VW", "Volkswagen"),
+    ("4S3", "Subaru"),
+    ("4S4", "Subaru"),
+    ("4T1", "Toyota"),
+    ("5FN", "Honda"),
+    ("5NP", "Hyundai"),
+    ("5TD", "Toyota"),
+    ("5YJ", "Tesla"),
+    ("JA3", "Mitsubishi"),
+    ("JF1", "Subaru"),
+    ("JF2", "Subaru"),
+    ("JHM", "Honda"),
+    ("JM1", "Mazda"),
+    ("JN1", "Nissan"),
+    ("JTH", "Lexus"),
+    ("JT", "Toyota"),
+    ("KMH", "Hyundai"),
+    ("KNA", "Kia"),
+    ("KND", "Kia"),
+    ("SAJ", "Jaguar"),
+    ("SAL", "Land Rover"),
+    ("TMB", "Škoda"),
+    ("VF1", "Renault"),
+    ("VF3", "Peugeot"),
+    ("VF7", "Citroën"),
+    ("VSS", "SEAT"),
+    ("WAU", "Audi"),
+    ("WBA", "BMW"),
+    ("WDD", "Mercedes-Benz"),
+    ("WMW", "MINI"),
+    ("WP0", "Porsche"),
+    ("WVW", "Volkswagen"),
+    ("YV1", "Volvo"),
+    ("ZFA", "Fiat"),
+    ("ZFF", "Ferrari"),
+];
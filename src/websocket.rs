@@ -0,0 +1,368 @@
+//! Streaming values to browsers over WebSocket
+//!
+//! A [WebSocketServer] accepts WebSocket connections, like those of a browser dashboard, and
+//! sends them polled values and changes to the DTCs as JSON text messages. Clients choose what
+//! they receive by sending a subscription:
+//!
+//! - `{"subscribe": [12, 13, "dtcs"]}` to receive PIDs 0x0C and 0x0D and the DTCs, or `"*"` for
+//!   every PID
+//! - `{"unsubscribe": [13]}` to stop receiving them
+//!
+//! which the server answers with the subscriptions the client now has, like
+//! `{"type":"subscribed","pids":[12],"all":false,"dtcs":true}`, or with
+//! `{"type":"error","message":"..."}` if it cannot be understood.
+//!
+//! Values are then sent as objects with a `type` of `"sample"`, the `pid` number, its `name` and
+//! `unit`, the `time` it was read in seconds since the Unix epoch, and the `values` of each ECU
+//! that responded. The DTCs are sent like `{"type":"dtcs","codes":["P0301"]}`, and clients
+//! subscribing to them are sent the current ones straight away.
+//!
+//! # Example
+//! ```
+//! use obd2::{
+//!     commands::pid::StandardPid, device::Obd2Simulator, poller::Obd2Poller,
+//!     websocket::WebSocketServer,
+//! };
+//! use std::time::Duration;
+//! use tungstenite::Message;
+//!
+//! let mut server = WebSocketServer::bind("127.0.0.1:0")?;
+//!
+//! // what a browser would do
+//! let url = format!("ws://{}", server.local_addr());
+//! let (mut client, _) = tungstenite::connect(url).unwrap();
+//! client.send(Message::text(r#"{"subscribe": [12]}"#)).unwrap();
+//! let reply = client.read().unwrap().into_text().unwrap();
+//! let reply: serde_json::Value = serde_json::from_str(&reply).unwrap();
+//! assert_eq!(reply["type"], "subscribed");
+//! assert_eq!(reply["pids"], serde_json::json!([12]));
+//!
+//! let poller = Obd2Poller::new(Obd2Simulator::new())
+//!     .poll(StandardPid::EngineSpeed, Duration::from_millis(10))
+//!     .start();
+//! server.send_sample(&poller.samples().recv().unwrap()?);
+//!
+//! let message = client.read().unwrap().into_text().unwrap();
+//! let message: serde_json::Value = serde_json::from_str(&message).unwrap();
+//! assert_eq!(message["type"], "sample");
+//! assert_eq!(message["name"], "Engine speed");
+//! # Ok::<(), obd2::Error>(())
+//! ```
+
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex, Weak,
+    },
+    thread,
+    time::Duration,
+};
+
+use tungstenite::Message;
+
+use crate::{commands::Dtc, logger::Clock, poller::Sample, Result};
+
+/// How long a client's thread waits for a message from the client before sending what the
+/// server has broadcast
+const READ_TIMEOUT: Duration = Duration::from_millis(20);
+
+/// Serves values and DTCs to WebSocket clients
+///
+/// Each client is served on a thread of its own. Messages are sent as the server gets them, and
+/// clients that disconnect are forgotten. When the server is dropped, the connections are closed,
+/// and the listening socket is closed when the next connection arrives.
+pub struct WebSocketServer {
+    address: SocketAddr,
+    clients: Arc<Clients>,
+    dtcs: Arc<CurrentDtcs>,
+    clock: Clock,
+}
+
+/// The channels to the threads serving each client
+type Clients = Mutex<Vec<Sender<Arc<Broadcast>>>>;
+
+/// The last DTCs sent, and the message they were sent in
+type CurrentDtcs = Mutex<Option<(Vec<Dtc>, String)>>;
+
+/// A message for the clients subscribed to its topic
+struct Broadcast {
+    topic: Topic,
+    text: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Topic {
+    Pid(u8),
+    Dtcs,
+    /// Sent to no client, to find the clients that disconnected
+    Ping,
+}
+
+impl WebSocketServer {
+    /// Listen for WebSocket connections on `address`, like `"0.0.0.0:8080"`
+    ///
+    /// Connections are accepted on a new thread.
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(address).map_err(crate::device::Error::from)?;
+        let address = listener.local_addr().map_err(crate::device::Error::from)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let dtcs = Arc::new(Mutex::new(None));
+
+        let weak_clients = Arc::downgrade(&clients);
+        let weak_dtcs = Arc::downgrade(&dtcs);
+        thread::spawn(move || accept(listener, weak_clients, weak_dtcs));
+
+        Ok(WebSocketServer {
+            address,
+            clients,
+            dtcs,
+            clock: Clock::new(),
+        })
+    }
+
+    /// Get the address the server listens on, with the port chosen if it was bound to port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Get the number of clients connected
+    pub fn client_count(&self) -> usize {
+        let mut clients = self.clients.lock().unwrap();
+        // clients that disconnected are only noticed when sending, so ping them
+        let ping = Arc::new(Broadcast {
+            topic: Topic::Ping,
+            text: String::new(),
+        });
+        clients.retain(|client| client.send(ping.clone()).is_ok());
+        clients.len()
+    }
+
+    /// Send `sample` to the clients subscribed to its PID
+    pub fn send_sample(&mut self, sample: &Sample) {
+        let text = json!({
+            "type": "sample",
+            "pid": sample.pid.pid(),
+            "name": sample.pid.name(),
+            "unit": sample.pid.unit(),
+            "time": self.clock.unix_time(sample.time),
+            "values": sample.values,
+        });
+        self.broadcast(Broadcast {
+            topic: Topic::Pid(sample.pid.pid()),
+            text: text.to_string(),
+        });
+    }
+
+    /// Send `dtcs` to the clients subscribed to the DTCs if they are different from the last ones
+    /// sent, and get whether they were
+    ///
+    /// The DTCs of every ECU, as read with
+    /// [get_dtcs](crate::commands::Obd2DataRetrieval::get_dtcs), can be passed every time they
+    /// are read, and only changes are sent.
+    pub fn send_dtcs(&mut self, dtcs: &[Dtc]) -> bool {
+        let mut dtcs = dtcs.to_vec();
+        dtcs.sort();
+        dtcs.dedup();
+        let text = {
+            let mut current = self.dtcs.lock().unwrap();
+            if current
+                .as_ref()
+                .is_some_and(|(current, _)| *current == dtcs)
+            {
+                return false;
+            }
+            let codes: Vec<String> = dtcs.iter().map(|dtc| dtc.to_string()).collect();
+            let text = json!({ "type": "dtcs", "codes": codes }).to_string();
+            *current = Some((dtcs, text.clone()));
+            text
+        };
+        self.broadcast(Broadcast {
+            topic: Topic::Dtcs,
+            text,
+        });
+        true
+    }
+
+    /// Send every sample from a poller's channel until the poller stops
+    ///
+    /// Failed requests sent on the channel are skipped.
+    pub fn run(&mut self, samples: &Receiver<Result<Sample>>) {
+        for sample in samples.iter().flatten() {
+            self.send_sample(&sample);
+        }
+    }
+
+    fn broadcast(&self, message: Broadcast) {
+        let message = Arc::new(message);
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|client| client.send(message.clone()).is_ok());
+    }
+}
+
+/// Accept connections until the server is dropped
+fn accept(listener: TcpListener, clients: Weak<Clients>, dtcs: Weak<CurrentDtcs>) {
+    for stream in listener.incoming() {
+        let (Some(clients), Some(dtcs)) = (clients.upgrade(), dtcs.upgrade()) else {
+            return;
+        };
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("could not accept WebSocket connection: {}", e);
+                continue;
+            }
+        };
+        let (sender, receiver) = mpsc::channel();
+        clients.lock().unwrap().push(sender);
+        thread::spawn(move || serve(stream, receiver, Arc::downgrade(&dtcs)));
+    }
+}
+
+/// What a client is subscribed to
+#[derive(Default)]
+struct Subscriptions {
+    pids: BTreeSet<u8>,
+    all: bool,
+    dtcs: bool,
+}
+
+impl Subscriptions {
+    fn contains(&self, topic: Topic) -> bool {
+        match topic {
+            Topic::Pid(pid) => self.all || self.pids.contains(&pid),
+            Topic::Dtcs => self.dtcs,
+            Topic::Ping => false,
+        }
+    }
+
+    /// Apply a subscription message, and get the reply and whether the client newly subscribed
+    /// to the DTCs
+    fn handle(&mut self, text: &str) -> (String, bool) {
+        match self.apply(text) {
+            Ok(new_dtcs) => {
+                let reply = json!({
+                    "type": "subscribed",
+                    "pids": self.pids,
+                    "all": self.all,
+                    "dtcs": self.dtcs,
+                });
+                (reply.to_string(), new_dtcs)
+            }
+            Err(message) => {
+                let reply = json!({ "type": "error", "message": message });
+                (reply.to_string(), false)
+            }
+        }
+    }
+
+    fn apply(&mut self, text: &str) -> core::result::Result<bool, String> {
+        let message: Value =
+            serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+        let (topics, subscribe) = match (message.get("subscribe"), message.get("unsubscribe")) {
+            (Some(topics), None) => (topics, true),
+            (None, Some(topics)) => (topics, false),
+            _ => return Err("expected \"subscribe\" or \"unsubscribe\"".to_string()),
+        };
+        let topics = topics
+            .as_array()
+            .ok_or("expected an array of PIDs and \"dtcs\"")?;
+
+        let had_dtcs = self.dtcs;
+        for topic in topics {
+            match topic {
+                Value::String(topic) if topic == "dtcs" => self.dtcs = subscribe,
+                Value::String(topic) if topic == "*" => {
+                    self.all = subscribe;
+                    self.pids.clear();
+                }
+                Value::Number(pid) => {
+                    let pid = pid
+                        .as_u64()
+                        .and_then(|pid| u8::try_from(pid).ok())
+                        .ok_or_else(|| format!("invalid PID: {}", pid))?;
+                    if subscribe {
+                        self.pids.insert(pid);
+                    } else {
+                        self.pids.remove(&pid);
+                    }
+                }
+                topic => return Err(format!("invalid topic: {}", topic)),
+            }
+        }
+        Ok(self.dtcs && !had_dtcs)
+    }
+}
+
+/// Handle a client's subscriptions and send it what it is subscribed to, until either side
+/// disconnects
+fn serve(stream: TcpStream, broadcasts: Receiver<Arc<Broadcast>>, dtcs: Weak<CurrentDtcs>) {
+    let peer = stream.peer_addr().ok();
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(e) => {
+            warn!("WebSocket handshake with {:?} failed: {}", peer, e);
+            return;
+        }
+    };
+    if let Err(e) = socket.get_ref().set_read_timeout(Some(READ_TIMEOUT)) {
+        warn!("could not set WebSocket read timeout: {}", e);
+        return;
+    }
+    debug!("WebSocket client {:?} connected", peer);
+
+    let mut subscriptions = Subscriptions::default();
+    let result = loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let (reply, new_dtcs) = subscriptions.handle(&text);
+                if let Err(e) = socket.send(Message::Text(reply)) {
+                    break e;
+                }
+                let current = dtcs.upgrade().and_then(|dtcs| dtcs.lock().unwrap().clone());
+                if let (true, Some((_, text))) = (new_dtcs, current) {
+                    if let Err(e) = socket.send(Message::Text(text)) {
+                        break e;
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => break e,
+        }
+
+        // send what the client is subscribed to, and close the connection if the server was
+        // dropped
+        let error = loop {
+            match broadcasts.try_recv() {
+                Ok(broadcast) if subscriptions.contains(broadcast.topic) => {
+                    if let Err(e) = socket.send(Message::Text(broadcast.text.clone())) {
+                        break Some(e);
+                    }
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => break None,
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    break Some(tungstenite::Error::ConnectionClosed);
+                }
+            }
+        };
+        if let Some(e) = error {
+            break e;
+        }
+    };
+    match result {
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            debug!("WebSocket client {:?} disconnected", peer)
+        }
+        e => debug!("WebSocket client {:?} disconnected: {}", peer, e),
+    }
+}